@@ -0,0 +1,87 @@
+#[macro_use]
+extern crate criterion;
+extern crate render_core;
+
+use criterion::{black_box, Criterion};
+use render_core::handles::{RenderConcurrentHandleAllocator, RenderResourceHandleAllocator};
+use render_core::types::RenderResourceType;
+use std::sync::{Arc, RwLock};
+use std::thread;
+
+const HANDLE_COUNT: usize = 10_000;
+const THREAD_COUNT: usize = 4;
+
+/// Allocates then releases `HANDLE_COUNT` buffer handles through the shared, locked allocator.
+fn churn_locked(handles: &RwLock<RenderResourceHandleAllocator>) {
+    let allocated: Vec<_> = (0..HANDLE_COUNT)
+        .map(|_| {
+            handles
+                .write()
+                .unwrap()
+                .allocate(RenderResourceType::Buffer)
+                .unwrap()
+        })
+        .collect();
+    for handle in allocated {
+        black_box(handles.read().unwrap().is_valid(&handle));
+        handles.write().unwrap().release(handle);
+    }
+}
+
+/// Allocates then releases `HANDLE_COUNT` buffer handles through the concurrent allocator.
+fn churn_concurrent(handles: &RenderConcurrentHandleAllocator) {
+    let allocated: Vec<_> = (0..HANDLE_COUNT)
+        .map(|_| handles.allocate(RenderResourceType::Buffer).unwrap())
+        .collect();
+    for handle in allocated {
+        black_box(handles.is_valid(&handle));
+        handles.release(handle).unwrap();
+    }
+}
+
+fn single_thread(c: &mut Criterion) {
+    let locked = RwLock::new(RenderResourceHandleAllocator::new());
+    c.bench_function("handles locked 1 thread", |b| {
+        b.iter(|| churn_locked(&locked))
+    });
+
+    let concurrent = RenderConcurrentHandleAllocator::new();
+    c.bench_function("handles concurrent 1 thread", |b| {
+        b.iter(|| churn_concurrent(&concurrent))
+    });
+}
+
+fn multi_thread(c: &mut Criterion) {
+    let locked = Arc::new(RwLock::new(RenderResourceHandleAllocator::new()));
+    c.bench_function("handles locked 4 threads", |b| {
+        b.iter(|| {
+            let threads: Vec<_> = (0..THREAD_COUNT)
+                .map(|_| {
+                    let locked = Arc::clone(&locked);
+                    thread::spawn(move || churn_locked(&locked))
+                })
+                .collect();
+            for thread in threads {
+                thread.join().unwrap();
+            }
+        })
+    });
+
+    let concurrent = Arc::new(RenderConcurrentHandleAllocator::new());
+    c.bench_function("handles concurrent 4 threads", |b| {
+        b.iter(|| {
+            let threads: Vec<_> = (0..THREAD_COUNT)
+                .map(|_| {
+                    let concurrent = Arc::clone(&concurrent);
+                    thread::spawn(move || churn_concurrent(&concurrent))
+                })
+                .collect();
+            for thread in threads {
+                thread.join().unwrap();
+            }
+        })
+    });
+}
+
+criterion_group!(benches, single_thread, multi_thread);
+criterion_main!(benches);
@@ -0,0 +1,14 @@
+fn main() {
+    export_rustc_version();
+}
+
+fn export_rustc_version() {
+    use std::env;
+    use std::process::Command;
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let version = match Command::new(rustc).arg("--version").output() {
+        Ok(output) => String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        Err(_) => "unknown".to_string(),
+    };
+    println!("cargo:rustc-env=RENDER_CORE_RUSTC_VERSION={}", version);
+}
@@ -2,53 +2,166 @@ use crate::error::{Error, Result};
 use crate::utilities::{align_forward, align_ptr_forward};
 use failure::Fail;
 use std::cmp;
-use std::marker::PhantomData;
 use std::mem;
+use std::ptr;
 use std::slice;
 
+/// Position of an allocation, counted across every page of the allocator.
 pub type LinearAllocatorMark = usize;
 
-/// A basic linear allocator that wraps an externally owned contiguous region of memory.
-pub struct LinearAllocator<'a> {
+/// Smallest page a growable allocator adds once its first page was empty.
+pub const LINEAR_ALLOCATOR_MIN_PAGE_SIZE: usize = 4096;
+
+/// What a `LinearAllocator` does once its current page is full.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinearAllocatorMode {
+    /// Allocations that don't fit fail with `ErrorKind::Memory`
+    Bounded,
+
+    /// Allocations that don't fit go to a new page, at least as large as the first one
+    /// (or `LINEAR_ALLOCATOR_MIN_PAGE_SIZE`, if the first one was empty)
+    Growable,
+}
+
+/// Utilisation of a `LinearAllocator`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LinearAllocatorStats {
+    /// Bytes handed out since the last rewind to 0, excluding alignment padding
+    pub allocated: usize,
+
+    /// Bytes skipped since the last rewind to 0 for alignment, or at the end of a full page
+    pub wasted: usize,
+
+    /// Number of allocations since the last rewind to 0
+    pub allocation_count: usize,
+
+    /// Bytes reserved by all pages
+    pub capacity: usize,
+
+    /// Number of pages
+    pub page_count: usize,
+
+    /// Highest mark reached
+    pub peak: usize,
+}
+
+impl LinearAllocatorStats {
+    /// Fraction of the reserved bytes that were handed out.
+    pub fn utilisation(&self) -> f32 {
+        if self.capacity == 0 {
+            0.0
+        } else {
+            self.allocated as f32 / self.capacity as f32
+        }
+    }
+}
+
+/// Contiguous block of allocator memory. Pages are never resized or freed before the
+/// allocator, so data placed in them doesn't move.
+struct LinearAllocatorPage {
+    /// Mark of the first byte of the page
+    base: LinearAllocatorMark,
+    data: Box<[u8]>,
+}
+
+/// A basic linear allocator over one or more owned pages of memory.
+pub struct LinearAllocator {
+    mode: LinearAllocatorMode,
+    page_size: usize,
+    pages: Vec<LinearAllocatorPage>,
+    /// Index of the page allocations are made from
+    current: usize,
+    /// Position within the current page
     pos: usize,
-    size: usize,
-    data: Vec<u8>,
-    phantom: PhantomData<&'a Vec<u8>>,
+    stats: LinearAllocatorStats,
 }
 
-impl<'a> LinearAllocator<'a> {
+impl LinearAllocator {
+    /// Creates a growable allocator with a first page of `size` bytes.
     pub fn new(size: usize) -> Self {
-        LinearAllocator {
+        Self::with_mode(size, LinearAllocatorMode::Growable)
+    }
+
+    /// Creates an allocator with a first page of `size` bytes.
+    pub fn with_mode(size: usize, mode: LinearAllocatorMode) -> Self {
+        let mut allocator = LinearAllocator {
+            mode,
+            page_size: size,
+            pages: Vec::new(),
+            current: 0,
             pos: 0,
-            size,
-            data: vec![0u8; size],
-            phantom: PhantomData,
-        }
+            stats: Default::default(),
+        };
+        allocator.add_page(size);
+        allocator
+    }
+
+    #[inline(always)]
+    pub fn mode(&self) -> LinearAllocatorMode {
+        self.mode
     }
 
     #[inline(always)]
     pub fn is_empty(&self) -> bool {
-        self.pos == 0
+        self.mark() == 0
     }
 
+    /// Bytes used up to the current mark, including padding.
     #[inline(always)]
     pub fn size(&self) -> usize {
-        self.pos
+        self.mark()
     }
 
+    /// Bytes reserved by all pages.
     #[inline(always)]
     pub fn capacity(&self) -> usize {
-        self.size
+        self.stats.capacity
     }
 
     #[inline(always)]
     pub fn mark(&self) -> LinearAllocatorMark {
-        self.pos
+        self.pages[self.current].base + self.pos
     }
 
+    /// Bytes left before a new page is needed (or allocation fails, if bounded).
     #[inline(always)]
     pub fn available(&self) -> usize {
-        self.size() - self.capacity()
+        self.capacity() - self.size()
+    }
+
+    pub fn stats(&self) -> LinearAllocatorStats {
+        self.stats
+    }
+
+    fn add_page(&mut self, size: usize) {
+        let base = match self.pages.last() {
+            Some(page) => page.base + page.data.len(),
+            None => 0,
+        };
+        self.pages.push(LinearAllocatorPage {
+            base,
+            data: vec![0u8; size].into_boxed_slice(),
+        });
+        self.stats.capacity += size;
+        self.stats.page_count += 1;
+    }
+
+    /// Returns the position within `page` where `size` bytes would start, if they fit.
+    fn fit(
+        page: &LinearAllocatorPage,
+        pos: usize,
+        size: usize,
+        alignment: usize,
+        offset: usize,
+    ) -> Option<usize> {
+        // Align the address rather than the position, since pages are only byte aligned
+        let address = page.data.as_ptr() as usize + pos + offset;
+        let start = align_forward(address, alignment) - offset - page.data.as_ptr() as usize;
+        if start + size <= page.data.len() {
+            Some(start)
+        } else {
+            None
+        }
     }
 
     /// Allocate `size` bytes with minimum `alignment` at `offset` bytes into the allocation.
@@ -59,21 +172,63 @@ impl<'a> LinearAllocator<'a> {
         alignment: usize,
         offset: usize,
     ) -> Result<LinearAllocatorMark> {
-        let pos = cmp::min(
-            align_forward(self.mark() + offset, alignment) - offset + size,
-            self.capacity() + 1,
-        );
-        if (self.pos + size) > self.capacity() {
-            error!(
-                "ran out of space in linear allocator! requested: {}, position: {}, capacity: {}",
-                self.pos,
-                size,
-                self.capacity()
-            );
-            panic!("TODO: Error here");
-        } else {
-            self.pos = pos;
-            Ok(self.pos - size)
+        let alignment = cmp::max(alignment, 1);
+        if !alignment.is_power_of_two() {
+            return Err(Error::memory(format!(
+                "linear allocator alignment must be a power of two, not {}",
+                alignment
+            )));
+        }
+
+        let start_mark = self.mark();
+        loop {
+            let page = &self.pages[self.current];
+            if let Some(start) = Self::fit(page, self.pos, size, alignment, offset) {
+                let mark = page.base + start;
+                self.stats.wasted += mark - start_mark;
+                self.stats.allocated += size;
+                self.stats.allocation_count += 1;
+                self.pos = start + size;
+                self.stats.peak = cmp::max(self.stats.peak, self.mark());
+                return Ok(mark);
+            }
+
+            match self.mode {
+                LinearAllocatorMode::Bounded => {
+                    return Err(Error::memory(format!(
+                        "ran out of space in linear allocator! requested: {}, position: {}, capacity: {}",
+                        size,
+                        self.mark(),
+                        self.capacity()
+                    )));
+                }
+                LinearAllocatorMode::Growable => {
+                    let mut page_size = cmp::max(self.page_size, size + offset + alignment);
+                    if self.pages[self.current].data.is_empty() {
+                        // Only a first page of size 0 is empty; it is filled in rather than
+                        // chained after, since the next page would share its base. Later pages
+                        // are at least as large, so they aren't sized to a single allocation.
+                        page_size = cmp::max(page_size, LINEAR_ALLOCATOR_MIN_PAGE_SIZE);
+                        self.page_size = page_size;
+                        trace!("linear allocator filling empty page - size: {}", page_size);
+                        self.pages[self.current].data = vec![0u8; page_size].into_boxed_slice();
+                        self.stats.capacity += page_size;
+                        continue;
+                    }
+
+                    // Reuse pages kept from before a rewind, if the allocation fits in them
+                    if self.current + 1 == self.pages.len() {
+                        trace!(
+                            "linear allocator adding page - size: {}, pages: {}",
+                            page_size,
+                            self.pages.len() + 1
+                        );
+                        self.add_page(page_size);
+                    }
+                    self.current += 1;
+                    self.pos = 0;
+                }
+            }
         }
     }
 
@@ -84,51 +239,101 @@ impl<'a> LinearAllocator<'a> {
         Ok(mark)
     }
 
-    #[inline(always)]
-    pub fn mark_ref<T>(&self, mark: LinearAllocatorMark) -> Result<&'a T> {
-        unsafe {
-            let base_ptr = self.data.as_ptr();
-            let type_ptr = base_ptr.offset(mark as isize) as *const T;
-            Ok(&*type_ptr)
+    /// Returns the page holding `size` bytes at `mark`, and the position of `mark` within it.
+    fn locate(&self, mark: LinearAllocatorMark, size: usize) -> Result<(usize, usize)> {
+        let index = match self.pages.binary_search_by_key(&mark, |page| page.base) {
+            Ok(index) => index,
+            Err(index) => index.saturating_sub(1),
+        };
+        let page = &self.pages[index];
+        let pos = mark - page.base;
+        if pos + size > page.data.len() {
+            Err(Error::memory(format!(
+                "linear allocator range out of bounds - mark: {}, size: {}",
+                mark, size
+            )))
+        } else {
+            Ok((index, pos))
         }
     }
 
     #[inline(always)]
-    pub fn mark_mut<T>(&mut self, mark: LinearAllocatorMark) -> Result<&'a mut T> {
-        unsafe {
-            let base_ptr = self.data.as_mut_ptr();
-            let type_ptr = base_ptr.offset(mark as isize) as *mut T;
-            Ok(&mut *type_ptr)
-        }
+    fn mark_ptr<T>(&self, mark: LinearAllocatorMark) -> Result<*const T> {
+        let (index, pos) = self.locate(mark, mem::size_of::<T>())?;
+        let type_ptr = unsafe { self.pages[index].data.as_ptr().add(pos) } as *const T;
+        debug_assert!(
+            align_ptr_forward(type_ptr as *mut u8, mem::align_of::<T>()) == type_ptr as *mut u8
+        );
+        Ok(type_ptr)
+    }
+
+    /// Same as `mark_ptr`, for writing through the pointer.
+    #[inline(always)]
+    fn mark_ptr_mut<T>(&mut self, mark: LinearAllocatorMark) -> Result<*mut T> {
+        let (index, pos) = self.locate(mark, mem::size_of::<T>())?;
+        let type_ptr = unsafe { self.pages[index].data.as_mut_ptr().add(pos) } as *mut T;
+        debug_assert!(
+            align_ptr_forward(type_ptr as *mut u8, mem::align_of::<T>()) == type_ptr as *mut u8
+        );
+        Ok(type_ptr)
     }
 
+    /// Returns the value at `mark`, which must have been placed with `mark_place`.
+    ///
+    /// Values are restricted to `Copy` types, since the allocator never drops them.
     #[inline(always)]
-    pub fn mark_place<T>(&mut self, mark: LinearAllocatorMark, entry: T) -> Result<&'a T> {
+    pub fn mark_ref<T: Copy>(&self, mark: LinearAllocatorMark) -> Result<&T> {
+        let type_ptr = self.mark_ptr::<T>(mark)?;
+        unsafe { Ok(&*type_ptr) }
+    }
+
+    #[inline(always)]
+    pub fn mark_mut<T: Copy>(&mut self, mark: LinearAllocatorMark) -> Result<&mut T> {
+        let type_ptr = self.mark_ptr_mut::<T>(mark)?;
+        unsafe { Ok(&mut *type_ptr) }
+    }
+
+    #[inline(always)]
+    pub fn mark_place<T: Copy>(&mut self, mark: LinearAllocatorMark, entry: T) -> Result<&T> {
+        let type_ptr = self.mark_ptr_mut::<T>(mark)?;
         unsafe {
-            let base_ptr = self.data.as_mut_ptr();
-            let type_ptr = base_ptr.offset(mark as isize) as *mut T;
-            *type_ptr = entry;
+            // The memory is uninitialized as far as T is concerned, so don't drop it
+            ptr::write(type_ptr, entry);
             Ok(&*type_ptr)
         }
     }
 
     #[inline(always)]
     pub fn mark_insert(&mut self, mark: LinearAllocatorMark, data: &[u8]) -> Result<()> {
-        // TODO: Error handling - make sure ranges and lengths are correct
-        let end = mark + data.len();
-        self.data[mark..end].copy_from_slice(data);
+        let (index, pos) = self.locate(mark, data.len())?;
+        self.pages[index].data[pos..pos + data.len()].copy_from_slice(data);
         Ok(())
     }
 
     #[inline(always)]
-    pub fn mark_data(&'a self, mark: LinearAllocatorMark, size: usize) -> Result<&'a [u8]> {
-        let end = mark + size;
-        Ok(&self.data[mark..end])
+    pub fn mark_data(&self, mark: LinearAllocatorMark, size: usize) -> Result<&[u8]> {
+        let (index, pos) = self.locate(mark, size)?;
+        Ok(&self.pages[index].data[pos..pos + size])
     }
 
+    /// Moves back to `mark`, keeping any pages for reuse.
     #[inline(always)]
     pub fn rewind(&mut self, mark: LinearAllocatorMark) -> Result<()> {
-        self.pos = mark;
+        if mark > self.mark() {
+            return Err(Error::memory(format!(
+                "cannot rewind linear allocator forward - mark: {}, position: {}",
+                mark,
+                self.mark()
+            )));
+        }
+        let (index, pos) = self.locate(mark, 0)?;
+        self.current = index;
+        self.pos = pos;
+        if mark == 0 {
+            self.stats.allocated = 0;
+            self.stats.wasted = 0;
+            self.stats.allocation_count = 0;
+        }
         Ok(())
     }
 }
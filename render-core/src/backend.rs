@@ -1,13 +1,19 @@
 #![allow(dead_code)]
 #![allow(unused_imports)]
 
-use crate::device::{RenderDevice, RenderDeviceId, RenderDeviceInfo};
+use crate::device::{
+    new_device_entry, RenderDevice, RenderDeviceEntry, RenderDeviceId, RenderDeviceInfo,
+};
 use crate::error::{Error, Result};
 use failure::Fail;
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeSeq, Serializer};
 use std::fmt;
+use std::str::FromStr;
 use std::sync::{Arc, RwLock};
 
 bitflags! {
+    #[derive(Default)]
     pub struct RenderDebugFlags: u32 {
         /// No debugger support.
         const NONE = 0x0;
@@ -29,6 +35,116 @@ bitflags! {
     }
 }
 
+impl RenderDebugFlags {
+    const NAMES: [(&'static str, RenderDebugFlags); 5] = [
+        ("RENDER_DOC", RenderDebugFlags::RENDER_DOC),
+        ("PIX", RenderDebugFlags::PIX),
+        ("CPU_VALIDATION", RenderDebugFlags::CPU_VALIDATION),
+        ("GPU_VALIDATION", RenderDebugFlags::GPU_VALIDATION),
+        ("POST_CRASH_ANALYSIS", RenderDebugFlags::POST_CRASH_ANALYSIS),
+    ];
+
+    /// Looks up a single flag by name (case insensitive), i.e. "RENDER_DOC" or "cpu_validation".
+    pub fn from_name(name: &str) -> Option<RenderDebugFlags> {
+        if name.eq_ignore_ascii_case("NONE") {
+            return Some(RenderDebugFlags::NONE);
+        }
+        RenderDebugFlags::NAMES
+            .iter()
+            .find(|(flag_name, _)| flag_name.eq_ignore_ascii_case(name))
+            .map(|(_, flag)| *flag)
+    }
+
+    /// Names of the flags that are set, in declaration order.
+    pub fn names(self) -> Vec<&'static str> {
+        RenderDebugFlags::NAMES
+            .iter()
+            .filter(|(_, flag)| self.contains(*flag))
+            .map(|(name, _)| *name)
+            .collect()
+    }
+}
+
+/// Parses flag names separated by `|`, `,` or whitespace (i.e. "RENDER_DOC|CPU_VALIDATION"),
+/// or the raw bits as a number.
+impl FromStr for RenderDebugFlags {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let value = value.trim();
+        if let Ok(bits) = value.parse::<u32>() {
+            return RenderDebugFlags::from_bits(bits).ok_or_else(|| {
+                Error::parse(format!("invalid render debug flag bits: {:#x}", bits))
+            });
+        }
+
+        let mut flags = RenderDebugFlags::NONE;
+        for name in value
+            .split(|c: char| c == '|' || c == ',' || c.is_whitespace())
+            .filter(|name| !name.is_empty())
+        {
+            flags |= RenderDebugFlags::from_name(name)
+                .ok_or_else(|| Error::parse(format!("unknown render debug flag: {:?}", name)))?;
+        }
+        Ok(flags)
+    }
+}
+
+impl Serialize for RenderDebugFlags {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let names = self.names();
+        let mut seq = serializer.serialize_seq(Some(names.len()))?;
+        for name in names {
+            seq.serialize_element(name)?;
+        }
+        seq.end()
+    }
+}
+
+struct RenderDebugFlagsVisitor;
+
+impl<'de> Visitor<'de> for RenderDebugFlagsVisitor {
+    type Value = RenderDebugFlags;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a list of debug flag names, a flag string, or the flag bits")
+    }
+
+    fn visit_u64<E: de::Error>(self, bits: u64) -> std::result::Result<Self::Value, E> {
+        if bits > u64::from(u32::MAX) {
+            return Err(E::custom(format!(
+                "invalid render debug flag bits: {:#x}",
+                bits
+            )));
+        }
+        RenderDebugFlags::from_bits(bits as u32)
+            .ok_or_else(|| E::custom(format!("invalid render debug flag bits: {:#x}", bits)))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> std::result::Result<Self::Value, E> {
+        value.parse().map_err(E::custom)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(
+        self,
+        mut seq: A,
+    ) -> std::result::Result<Self::Value, A::Error> {
+        let mut flags = RenderDebugFlags::NONE;
+        while let Some(name) = seq.next_element::<String>()? {
+            flags |= RenderDebugFlags::from_name(&name).ok_or_else(|| {
+                de::Error::custom(format!("unknown render debug flag: {:?}", name))
+            })?;
+        }
+        Ok(flags)
+    }
+}
+
+impl<'de> Deserialize<'de> for RenderDebugFlags {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserializer.deserialize_any(RenderDebugFlagsVisitor)
+    }
+}
+
 /*pub enum RenderBackendApi {
     Dx12,
     Vulkan,
@@ -37,16 +153,18 @@ bitflags! {
     Proxy(String),
 }*/
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RenderBackendSettings {
     /// API to use (i.e. "Dx12", "Vulkan", etc)
     pub api: String, //RenderBackendApi,
 
     /// Address to use (i.e. when routing through proxy)
     /// i.e. x.x.x.x:50080
+    #[serde(default)]
     pub address: Option<String>,
 
     //Handle deviceWindow = nullptr;
+    #[serde(default)]
     pub debug_flags: RenderDebugFlags,
 }
 
@@ -73,6 +191,77 @@ pub trait RenderBackend: fmt::Debug {
     fn launch_debug_capture(&self, quit: bool) -> Result<()>;
 }
 
+/// Device bookkeeping for a backend wrapping another backend (i.e. for validation or capture),
+/// which hands out its own devices, each wrapping the matching device of the inner backend.
+#[derive(Debug)]
+pub struct RenderBackendLayer {
+    inner: Box<dyn RenderBackend>,
+    devices: Vec<RenderDeviceEntry>,
+}
+
+impl RenderBackendLayer {
+    pub fn new(inner: Box<dyn RenderBackend>) -> Self {
+        RenderBackendLayer {
+            inner,
+            devices: Vec::new(),
+        }
+    }
+
+    pub fn inner(&self) -> &dyn RenderBackend {
+        &*self.inner
+    }
+
+    pub fn enumerate_devices(
+        &mut self,
+        max_devices: u32,
+        mirror_count: u32,
+        software: bool,
+    ) -> Result<Vec<RenderDeviceInfo>> {
+        let device_info = self
+            .inner
+            .enumerate_devices(max_devices, mirror_count, software)?;
+        self.devices = (0..device_info.len())
+            .map(|_| new_device_entry(None))
+            .collect();
+        Ok(device_info)
+    }
+
+    /// Creates the inner device, then the layer's own device from the inner device entry.
+    pub fn create_device<F>(&mut self, device_id: RenderDeviceId, wrap: F) -> Result<()>
+    where
+        F: FnOnce(RenderDeviceEntry) -> Result<Box<dyn RenderDevice>>,
+    {
+        let device_index = self.device_index(device_id)?;
+        self.inner.create_device(device_id)?;
+        let device = wrap(self.inner.get_device(device_id)?)?;
+        self.devices[device_index] = new_device_entry(Some(device));
+        Ok(())
+    }
+
+    pub fn destroy_device(&mut self, device_id: RenderDeviceId) -> Result<()> {
+        let device_index = self.device_index(device_id)?;
+        self.devices[device_index] = new_device_entry(None);
+        self.inner.destroy_device(device_id)
+    }
+
+    pub fn get_device(&self, device_id: RenderDeviceId) -> Result<RenderDeviceEntry> {
+        let device_index = self.device_index(device_id)?;
+        Ok(Arc::clone(&self.devices[device_index]))
+    }
+
+    fn device_index(&self, device_id: RenderDeviceId) -> Result<usize> {
+        let device_index = device_id as usize;
+        if device_index >= self.devices.len() {
+            Err(Error::backend(format!(
+                "no device found for id {}",
+                device_index
+            )))
+        } else {
+            Ok(device_index)
+        }
+    }
+}
+
 pub struct RenderBackendRegistry {
     pub settings: RenderBackendSettings,
     pub backend: Arc<RwLock<Box<dyn RenderBackend>>>,
@@ -82,4 +271,9 @@ pub trait RenderBackendModule: fmt::Debug {
     fn name(&self) -> &'static str;
     fn api(&self) -> &'static str;
     fn create(&self) -> Box<dyn RenderBackend>;
+
+    /// Creates a backend for a specific registry entry (i.e. to connect to `settings.address`).
+    fn create_with_settings(&self, _settings: &RenderBackendSettings) -> Box<dyn RenderBackend> {
+        self.create()
+    }
 }
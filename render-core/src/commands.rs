@@ -6,8 +6,10 @@ use crate::types::*;
 use downcast_rs::Downcast;
 use std::borrow::Cow;
 use std::fmt;
+use std::ops::Deref;
 
 bitflags! {
+    #[derive(Serialize, Deserialize)]
     pub struct RenderCommandQueueType: u8
     {
         const NONE		= 0x00;
@@ -18,7 +20,7 @@ bitflags! {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum RenderCommandType {
     Draw = 0,
     DrawIndirect = 1,
@@ -43,6 +45,24 @@ pub enum RenderCommandType {
     UpdateShaderTable = 20,
 }
 
+impl RenderCommandType {
+    /// Whether the command may change resource states (i.e. image layouts read by shader
+    /// arguments, or buffers used by draw bindings), so bindings must be applied again after it.
+    pub fn invalidates_bindings(self) -> bool {
+        !matches!(
+            self,
+            RenderCommandType::Draw
+                | RenderCommandType::DrawIndirect
+                | RenderCommandType::Dispatch
+                | RenderCommandType::DispatchIndirect
+                | RenderCommandType::BeginTiming
+                | RenderCommandType::EndTiming
+                | RenderCommandType::BeginEvent
+                | RenderCommandType::EndEvent
+        )
+    }
+}
+
 pub trait RenderCommand: Downcast + fmt::Debug {
     fn get_type(&self) -> RenderCommandType;
     fn get_queue(&self) -> RenderCommandQueueType;
@@ -54,10 +74,74 @@ pub fn command_as_trait<T: RenderCommand>(t: T) -> T {
     t
 }
 
-#[derive(Clone, Debug, PartialEq)]
+macro_rules! render_command_entries {
+    ($($variant:ident($command:ident),)*) => {
+        /// A recorded command, stored by value in its command list.
+        #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+        pub enum RenderCommandEntry {
+            $($variant($command),)*
+        }
+
+        impl RenderCommandEntry {
+            #[inline]
+            pub fn as_command(&self) -> &dyn RenderCommand {
+                match self {
+                    $(RenderCommandEntry::$variant(command) => command,)*
+                }
+            }
+        }
+
+        $(
+            impl From<$command> for RenderCommandEntry {
+                #[inline]
+                fn from(command: $command) -> Self {
+                    RenderCommandEntry::$variant(command)
+                }
+            }
+        )*
+    };
+}
+
+render_command_entries! {
+    Draw(RenderCommandDraw),
+    DrawIndirect(RenderCommandDrawIndirect),
+    Dispatch(RenderCommandDispatch),
+    DispatchIndirect(RenderCommandDispatchIndirect),
+    UpdateBuffer(RenderCommandUpdateBuffer),
+    UpdateTexture(RenderCommandUpdateTexture),
+    CopyBuffer(RenderCommandCopyBuffer),
+    CopyTexture(RenderCommandCopyTexture),
+    Barriers(RenderCommandBarriers),
+    Transitions(RenderCommandTransitions),
+    BeginTiming(RenderCommandBeginTiming),
+    EndTiming(RenderCommandEndTiming),
+    ResolveTimings(RenderCommandResolveTimings),
+    BeginEvent(RenderCommandBeginEvent),
+    EndEvent(RenderCommandEndEvent),
+    BeginRenderPass(RenderCommandBeginRenderPass),
+    EndRenderPass(RenderCommandEndRenderPass),
+    RayTrace(RenderCommandRayTrace),
+    UpdateTopLevelAcceleration(RenderCommandUpdateTopLevelAcceleration),
+    UpdateBottomLevelAcceleration(RenderCommandUpdateBottomLevelAcceleration),
+    UpdateShaderTable(RenderCommandUpdateShaderTable),
+}
+
+/// Lets entries be used as `dyn RenderCommand`, i.e. with `get_type` and `downcast_ref`.
+impl Deref for RenderCommandEntry {
+    type Target = dyn RenderCommand;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.as_command()
+    }
+}
+
+/// `None` shader arguments keep those of the previous draw with the same pipeline state, and a
+/// `None` draw state keeps the previous one.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct RenderCommandDraw {
     pub pipeline_state: RenderResourceHandle,
-    pub shader_arguments: Vec<RenderShaderArgument>,
+    pub shader_arguments: Option<Vec<RenderShaderArgument>>,
     pub draw_state: Option<RenderDrawState>,
     pub draw_binding: Option<RenderResourceHandle>,
     pub draw_packet: RenderDrawPacket,
@@ -73,7 +157,7 @@ impl RenderCommandDraw {
     ) -> RenderCommandDraw {
         RenderCommandDraw {
             pipeline_state,
-            shader_arguments: shader_arguments.to_vec(),
+            shader_arguments: Some(shader_arguments.to_vec()),
             draw_state,
             draw_binding,
             draw_packet,
@@ -93,10 +177,10 @@ impl RenderCommand for RenderCommandDraw {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct RenderCommandDrawIndirect {
     pub pipeline_state: RenderResourceHandle,
-    pub shader_arguments: Vec<RenderShaderArgument>,
+    pub shader_arguments: Option<Vec<RenderShaderArgument>>,
     pub draw_state: Option<RenderDrawState>,
     pub draw_binding: RenderResourceHandle,
     pub primitive: RenderPrimitiveType,
@@ -122,7 +206,7 @@ impl RenderCommandDrawIndirect {
     ) -> RenderCommandDrawIndirect {
         RenderCommandDrawIndirect {
             pipeline_state,
-            shader_arguments: shader_arguments.to_vec(),
+            shader_arguments: Some(shader_arguments.to_vec()),
             draw_state,
             draw_binding,
             primitive,
@@ -147,10 +231,11 @@ impl RenderCommand for RenderCommandDrawIndirect {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+/// `None` shader arguments keep those of the previous dispatch with the same pipeline state.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RenderCommandDispatch {
     pub pipeline_state: RenderResourceHandle,
-    pub shader_arguments: Vec<RenderShaderArgument>,
+    pub shader_arguments: Option<Vec<RenderShaderArgument>>,
     pub dispatch_x: u32,
     pub dispatch_y: u32,
     pub dispatch_z: u32,
@@ -166,7 +251,7 @@ impl RenderCommandDispatch {
     ) -> RenderCommandDispatch {
         RenderCommandDispatch {
             pipeline_state,
-            shader_arguments: shader_arguments.to_vec(),
+            shader_arguments: Some(shader_arguments.to_vec()),
             dispatch_x,
             dispatch_y,
             dispatch_z,
@@ -186,10 +271,10 @@ impl RenderCommand for RenderCommandDispatch {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RenderCommandDispatchIndirect {
     pub pipeline_state: RenderResourceHandle,
-    pub shader_arguments: Vec<RenderShaderArgument>,
+    pub shader_arguments: Option<Vec<RenderShaderArgument>>,
     pub indirect_buffer: RenderResourceHandle,
     pub indirect_byte_offset: usize,
     pub count_buffer: RenderResourceHandle,
@@ -209,7 +294,7 @@ impl RenderCommandDispatchIndirect {
     ) -> RenderCommandDispatchIndirect {
         RenderCommandDispatchIndirect {
             pipeline_state,
-            shader_arguments: shader_arguments.to_vec(),
+            shader_arguments: Some(shader_arguments.to_vec()),
             indirect_buffer,
             indirect_byte_offset,
             count_buffer,
@@ -231,7 +316,7 @@ impl RenderCommand for RenderCommandDispatchIndirect {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RenderCommandUpdateBuffer {
     pub buffer: RenderResourceHandle,
     pub offset: usize,
@@ -267,7 +352,7 @@ impl RenderCommand for RenderCommandUpdateBuffer {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RenderCommandUpdateTexture {
     pub texture: RenderResourceHandle,
     pub sub_resource: u16,
@@ -309,7 +394,7 @@ impl RenderCommand for RenderCommandUpdateTexture {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RenderCommandCopyBuffer {
     pub src_buffer: RenderResourceHandle,
     pub src_offset: usize,
@@ -348,7 +433,7 @@ impl RenderCommand for RenderCommandCopyBuffer {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RenderCommandCopyTexture {
     pub src_texture: RenderResourceHandle,
     pub src_sub_resource: u16,
@@ -390,7 +475,7 @@ impl RenderCommand for RenderCommandCopyTexture {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RenderCommandBarriers {
     pub barriers: Vec<RenderResourceHandle>,
 }
@@ -415,7 +500,7 @@ impl RenderCommand for RenderCommandBarriers {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RenderCommandTransitions {
     pub transitions: Vec<RenderTransitionRecord>,
 }
@@ -440,7 +525,7 @@ impl RenderCommand for RenderCommandTransitions {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RenderCommandBeginTiming {
     pub timing_heap: RenderResourceHandle,
     pub region: u32,
@@ -467,7 +552,7 @@ impl RenderCommand for RenderCommandBeginTiming {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RenderCommandEndTiming {
     pub timing_heap: RenderResourceHandle,
     pub region: u32,
@@ -494,7 +579,7 @@ impl RenderCommand for RenderCommandEndTiming {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RenderCommandResolveTimings {
     pub timing_heap: RenderResourceHandle,
     pub region_start: u32,
@@ -527,7 +612,7 @@ impl RenderCommand for RenderCommandResolveTimings {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RenderCommandBeginEvent {
     pub user_data: u32,
     pub message: Cow<'static, str>,
@@ -557,7 +642,7 @@ impl RenderCommand for RenderCommandBeginEvent {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RenderCommandEndEvent {}
 
 impl RenderCommandEndEvent {
@@ -578,7 +663,7 @@ impl RenderCommand for RenderCommandEndEvent {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RenderCommandBeginRenderPass {
     pub render_pass: RenderResourceHandle,
 }
@@ -601,7 +686,7 @@ impl RenderCommand for RenderCommandBeginRenderPass {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RenderCommandEndRenderPass {}
 
 impl RenderCommandEndRenderPass {
@@ -622,7 +707,7 @@ impl RenderCommand for RenderCommandEndRenderPass {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RenderCommandRayTrace {
     pub pipeline_state: RenderResourceHandle,
     pub shader_table: RenderResourceHandle,
@@ -664,7 +749,7 @@ impl RenderCommand for RenderCommandRayTrace {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct RenderCommandUpdateTopLevelAcceleration {
     pub acceleration: RenderResourceHandle,
     pub desc: RenderAccelerationTopDesc,
@@ -691,7 +776,7 @@ impl RenderCommand for RenderCommandUpdateTopLevelAcceleration {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RenderCommandUpdateBottomLevelAcceleration {
     pub acceleration: RenderResourceHandle,
     pub refit: bool,
@@ -721,7 +806,7 @@ impl RenderCommand for RenderCommandUpdateBottomLevelAcceleration {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RenderCommandUpdateShaderTable {
     pub shader_table: RenderResourceHandle,
     pub desc: RenderShaderTableUpdateDesc,
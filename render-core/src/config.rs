@@ -0,0 +1,129 @@
+use crate::backend::{RenderBackendSettings, RenderDebugFlags};
+use crate::error::{Error, Result};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Comma separated list of backend apis, in order of preference (i.e. "vk,mock")
+pub const RENDER_BACKEND_API_ENV: &str = "RENDER_BACKEND_API";
+
+/// Debug flags applied to every backend (i.e. "RENDER_DOC|CPU_VALIDATION")
+pub const RENDER_DEBUG_FLAGS_ENV: &str = "RENDER_DEBUG_FLAGS";
+
+/// Directory to discover dynamically loaded backend modules in
+pub const RENDER_MODULE_PATH_ENV: &str = "RENDER_MODULE_PATH";
+
+/// Backend configuration for a `RenderSystem`, typically loaded from a JSON file.
+///
+/// ```json
+/// {
+///     "module_path": "target/debug",
+///     "backends": [
+///         { "api": "vk", "debug_flags": ["RENDER_DOC", "CPU_VALIDATION"] },
+///         { "api": "mock" }
+///     ]
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RenderSystemConfig {
+    /// Directory to discover backend modules in, or `None` to only use registered modules
+    pub module_path: Option<PathBuf>,
+
+    /// Backends in order of preference
+    pub backends: Vec<RenderBackendSettings>,
+}
+
+impl RenderSystemConfig {
+    pub fn new(backends: &[RenderBackendSettings]) -> Self {
+        RenderSystemConfig {
+            module_path: None,
+            backends: backends.to_vec(),
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json)
+            .map_err(|err| Error::parse(format!("invalid render system config - {}", err)))
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|err| Error::bug(format!("failed to serialize config - {}", err)))
+    }
+
+    /// Loads a JSON config file, without applying environment overrides.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path)
+            .map_err(|err| Error::config(format!("failed to read {} - {}", path.display(), err)))?;
+        RenderSystemConfig::from_json(&json)
+    }
+
+    /// Loads a JSON config file, then applies environment overrides on top.
+    pub fn load_with_env<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut config = RenderSystemConfig::load(path)?;
+        config.apply_env()?;
+        Ok(config)
+    }
+
+    /// Applies `RENDER_BACKEND_API`, `RENDER_DEBUG_FLAGS` and `RENDER_MODULE_PATH` overrides.
+    pub fn apply_env(&mut self) -> Result<()> {
+        self.apply_overrides(|name| env::var(name).ok())
+    }
+
+    /// Applies overrides using `lookup` in place of the process environment.
+    ///
+    /// An api override replaces the backend list, keeping the settings of any backend that
+    /// was already configured. A debug flags override replaces the flags of every backend.
+    pub fn apply_overrides<F>(&mut self, lookup: F) -> Result<()>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        if let Some(apis) = lookup(RENDER_BACKEND_API_ENV) {
+            let backends = apis
+                .split(',')
+                .map(|api| api.trim())
+                .filter(|api| !api.is_empty())
+                .map(|api| {
+                    self.backends
+                        .iter()
+                        .find(|settings| settings.api == api)
+                        .cloned()
+                        .unwrap_or_else(|| RenderBackendSettings {
+                            api: api.to_string(),
+                            address: None,
+                            debug_flags: RenderDebugFlags::NONE,
+                        })
+                })
+                .collect::<Vec<_>>();
+
+            if backends.is_empty() {
+                return Err(Error::config(format!(
+                    "{} does not list any backends",
+                    RENDER_BACKEND_API_ENV
+                )));
+            }
+            self.backends = backends;
+        }
+
+        if let Some(flags) = lookup(RENDER_DEBUG_FLAGS_ENV) {
+            let debug_flags: RenderDebugFlags = flags.parse().map_err(|err| {
+                Error::config(format!("invalid {} - {}", RENDER_DEBUG_FLAGS_ENV, err))
+            })?;
+            for settings in &mut self.backends {
+                settings.debug_flags = debug_flags;
+            }
+        }
+
+        if let Some(module_path) = lookup(RENDER_MODULE_PATH_ENV) {
+            self.module_path = if module_path.is_empty() {
+                None
+            } else {
+                Some(PathBuf::from(module_path))
+            };
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+use crate::device::{RenderDevice, RenderDeviceEntry};
+use crate::error::Result;
+use crate::handles::{RenderResourceHandle, RenderTypedHandle};
+use std::collections::VecDeque;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+
+/// Called once a deferred resource has been destroyed, i.e. to release its handle.
+pub type RenderDestroyedFn = Box<dyn FnOnce(RenderResourceHandle) + Send>;
+
+struct RenderDeferredDestroy {
+    frame_index: u64,
+    handle: RenderResourceHandle,
+    on_destroyed: Option<RenderDestroyedFn>,
+}
+
+/// Resources waiting for the frames that may still use them to complete.
+///
+/// Backends push to the queue from `RenderDevice::destroy_resource_deferred`, and flush it
+/// from `RenderDevice::advance_frame`.
+pub struct RenderDestroyQueue {
+    frames_in_flight: u64,
+    pending: Mutex<VecDeque<RenderDeferredDestroy>>,
+}
+
+impl RenderDestroyQueue {
+    pub fn new(frames_in_flight: u64) -> Self {
+        RenderDestroyQueue {
+            frames_in_flight,
+            pending: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn frames_in_flight(&self) -> u64 {
+        self.frames_in_flight
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.pending.lock().unwrap().len()
+    }
+
+    /// Queues `handle`, last used during frame `frame_index`.
+    pub fn push(
+        &self,
+        frame_index: u64,
+        handle: RenderResourceHandle,
+        on_destroyed: Option<RenderDestroyedFn>,
+    ) {
+        self.pending
+            .lock()
+            .unwrap()
+            .push_back(RenderDeferredDestroy {
+                frame_index,
+                handle,
+                on_destroyed,
+            });
+    }
+
+    /// Destroys every resource queued at least `frames_in_flight` frames before `frame_index`.
+    pub fn flush(&self, device: &dyn RenderDevice, frame_index: u64) -> Result<()> {
+        let ready: Vec<RenderDeferredDestroy> = {
+            let mut pending = self.pending.lock().unwrap();
+            let ready_count = pending
+                .iter()
+                .take_while(|entry| entry.frame_index + self.frames_in_flight <= frame_index)
+                .count();
+            pending.drain(..ready_count).collect()
+        };
+        Self::destroy(device, ready)
+    }
+
+    /// Destroys every queued resource, regardless of frame (i.e. once the device is idle).
+    pub fn flush_all(&self, device: &dyn RenderDevice) -> Result<()> {
+        let ready: Vec<RenderDeferredDestroy> = self.pending.lock().unwrap().drain(..).collect();
+        Self::destroy(device, ready)
+    }
+
+    /// Destroys `entries` in queue order, returning the first failure once all were visited.
+    fn destroy(device: &dyn RenderDevice, entries: Vec<RenderDeferredDestroy>) -> Result<()> {
+        let mut result = Ok(());
+        for entry in entries {
+            trace!(
+                "Destroying deferred resource - handle: {:?}, queued frame: {}",
+                entry.handle,
+                entry.frame_index
+            );
+            if let Err(err) = device.destroy_resource(entry.handle) {
+                error!(
+                    "failed to destroy deferred resource {:?}: {}",
+                    entry.handle, err
+                );
+                if result.is_ok() {
+                    result = Err(err);
+                }
+            }
+            if let Some(on_destroyed) = entry.on_destroyed {
+                on_destroyed(entry.handle);
+            }
+        }
+        result
+    }
+}
+
+impl fmt::Debug for RenderDestroyQueue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RenderDestroyQueue")
+            .field("frames_in_flight", &self.frames_in_flight)
+            .field("pending", &self.pending_count())
+            .finish()
+    }
+}
+
+/// Owns a device resource, queueing it for deferred destruction (see
+/// `RenderDevice::destroy_resource_deferred`) when dropped.
+pub struct RenderOwnedResource<H: RenderTypedHandle> {
+    handle: H,
+    device: Option<RenderDeviceEntry>,
+    on_destroyed: Option<RenderDestroyedFn>,
+}
+
+impl<H: RenderTypedHandle> RenderOwnedResource<H> {
+    /// Takes ownership of `handle`, which must already be created on `device`.
+    ///
+    /// `on_destroyed` runs once the resource has been destroyed, or straight away if the device
+    /// is gone by the time the owner is dropped.
+    pub fn new(
+        handle: H,
+        device: &RenderDeviceEntry,
+        on_destroyed: Option<RenderDestroyedFn>,
+    ) -> Self {
+        RenderOwnedResource {
+            handle,
+            device: Some(Arc::clone(device)),
+            on_destroyed,
+        }
+    }
+
+    pub fn handle(&self) -> H {
+        self.handle
+    }
+
+    /// Gives up ownership without destroying the resource.
+    pub fn into_handle(mut self) -> H {
+        self.device = None;
+        self.on_destroyed = None;
+        self.handle
+    }
+}
+
+impl<H: RenderTypedHandle> Deref for RenderOwnedResource<H> {
+    type Target = H;
+
+    fn deref(&self) -> &H {
+        &self.handle
+    }
+}
+
+impl<H: RenderTypedHandle> Drop for RenderOwnedResource<H> {
+    fn drop(&mut self) {
+        let device = match self.device.take() {
+            Some(device) => device,
+            None => return,
+        };
+        let handle = self.handle.handle();
+        let on_destroyed = self.on_destroyed.take();
+
+        let device_read = device
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        match *device_read {
+            Some(ref device) => {
+                if let Err(err) = device.destroy_resource_deferred(handle, on_destroyed) {
+                    error!("failed to queue destruction of {:?}: {}", handle, err);
+                }
+            }
+            None => {
+                if let Some(on_destroyed) = on_destroyed {
+                    on_destroyed(handle);
+                }
+            }
+        }
+    }
+}
+
+impl<H: RenderTypedHandle> fmt::Debug for RenderOwnedResource<H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RenderOwnedResource")
+            .field("handle", &self.handle)
+            .field("owned", &self.device.is_some())
+            .finish()
+    }
+}
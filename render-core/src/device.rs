@@ -1,21 +1,33 @@
+use crate::deferred::RenderDestroyedFn;
 use crate::encoder::*;
-use crate::error::{Error, Result};
+use crate::error::{Error, ErrorKind, Result};
 use crate::format::*;
-use crate::handles::RenderResourceHandle;
+use crate::handles::*;
 use crate::state::*;
+use crate::statistics::RenderFrameStats;
 use crate::types::*;
 use failure::Fail;
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt;
+use std::ops::{Deref, DerefMut};
 use std::sync::Arc;
-use std::sync::RwLock;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 pub type RenderDeviceId = u32;
 pub type RenderDeviceEntry = Arc<RwLock<Option<Box<dyn RenderDevice>>>>;
 
+/// Creates the entry a backend hands out for a device, empty until the device is created.
+///
+/// Devices are neither `Send` nor `Sync`; the entry is shared between the backend and the
+/// `RenderSystem` (or a wrapping backend) on the thread that created it.
+#[allow(clippy::arc_with_non_send_sync)]
+pub fn new_device_entry(device: Option<Box<dyn RenderDevice>>) -> RenderDeviceEntry {
+    Arc::new(RwLock::new(device))
+}
+
 enum_from_primitive! {
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
     #[repr(u32)]
     pub enum RenderDeviceVendor {
         Nvidia		= 0x10DE,
@@ -26,7 +38,7 @@ enum_from_primitive! {
 }
 
 enum_from_primitive! {
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
     pub enum RenderDeviceType {
         /// The device does not match any other available types.
         Other = 0,
@@ -45,7 +57,7 @@ enum_from_primitive! {
     }
 }
 
-#[derive(Clone, Default, Debug, PartialEq, Eq)]
+#[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RenderDeviceCaps {
     /// Does the adapter support RenderPrimitiveType::QuadList
     pub supports_quads: bool,
@@ -70,7 +82,7 @@ pub struct RenderDeviceCaps {
     pub max_texture_array_size: u32,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RenderDeviceInfo {
     /// Name of the device. Should only be used for logging / debugging purposes and may not be available
     pub name: String,
@@ -94,17 +106,26 @@ pub trait RenderDevice: fmt::Debug {
     fn valid_resource(&self, handle: RenderResourceHandle) -> bool;
     fn destroy_resource(&self, handle: RenderResourceHandle) -> Result<()>;
 
+    /// Destroys `handle` once every frame in flight at the time of the call has completed,
+    /// flushed by `advance_frame`. `on_destroyed` runs after the resource is destroyed, or
+    /// right away when the call fails, so whatever the caller tracks for `handle` is released.
+    fn destroy_resource_deferred(
+        &self,
+        handle: RenderResourceHandle,
+        on_destroyed: Option<RenderDestroyedFn>,
+    ) -> Result<()>;
+
     // Resource Management
     fn create_swap_chain(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderSwapChainHandle,
         desc: &RenderSwapChainDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()>;
 
     fn create_buffer(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderBufferHandle,
         desc: &RenderBufferDesc,
         initial_data: Option<&[u8]>,
         debug_name: Cow<'static, str>,
@@ -112,7 +133,7 @@ pub trait RenderDevice: fmt::Debug {
 
     fn create_texture(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderTextureHandle,
         desc: &RenderTextureDesc,
         initial_data: Option<RenderTextureSubResourceData>,
         debug_name: Cow<'static, str>,
@@ -120,21 +141,21 @@ pub trait RenderDevice: fmt::Debug {
 
     fn create_sampler_state(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderSamplerStateHandle,
         state: &RenderSamplerState,
         debug_name: Cow<'static, str>,
     ) -> Result<()>;
 
     fn create_shader(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderShaderHandle,
         desc: &RenderShaderDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()>;
 
     fn create_shader_views(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderShaderViewsHandle,
         desc: &RenderShaderViewsDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()>;
@@ -142,97 +163,97 @@ pub trait RenderDevice: fmt::Debug {
     // Ray tracing features are only supported on some devices
     fn create_ray_tracing_program(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderRayTracingProgramHandle,
         desc: &RayTracingProgramDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()>;
 
     fn create_ray_tracing_geometry(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderRayTracingGeometryHandle,
         desc: &RayTracingGeometryDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()>;
 
     fn create_ray_tracing_top_acceleration(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderRayTracingAccelerationHandle,
         desc: &RayTracingTopAccelerationDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()>;
 
     fn create_ray_tracing_bottom_acceleration(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderRayTracingAccelerationHandle,
         desc: &RayTracingBottomAccelerationDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()>;
 
     fn create_ray_tracing_pipeline_state(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderRayTracingPipelineStateHandle,
         desc: &RayTracingPipelineStateDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()>;
 
     fn create_ray_tracing_shader_table(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderRayTracingShaderTableHandle,
         desc: &RayTracingShaderTableDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()>;
 
     fn create_graphics_pipeline_state(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderGraphicsPipelineStateHandle,
         desc: &RenderGraphicsPipelineStateDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()>;
 
     fn create_compute_pipeline_state(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderComputePipelineStateHandle,
         desc: &RenderComputePipelineStateDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()>;
 
     fn create_draw_binding_set(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderDrawBindingSetHandle,
         desc: &RenderDrawBindingSetDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()>;
 
     fn create_frame_binding_set(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderFrameBindingSetHandle,
         desc: &RenderFrameBindingSetDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()>;
 
     fn create_render_pass(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderPassHandle,
         desc: &RenderPassDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()>;
 
     fn create_command_list(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderCommandListHandle,
         debug_name: Cow<'static, str>,
     ) -> Result<()>;
 
     fn create_fence(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderFenceHandle,
         desc: &RenderFenceDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()>;
 
     fn create_timing_heap(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderTimingHeapHandle,
         desc: &RenderTimingHeapDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()>;
@@ -246,40 +267,46 @@ pub trait RenderDevice: fmt::Debug {
     // CommandList Management
     fn submit_command_list(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderCommandListHandle,
         flush: bool,
-        wait_before: Option<&[RenderResourceHandle]>,
-        signal_after: Option<RenderResourceHandle>,
+        wait_before: Option<&[RenderFenceHandle]>,
+        signal_after: Option<RenderFenceHandle>,
     ) -> Result<()>;
 
     fn compile_command_list(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderCommandListHandle,
         command_list: &RenderCommandList,
     ) -> Result<()>;
 
     fn compile_command_lists(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderCommandListHandle,
         command_lists: &[RenderCommandList],
     ) -> Result<()>;
 
     // Present Management
     fn present_swap_chain(
         &mut self,
-        swap_chain: RenderResourceHandle,
-        source_texture: RenderResourceHandle,
+        swap_chain: RenderSwapChainHandle,
+        source_texture: RenderTextureHandle,
     ) -> Result<()>;
 
     fn resize_swap_chain(
         &self,
-        swap_chain: RenderResourceHandle,
+        swap_chain: RenderSwapChainHandle,
         width: u32,
         height: u32,
     ) -> Result<()>;
 
     fn advance_frame(&self) -> Result<()>;
 
+    /// Number of times `advance_frame` has been called
+    fn get_frame_index(&self) -> u64;
+
+    /// Statistics of the command lists compiled between the last two `advance_frame` calls
+    fn get_frame_stats(&self) -> RenderFrameStats;
+
     // Transfer Management
     // TODO:
 
@@ -288,17 +315,17 @@ pub trait RenderDevice: fmt::Debug {
         &self,
         wait_value: u64,
         signal_value: u64,
-        fence: RenderResourceHandle,
+        fence: RenderFenceHandle,
         command_list: &RenderCommandList,
     ) -> Result<()>;
 
-    fn device_graphics_signal(&self, signal_value: u64, fence: RenderResourceHandle) -> Result<()>;
+    fn device_graphics_signal(&self, signal_value: u64, fence: RenderFenceHandle) -> Result<()>;
 
-    fn device_graphics_wait(&self, wait_value: u64, fence: RenderResourceHandle) -> Result<()>;
+    fn device_graphics_wait(&self, wait_value: u64, fence: RenderFenceHandle) -> Result<()>;
 
-    fn device_copy_signal(&self, signal_value: u64, fence: RenderResourceHandle) -> Result<()>;
+    fn device_copy_signal(&self, signal_value: u64, fence: RenderFenceHandle) -> Result<()>;
 
-    fn device_copy_wait(&self, wait_value: u64, fence: RenderResourceHandle) -> Result<()>;
+    fn device_copy_wait(&self, wait_value: u64, fence: RenderFenceHandle) -> Result<()>;
 
     fn device_acquire(&self, resource: RenderResourceHandle) -> Result<()>;
 
@@ -315,14 +342,368 @@ pub trait RenderDevice: fmt::Debug {
     }
 }
 
+/// Read access to the device wrapped by a `RenderDeviceLayer`.
+pub struct RenderDeviceLayerInner<'a>(RwLockReadGuard<'a, Option<Box<dyn RenderDevice>>>);
+
+impl<'a> Deref for RenderDeviceLayerInner<'a> {
+    type Target = dyn RenderDevice;
+
+    fn deref(&self) -> &Self::Target {
+        &**self.0.as_ref().unwrap()
+    }
+}
+
+/// Write access to the device wrapped by a `RenderDeviceLayer`.
+pub struct RenderDeviceLayerInnerMut<'a>(RwLockWriteGuard<'a, Option<Box<dyn RenderDevice>>>);
+
+impl<'a> Deref for RenderDeviceLayerInnerMut<'a> {
+    type Target = dyn RenderDevice;
+
+    fn deref(&self) -> &Self::Target {
+        &**self.0.as_ref().unwrap()
+    }
+}
+
+impl<'a> DerefMut for RenderDeviceLayerInnerMut<'a> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut **self.0.as_mut().unwrap()
+    }
+}
+
+macro_rules! render_device_layer {
+    ($(fn $name:ident(&self $(, $arg:ident: $arg_type:ty)*) -> $result:ty;)*) => {
+        /// A device wrapping another backend's device (i.e. for validation or capture).
+        ///
+        /// Every `RenderDeviceLayer` is a `RenderDevice`, forwarding each call to the wrapped
+        /// device unless the layer overrides it.
+        pub trait RenderDeviceLayer: fmt::Debug {
+            fn layer_device_id(&self) -> RenderDeviceId;
+
+            /// Entry of the wrapped device.
+            fn layer_entry(&self) -> &RenderDeviceEntry;
+
+            /// The wrapped device, failing once it has been destroyed.
+            fn layer_inner(&self) -> Result<RenderDeviceLayerInner<'_>> {
+                let inner = self.layer_entry().read().map_err(|_| {
+                    Error::device(format!(
+                        "device {} lock is poisoned",
+                        self.layer_device_id()
+                    ))
+                })?;
+                if inner.is_none() {
+                    return Err(Error::device(format!(
+                        "wrapped device {} has been destroyed",
+                        self.layer_device_id()
+                    )));
+                }
+                Ok(RenderDeviceLayerInner(inner))
+            }
+
+            /// The wrapped device, for calls taking it mutably (i.e. `present_swap_chain`).
+            fn layer_inner_mut(&self) -> Result<RenderDeviceLayerInnerMut<'_>> {
+                let inner = self.layer_entry().write().map_err(|_| {
+                    Error::device(format!(
+                        "device {} lock is poisoned",
+                        self.layer_device_id()
+                    ))
+                })?;
+                if inner.is_none() {
+                    return Err(Error::device(format!(
+                        "wrapped device {} has been destroyed",
+                        self.layer_device_id()
+                    )));
+                }
+                Ok(RenderDeviceLayerInnerMut(inner))
+            }
+
+            /// The wrapped device for `destroy_resource_deferred`, running `on_destroyed` right
+            /// away if there is none, as the resource can't be queued.
+            fn layer_inner_or_release(
+                &self,
+                handle: RenderResourceHandle,
+                on_destroyed: Option<RenderDestroyedFn>,
+            ) -> Result<(RenderDeviceLayerInner<'_>, Option<RenderDestroyedFn>)> {
+                match self.layer_inner() {
+                    Ok(inner) => Ok((inner, on_destroyed)),
+                    Err(err) => {
+                        if let Some(on_destroyed) = on_destroyed {
+                            on_destroyed(handle);
+                        }
+                        Err(err)
+                    }
+                }
+            }
+
+            fn valid_resource(&self, handle: RenderResourceHandle) -> bool {
+                self.layer_inner()
+                    .map(|inner| inner.valid_resource(handle))
+                    .unwrap_or(false)
+            }
+
+            fn destroy_resource_deferred(
+                &self,
+                handle: RenderResourceHandle,
+                on_destroyed: Option<RenderDestroyedFn>,
+            ) -> Result<()> {
+                let (inner, on_destroyed) = self.layer_inner_or_release(handle, on_destroyed)?;
+                inner.destroy_resource_deferred(handle, on_destroyed)
+            }
+
+            fn present_swap_chain(
+                &mut self,
+                swap_chain: RenderSwapChainHandle,
+                source_texture: RenderTextureHandle,
+            ) -> Result<()> {
+                self.layer_inner_mut()?
+                    .present_swap_chain(swap_chain, source_texture)
+            }
+
+            fn get_frame_index(&self) -> u64 {
+                self.layer_inner()
+                    .map(|inner| inner.get_frame_index())
+                    .unwrap_or(0)
+            }
+
+            fn get_frame_stats(&self) -> RenderFrameStats {
+                self.layer_inner()
+                    .map(|inner| inner.get_frame_stats())
+                    .unwrap_or_default()
+            }
+
+            fn ray_tracing_supported(&self) -> bool {
+                self.layer_inner()
+                    .map(|inner| inner.ray_tracing_supported())
+                    .unwrap_or(false)
+            }
+
+            $(
+                fn $name(&self $(, $arg: $arg_type)*) -> Result<$result> {
+                    self.layer_inner()?.$name($($arg),*)
+                }
+            )*
+        }
+
+        impl<T: RenderDeviceLayer> RenderDevice for T {
+            fn valid_resource(&self, handle: RenderResourceHandle) -> bool {
+                RenderDeviceLayer::valid_resource(self, handle)
+            }
+
+            fn destroy_resource_deferred(
+                &self,
+                handle: RenderResourceHandle,
+                on_destroyed: Option<RenderDestroyedFn>,
+            ) -> Result<()> {
+                RenderDeviceLayer::destroy_resource_deferred(self, handle, on_destroyed)
+            }
+
+            fn present_swap_chain(
+                &mut self,
+                swap_chain: RenderSwapChainHandle,
+                source_texture: RenderTextureHandle,
+            ) -> Result<()> {
+                RenderDeviceLayer::present_swap_chain(self, swap_chain, source_texture)
+            }
+
+            fn get_frame_index(&self) -> u64 {
+                RenderDeviceLayer::get_frame_index(self)
+            }
+
+            fn get_frame_stats(&self) -> RenderFrameStats {
+                RenderDeviceLayer::get_frame_stats(self)
+            }
+
+            fn ray_tracing_supported(&self) -> bool {
+                RenderDeviceLayer::ray_tracing_supported(self)
+            }
+
+            $(
+                fn $name(&self $(, $arg: $arg_type)*) -> Result<$result> {
+                    RenderDeviceLayer::$name(self $(, $arg)*)
+                }
+            )*
+        }
+    };
+}
+
+render_device_layer! {
+    fn destroy_resource(&self, handle: RenderResourceHandle) -> ();
+    fn create_swap_chain(
+        &self,
+        handle: RenderSwapChainHandle,
+        desc: &RenderSwapChainDesc,
+        debug_name: Cow<'static, str>
+    ) -> ();
+    fn create_buffer(
+        &self,
+        handle: RenderBufferHandle,
+        desc: &RenderBufferDesc,
+        initial_data: Option<&[u8]>,
+        debug_name: Cow<'static, str>
+    ) -> ();
+    fn create_texture(
+        &self,
+        handle: RenderTextureHandle,
+        desc: &RenderTextureDesc,
+        initial_data: Option<RenderTextureSubResourceData>,
+        debug_name: Cow<'static, str>
+    ) -> ();
+    fn create_sampler_state(
+        &self,
+        handle: RenderSamplerStateHandle,
+        state: &RenderSamplerState,
+        debug_name: Cow<'static, str>
+    ) -> ();
+    fn create_shader(
+        &self,
+        handle: RenderShaderHandle,
+        desc: &RenderShaderDesc,
+        debug_name: Cow<'static, str>
+    ) -> ();
+    fn create_shader_views(
+        &self,
+        handle: RenderShaderViewsHandle,
+        desc: &RenderShaderViewsDesc,
+        debug_name: Cow<'static, str>
+    ) -> ();
+    fn create_ray_tracing_program(
+        &self,
+        handle: RenderRayTracingProgramHandle,
+        desc: &RayTracingProgramDesc,
+        debug_name: Cow<'static, str>
+    ) -> ();
+    fn create_ray_tracing_geometry(
+        &self,
+        handle: RenderRayTracingGeometryHandle,
+        desc: &RayTracingGeometryDesc,
+        debug_name: Cow<'static, str>
+    ) -> ();
+    fn create_ray_tracing_top_acceleration(
+        &self,
+        handle: RenderRayTracingAccelerationHandle,
+        desc: &RayTracingTopAccelerationDesc,
+        debug_name: Cow<'static, str>
+    ) -> ();
+    fn create_ray_tracing_bottom_acceleration(
+        &self,
+        handle: RenderRayTracingAccelerationHandle,
+        desc: &RayTracingBottomAccelerationDesc,
+        debug_name: Cow<'static, str>
+    ) -> ();
+    fn create_ray_tracing_pipeline_state(
+        &self,
+        handle: RenderRayTracingPipelineStateHandle,
+        desc: &RayTracingPipelineStateDesc,
+        debug_name: Cow<'static, str>
+    ) -> ();
+    fn create_ray_tracing_shader_table(
+        &self,
+        handle: RenderRayTracingShaderTableHandle,
+        desc: &RayTracingShaderTableDesc,
+        debug_name: Cow<'static, str>
+    ) -> ();
+    fn create_graphics_pipeline_state(
+        &self,
+        handle: RenderGraphicsPipelineStateHandle,
+        desc: &RenderGraphicsPipelineStateDesc,
+        debug_name: Cow<'static, str>
+    ) -> ();
+    fn create_compute_pipeline_state(
+        &self,
+        handle: RenderComputePipelineStateHandle,
+        desc: &RenderComputePipelineStateDesc,
+        debug_name: Cow<'static, str>
+    ) -> ();
+    fn create_draw_binding_set(
+        &self,
+        handle: RenderDrawBindingSetHandle,
+        desc: &RenderDrawBindingSetDesc,
+        debug_name: Cow<'static, str>
+    ) -> ();
+    fn create_frame_binding_set(
+        &self,
+        handle: RenderFrameBindingSetHandle,
+        desc: &RenderFrameBindingSetDesc,
+        debug_name: Cow<'static, str>
+    ) -> ();
+    fn create_render_pass(
+        &self,
+        handle: RenderPassHandle,
+        desc: &RenderPassDesc,
+        debug_name: Cow<'static, str>
+    ) -> ();
+    fn create_command_list(
+        &self,
+        handle: RenderCommandListHandle,
+        debug_name: Cow<'static, str>
+    ) -> ();
+    fn create_fence(
+        &self,
+        handle: RenderFenceHandle,
+        desc: &RenderFenceDesc,
+        debug_name: Cow<'static, str>
+    ) -> ();
+    fn create_timing_heap(
+        &self,
+        handle: RenderTimingHeapHandle,
+        desc: &RenderTimingHeapDesc,
+        debug_name: Cow<'static, str>
+    ) -> ();
+    fn get_timing_frequency(&self) -> f64;
+    fn submit_command_list(
+        &self,
+        handle: RenderCommandListHandle,
+        flush: bool,
+        wait_before: Option<&[RenderFenceHandle]>,
+        signal_after: Option<RenderFenceHandle>
+    ) -> ();
+    fn compile_command_list(
+        &self,
+        handle: RenderCommandListHandle,
+        command_list: &RenderCommandList
+    ) -> ();
+    fn compile_command_lists(
+        &self,
+        handle: RenderCommandListHandle,
+        command_lists: &[RenderCommandList]
+    ) -> ();
+    fn resize_swap_chain(
+        &self,
+        swap_chain: RenderSwapChainHandle,
+        width: u32,
+        height: u32
+    ) -> ();
+    fn advance_frame(&self) -> ();
+    fn device_transfer(
+        &self,
+        wait_value: u64,
+        signal_value: u64,
+        fence: RenderFenceHandle,
+        command_list: &RenderCommandList
+    ) -> ();
+    fn device_graphics_signal(&self, signal_value: u64, fence: RenderFenceHandle) -> ();
+    fn device_graphics_wait(&self, wait_value: u64, fence: RenderFenceHandle) -> ();
+    fn device_copy_signal(&self, signal_value: u64, fence: RenderFenceHandle) -> ();
+    fn device_copy_wait(&self, wait_value: u64, fence: RenderFenceHandle) -> ();
+    fn device_acquire(&self, resource: RenderResourceHandle) -> ();
+    fn device_unacquire(&self, resource: RenderResourceHandle) -> ();
+    fn device_flush(&self) -> ();
+    fn get_device_info(&self) -> RenderDeviceInfo;
+    fn shader_format(&self) -> String;
+}
+
+/// A primary device plus any number of secondary devices (multi-GPU).
+///
+/// Resources are mirrored across every device using the same handle, so a handle
+/// created through the group is valid on any of its devices. Calls visit the primary
+/// first (device index 0), then the secondaries in order (device index 1..).
 #[derive(Default, Debug, Clone)]
 pub struct RenderDeviceGroup {
     pub primary: RenderDeviceEntry,
     pub secondaries: Vec<RenderDeviceEntry>,
 }
 
-pub type RenderDeviceGroupCallRefFn = Box<dyn Fn(&Box<dyn RenderDevice>)>;
-pub type RenderDeviceGroupCallMutFn = Box<dyn Fn(&mut Box<dyn RenderDevice>)>;
+pub type RenderDeviceGroupCallRefFn = Box<dyn Fn(&dyn RenderDevice) -> Result<()>>;
+pub type RenderDeviceGroupCallMutFn = Box<dyn Fn(&mut dyn RenderDevice) -> Result<()>>;
 
 impl RenderDeviceGroup {
     pub fn new(primary: RenderDeviceEntry, secondaries: &[RenderDeviceEntry]) -> Self {
@@ -332,47 +713,210 @@ impl RenderDeviceGroup {
         }
     }
 
-    /*pub fn call_mut(&self, func: impl Fn(&mut Box<RenderDevice>)) {
-        let mut primary = self.primary.write();
-        match primary {
-            Ok(device) => match *device {
-                Some(&mut device) => {
-                    (func)(device);
-                }
-                None => {}
-            },
-            Err(_err) => {}
+    /// Number of devices in the group, including the primary.
+    pub fn device_count(&self) -> usize {
+        1 + self.secondaries.len()
+    }
+
+    pub fn get_entry(&self, device_index: usize) -> Result<&RenderDeviceEntry> {
+        if device_index == 0 {
+            Ok(&self.primary)
+        } else {
+            self.secondaries.get(device_index - 1).ok_or_else(|| {
+                Error::device(format!(
+                    "device index {} is out of range for a group of {}",
+                    device_index,
+                    self.device_count()
+                ))
+            })
         }
-    }*/
-
-    pub fn call_ref(&self, func: impl Fn(&Box<dyn RenderDevice>) -> Result<()>) -> Result<()> {
-        // Primary device
-        {
-            let primary = self.primary.read();
-            match primary {
-                Ok(device) => match *device {
-                    Some(ref device) => {
-                        (func)(device)?;
-                    }
-                    None => {}
-                },
-                Err(_err) => {}
-            }
+    }
+
+    /// Runs `func` on a single device of the group.
+    pub fn call_device_ref<T>(
+        &self,
+        device_index: usize,
+        func: impl FnOnce(&dyn RenderDevice) -> Result<T>,
+    ) -> Result<T> {
+        let entry = self.get_entry(device_index)?;
+        let device = entry
+            .read()
+            .map_err(|_| Error::device(format!("device {} lock is poisoned", device_index)))?;
+        match *device {
+            Some(ref device) => func(device.as_ref()),
+            None => Err(Error::device(format!(
+                "device {} has not been created",
+                device_index
+            ))),
+        }
+    }
+
+    /// Runs `func` on a single device of the group, with mutable access.
+    pub fn call_device_mut<T>(
+        &self,
+        device_index: usize,
+        func: impl FnOnce(&mut dyn RenderDevice) -> Result<T>,
+    ) -> Result<T> {
+        let entry = self.get_entry(device_index)?;
+        let mut device = entry
+            .write()
+            .map_err(|_| Error::device(format!("device {} lock is poisoned", device_index)))?;
+        match *device {
+            Some(ref mut device) => func(device.as_mut()),
+            None => Err(Error::device(format!(
+                "device {} has not been created",
+                device_index
+            ))),
         }
-        // Secondary devices
-        for secondary in &self.secondaries {
-            let secondary = secondary.read();
-            match secondary {
-                Ok(device) => match *device {
-                    Some(ref device) => {
-                        (func)(device)?;
+    }
+
+    /// Runs `func` on every device, returning one result per device index.
+    pub fn call_each_ref<T>(
+        &self,
+        mut func: impl FnMut(&dyn RenderDevice) -> Result<T>,
+    ) -> Vec<Result<T>> {
+        (0..self.device_count())
+            .map(|device_index| self.call_device_ref(device_index, &mut func))
+            .collect()
+    }
+
+    /// Runs `func` on every device with mutable access, returning one result per device index.
+    pub fn call_each_mut<T>(
+        &self,
+        mut func: impl FnMut(&mut dyn RenderDevice) -> Result<T>,
+    ) -> Vec<Result<T>> {
+        (0..self.device_count())
+            .map(|device_index| self.call_device_mut(device_index, &mut func))
+            .collect()
+    }
+
+    /// Runs `func` on every device, even if some fail.
+    ///
+    /// Failures are reported together as `ErrorKind::DeviceGroup`.
+    pub fn call_ref(&self, func: impl FnMut(&dyn RenderDevice) -> Result<()>) -> Result<()> {
+        collect_group_results(self.call_each_ref(func))
+    }
+
+    /// Runs `func` on every device with mutable access, even if some fail.
+    ///
+    /// Failures are reported together as `ErrorKind::DeviceGroup`.
+    pub fn call_mut(&self, func: impl FnMut(&mut dyn RenderDevice) -> Result<()>) -> Result<()> {
+        collect_group_results(self.call_each_mut(func))
+    }
+
+    /// Creates a resource on every device with the same handle.
+    ///
+    /// If creation fails on any device, the resource is destroyed again on the devices
+    /// where it succeeded, so the handle is never left valid on only part of the group.
+    /// Devices where that fails are reported along with the creation failures.
+    pub fn create_resource<H: Into<RenderResourceHandle>>(
+        &self,
+        handle: H,
+        func: impl FnMut(&dyn RenderDevice) -> Result<()>,
+    ) -> Result<()> {
+        let handle = handle.into();
+        let mut results = self.call_each_ref(func);
+        if results.iter().any(|result| result.is_err()) {
+            for (device_index, result) in results.iter_mut().enumerate() {
+                if result.is_ok() {
+                    if let Err(err) =
+                        self.call_device_ref(device_index, |device| device.destroy_resource(handle))
+                    {
+                        *result = Err(Error::device(format!(
+                            "failed to destroy resource after group creation failed - {}",
+                            err
+                        )));
                     }
-                    None => {}
-                },
-                Err(_err) => {}
+                }
             }
         }
+        collect_group_results(results)
+    }
+
+    pub fn destroy_resource<H: Into<RenderResourceHandle>>(&self, handle: H) -> Result<()> {
+        let handle = handle.into();
+        self.call_ref(|device| device.destroy_resource(handle))
+    }
+
+    /// True if the resource exists on every device of the group.
+    pub fn valid_resource<H: Into<RenderResourceHandle>>(&self, handle: H) -> bool {
+        let handle = handle.into();
+        self.call_each_ref(|device| Ok(device.valid_resource(handle)))
+            .into_iter()
+            .all(|result| result.unwrap_or(false))
+    }
+
+    pub fn present_swap_chain(
+        &self,
+        swap_chain: RenderSwapChainHandle,
+        source_texture: RenderTextureHandle,
+    ) -> Result<()> {
+        self.call_mut(|device| device.present_swap_chain(swap_chain, source_texture))
+    }
+
+    pub fn advance_frame(&self) -> Result<()> {
+        self.call_ref(|device| device.advance_frame())
+    }
+
+    /// Device index that renders `frame_index` with alternate-frame rendering.
+    pub fn alternate_frame_index(&self, frame_index: u64) -> usize {
+        (frame_index % self.device_count() as u64) as usize
+    }
+
+    /// Runs `func` on the device that renders `frame_index` with alternate-frame rendering.
+    pub fn call_alternate_frame<T>(
+        &self,
+        frame_index: u64,
+        func: impl FnOnce(&dyn RenderDevice) -> Result<T>,
+    ) -> Result<T> {
+        self.call_device_ref(self.alternate_frame_index(frame_index), func)
+    }
+
+    /// Splits `height` rows into one contiguous band per device for split-frame rendering.
+    ///
+    /// Returns `(first_row, row_count)` per device index; the last band absorbs any remainder.
+    pub fn split_frame_bands(&self, height: u32) -> Vec<(u32, u32)> {
+        let device_count = self.device_count() as u32;
+        let band_height = height / device_count;
+        (0..device_count)
+            .map(|device_index| {
+                let first_row = device_index * band_height;
+                if device_index + 1 == device_count {
+                    (first_row, height - first_row)
+                } else {
+                    (first_row, band_height)
+                }
+            })
+            .collect()
+    }
+
+    /// Submits a cross-device transfer from `device_index`, e.g. to copy a secondary
+    /// device's frame (or band) back to the primary for presentation.
+    pub fn device_transfer(
+        &self,
+        device_index: usize,
+        wait_value: u64,
+        signal_value: u64,
+        fence: RenderFenceHandle,
+        command_list: &RenderCommandList,
+    ) -> Result<()> {
+        self.call_device_ref(device_index, |device| {
+            device.device_transfer(wait_value, signal_value, fence, command_list)
+        })
+    }
+}
 
+fn collect_group_results(results: Vec<Result<()>>) -> Result<()> {
+    let errors: Vec<(usize, ErrorKind)> = results
+        .into_iter()
+        .enumerate()
+        .filter_map(|(device_index, result)| {
+            result.err().map(|err| (device_index, err.kind().clone()))
+        })
+        .collect();
+    if errors.is_empty() {
         Ok(())
+    } else {
+        Err(Error::from(ErrorKind::DeviceGroup(errors)))
     }
 }
@@ -0,0 +1,450 @@
+use crate::commands::*;
+use crate::encoder::{RenderCommandId, RenderCommandList};
+use crate::error::{Error, Result};
+use crate::format::build_resource_state_text;
+use crate::handles::RenderResourceHandle;
+use crate::state::RenderDrawState;
+use crate::system::RenderSystem;
+use crate::types::*;
+use std::fmt;
+
+/// A named value of a disassembled command.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenderDisassemblyOperand {
+    pub name: String,
+    pub value: String,
+}
+
+/// One line of a disassembly listing.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenderDisassembledCommand {
+    pub command_id: RenderCommandId,
+    pub command_type: RenderCommandType,
+
+    /// Number of event and render pass scopes enclosing the command
+    pub depth: usize,
+    pub operands: Vec<RenderDisassemblyOperand>,
+
+    /// Size of the data recorded with buffer and texture updates
+    pub payload_size: Option<usize>,
+}
+
+impl fmt::Display for RenderDisassembledCommand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:>5} {:indent$}{:?}",
+            self.command_id,
+            "",
+            self.command_type,
+            indent = self.depth * 2
+        )?;
+        for (index, operand) in self.operands.iter().enumerate() {
+            let separator = if index == 0 { " " } else { ", " };
+            write!(f, "{}{}={}", separator, operand.name, operand.value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Listing of a recorded command list, printed as indented text or serialized to JSON.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenderDisassembly {
+    pub commands: Vec<RenderDisassembledCommand>,
+
+    /// Number of buffer and texture updates
+    pub update_count: usize,
+
+    /// Total size of the data recorded with buffer and texture updates
+    pub payload_size: usize,
+}
+
+impl RenderDisassembly {
+    pub fn to_text(&self) -> String {
+        self.to_string()
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|err| Error::bug(format!("failed to serialize disassembly - {}", err)))
+    }
+}
+
+impl fmt::Display for RenderDisassembly {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for command in &self.commands {
+            writeln!(f, "{}", command)?;
+        }
+        writeln!(
+            f,
+            "; {} command(s), {} update(s) with {} byte(s) of data",
+            self.commands.len(),
+            self.update_count,
+            self.payload_size
+        )
+    }
+}
+
+/// Builds disassembly listings, naming handles through a `RenderSystem` when given one.
+#[derive(Default)]
+pub struct RenderCommandDisassembler<'a> {
+    render_system: Option<&'a RenderSystem>,
+}
+
+impl<'a> RenderCommandDisassembler<'a> {
+    pub fn new() -> Self {
+        RenderCommandDisassembler {
+            render_system: None,
+        }
+    }
+
+    pub fn with_names(render_system: &'a RenderSystem) -> Self {
+        RenderCommandDisassembler {
+            render_system: Some(render_system),
+        }
+    }
+
+    pub fn disassemble(&self, command_list: &RenderCommandList) -> RenderDisassembly {
+        self.disassemble_commands(command_list.get_commands())
+    }
+
+    pub fn disassemble_commands(&self, commands: &[RenderCommandEntry]) -> RenderDisassembly {
+        let mut disassembly = RenderDisassembly::default();
+        let mut depth: usize = 0;
+        for (command_id, entry) in commands.iter().enumerate() {
+            match entry {
+                RenderCommandEntry::EndEvent(_) | RenderCommandEntry::EndRenderPass(_) => {
+                    depth = depth.saturating_sub(1)
+                }
+                _ => {}
+            }
+
+            let (operands, payload_size) = self.decode(entry);
+            let command = RenderDisassembledCommand {
+                command_id,
+                command_type: entry.get_type(),
+                depth,
+                operands,
+                payload_size,
+            };
+            if let Some(payload_size) = payload_size {
+                disassembly.update_count += 1;
+                disassembly.payload_size += payload_size;
+            }
+            disassembly.commands.push(command);
+
+            match entry {
+                RenderCommandEntry::BeginEvent(_) | RenderCommandEntry::BeginRenderPass(_) => {
+                    depth += 1
+                }
+                _ => {}
+            }
+        }
+        disassembly
+    }
+
+    /// Formats a handle as its type and index, followed by its name if known.
+    pub fn handle_text(&self, handle: RenderResourceHandle) -> String {
+        if !handle.is_valid() {
+            return "null".to_string();
+        }
+        let text = format!("{:?}#{}", handle.get_type(), handle.get_id());
+        let name = self
+            .render_system
+            .and_then(|render_system| render_system.get_handle_name(handle).ok());
+        match name {
+            Some(name) => format!("{} {:?}", text, name),
+            None => text,
+        }
+    }
+
+    fn optional_handle_text(&self, handle: Option<RenderResourceHandle>) -> String {
+        match handle {
+            Some(handle) => self.handle_text(handle),
+            None => "none".to_string(),
+        }
+    }
+
+    fn handles_text(&self, handles: &[RenderResourceHandle]) -> String {
+        let handles: Vec<String> = handles
+            .iter()
+            .map(|&handle| self.handle_text(handle))
+            .collect();
+        format!("[{}]", handles.join(", "))
+    }
+
+    fn shader_arguments_text(
+        &self,
+        shader_arguments: &Option<Vec<RenderShaderArgument>>,
+    ) -> String {
+        let shader_arguments = match shader_arguments {
+            Some(shader_arguments) => shader_arguments,
+            None => return "unchanged".to_string(),
+        };
+        let arguments: Vec<String> = shader_arguments
+            .iter()
+            .map(|argument| {
+                format!(
+                    "{{cb {}+{}, views {}}}",
+                    self.optional_handle_text(argument.constant_buffer),
+                    argument.constant_buffer_offset,
+                    self.optional_handle_text(argument.shader_views)
+                )
+            })
+            .collect();
+        format!("[{}]", arguments.join(", "))
+    }
+
+    /// Returns the operands of a command, and the size of its update data.
+    fn decode(&self, entry: &RenderCommandEntry) -> (Vec<RenderDisassemblyOperand>, Option<usize>) {
+        let mut operands = Vec::new();
+        let mut payload_size = None;
+        let mut operand = |name: &str, value: String| {
+            operands.push(RenderDisassemblyOperand {
+                name: name.to_string(),
+                value,
+            })
+        };
+        match entry {
+            RenderCommandEntry::Draw(draw) => {
+                operand("pipeline_state", self.handle_text(draw.pipeline_state));
+                operand(
+                    "shader_arguments",
+                    self.shader_arguments_text(&draw.shader_arguments),
+                );
+                operand("draw_binding", self.optional_handle_text(draw.draw_binding));
+                operand("draw_state", draw_state_text(&draw.draw_state));
+                operand("vertex_count", draw.draw_packet.vertex_count.to_string());
+                operand("vertex_offset", draw.draw_packet.vertex_offset.to_string());
+                operand("index_offset", draw.draw_packet.index_offset.to_string());
+                operand(
+                    "instance_count",
+                    draw.draw_packet.instance_count.to_string(),
+                );
+                operand(
+                    "first_instance",
+                    draw.draw_packet.first_instance.to_string(),
+                );
+            }
+            RenderCommandEntry::DrawIndirect(draw) => {
+                operand("pipeline_state", self.handle_text(draw.pipeline_state));
+                operand(
+                    "shader_arguments",
+                    self.shader_arguments_text(&draw.shader_arguments),
+                );
+                operand("draw_binding", self.handle_text(draw.draw_binding));
+                operand("draw_state", draw_state_text(&draw.draw_state));
+                operand("primitive", format!("{:?}", draw.primitive));
+                operand(
+                    "indirect_buffer",
+                    format!(
+                        "{}+{}",
+                        self.handle_text(draw.indirect_buffer),
+                        draw.indirect_byte_offset
+                    ),
+                );
+                operand(
+                    "count_buffer",
+                    format!(
+                        "{}+{}",
+                        self.handle_text(draw.count_buffer),
+                        draw.count_byte_offset
+                    ),
+                );
+                operand("command_limit", draw.command_limit.to_string());
+            }
+            RenderCommandEntry::Dispatch(dispatch) => {
+                operand("pipeline_state", self.handle_text(dispatch.pipeline_state));
+                operand(
+                    "shader_arguments",
+                    self.shader_arguments_text(&dispatch.shader_arguments),
+                );
+                operand(
+                    "groups",
+                    format!(
+                        "{}x{}x{}",
+                        dispatch.dispatch_x, dispatch.dispatch_y, dispatch.dispatch_z
+                    ),
+                );
+            }
+            RenderCommandEntry::DispatchIndirect(dispatch) => {
+                operand("pipeline_state", self.handle_text(dispatch.pipeline_state));
+                operand(
+                    "shader_arguments",
+                    self.shader_arguments_text(&dispatch.shader_arguments),
+                );
+                operand(
+                    "indirect_buffer",
+                    format!(
+                        "{}+{}",
+                        self.handle_text(dispatch.indirect_buffer),
+                        dispatch.indirect_byte_offset
+                    ),
+                );
+                operand(
+                    "count_buffer",
+                    format!(
+                        "{}+{}",
+                        self.handle_text(dispatch.count_buffer),
+                        dispatch.count_byte_offset
+                    ),
+                );
+                operand("command_limit", dispatch.command_limit.to_string());
+            }
+            RenderCommandEntry::UpdateBuffer(update) => {
+                operand("buffer", self.handle_text(update.buffer));
+                operand("offset", update.offset.to_string());
+                operand("size", update.size.to_string());
+                payload_size = Some(update.size);
+            }
+            RenderCommandEntry::UpdateTexture(update) => {
+                operand("texture", self.handle_text(update.texture));
+                operand("sub_resource", update.sub_resource.to_string());
+                operand("row_pitch", update.sub_row_pitch.to_string());
+                operand("slice_pitch", update.sub_slice_pitch.to_string());
+                operand("size", update.sub_data_size.to_string());
+                payload_size = Some(update.sub_data_size);
+            }
+            RenderCommandEntry::CopyBuffer(copy) => {
+                operand(
+                    "src",
+                    format!("{}+{}", self.handle_text(copy.src_buffer), copy.src_offset),
+                );
+                operand(
+                    "dst",
+                    format!("{}+{}", self.handle_text(copy.dst_buffer), copy.dst_offset),
+                );
+                operand("size", copy.src_size.to_string());
+            }
+            RenderCommandEntry::CopyTexture(copy) => {
+                let src_box = copy.src_box;
+                let dst_point = copy.dst_point;
+                operand(
+                    "src",
+                    format!(
+                        "{}[{}]",
+                        self.handle_text(copy.src_texture),
+                        copy.src_sub_resource
+                    ),
+                );
+                operand(
+                    "src_box",
+                    format!(
+                        "({}, {}, {}) {}x{}x{}",
+                        src_box.x, src_box.y, src_box.z, src_box.w, src_box.h, src_box.d
+                    ),
+                );
+                operand(
+                    "dst",
+                    format!(
+                        "{}[{}]",
+                        self.handle_text(copy.dst_texture),
+                        copy.dst_sub_resource
+                    ),
+                );
+                operand(
+                    "dst_point",
+                    format!("({}, {}, {})", dst_point.x, dst_point.y, dst_point.z),
+                );
+            }
+            RenderCommandEntry::Barriers(barriers) => {
+                operand("barriers", self.handles_text(&barriers.barriers));
+            }
+            RenderCommandEntry::Transitions(transitions) => {
+                let records: Vec<String> = transitions
+                    .transitions
+                    .iter()
+                    .map(|&(handle, states)| {
+                        format!("{} -> {}", self.handle_text(handle), states_text(states))
+                    })
+                    .collect();
+                operand("transitions", format!("[{}]", records.join(", ")));
+            }
+            RenderCommandEntry::BeginTiming(timing) => {
+                operand("timing_heap", self.handle_text(timing.timing_heap));
+                operand("region", timing.region.to_string());
+            }
+            RenderCommandEntry::EndTiming(timing) => {
+                operand("timing_heap", self.handle_text(timing.timing_heap));
+                operand("region", timing.region.to_string());
+            }
+            RenderCommandEntry::ResolveTimings(timing) => {
+                operand("timing_heap", self.handle_text(timing.timing_heap));
+                operand("region_start", timing.region_start.to_string());
+                operand("region_count", timing.region_count.to_string());
+            }
+            RenderCommandEntry::BeginEvent(event) => {
+                operand("message", format!("{:?}", event.message));
+                operand("user_data", event.user_data.to_string());
+            }
+            RenderCommandEntry::BeginRenderPass(pass) => {
+                operand("render_pass", self.handle_text(pass.render_pass));
+            }
+            RenderCommandEntry::EndEvent(_) | RenderCommandEntry::EndRenderPass(_) => {}
+            RenderCommandEntry::RayTrace(ray_trace) => {
+                operand("pipeline_state", self.handle_text(ray_trace.pipeline_state));
+                operand("shader_table", self.handle_text(ray_trace.shader_table));
+                operand("output", self.handle_text(ray_trace.rt_output));
+                operand("size", format!("{}x{}", ray_trace.width, ray_trace.height));
+                operand("ray_gen_index", ray_trace.ray_gen_index.to_string());
+            }
+            RenderCommandEntry::UpdateTopLevelAcceleration(update) => {
+                operand("acceleration", self.handle_text(update.acceleration));
+                operand("instances", update.desc.instances.len().to_string());
+                operand(
+                    "instances_buffer",
+                    self.handle_text(update.desc.instances_buffer),
+                );
+                operand(
+                    "performance_hint",
+                    format!("{:?}", update.desc.performance_hint),
+                );
+            }
+            RenderCommandEntry::UpdateBottomLevelAcceleration(update) => {
+                operand("acceleration", self.handle_text(update.acceleration));
+                operand("refit", update.refit.to_string());
+            }
+            RenderCommandEntry::UpdateShaderTable(update) => {
+                operand("shader_table", self.handle_text(update.shader_table));
+                operand(
+                    "entries",
+                    format!(
+                        "{} ray gen, {} hit, {} miss",
+                        update.desc.ray_gen_entries.len(),
+                        update.desc.hit_entries.len(),
+                        update.desc.miss_entries.len()
+                    ),
+                );
+            }
+        }
+        (operands, payload_size)
+    }
+}
+
+/// Decodes resource states, i.e. `RenderTarget|PixelShaderResource`.
+fn states_text(states: RenderResourceStates) -> String {
+    let text = build_resource_state_text(states);
+    text.trim_end_matches('|').to_string()
+}
+
+fn draw_state_text(draw_state: &Option<RenderDrawState>) -> String {
+    let draw_state = match draw_state {
+        Some(draw_state) => draw_state,
+        None => return "unchanged".to_string(),
+    };
+    let mut parts = Vec::new();
+    if let Some(viewport) = draw_state.viewport {
+        parts.push(format!(
+            "viewport ({}, {}) {}x{} z {}..{}",
+            viewport.x, viewport.y, viewport.width, viewport.height, viewport.min_z, viewport.max_z
+        ));
+    }
+    if let Some(scissor) = draw_state.scissor {
+        parts.push(format!(
+            "scissor ({}, {}) {}x{}",
+            scissor.x, scissor.y, scissor.width, scissor.height
+        ));
+    }
+    parts.push(format!("stencil_ref {}", draw_state.stencil_ref));
+    format!("{{{}}}", parts.join(", "))
+}
@@ -1,45 +1,507 @@
-use crate::allocator::{LinearAllocator, LinearAllocatorMark};
+use crate::allocator::{
+    LinearAllocator, LinearAllocatorMark, LinearAllocatorMode, LinearAllocatorStats,
+};
 use crate::commands::*;
 use crate::error::{Error, Result};
-use crate::handles::{RenderResourceHandle, RenderResourceHandleAllocator};
+use crate::handles::*;
 use crate::state::*;
+use crate::statistics::RenderCommandListStats;
 use crate::types::*;
 use crate::utilities::*;
+use crate::validation::{render_pass_placement, RenderPassPlacement};
 use failure::Fail;
 use std::borrow::Cow;
+use std::mem;
+use std::ops::{Deref, DerefMut};
 use std::sync::{Arc, RwLock};
 
-pub type RenderCommandId = LinearAllocatorMark;
+/// Index of a command within its `RenderCommandList`.
+pub type RenderCommandId = usize;
 
-pub struct RenderCommandList<'a> {
-    commands: Vec<&'a dyn RenderCommand>,
-    allocator: LinearAllocator<'a>,
-    handles: Arc<RwLock<RenderResourceHandleAllocator>>,
+/// Recording position of a `RenderCommandList`, used to undo a partial recording.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RenderCommandListMark {
+    command_count: usize,
+    allocator_mark: LinearAllocatorMark,
     queue_type: RenderCommandQueueType,
-    draw_state: RenderDrawState,
-    draw_state_cache: Option<RenderDrawState>,
-    render_pass_active: bool,
+    filter_stats: RenderCommandFilterStats,
+    event_depth: usize,
+    render_pass_depth: usize,
 }
 
-impl<'a> RenderCommandList<'a> {
+/// State changes left out of the draws and dispatches recorded by a `RenderCommandList`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenderCommandFilterStats {
+    /// Draws and dispatches using the pipeline state of the previous one
+    pub redundant_pipelines: usize,
+
+    /// Shader arguments left out as they were already bound
+    pub redundant_shader_arguments: usize,
+
+    /// Draws using the draw binding set of the previous draw
+    pub redundant_draw_bindings: usize,
+
+    /// Draw states left out as they were already set
+    pub redundant_draw_states: usize,
+
+    /// Size of the shader arguments and draw states left out
+    pub filtered_bytes: usize,
+}
+
+/// Pipeline state and shader arguments last recorded for a graphics or compute draw.
+#[derive(Default)]
+struct RenderBoundPipeline {
+    pipeline_state: Option<RenderResourceHandle>,
+    shader_arguments: Vec<RenderShaderArgument>,
+}
+
+impl RenderBoundPipeline {
+    fn invalidate(&mut self) {
+        self.pipeline_state = None;
+        self.shader_arguments.clear();
+    }
+
+    /// Returns false if the shader arguments are already bound to the same pipeline state.
+    fn bind(
+        &mut self,
+        stats: &mut RenderCommandFilterStats,
+        pipeline_state: RenderResourceHandle,
+        shader_arguments: &[RenderShaderArgument],
+    ) -> bool {
+        if self.pipeline_state == Some(pipeline_state) {
+            stats.redundant_pipelines += 1;
+            if self.shader_arguments.as_slice() == shader_arguments {
+                if !shader_arguments.is_empty() {
+                    stats.redundant_shader_arguments += 1;
+                    stats.filtered_bytes += mem::size_of_val(shader_arguments);
+                }
+                return false;
+            }
+        }
+        self.pipeline_state = Some(pipeline_state);
+        self.shader_arguments.clear();
+        self.shader_arguments.extend_from_slice(shader_arguments);
+        true
+    }
+}
+
+/// State last recorded by a `RenderCommandList`, so that draws and dispatches only record what
+/// changed: shader arguments are `None` while bound to the same pipeline state, and the draw
+/// state is `None` while unchanged. Commands that may change resource states forget it all.
+#[derive(Default)]
+struct RenderCommandFilter {
+    graphics: RenderBoundPipeline,
+    compute: RenderBoundPipeline,
+    draw_binding: Option<Option<RenderResourceHandle>>,
+    draw_state: Option<RenderDrawState>,
+    stats: RenderCommandFilterStats,
+}
+
+impl RenderCommandFilter {
+    /// Forgets the recorded state, so the next draw or dispatch records all of it.
+    fn invalidate(&mut self) {
+        self.graphics.invalidate();
+        self.compute.invalidate();
+        self.draw_binding = None;
+        self.draw_state = None;
+    }
+
+    fn bind_graphics(
+        &mut self,
+        pipeline_state: RenderResourceHandle,
+        shader_arguments: &[RenderShaderArgument],
+        draw_binding: Option<RenderResourceHandle>,
+        draw_state: &RenderDrawState,
+    ) -> (bool, Option<RenderDrawState>) {
+        let bind_arguments = self
+            .graphics
+            .bind(&mut self.stats, pipeline_state, shader_arguments);
+        if self.draw_binding == Some(draw_binding) {
+            self.stats.redundant_draw_bindings += 1;
+        }
+        self.draw_binding = Some(draw_binding);
+        if self.draw_state.as_ref() == Some(draw_state) {
+            self.stats.redundant_draw_states += 1;
+            self.stats.filtered_bytes += mem::size_of::<RenderDrawState>();
+            (bind_arguments, None)
+        } else {
+            self.draw_state = Some(*draw_state);
+            (bind_arguments, Some(*draw_state))
+        }
+    }
+
+    fn bind_compute(
+        &mut self,
+        pipeline_state: RenderResourceHandle,
+        shader_arguments: &[RenderShaderArgument],
+    ) -> bool {
+        self.compute
+            .bind(&mut self.stats, pipeline_state, shader_arguments)
+    }
+}
+
+/// Payload vectors of discarded commands, reused so that recording into a reset command list
+/// doesn't allocate.
+#[derive(Default)]
+struct RenderCommandPayloads {
+    shader_arguments: Vec<Vec<RenderShaderArgument>>,
+    barriers: Vec<Vec<RenderResourceHandle>>,
+    transitions: Vec<Vec<RenderTransitionRecord>>,
+}
+
+fn reuse_payload<T: Clone>(free: &mut Vec<Vec<T>>, items: &[T]) -> Vec<T> {
+    if items.is_empty() {
+        return Vec::new();
+    }
+    let mut payload = free.pop().unwrap_or_default();
+    payload.extend_from_slice(items);
+    payload
+}
+
+fn recycle_payload<T>(free: &mut Vec<Vec<T>>, mut payload: Vec<T>) {
+    if payload.capacity() > 0 {
+        payload.clear();
+        free.push(payload);
+    }
+}
+
+impl RenderCommandPayloads {
+    fn shader_arguments(&mut self, items: &[RenderShaderArgument]) -> Vec<RenderShaderArgument> {
+        reuse_payload(&mut self.shader_arguments, items)
+    }
+
+    fn barriers(&mut self, items: &[RenderResourceHandle]) -> Vec<RenderResourceHandle> {
+        reuse_payload(&mut self.barriers, items)
+    }
+
+    fn transitions(&mut self, items: &[RenderTransitionRecord]) -> Vec<RenderTransitionRecord> {
+        reuse_payload(&mut self.transitions, items)
+    }
+
+    fn recycle(&mut self, entry: RenderCommandEntry) {
+        match entry {
+            RenderCommandEntry::Draw(RenderCommandDraw {
+                shader_arguments: Some(shader_arguments),
+                ..
+            })
+            | RenderCommandEntry::DrawIndirect(RenderCommandDrawIndirect {
+                shader_arguments: Some(shader_arguments),
+                ..
+            })
+            | RenderCommandEntry::Dispatch(RenderCommandDispatch {
+                shader_arguments: Some(shader_arguments),
+                ..
+            })
+            | RenderCommandEntry::DispatchIndirect(RenderCommandDispatchIndirect {
+                shader_arguments: Some(shader_arguments),
+                ..
+            }) => recycle_payload(&mut self.shader_arguments, shader_arguments),
+            RenderCommandEntry::Barriers(command) => {
+                recycle_payload(&mut self.barriers, command.barriers)
+            }
+            RenderCommandEntry::Transitions(command) => {
+                recycle_payload(&mut self.transitions, command.transitions)
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RenderScopeKind {
+    Event,
+    RenderPass,
+}
+
+/// Event or render pass opened by a `RenderCommandScope` that is still alive.
+#[derive(Clone, Copy, Debug)]
+struct RenderOpenScope {
+    kind: RenderScopeKind,
+    begin_id: RenderCommandId,
+
+    /// Events open before the scope began
+    event_depth: usize,
+}
+
+/// Commands recorded by value, with bulk data (i.e. buffer and texture updates) kept in a
+/// linear allocator.
+pub struct RenderCommandList {
+    commands: Vec<RenderCommandEntry>,
+    payloads: RenderCommandPayloads,
+    allocator: LinearAllocator,
+    handles: Arc<RenderConcurrentHandleAllocator>,
+    queue_type: RenderCommandQueueType,
+    filter: RenderCommandFilter,
+    scopes: Vec<RenderOpenScope>,
+    event_depth: usize,
+
+    /// Render passes begun with `begin_render_pass` and not ended yet
+    render_pass_depth: usize,
+}
+
+impl RenderCommandList {
+    /// Creates a command list whose storage grows past `size_bytes` as needed.
     pub fn new(
-        handles: Arc<RwLock<RenderResourceHandleAllocator>>,
+        handles: Arc<RenderConcurrentHandleAllocator>,
         size_bytes: usize,
         command_reserve: usize,
+    ) -> Result<Self> {
+        Self::with_mode(
+            handles,
+            size_bytes,
+            command_reserve,
+            LinearAllocatorMode::Growable,
+        )
+    }
+
+    /// Creates a command list, with `LinearAllocatorMode::Bounded` failing to record any
+    /// update whose data doesn't fit in `size_bytes`.
+    pub fn with_mode(
+        handles: Arc<RenderConcurrentHandleAllocator>,
+        size_bytes: usize,
+        command_reserve: usize,
+        mode: LinearAllocatorMode,
     ) -> Result<Self> {
         Ok(RenderCommandList {
             commands: Vec::with_capacity(command_reserve),
-            allocator: LinearAllocator::new(size_bytes),
+            payloads: Default::default(),
+            allocator: LinearAllocator::with_mode(size_bytes, mode),
             handles: Arc::clone(&handles),
             queue_type: RenderCommandQueueType::NONE,
-            draw_state: Default::default(),
-            draw_state_cache: None,
-            render_pass_active: false,
+            filter: Default::default(),
+            scopes: Vec::new(),
+            event_depth: 0,
+            render_pass_depth: 0,
         })
     }
 
     #[inline(always)]
-    pub fn get_commands(&self) -> &Vec<&'a dyn RenderCommand> {
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    #[inline(always)]
+    pub fn mark(&self) -> RenderCommandListMark {
+        RenderCommandListMark {
+            command_count: self.commands.len(),
+            allocator_mark: self.allocator.mark(),
+            queue_type: self.queue_type,
+            filter_stats: self.filter.stats,
+            event_depth: self.event_depth,
+            render_pass_depth: self.render_pass_depth,
+        }
+    }
+
+    /// Discards every command recorded since `mark` was taken.
+    pub fn rewind(&mut self, mark: RenderCommandListMark) -> Result<()> {
+        if mark.command_count > self.commands.len() || mark.allocator_mark > self.allocator.mark() {
+            return Err(Error::encoder(format!(
+                "cannot rewind command list forward - commands: {} vs {}",
+                mark.command_count,
+                self.commands.len()
+            )));
+        }
+        if let Some(scope) = self.scopes.last() {
+            if mark.command_count <= scope.begin_id {
+                return Err(Error::encoder(format!(
+                    "cannot rewind command list past the begin of an active {:?} scope - commands: {} vs {}",
+                    scope.kind, mark.command_count, scope.begin_id
+                )));
+            }
+        }
+        self.truncate_commands(mark.command_count);
+        self.allocator.rewind(mark.allocator_mark)?;
+        self.queue_type = mark.queue_type;
+        self.filter.invalidate();
+        self.filter.stats = mark.filter_stats;
+        self.event_depth = mark.event_depth;
+        self.render_pass_depth = mark.render_pass_depth;
+        Ok(())
+    }
+
+    /// Discards every recorded command, keeping the allocated storage for the next recording.
+    pub fn reset(&mut self) -> Result<()> {
+        if !self.scopes.is_empty() {
+            return Err(Error::encoder(format!(
+                "cannot reset command list with {} active scope(s)",
+                self.scopes.len()
+            )));
+        }
+        self.truncate_commands(0);
+        self.allocator.rewind(0)?;
+        self.queue_type = RenderCommandQueueType::NONE;
+        self.filter.invalidate();
+        self.filter.stats = Default::default();
+        self.event_depth = 0;
+        self.render_pass_depth = 0;
+        Ok(())
+    }
+
+    /// Discards the commands past `count`, keeping their payloads for reuse.
+    fn truncate_commands(&mut self, count: usize) {
+        for entry in self.commands.drain(count..) {
+            self.payloads.recycle(entry);
+        }
+    }
+
+    #[inline(always)]
+    fn push<C: RenderCommand + Into<RenderCommandEntry>>(
+        &mut self,
+        command: C,
+    ) -> Result<RenderCommandId> {
+        self.push_entry(command.into())
+    }
+
+    /// Records a command, unless it would break a scope opened by a `RenderCommandScope`.
+    fn push_entry(&mut self, entry: RenderCommandEntry) -> Result<RenderCommandId> {
+        let command_type = entry.get_type();
+        self.expect_placement(command_type)?;
+        match command_type {
+            RenderCommandType::BeginEvent => self.event_depth += 1,
+            RenderCommandType::EndEvent => {
+                if let Some(scope) = self.scopes.last() {
+                    let scope_depth = match scope.kind {
+                        RenderScopeKind::Event => scope.event_depth + 1,
+                        RenderScopeKind::RenderPass => scope.event_depth,
+                    };
+                    if self.event_depth <= scope_depth {
+                        return Err(Error::encoder(format!(
+                            "cannot end an event begun outside of the active {:?} scope",
+                            scope.kind
+                        )));
+                    }
+                }
+                self.event_depth = self.event_depth.saturating_sub(1);
+            }
+            RenderCommandType::BeginRenderPass => {
+                self.expect_render_pass_begin(false)?;
+                self.render_pass_depth += 1;
+            }
+            RenderCommandType::EndRenderPass if self.in_render_pass_scope() => {
+                return Err(Error::encoder(
+                    "render pass scopes are ended by dropping their guard",
+                ));
+            }
+            RenderCommandType::EndRenderPass if !self.scopes.is_empty() => {
+                return Err(Error::encoder(
+                    "cannot record EndRenderPass inside an event scope, use scoped_render_pass",
+                ));
+            }
+            RenderCommandType::EndRenderPass => {
+                self.render_pass_depth = self.render_pass_depth.saturating_sub(1);
+            }
+            _ => {}
+        }
+        Ok(self.append(entry))
+    }
+
+    #[inline(always)]
+    fn append(&mut self, entry: RenderCommandEntry) -> RenderCommandId {
+        if entry.get_type().invalidates_bindings() {
+            self.filter.invalidate();
+        }
+        self.queue_type.insert(entry.get_queue());
+        self.commands.push(entry);
+        self.commands.len() - 1
+    }
+
+    #[inline(always)]
+    fn in_render_pass_scope(&self) -> bool {
+        self.scopes
+            .iter()
+            .any(|scope| scope.kind == RenderScopeKind::RenderPass)
+    }
+
+    /// Fails if a render pass can't begin here, either from a `RenderCommandScope` (`scoped`)
+    /// or with `begin_render_pass`.
+    fn expect_render_pass_begin(&self, scoped: bool) -> Result<()> {
+        if self.in_render_pass_scope() || (scoped && self.render_pass_depth > 0) {
+            Err(Error::encoder("render passes cannot be nested"))
+        } else if !scoped && !self.scopes.is_empty() {
+            // The guard would end its event inside the render pass (or the pass inside it)
+            Err(Error::encoder(
+                "cannot record BeginRenderPass inside an event scope, use scoped_render_pass",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Fails for commands that cannot be recorded inside the active render pass scope, before
+    /// anything (i.e. update data) is allocated for them.
+    fn expect_placement(&self, command_type: RenderCommandType) -> Result<()> {
+        if render_pass_placement(command_type) == RenderPassPlacement::Outside
+            && self.in_render_pass_scope()
+        {
+            Err(Error::encoder(format!(
+                "{:?} is not allowed inside a render pass",
+                command_type
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Ends the scopes past `count`, innermost first, along with any event left open in them.
+    fn end_scopes(&mut self, count: usize) {
+        while self.scopes.len() > count {
+            let scope = self.scopes.pop().unwrap();
+            while self.event_depth > scope.event_depth {
+                self.event_depth -= 1;
+                self.append(RenderCommandEndEvent::new().into());
+            }
+            if scope.kind == RenderScopeKind::RenderPass {
+                self.append(RenderCommandEndRenderPass::new().into());
+            }
+        }
+    }
+
+    /// Begins an event that is ended when the returned guard is dropped.
+    pub fn scoped_event(
+        &mut self,
+        user_data: u32,
+        message: Cow<'static, str>,
+    ) -> Result<RenderCommandScope<'_>> {
+        let event_depth = self.event_depth;
+        let begin_id = self.begin_event(user_data, message)?;
+        Ok(self.begin_scope(RenderScopeKind::Event, begin_id, event_depth))
+    }
+
+    /// Begins a render pass that is ended when the returned guard is dropped; commands that
+    /// aren't allowed inside a render pass fail to record until then.
+    pub fn scoped_render_pass(
+        &mut self,
+        render_pass: RenderPassHandle,
+    ) -> Result<RenderCommandScope<'_>> {
+        self.expect_placement(RenderCommandType::BeginRenderPass)?;
+        self.expect_render_pass_begin(true)?;
+
+        // Appended past push_entry, which only accepts manual render passes outside of scopes
+        let event_depth = self.event_depth;
+        let begin_id = self.append(RenderCommandBeginRenderPass::new(render_pass.into()).into());
+        Ok(self.begin_scope(RenderScopeKind::RenderPass, begin_id, event_depth))
+    }
+
+    fn begin_scope(
+        &mut self,
+        kind: RenderScopeKind,
+        begin_id: RenderCommandId,
+        event_depth: usize,
+    ) -> RenderCommandScope<'_> {
+        let scope_index = self.scopes.len();
+        self.scopes.push(RenderOpenScope {
+            kind,
+            begin_id,
+            event_depth,
+        });
+        RenderCommandScope {
+            command_list: self,
+            scope_index,
+        }
+    }
+
+    #[inline(always)]
+    pub fn get_commands(&self) -> &[RenderCommandEntry] {
         &self.commands
     }
 
@@ -48,99 +510,198 @@ impl<'a> RenderCommandList<'a> {
         self.queue_type
     }
 
+    #[inline(always)]
+    pub fn get_allocator_stats(&self) -> LinearAllocatorStats {
+        self.allocator.stats()
+    }
+
+    #[inline(always)]
+    pub fn get_filter_stats(&self) -> RenderCommandFilterStats {
+        self.filter.stats
+    }
+
+    /// Collects the statistics of the recorded commands. The list doesn't know the primitive
+    /// type of its pipeline states, so `primitive_type` looks it up for each draw (i.e. from
+    /// the pipeline descriptions); unknown pipeline states count triangle lists.
+    pub fn get_stats(
+        &self,
+        primitive_type: impl Fn(RenderResourceHandle) -> Option<RenderPrimitiveType>,
+    ) -> RenderCommandListStats {
+        RenderCommandListStats::collect(self, primitive_type)
+    }
+
     #[inline(always)]
     pub fn get_command_data(&self, mark: LinearAllocatorMark, size: usize) -> Result<&[u8]> {
         Ok(self.allocator.mark_data(mark, size)?)
     }
 
+    /// Returns the buffer or texture data recorded with an update command.
+    pub fn get_entry_data(&self, entry: &RenderCommandEntry) -> Result<Option<&[u8]>> {
+        match entry {
+            RenderCommandEntry::UpdateBuffer(command) => {
+                Ok(Some(self.get_command_data(command.data, command.size)?))
+            }
+            RenderCommandEntry::UpdateTexture(command) => Ok(Some(
+                self.get_command_data(command.sub_data_mark, command.sub_data_size)?,
+            )),
+            _ => Ok(None),
+        }
+    }
+
+    /// Records a command copied from another command list (i.e. when deserializing), along
+    /// with the data of update commands.
+    pub fn record_entry(
+        &mut self,
+        entry: RenderCommandEntry,
+        data: Option<&[u8]>,
+    ) -> Result<RenderCommandId> {
+        match (entry, data) {
+            (RenderCommandEntry::UpdateBuffer(mut command), Some(data)) => {
+                self.expect_placement(RenderCommandType::UpdateBuffer)?;
+                if data.len() != command.size || !is_aligned(command.size, 4) {
+                    return Err(Error::encoder(format!(
+                        "invalid buffer update data - {} bytes for an update of {}",
+                        data.len(),
+                        command.size
+                    )));
+                }
+                command.data =
+                    self.allocator
+                        .allocate_raw(command.size, 16 /* for SIMD */, 0)?;
+                self.allocator.mark_insert(command.data, data)?;
+                self.push(command)
+            }
+            (RenderCommandEntry::UpdateTexture(mut command), Some(data)) => {
+                self.expect_placement(RenderCommandType::UpdateTexture)?;
+                if data.len() != command.sub_data_size {
+                    return Err(Error::encoder(format!(
+                        "invalid texture update data - {} bytes for an update of {}",
+                        data.len(),
+                        command.sub_data_size
+                    )));
+                }
+                command.sub_data_mark = self.allocator.allocate_raw(command.sub_data_size, 8, 0)?;
+                self.allocator.mark_insert(command.sub_data_mark, data)?;
+                self.push(command)
+            }
+            (RenderCommandEntry::UpdateBuffer(_), None)
+            | (RenderCommandEntry::UpdateTexture(_), None) => Err(Error::encoder(
+                "update commands must be recorded with their data",
+            )),
+            (entry, None) => {
+                match entry {
+                    // The copied state may be relative to commands this list hasn't seen
+                    RenderCommandEntry::Draw(_)
+                    | RenderCommandEntry::DrawIndirect(_)
+                    | RenderCommandEntry::Dispatch(_)
+                    | RenderCommandEntry::DispatchIndirect(_) => self.filter.invalidate(),
+                    _ => {}
+                }
+                self.push_entry(entry)
+            }
+            (entry, Some(_)) => Err(Error::encoder(format!(
+                "{:?} commands have no data",
+                entry.get_type()
+            ))),
+        }
+    }
+
+    /// Shader arguments to record with a draw or dispatch, `None` when already bound.
+    fn filtered_arguments(
+        &mut self,
+        bind_arguments: bool,
+        shader_arguments: &[RenderShaderArgument],
+    ) -> Option<Vec<RenderShaderArgument>> {
+        if bind_arguments {
+            Some(self.payloads.shader_arguments(shader_arguments))
+        } else {
+            None
+        }
+    }
+
     pub fn draw(
         &mut self,
-        pipeline_state: RenderResourceHandle,
+        pipeline_state: RenderGraphicsPipelineStateHandle,
         shader_arguments: &[RenderShaderArgument],
-        draw_binding: Option<RenderResourceHandle>,
+        draw_binding: Option<RenderDrawBindingSetHandle>,
         draw_state: &RenderDrawState,
         draw_packet: &RenderDrawPacket,
     ) -> Result<RenderCommandId> {
-        type CommandType = RenderCommandDraw;
-        let mark = self.allocator.allocate_typed::<CommandType>()?;
-        let cmd = self.allocator.mark_place::<CommandType>(
-            mark,
-            CommandType::new(
-                pipeline_state,
-                shader_arguments,
-                Some(*draw_state),
-                draw_binding,
-                *draw_packet,
-            ),
-        )?;
-        self.commands.push(cmd);
-        self.queue_type.insert(cmd.get_queue());
-        Ok(mark)
+        let pipeline_state = pipeline_state.into();
+        let draw_binding = draw_binding.map(Into::into);
+        let (bind_arguments, draw_state) =
+            self.filter
+                .bind_graphics(pipeline_state, shader_arguments, draw_binding, draw_state);
+        let shader_arguments = self.filtered_arguments(bind_arguments, shader_arguments);
+        self.push(RenderCommandDraw {
+            pipeline_state,
+            shader_arguments,
+            draw_state,
+            draw_binding,
+            draw_packet: *draw_packet,
+        })
     }
 
     pub fn draw_indirect(
         &mut self,
-        pipeline_state: RenderResourceHandle,
+        pipeline_state: RenderGraphicsPipelineStateHandle,
         shader_arguments: &[RenderShaderArgument],
-        draw_binding: RenderResourceHandle,
+        draw_binding: RenderDrawBindingSetHandle,
         draw_state: &RenderDrawState,
         primitive: RenderPrimitiveType,
-        indirect_buffer: RenderResourceHandle,
+        indirect_buffer: RenderBufferHandle,
         indirect_byte_offset: usize,
-        count_buffer: RenderResourceHandle,
+        count_buffer: RenderBufferHandle,
         count_byte_offset: usize,
         command_limit: u32,
     ) -> Result<RenderCommandId> {
-        type CommandType = RenderCommandDrawIndirect;
-        let mark = self.allocator.allocate_typed::<CommandType>()?;
-        let cmd = self.allocator.mark_place::<CommandType>(
-            mark,
-            CommandType::new(
-                pipeline_state,
-                shader_arguments,
-                Some(*draw_state),
-                draw_binding,
-                primitive,
-                indirect_buffer,
-                indirect_byte_offset,
-                count_buffer,
-                count_byte_offset,
-                command_limit,
-            ),
-        )?;
-        self.commands.push(cmd);
-        self.queue_type.insert(cmd.get_queue());
-        Ok(mark)
+        let pipeline_state = pipeline_state.into();
+        let draw_binding = draw_binding.into();
+        let (bind_arguments, draw_state) = self.filter.bind_graphics(
+            pipeline_state,
+            shader_arguments,
+            Some(draw_binding),
+            draw_state,
+        );
+        let shader_arguments = self.filtered_arguments(bind_arguments, shader_arguments);
+        self.push(RenderCommandDrawIndirect {
+            pipeline_state,
+            shader_arguments,
+            draw_state,
+            draw_binding,
+            primitive,
+            indirect_buffer: indirect_buffer.into(),
+            indirect_byte_offset,
+            count_buffer: count_buffer.into(),
+            count_byte_offset,
+            command_limit,
+        })
     }
 
     pub fn dispatch(
         &mut self,
-        pipeline_state: RenderResourceHandle,
+        pipeline_state: RenderComputePipelineStateHandle,
         shader_arguments: &[RenderShaderArgument],
         dispatch_x: u32,
         dispatch_y: u32,
         dispatch_z: u32,
     ) -> Result<RenderCommandId> {
-        type CommandType = RenderCommandDispatch;
-        let mark = self.allocator.allocate_typed::<CommandType>()?;
-        let cmd = self.allocator.mark_place::<CommandType>(
-            mark,
-            CommandType::new(
-                pipeline_state,
-                shader_arguments,
-                dispatch_x,
-                dispatch_y,
-                dispatch_z,
-            ),
-        )?;
-        self.commands.push(cmd);
-        self.queue_type.insert(cmd.get_queue());
-        Ok(mark)
+        self.expect_placement(RenderCommandType::Dispatch)?;
+        let pipeline_state = pipeline_state.into();
+        let bind_arguments = self.filter.bind_compute(pipeline_state, shader_arguments);
+        let shader_arguments = self.filtered_arguments(bind_arguments, shader_arguments);
+        self.push(RenderCommandDispatch {
+            pipeline_state,
+            shader_arguments,
+            dispatch_x,
+            dispatch_y,
+            dispatch_z,
+        })
     }
 
     pub fn dispatch_1d(
         &mut self,
-        pipeline_state: RenderResourceHandle,
+        pipeline_state: RenderComputePipelineStateHandle,
         shader_arguments: &[RenderShaderArgument],
         thread_count_x: u32,
         group_size_x: Option<u32>,
@@ -156,7 +717,7 @@ impl<'a> RenderCommandList<'a> {
 
     pub fn dispatch_2d(
         &mut self,
-        pipeline_state: RenderResourceHandle,
+        pipeline_state: RenderComputePipelineStateHandle,
         shader_arguments: &[RenderShaderArgument],
         thread_count_x: u32,
         thread_count_y: u32,
@@ -174,7 +735,7 @@ impl<'a> RenderCommandList<'a> {
 
     pub fn dispatch_3d(
         &mut self,
-        pipeline_state: RenderResourceHandle,
+        pipeline_state: RenderComputePipelineStateHandle,
         shader_arguments: &[RenderShaderArgument],
         thread_count_x: u32,
         thread_count_y: u32,
@@ -194,72 +755,65 @@ impl<'a> RenderCommandList<'a> {
 
     pub fn dispatch_indirect(
         &mut self,
-        pipeline_state: RenderResourceHandle,
+        pipeline_state: RenderComputePipelineStateHandle,
         shader_arguments: &[RenderShaderArgument],
-        indirect_buffer: RenderResourceHandle,
+        indirect_buffer: RenderBufferHandle,
         indirect_byte_offset: usize,
-        count_buffer: RenderResourceHandle,
+        count_buffer: RenderBufferHandle,
         count_byte_offset: usize,
         command_limit: u32,
     ) -> Result<RenderCommandId> {
-        type CommandType = RenderCommandDispatchIndirect;
-        let mark = self.allocator.allocate_typed::<CommandType>()?;
-        let cmd = self.allocator.mark_place::<CommandType>(
-            mark,
-            CommandType::new(
-                pipeline_state,
-                shader_arguments,
-                indirect_buffer,
-                indirect_byte_offset,
-                count_buffer,
-                count_byte_offset,
-                command_limit,
-            ),
-        )?;
-        self.commands.push(cmd);
-        self.queue_type.insert(cmd.get_queue());
-        Ok(mark)
+        self.expect_placement(RenderCommandType::DispatchIndirect)?;
+        let pipeline_state = pipeline_state.into();
+        let bind_arguments = self.filter.bind_compute(pipeline_state, shader_arguments);
+        let shader_arguments = self.filtered_arguments(bind_arguments, shader_arguments);
+        self.push(RenderCommandDispatchIndirect {
+            pipeline_state,
+            shader_arguments,
+            indirect_buffer: indirect_buffer.into(),
+            indirect_byte_offset,
+            count_buffer: count_buffer.into(),
+            count_byte_offset,
+            command_limit,
+        })
     }
 
     pub fn update_buffer(
         &mut self,
-        buffer: RenderResourceHandle,
+        buffer: RenderBufferHandle,
         offset: usize,
         data: &[u8],
     ) -> Result<RenderCommandId> {
+        self.expect_placement(RenderCommandType::UpdateBuffer)?;
         let aligned_len = align_forward(data.len(), 4); // Most APIs require multiple of 4 sizes
         let data_mark = self
             .allocator
             .allocate_raw(aligned_len, 16 /* for SIMD */, 0)?;
         self.allocator.mark_insert(data_mark, data)?;
         type CommandType = RenderCommandUpdateBuffer;
-        let mark = self.allocator.allocate_typed::<CommandType>()?;
-        let cmd = self.allocator.mark_place::<CommandType>(
-            mark,
-            CommandType::new(buffer, offset, aligned_len, data_mark),
-        )?;
-        self.commands.push(cmd);
-        self.queue_type.insert(cmd.get_queue());
-        Ok(mark)
+        self.push(CommandType::new(
+            buffer.into(),
+            offset,
+            aligned_len,
+            data_mark,
+        ))
     }
 
     pub fn update_buffer_mark(
         &mut self,
-        buffer: RenderResourceHandle,
+        buffer: RenderBufferHandle,
         offset: usize,
         data_size: usize,
         data_mark: LinearAllocatorMark,
     ) -> Result<RenderCommandId> {
         if is_aligned(data_size, 4) {
             type CommandType = RenderCommandUpdateBuffer;
-            let cmd_mark = self.allocator.allocate_typed::<CommandType>()?;
-            let cmd = self.allocator.mark_place::<CommandType>(
-                cmd_mark,
-                CommandType::new(buffer, offset, data_size, data_mark),
-            )?;
-            self.commands.push(cmd);
-            self.queue_type.insert(cmd.get_queue());
-            Ok(cmd_mark)
+            self.push(CommandType::new(
+                buffer.into(),
+                offset,
+                data_size,
+                data_mark,
+            ))
         } else {
             Err(Error::backend(format!(
                 "Buffer update sizes must be a multiple of 4 - {} vs {}",
@@ -271,149 +825,108 @@ impl<'a> RenderCommandList<'a> {
 
     pub fn update_texture(
         &mut self,
-        texture: RenderResourceHandle,
+        texture: RenderTextureHandle,
         sub_resource: u16,
         sub_row_pitch: u32,
         sub_slice_pitch: u32,
         sub_data: &[u8],
     ) -> Result<RenderCommandId> {
+        self.expect_placement(RenderCommandType::UpdateTexture)?;
         let sub_data_size = sub_data.len(); // TODO: Check alignment restrictions similar to update_buffer
         let sub_data_mark = self.allocator.allocate_raw(sub_data_size, 8, 0)?;
         self.allocator.mark_insert(sub_data_mark, sub_data)?;
         type CommandType = RenderCommandUpdateTexture;
-        let mark = self.allocator.allocate_typed::<CommandType>()?;
-        let cmd = self.allocator.mark_place::<CommandType>(
-            mark,
-            CommandType::new(
-                texture,
-                sub_resource,
-                sub_row_pitch,
-                sub_slice_pitch,
-                sub_data_size,
-                sub_data_mark,
-            ),
-        )?;
-        self.commands.push(cmd);
-        self.queue_type.insert(cmd.get_queue());
-        Ok(mark)
+        self.push(CommandType::new(
+            texture.into(),
+            sub_resource,
+            sub_row_pitch,
+            sub_slice_pitch,
+            sub_data_size,
+            sub_data_mark,
+        ))
     }
 
     pub fn copy_buffer(
         &mut self,
-        src_buffer: RenderResourceHandle,
+        src_buffer: RenderBufferHandle,
         src_offset: usize,
         src_size: usize,
-        dst_buffer: RenderResourceHandle,
+        dst_buffer: RenderBufferHandle,
         dst_offset: usize,
     ) -> Result<RenderCommandId> {
         type CommandType = RenderCommandCopyBuffer;
-        let mark = self.allocator.allocate_typed::<CommandType>()?;
-        let cmd = self.allocator.mark_place::<CommandType>(
-            mark,
-            CommandType::new(src_buffer, src_offset, src_size, dst_buffer, dst_offset),
-        )?;
-        self.commands.push(cmd);
-        self.queue_type.insert(cmd.get_queue());
-        Ok(mark)
+        self.push(CommandType::new(
+            src_buffer.into(),
+            src_offset,
+            src_size,
+            dst_buffer.into(),
+            dst_offset,
+        ))
     }
 
     pub fn copy_texture(
         &mut self,
-        src_texture: RenderResourceHandle,
+        src_texture: RenderTextureHandle,
         src_sub_resource: u16,
         src_box: RenderBox,
-        dst_texture: RenderResourceHandle,
+        dst_texture: RenderTextureHandle,
         dst_sub_resource: u16,
         dst_point: RenderPoint,
     ) -> Result<RenderCommandId> {
         type CommandType = RenderCommandCopyTexture;
-        let mark = self.allocator.allocate_typed::<CommandType>()?;
-        let cmd = self.allocator.mark_place::<CommandType>(
-            mark,
-            CommandType::new(
-                src_texture,
-                src_sub_resource,
-                src_box,
-                dst_texture,
-                dst_sub_resource,
-                dst_point,
-            ),
-        )?;
-        self.commands.push(cmd);
-        self.queue_type.insert(cmd.get_queue());
-        Ok(mark)
+        self.push(CommandType::new(
+            src_texture.into(),
+            src_sub_resource,
+            src_box,
+            dst_texture.into(),
+            dst_sub_resource,
+            dst_point,
+        ))
     }
 
     pub fn barriers(&mut self, barriers: &[RenderResourceHandle]) -> Result<RenderCommandId> {
-        type CommandType = RenderCommandBarriers;
-        let mark = self.allocator.allocate_typed::<CommandType>()?;
-        let cmd = self
-            .allocator
-            .mark_place::<CommandType>(mark, CommandType::new(barriers))?;
-        self.commands.push(cmd);
-        self.queue_type.insert(cmd.get_queue());
-        Ok(mark)
+        let barriers = self.payloads.barriers(barriers);
+        self.push(RenderCommandBarriers { barriers })
     }
 
     pub fn transitions(
         &mut self,
         transitions: &[RenderTransitionRecord],
     ) -> Result<RenderCommandId> {
-        type CommandType = RenderCommandTransitions;
-        let mark = self.allocator.allocate_typed::<CommandType>()?;
-        let cmd = self
-            .allocator
-            .mark_place::<CommandType>(mark, CommandType::new(transitions))?;
-        self.commands.push(cmd);
-        self.queue_type.insert(cmd.get_queue());
-        Ok(mark)
+        let transitions = self.payloads.transitions(transitions);
+        self.push(RenderCommandTransitions { transitions })
     }
 
     pub fn begin_timing(
         &mut self,
-        timing_heap: RenderResourceHandle,
+        timing_heap: RenderTimingHeapHandle,
         region: u32,
     ) -> Result<RenderCommandId> {
         type CommandType = RenderCommandBeginTiming;
-        let mark = self.allocator.allocate_typed::<CommandType>()?;
-        let cmd = self
-            .allocator
-            .mark_place::<CommandType>(mark, CommandType::new(timing_heap, region))?;
-        self.commands.push(cmd);
-        self.queue_type.insert(cmd.get_queue());
-        Ok(mark)
+        self.push(CommandType::new(timing_heap.into(), region))
     }
 
     pub fn end_timing(
         &mut self,
-        timing_heap: RenderResourceHandle,
+        timing_heap: RenderTimingHeapHandle,
         region: u32,
     ) -> Result<RenderCommandId> {
         type CommandType = RenderCommandEndTiming;
-        let mark = self.allocator.allocate_typed::<CommandType>()?;
-        let cmd = self
-            .allocator
-            .mark_place::<CommandType>(mark, CommandType::new(timing_heap, region))?;
-        self.commands.push(cmd);
-        self.queue_type.insert(cmd.get_queue());
-        Ok(mark)
+        self.push(CommandType::new(timing_heap.into(), region))
     }
 
     pub fn resolve_timings(
         &mut self,
-        timing_heap: RenderResourceHandle,
+        timing_heap: RenderTimingHeapHandle,
         region_start: u32,
         region_count: u32,
     ) -> Result<RenderCommandId> {
         type CommandType = RenderCommandResolveTimings;
-        let mark = self.allocator.allocate_typed::<CommandType>()?;
-        let cmd = self.allocator.mark_place::<CommandType>(
-            mark,
-            CommandType::new(timing_heap, region_start, region_count),
-        )?;
-        self.commands.push(cmd);
-        self.queue_type.insert(cmd.get_queue());
-        Ok(mark)
+        self.push(CommandType::new(
+            timing_heap.into(),
+            region_start,
+            region_count,
+        ))
     }
 
     pub fn begin_event(
@@ -422,120 +935,172 @@ impl<'a> RenderCommandList<'a> {
         message: Cow<'static, str>,
     ) -> Result<RenderCommandId> {
         type CommandType = RenderCommandBeginEvent;
-        let mark = self.allocator.allocate_typed::<CommandType>()?;
-        let cmd = self
-            .allocator
-            .mark_place::<CommandType>(mark, CommandType::new(user_data, message))?;
-        self.commands.push(cmd);
-        self.queue_type.insert(cmd.get_queue());
-        Ok(mark)
+        self.push(CommandType::new(user_data, message))
     }
 
     pub fn end_event(&mut self) -> Result<RenderCommandId> {
         type CommandType = RenderCommandEndEvent;
-        let mark = self.allocator.allocate_typed::<CommandType>()?;
-        let cmd = self
-            .allocator
-            .mark_place::<CommandType>(mark, CommandType::new())?;
-        self.commands.push(cmd);
-        self.queue_type.insert(cmd.get_queue());
-        Ok(mark)
+        self.push(CommandType::new())
     }
 
-    pub fn begin_render_pass(
-        &mut self,
-        render_pass: RenderResourceHandle,
-    ) -> Result<RenderCommandId> {
+    pub fn begin_render_pass(&mut self, render_pass: RenderPassHandle) -> Result<RenderCommandId> {
         type CommandType = RenderCommandBeginRenderPass;
-        let mark = self.allocator.allocate_typed::<CommandType>()?;
-        let cmd = self
-            .allocator
-            .mark_place::<CommandType>(mark, CommandType::new(render_pass))?;
-        self.commands.push(cmd);
-        self.queue_type.insert(cmd.get_queue());
-        Ok(mark)
+        self.push(CommandType::new(render_pass.into()))
     }
 
     pub fn end_render_pass(&mut self) -> Result<RenderCommandId> {
         type CommandType = RenderCommandEndRenderPass;
-        let mark = self.allocator.allocate_typed::<CommandType>()?;
-        let cmd = self
-            .allocator
-            .mark_place::<CommandType>(mark, CommandType::new())?;
-        self.commands.push(cmd);
-        self.queue_type.insert(cmd.get_queue());
-        Ok(mark)
+        self.push(CommandType::new())
     }
 
     pub fn ray_trace(
         &mut self,
-        pipeline_state: RenderResourceHandle,
-        shader_table: RenderResourceHandle,
+        pipeline_state: RenderRayTracingPipelineStateHandle,
+        shader_table: RenderRayTracingShaderTableHandle,
         rt_output: RenderResourceHandle, // TODO: Eliminate this once we have a better way to specify explicit transitions
         width: u32,
         height: u32,
         ray_gen_index: u32,
     ) -> Result<RenderCommandId> {
         type CommandType = RenderCommandRayTrace;
-        let mark = self.allocator.allocate_typed::<CommandType>()?;
-        let cmd = self.allocator.mark_place::<CommandType>(
-            mark,
-            CommandType::new(
-                pipeline_state,
-                shader_table,
-                rt_output,
-                width,
-                height,
-                ray_gen_index,
-            ),
-        )?;
-        self.commands.push(cmd);
-        self.queue_type.insert(cmd.get_queue());
-        Ok(mark)
+        self.push(CommandType::new(
+            pipeline_state.into(),
+            shader_table.into(),
+            rt_output,
+            width,
+            height,
+            ray_gen_index,
+        ))
     }
 
     pub fn update_top_level_acceleration(
         &mut self,
-        acceleration: RenderResourceHandle,
+        acceleration: RenderRayTracingAccelerationHandle,
         desc: RenderAccelerationTopDesc,
     ) -> Result<RenderCommandId> {
         type CommandType = RenderCommandUpdateTopLevelAcceleration;
-        let mark = self.allocator.allocate_typed::<CommandType>()?;
-        let cmd = self
-            .allocator
-            .mark_place::<CommandType>(mark, CommandType::new(acceleration, desc))?;
-        self.commands.push(cmd);
-        self.queue_type.insert(cmd.get_queue());
-        Ok(mark)
+        self.push(CommandType::new(acceleration.into(), desc))
     }
 
     pub fn update_bottom_level_acceleration(
         &mut self,
-        acceleration: RenderResourceHandle,
+        acceleration: RenderRayTracingAccelerationHandle,
         refit: bool,
     ) -> Result<RenderCommandId> {
         type CommandType = RenderCommandUpdateBottomLevelAcceleration;
-        let mark = self.allocator.allocate_typed::<CommandType>()?;
-        let cmd = self
-            .allocator
-            .mark_place::<CommandType>(mark, CommandType::new(acceleration, refit))?;
-        self.commands.push(cmd);
-        self.queue_type.insert(cmd.get_queue());
-        Ok(mark)
+        self.push(CommandType::new(acceleration.into(), refit))
     }
 
     pub fn update_shader_table(
         &mut self,
-        shader_table: RenderResourceHandle,
+        shader_table: RenderRayTracingShaderTableHandle,
         desc: RenderShaderTableUpdateDesc,
     ) -> Result<RenderCommandId> {
         type CommandType = RenderCommandUpdateShaderTable;
-        let mark = self.allocator.allocate_typed::<CommandType>()?;
-        let cmd = self
-            .allocator
-            .mark_place::<CommandType>(mark, CommandType::new(shader_table, desc))?;
-        self.commands.push(cmd);
-        self.queue_type.insert(cmd.get_queue());
-        Ok(mark)
+        self.push(CommandType::new(shader_table.into(), desc))
+    }
+}
+
+/// Guard returned by `RenderCommandList::scoped_event` and `scoped_render_pass`, recording
+/// the end of its scope when dropped (i.e. on an early return with `?`).
+///
+/// The command list stays usable through the guard, and nested scopes are opened from it.
+pub struct RenderCommandScope<'a> {
+    command_list: &'a mut RenderCommandList,
+    scope_index: usize,
+}
+
+impl<'a> Deref for RenderCommandScope<'a> {
+    type Target = RenderCommandList;
+
+    fn deref(&self) -> &RenderCommandList {
+        self.command_list
+    }
+}
+
+impl<'a> DerefMut for RenderCommandScope<'a> {
+    fn deref_mut(&mut self) -> &mut RenderCommandList {
+        self.command_list
+    }
+}
+
+impl<'a> Drop for RenderCommandScope<'a> {
+    fn drop(&mut self) {
+        self.command_list.end_scopes(self.scope_index);
+    }
+}
+
+/// Command lists kept for reuse by a recording thread, so their storage is allocated once
+/// rather than every frame.
+pub struct RenderCommandListPool {
+    handles: Arc<RenderConcurrentHandleAllocator>,
+    size_bytes: usize,
+    command_reserve: usize,
+    mode: LinearAllocatorMode,
+    free: Vec<RenderCommandList>,
+}
+
+impl RenderCommandListPool {
+    pub fn new(
+        handles: Arc<RenderConcurrentHandleAllocator>,
+        size_bytes: usize,
+        command_reserve: usize,
+    ) -> Self {
+        Self::with_mode(
+            handles,
+            size_bytes,
+            command_reserve,
+            LinearAllocatorMode::Growable,
+        )
+    }
+
+    pub fn with_mode(
+        handles: Arc<RenderConcurrentHandleAllocator>,
+        size_bytes: usize,
+        command_reserve: usize,
+        mode: LinearAllocatorMode,
+    ) -> Self {
+        RenderCommandListPool {
+            handles,
+            size_bytes,
+            command_reserve,
+            mode,
+            free: Vec::new(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn free_count(&self) -> usize {
+        self.free.len()
+    }
+
+    /// Returns an empty command list, reusing a released one when available.
+    pub fn acquire(&mut self) -> Result<RenderCommandList> {
+        match self.free.pop() {
+            Some(list) => Ok(list),
+            None => RenderCommandList::with_mode(
+                Arc::clone(&self.handles),
+                self.size_bytes,
+                self.command_reserve,
+                self.mode,
+            ),
+        }
+    }
+
+    /// Resets `list` and keeps it for a later `acquire`.
+    pub fn release(&mut self, mut list: RenderCommandList) -> Result<()> {
+        if !Arc::ptr_eq(&list.handles, &self.handles) {
+            return Err(Error::encoder(
+                "command list was not created with the pool's handle allocator",
+            ));
+        }
+        list.reset()?;
+        self.free.push(list);
+        Ok(())
+    }
+
+    /// Frees all but `count` of the released command lists.
+    pub fn trim(&mut self, count: usize) {
+        self.free.truncate(count);
     }
 }
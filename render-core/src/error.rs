@@ -1,7 +1,9 @@
 #![allow(dead_code)]
 
+use crate::handles::RenderResourceHandle;
 use failure::{Backtrace, Context, Fail};
 use std::fmt;
+use std::io;
 use std::path::{Path, PathBuf};
 use std::result;
 
@@ -24,6 +26,14 @@ impl Error {
         Error::from(ErrorKind::Backend(msg.as_ref().to_string()))
     }
 
+    pub fn device<T: AsRef<str>>(msg: T) -> Error {
+        Error::from(ErrorKind::Device(msg.as_ref().to_string()))
+    }
+
+    pub fn stale_handle(handle: RenderResourceHandle) -> Error {
+        Error::from(ErrorKind::StaleHandle(handle))
+    }
+
     pub fn encoder<T: AsRef<str>>(msg: T) -> Error {
         Error::from(ErrorKind::Encoder(msg.as_ref().to_string()))
     }
@@ -47,6 +57,10 @@ impl Error {
     pub fn number<E: Fail>(err: E) -> Error {
         Error::from(err.context(ErrorKind::Number))
     }
+
+    pub fn io(err: io::Error) -> Error {
+        Error::from(err.context(ErrorKind::Io))
+    }
 }
 
 impl Fail for Error {
@@ -66,7 +80,7 @@ impl fmt::Display for Error {
 }
 
 /// The specific kind of error that can occur.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum ErrorKind {
     /// An error that occurred while interacting with a render backend
     Backend(String),
@@ -74,6 +88,12 @@ pub enum ErrorKind {
     /// An error that occurred while interacting with a render device
     Device(String),
 
+    /// Errors that occurred on individual devices of a device group, by device index
+    DeviceGroup(Vec<(usize, ErrorKind)>),
+
+    /// A handle was used after its resource was destroyed (its cookie is from an older generation)
+    StaleHandle(RenderResourceHandle),
+
     /// An error that occurred while encoding render commands
     Encoder(String),
 
@@ -120,6 +140,20 @@ impl fmt::Display for ErrorKind {
         match *self {
             ErrorKind::Backend(ref msg) => write!(f, "backend error: {}", msg),
             ErrorKind::Device(ref msg) => write!(f, "device error: {}", msg),
+            ErrorKind::DeviceGroup(ref errors) => {
+                write!(f, "device group error:")?;
+                for (device_index, kind) in errors {
+                    write!(f, " [device {}] {};", device_index, kind)?;
+                }
+                Ok(())
+            }
+            ErrorKind::StaleHandle(ref handle) => write!(
+                f,
+                "stale {:?} handle: id {}, cookie {}",
+                handle.get_type(),
+                handle.get_id(),
+                handle.get_cookie()
+            ),
             ErrorKind::Encoder(ref msg) => write!(f, "encoder error: {}", msg),
             ErrorKind::Memory(ref msg) => write!(f, "memory error: {}", msg),
             ErrorKind::Parse(ref msg) => write!(f, "parse error: {}", msg),
@@ -261,6 +261,7 @@ impl Into<RenderNumericFormat> for RenderFormat {
 }
 
 bitflags! {
+    #[derive(Serialize, Deserialize)]
     pub struct RenderFormatCapability: u32 {
         /// The format is unsupported
         const UNSUPPORTED = 0;
@@ -1,18 +1,24 @@
 #![allow(dead_code)]
 
+use crate::error::{Error, Result};
 use crate::types::RenderResourceType;
 use enum_count::EnumCount;
 use enum_primitive::FromPrimitive;
 use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::fmt;
 use std::hash::{Hash, Hasher};
-use std::ops::{Shl, Shr};
+use std::ops::{Deref, Shl, Shr};
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicU32, AtomicU64, AtomicUsize, Ordering};
 
-type RenderResourceId = u16;
-type RenderResourceCookie = u16;
+/// Index of a handle within its resource type
+pub type RenderResourceId = u32;
 
-// TODO: Add bit-wise limits
+/// Generation of a handle index, bumped each time the index is released
+pub type RenderResourceCookie = u32;
 
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Serialize, Deserialize)]
 pub struct RenderResourceHandle {
     index: RenderResourceId,
     kind: RenderResourceType,
@@ -23,9 +29,9 @@ impl Default for RenderResourceHandle {
     #[inline(always)]
     fn default() -> RenderResourceHandle {
         RenderResourceHandle {
-            index: 0u16,
+            index: 0,
             kind: RenderResourceType::Buffer,
-            cookie: 0u16,
+            cookie: 0,
         }
     }
 }
@@ -37,6 +43,7 @@ impl Hash for RenderResourceHandle {
     {
         self.index.hash(state);
         self.kind.hash(state);
+        self.cookie.hash(state);
     }
 }
 
@@ -84,97 +91,219 @@ impl RenderResourceHandle {
         self.cookie
     }
 
+    /// Packs the resource type (upper 32 bits) and index (lower 32 bits), without the cookie.
     #[inline(always)]
-    pub fn get_packed(&self) -> u32 {
-        let type_val = self.get_type() as u32;
-        type_val.shl(16) | self.get_id() as u32
+    pub fn get_packed(&self) -> u64 {
+        let type_val = self.get_type() as u64;
+        type_val.shl(32) | u64::from(self.get_id())
     }
 
+    /// Reverses `get_packed`, failing if the upper 32 bits are not a valid resource type.
     #[inline(always)]
-    pub fn from_packed(packed: u32, cookie: u16) -> RenderResourceHandle {
-        let type_val = (packed & 0xffff0000).shr(16) as u16;
-        let id_val = (packed & 0x0000ffff) as u16;
-        match RenderResourceType::from_u16(type_val) {
-            Some(kind) => RenderResourceHandle::new(id_val, kind, cookie),
-            None => {
-                assert!(
-                    false,
-                    "failed to create render resource handle from packed value: {}",
+    pub fn from_packed(packed: u64, cookie: RenderResourceCookie) -> Result<RenderResourceHandle> {
+        let type_val = packed.shr(32);
+        let id_val = (packed & 0xffff_ffff) as RenderResourceId;
+        u16::try_from(type_val)
+            .ok()
+            .and_then(RenderResourceType::from_u16)
+            .map(|kind| RenderResourceHandle::new(id_val, kind, cookie))
+            .ok_or_else(|| {
+                Error::parse(format!(
+                    "failed to create render resource handle from packed value: {:#x}",
                     packed
-                );
-                RenderResourceHandle::new(0, RenderResourceType::Buffer, 0)
-            }
-        }
+                ))
+            })
     }
 
     #[inline(always)]
     pub fn is_valid(&self) -> bool {
-        if self.index == 0u16 && self.kind == RenderResourceType::Buffer && self.cookie == 0u16 {
-            false
-        } else {
-            true
-        }
+        self.index != 0 || self.kind != RenderResourceType::Buffer || self.cookie != 0
+    }
+}
+
+/// A `RenderResourceHandle` known to refer to one resource type.
+///
+/// Typed handles convert to the untyped handle with `From`/`Into` (or deref to it), and back
+/// with `TryFrom`, which fails if the handle refers to a different resource type.
+pub trait RenderTypedHandle:
+    Copy + fmt::Debug + Into<RenderResourceHandle> + Deref<Target = RenderResourceHandle>
+{
+    const RESOURCE_TYPE: RenderResourceType;
+
+    /// Wraps `handle`, failing if it refers to a different resource type.
+    fn from_handle(handle: RenderResourceHandle) -> Result<Self>;
+
+    fn handle(&self) -> RenderResourceHandle {
+        **self
     }
 }
 
-#[derive(Default, Debug)]
+macro_rules! typed_handles {
+    ($($name:ident => $kind:ident),* $(,)*) => {
+        $(
+            #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Hash, Serialize, Deserialize)]
+            #[repr(transparent)]
+            pub struct $name(RenderResourceHandle);
+
+            impl RenderTypedHandle for $name {
+                const RESOURCE_TYPE: RenderResourceType = RenderResourceType::$kind;
+
+                fn from_handle(handle: RenderResourceHandle) -> Result<Self> {
+                    if handle.get_type() == Self::RESOURCE_TYPE {
+                        Ok($name(handle))
+                    } else {
+                        Err(Error::backend(format!(
+                            "expected a {:?} handle, got {:?}",
+                            Self::RESOURCE_TYPE,
+                            handle
+                        )))
+                    }
+                }
+            }
+
+            impl Deref for $name {
+                type Target = RenderResourceHandle;
+
+                #[inline(always)]
+                fn deref(&self) -> &RenderResourceHandle {
+                    &self.0
+                }
+            }
+
+            impl From<$name> for RenderResourceHandle {
+                #[inline(always)]
+                fn from(handle: $name) -> RenderResourceHandle {
+                    handle.0
+                }
+            }
+
+            impl TryFrom<RenderResourceHandle> for $name {
+                type Error = Error;
+
+                fn try_from(handle: RenderResourceHandle) -> Result<Self> {
+                    $name::from_handle(handle)
+                }
+            }
+
+            impl PartialEq<RenderResourceHandle> for $name {
+                fn eq(&self, other: &RenderResourceHandle) -> bool {
+                    self.0 == *other
+                }
+            }
+
+            impl PartialEq<$name> for RenderResourceHandle {
+                fn eq(&self, other: &$name) -> bool {
+                    *self == other.0
+                }
+            }
+        )*
+    };
+}
+
+typed_handles! {
+    RenderSwapChainHandle => SwapChain,
+    RenderBufferHandle => Buffer,
+    RenderTextureHandle => Texture,
+    RenderSamplerStateHandle => SamplerState,
+    RenderShaderHandle => Shader,
+    RenderShaderViewsHandle => ShaderViews,
+    RenderGraphicsPipelineStateHandle => GraphicsPipelineState,
+    RenderComputePipelineStateHandle => ComputePipelineState,
+    RenderRayTracingGeometryHandle => RayTracingGeometry,
+    RenderRayTracingProgramHandle => RayTracingProgram,
+    RenderRayTracingAccelerationHandle => RayTracingAcceleration,
+    RenderRayTracingPipelineStateHandle => RayTracingPipelineState,
+    RenderRayTracingShaderTableHandle => RayTracingShaderTable,
+    RenderDrawBindingSetHandle => DrawBindingSet,
+    RenderFrameBindingSetHandle => FrameBindingSet,
+    RenderPassHandle => RenderPass,
+    RenderCommandListHandle => CommandList,
+    RenderFenceHandle => Fence,
+    RenderTimingHeapHandle => TimingHeap,
+}
+
+#[derive(Debug)]
 pub struct RenderResourceHandleAllocator {
-    cookies: Vec<u16>,
-    unallocated: Vec<VecDeque<u16>>,
+    limit: usize,
+    cookies: Vec<Vec<RenderResourceCookie>>,
+    unallocated: Vec<VecDeque<RenderResourceId>>,
     allocated: Vec<VecDeque<u8>>,
 }
 
+impl Default for RenderResourceHandleAllocator {
+    fn default() -> Self {
+        RenderResourceHandleAllocator::new()
+    }
+}
+
 impl RenderResourceHandleAllocator {
     #[inline(always)]
     pub fn new() -> RenderResourceHandleAllocator {
-        let cookie_size = RenderResourceType::count() * RenderResourceCookie::max_value() as usize;
+        RenderResourceHandleAllocator::with_limit(RenderResourceId::MAX as usize)
+    }
+
+    /// Creates an allocator handing out at most `limit` handle indices per resource type.
+    pub fn with_limit(limit: usize) -> RenderResourceHandleAllocator {
         RenderResourceHandleAllocator {
-            cookies: vec![1u16; cookie_size],
+            limit: limit.min(RenderResourceId::MAX as usize),
+            cookies: vec![Vec::new(); RenderResourceType::count()],
             unallocated: vec![VecDeque::new(); RenderResourceType::count()],
             allocated: vec![VecDeque::new(); RenderResourceType::count()],
         }
     }
 
+    /// Allocates a handle, reusing a released index if there is one.
+    ///
+    /// Fails once every index of the resource type is in use (or retired, see `release`).
     #[inline(always)]
-    pub fn allocate(&mut self, kind: RenderResourceType) -> RenderResourceHandle {
+    pub fn allocate(&mut self, kind: RenderResourceType) -> Result<RenderResourceHandle> {
         let tracking = kind as usize;
-        let mut handle = RenderResourceHandle::new(0, kind, 0u16);
-        let mut valid_handle = false;
-        if self.unallocated[tracking].len() > 0 {
-            handle.set_id(self.unallocated[tracking].pop_back().unwrap());
-            valid_handle = true;
-        } else {
-            if self.allocated[tracking].len() < RenderResourceId::max_value() as usize {
+        let index = match self.unallocated[tracking].pop_back() {
+            Some(index) => index,
+            None => {
+                let index = self.allocated[tracking].len();
+                if index >= self.limit {
+                    return Err(Error::memory(format!(
+                        "exhausted {:?} handles (limit {})",
+                        kind, self.limit
+                    )));
+                }
                 self.allocated[tracking].push_back(0);
-                handle.set_id(self.allocated[tracking].len() as u16 - 1);
-                valid_handle = true;
+                self.cookies[tracking].push(1);
+                index as RenderResourceId
             }
-        }
+        };
 
-        // TODO: Error handling around valid_handle
-        if valid_handle {
-            let handle_id = handle.get_id() as usize;
-            if handle_id as usize >= self.allocated[tracking].len() {
-                self.allocated[tracking].resize(handle_id + 1, 0);
-            }
-            assert!(self.allocated[tracking][handle_id] == 0);
-            self.allocated[tracking][handle_id] = 1;
-            let cookie = self.get_cookie(handle.get_id(), handle.get_type());
-            assert!(cookie != 0);
-            handle.set_cookie(cookie);
-        }
+        let slot = index as usize;
+        assert!(self.allocated[tracking][slot] == 0);
+        self.allocated[tracking][slot] = 1;
+        let cookie = self.get_cookie(index, kind);
+        assert!(cookie != 0);
+        Ok(RenderResourceHandle::new(index, kind, cookie))
+    }
 
-        handle
+    /// Allocates a handle of the resource type of `H`.
+    #[inline(always)]
+    pub fn allocate_typed<H: RenderTypedHandle>(&mut self) -> Result<H> {
+        H::from_handle(self.allocate(H::RESOURCE_TYPE)?)
     }
 
+    /// Releases `handle`, bumping the cookie of its index so any copies of it go stale.
+    ///
+    /// An index whose cookie would overflow is retired rather than wrapped, so stale copies can
+    /// never become valid again.
     #[inline(always)]
     pub fn release(&mut self, handle: RenderResourceHandle) {
         assert!(self.is_valid(&handle), "Attempting to free invalid handle.");
         let tracking = handle.get_type() as usize;
         assert!(tracking < self.unallocated.len() && tracking < self.allocated.len());
-        let _cookie = self.inc_cookie(handle.get_id(), handle.get_type());
         self.allocated[tracking][handle.get_id() as usize] = 0;
-        self.unallocated[tracking].push_back(handle.get_id());
+        if self
+            .inc_cookie(handle.get_id(), handle.get_type())
+            .is_some()
+        {
+            self.unallocated[tracking].push_back(handle.get_id());
+        }
     }
 
     #[inline(always)]
@@ -193,10 +322,28 @@ impl RenderResourceHandleAllocator {
         }
     }
 
+    /// True if `handle` is allocated and from the current generation of its index.
     #[inline(always)]
     pub fn is_valid(&self, handle: &RenderResourceHandle) -> bool {
         let cookie = self.get_cookie(handle.get_id(), handle.get_type());
-        cookie == handle.get_cookie()
+        cookie == handle.get_cookie() && self.is_allocated(handle.get_id(), handle.get_type())
+    }
+
+    /// Like `is_valid`, but returns a stale handle error for handles to an index that has since
+    /// been released (and possibly reused).
+    pub fn check(&self, handle: &RenderResourceHandle) -> Result<()> {
+        if self.is_valid(handle) {
+            Ok(())
+        } else if handle.get_cookie() != 0
+            && (handle.get_id() as usize) < self.get_max(handle.get_type())
+        {
+            Err(Error::stale_handle(*handle))
+        } else {
+            Err(Error::backend(format!(
+                "resource handle was never allocated: {:?}",
+                handle
+            )))
+        }
     }
 
     #[inline(always)]
@@ -220,32 +367,375 @@ impl RenderResourceHandleAllocator {
         self.allocated[tracking].len()
     }
 
+    /// Returns every live handle of a resource type, ordered by id.
+    pub fn get_handles(&self, kind: RenderResourceType) -> Vec<RenderResourceHandle> {
+        let tracking = kind as usize;
+        assert!(tracking < self.unallocated.len() && tracking < self.allocated.len());
+        let mut handles = Vec::with_capacity(self.get_count(kind));
+        for (index, entry) in self.allocated[tracking].iter().enumerate() {
+            if *entry > 0 {
+                let index = index as RenderResourceId;
+                handles.push(RenderResourceHandle::new(
+                    index,
+                    kind,
+                    self.get_cookie(index, kind),
+                ));
+            }
+        }
+        handles
+    }
+
+    /// Current cookie of an index, or 0 if the index was never allocated.
     #[inline(always)]
     pub(crate) fn get_cookie(
         &self,
         index: RenderResourceId,
         kind: RenderResourceType,
     ) -> RenderResourceCookie {
-        let offset = kind as usize * RenderResourceCookie::max_value() as usize;
-        self.cookies[offset + index as usize]
+        self.cookies[kind as usize]
+            .get(index as usize)
+            .cloned()
+            .unwrap_or(0)
     }
 
+    /// Bumps the cookie of an index, or returns None (leaving it unchanged) if it would overflow.
     #[inline(always)]
     pub(crate) fn inc_cookie(
         &mut self,
         index: RenderResourceId,
         kind: RenderResourceType,
-    ) -> RenderResourceCookie {
-        let offset = kind as usize * RenderResourceCookie::max_value() as usize;
-        let mut cookie = self.cookies[offset + index as usize];
+    ) -> Option<RenderResourceCookie> {
+        let cookie = &mut self.cookies[kind as usize][index as usize];
+        *cookie = cookie.checked_add(1)?;
+        Some(*cookie)
+    }
+}
+
+/// Slots of the first bucket of `RenderConcurrentHandleAllocator`; each further bucket doubles.
+const CONCURRENT_BUCKET_SHIFT: u32 = 6;
+
+/// Enough buckets to cover every `RenderResourceId`
+const CONCURRENT_BUCKET_COUNT: usize = 32 - CONCURRENT_BUCKET_SHIFT as usize + 1;
+
+/// Slot state is `cookie << 32 | allocated`
+const SLOT_ALLOCATED: u64 = 1;
+
+#[derive(Debug, Default)]
+struct ConcurrentHandleSlot {
+    state: AtomicU64,
+    /// Next entry of the free list (index + 1, 0 for none)
+    next_free: AtomicU32,
+}
+
+#[derive(Debug)]
+struct ConcurrentHandleSlots {
+    /// Lazily allocated, never freed before drop, so slot references stay valid
+    buckets: Vec<AtomicPtr<ConcurrentHandleSlot>>,
+    /// Number of indices handed out so far (high water mark)
+    reserved: AtomicUsize,
+    /// Free list head, `tag << 32 | (index + 1)`. The tag is bumped on every update to
+    /// avoid ABA problems.
+    free_head: AtomicU64,
+}
+
+#[inline(always)]
+fn bucket_location(index: RenderResourceId) -> (usize, usize) {
+    let position = u64::from(index) + (1 << CONCURRENT_BUCKET_SHIFT);
+    let bucket = 63 - position.leading_zeros();
+    let offset = position - (1 << bucket);
+    ((bucket - CONCURRENT_BUCKET_SHIFT) as usize, offset as usize)
+}
+
+#[inline(always)]
+fn bucket_len(bucket: usize) -> usize {
+    1 << (bucket + CONCURRENT_BUCKET_SHIFT as usize)
+}
+
+impl ConcurrentHandleSlots {
+    fn new() -> Self {
+        ConcurrentHandleSlots {
+            buckets: (0..CONCURRENT_BUCKET_COUNT)
+                .map(|_| AtomicPtr::new(ptr::null_mut()))
+                .collect(),
+            reserved: AtomicUsize::new(0),
+            free_head: AtomicU64::new(0),
+        }
+    }
 
-        if (cookie as usize) + 1 > RenderResourceCookie::max_value() as usize {
-            cookie = 1;
+    /// Returns the slot of `index`, or None if its bucket was never allocated.
+    #[inline(always)]
+    fn slot(&self, index: RenderResourceId) -> Option<&ConcurrentHandleSlot> {
+        let (bucket, offset) = bucket_location(index);
+        let slots = self.buckets[bucket].load(Ordering::Acquire);
+        if slots.is_null() {
+            None
         } else {
-            cookie += 1;
+            // Buckets live as long as self and are bucket_len(bucket) slots long
+            Some(unsafe { &*slots.add(offset) })
+        }
+    }
+
+    /// Returns the slot of `index`, allocating its bucket if needed.
+    fn slot_or_create(&self, index: RenderResourceId) -> &ConcurrentHandleSlot {
+        if let Some(slot) = self.slot(index) {
+            return slot;
+        }
+
+        let (bucket, offset) = bucket_location(index);
+        let slots: Vec<ConcurrentHandleSlot> = (0..bucket_len(bucket))
+            .map(|_| ConcurrentHandleSlot::default())
+            .collect();
+        // Owned by the bucket once published, freed in drop
+        let slots = Box::into_raw(slots.into_boxed_slice());
+        let new_slots = slots as *mut ConcurrentHandleSlot;
+        let current = self.buckets[bucket].compare_exchange(
+            ptr::null_mut(),
+            new_slots,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        );
+        let slots_ptr = match current {
+            Ok(_) => new_slots,
+            Err(existing) => {
+                // Another thread won the race; ours is dropped
+                unsafe { drop(Box::from_raw(slots)) };
+                existing
+            }
+        };
+        unsafe { &*slots_ptr.add(offset) }
+    }
+
+    fn pop_free(&self) -> Option<RenderResourceId> {
+        let mut head = self.free_head.load(Ordering::Acquire);
+        loop {
+            let entry = head as u32;
+            if entry == 0 {
+                return None;
+            }
+            let index = entry - 1;
+            let next = self
+                .slot(index)
+                .expect("free list entry without slot")
+                .next_free
+                .load(Ordering::Acquire);
+            let new_head = ((head >> 32) + 1) << 32 | u64::from(next);
+            match self.free_head.compare_exchange_weak(
+                head,
+                new_head,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return Some(index),
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    fn push_free(&self, index: RenderResourceId, slot: &ConcurrentHandleSlot) {
+        let mut head = self.free_head.load(Ordering::Acquire);
+        loop {
+            slot.next_free.store(head as u32, Ordering::Release);
+            let new_head = ((head >> 32) + 1) << 32 | u64::from(index + 1);
+            match self.free_head.compare_exchange_weak(
+                head,
+                new_head,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return,
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    fn reserve(&self, limit: usize) -> Option<RenderResourceId> {
+        let mut reserved = self.reserved.load(Ordering::Acquire);
+        loop {
+            if reserved >= limit {
+                return None;
+            }
+            match self.reserved.compare_exchange_weak(
+                reserved,
+                reserved + 1,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return Some(reserved as RenderResourceId),
+                Err(current) => reserved = current,
+            }
         }
+    }
+}
+
+impl Drop for ConcurrentHandleSlots {
+    fn drop(&mut self) {
+        for (bucket, slots) in self.buckets.iter().enumerate() {
+            let slots = slots.load(Ordering::Acquire);
+            if !slots.is_null() {
+                let slots = ptr::slice_from_raw_parts_mut(slots, bucket_len(bucket));
+                unsafe { drop(Box::from_raw(slots)) };
+            }
+        }
+    }
+}
 
-        self.cookies[offset + index as usize] = cookie;
-        cookie
+/// Handle allocator that can be shared between threads without a lock.
+///
+/// Allocation and release are lock-free: each resource type has its own free list, and slots
+/// are stored in buckets that are allocated on demand and never move. Cookie and limit
+/// semantics match `RenderResourceHandleAllocator`.
+#[derive(Debug)]
+pub struct RenderConcurrentHandleAllocator {
+    limit: usize,
+    slots: Vec<ConcurrentHandleSlots>,
+}
+
+impl Default for RenderConcurrentHandleAllocator {
+    fn default() -> Self {
+        RenderConcurrentHandleAllocator::new()
+    }
+}
+
+impl RenderConcurrentHandleAllocator {
+    pub fn new() -> Self {
+        RenderConcurrentHandleAllocator::with_limit(RenderResourceId::MAX as usize)
+    }
+
+    /// Creates an allocator handing out at most `limit` handle indices per resource type.
+    pub fn with_limit(limit: usize) -> Self {
+        RenderConcurrentHandleAllocator {
+            limit: limit.min(RenderResourceId::MAX as usize),
+            slots: (0..RenderResourceType::count())
+                .map(|_| ConcurrentHandleSlots::new())
+                .collect(),
+        }
+    }
+
+    /// Allocates a handle, reusing a released index if there is one.
+    pub fn allocate(&self, kind: RenderResourceType) -> Result<RenderResourceHandle> {
+        let slots = &self.slots[kind as usize];
+        let (index, cookie) = match slots.pop_free() {
+            Some(index) => {
+                let slot = slots.slot(index).expect("free list entry without slot");
+                let state = slot.state.fetch_or(SLOT_ALLOCATED, Ordering::AcqRel);
+                assert!(state & SLOT_ALLOCATED == 0);
+                (index, (state >> 32) as RenderResourceCookie)
+            }
+            None => {
+                let index = slots.reserve(self.limit).ok_or_else(|| {
+                    Error::memory(format!(
+                        "exhausted {:?} handles (limit {})",
+                        kind, self.limit
+                    ))
+                })?;
+                let slot = slots.slot_or_create(index);
+                slot.state
+                    .store(1 << 32 | SLOT_ALLOCATED, Ordering::Release);
+                (index, 1)
+            }
+        };
+        Ok(RenderResourceHandle::new(index, kind, cookie))
+    }
+
+    /// Allocates a handle of the resource type of `H`.
+    pub fn allocate_typed<H: RenderTypedHandle>(&self) -> Result<H> {
+        H::from_handle(self.allocate(H::RESOURCE_TYPE)?)
+    }
+
+    /// Releases `handle`, bumping the cookie of its index so any copies of it go stale.
+    ///
+    /// Unlike `RenderResourceHandleAllocator::release`, releasing an invalid handle is an error
+    /// rather than a panic, since another thread may have released it first.
+    pub fn release(&self, handle: RenderResourceHandle) -> Result<()> {
+        let slots = &self.slots[handle.get_type() as usize];
+        let slot = match slots.slot(handle.get_id()) {
+            Some(slot) => slot,
+            None => return self.check(&handle),
+        };
+
+        let cookie = handle.get_cookie();
+        let current = u64::from(cookie) << 32 | SLOT_ALLOCATED;
+        let next_cookie = cookie.checked_add(1);
+        // An index whose cookie would overflow is retired instead of reused
+        let released = u64::from(next_cookie.unwrap_or(cookie)) << 32;
+        if slot
+            .state
+            .compare_exchange(current, released, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            return self.check(&handle);
+        }
+
+        if next_cookie.is_some() {
+            slots.push_free(handle.get_id(), slot);
+        }
+        Ok(())
+    }
+
+    pub fn is_allocated(&self, index: RenderResourceId, kind: RenderResourceType) -> bool {
+        match self.slots[kind as usize].slot(index) {
+            Some(slot) => slot.state.load(Ordering::Acquire) & SLOT_ALLOCATED != 0,
+            None => false,
+        }
+    }
+
+    /// True if `handle` is allocated and from the current generation of its index.
+    pub fn is_valid(&self, handle: &RenderResourceHandle) -> bool {
+        match self.slots[handle.get_type() as usize].slot(handle.get_id()) {
+            Some(slot) => {
+                slot.state.load(Ordering::Acquire)
+                    == u64::from(handle.get_cookie()) << 32 | SLOT_ALLOCATED
+            }
+            None => false,
+        }
+    }
+
+    /// Like `is_valid`, but returns a stale handle error for handles to an index that has since
+    /// been released (and possibly reused).
+    pub fn check(&self, handle: &RenderResourceHandle) -> Result<()> {
+        if self.is_valid(handle) {
+            Ok(())
+        } else if handle.get_cookie() != 0
+            && (handle.get_id() as usize) < self.get_max(handle.get_type())
+        {
+            Err(Error::stale_handle(*handle))
+        } else {
+            Err(Error::backend(format!(
+                "resource handle was never allocated: {:?}",
+                handle
+            )))
+        }
+    }
+
+    /// Number of live handles of a resource type (a snapshot if other threads are allocating).
+    pub fn get_count(&self, kind: RenderResourceType) -> usize {
+        (0..self.get_max(kind) as RenderResourceId)
+            .filter(|index| self.is_allocated(*index, kind))
+            .count()
+    }
+
+    pub fn get_max(&self, kind: RenderResourceType) -> usize {
+        self.slots[kind as usize].reserved.load(Ordering::Acquire)
+    }
+
+    /// Returns every live handle of a resource type, ordered by id.
+    ///
+    /// Handles allocated or released by other threads during the call may or may not be listed.
+    pub fn get_handles(&self, kind: RenderResourceType) -> Vec<RenderResourceHandle> {
+        let slots = &self.slots[kind as usize];
+        let mut handles = Vec::with_capacity(self.get_count(kind));
+        for index in 0..self.get_max(kind) as RenderResourceId {
+            if let Some(slot) = slots.slot(index) {
+                let state = slot.state.load(Ordering::Acquire);
+                if state & SLOT_ALLOCATED != 0 {
+                    handles.push(RenderResourceHandle::new(
+                        index,
+                        kind,
+                        (state >> 32) as RenderResourceCookie,
+                    ));
+                }
+            }
+        }
+        handles
     }
 }
@@ -5,6 +5,7 @@
 extern crate option_set;
 #[macro_use]
 extern crate bitflags;
+extern crate bincode;
 extern crate serde;
 extern crate serde_json;
 #[macro_use]
@@ -27,20 +28,28 @@ extern crate fern;
 pub mod allocator;
 pub mod backend;
 pub mod commands;
+pub mod config;
 pub mod constants;
 pub mod debug;
+pub mod deferred;
 pub mod device;
+pub mod disassembly;
 pub mod encoder;
 pub mod error;
 pub mod format;
 pub mod handles;
+pub mod logging;
 pub mod modules;
 pub mod profile;
 pub mod resources;
+pub mod selection;
+pub mod serialization;
 pub mod state;
+pub mod statistics;
 pub mod system;
 pub mod types;
 pub mod utilities;
+pub mod validation;
 
 pub use crate::error::{Error, ErrorKind, Result};
 
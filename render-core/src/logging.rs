@@ -0,0 +1,220 @@
+use crate::error::{Error, Result};
+use fern::colors::{Color, ColoredLevelConfig};
+use std::env;
+use std::io;
+use std::path::PathBuf;
+
+pub use log::{LevelFilter, Log, Metadata, Record};
+
+/// Log filters (i.e. "info,render_core::system=debug"), applied on top of the log config
+pub const RENDER_LOG_ENV: &str = "RENDER_LOG";
+
+/// Logging configuration for `init_logging`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenderLogConfig {
+    /// Level for any module without its own filter
+    pub level: LevelFilter,
+
+    /// Per-module level filters, i.e. `("render_hal_vk", LevelFilter::Trace)`
+    pub module_levels: Vec<(String, LevelFilter)>,
+
+    /// Write records to stdout
+    pub console: bool,
+
+    /// Colour the level of console records
+    pub colors: bool,
+
+    /// Prefix records with the local date and time
+    pub timestamps: bool,
+
+    /// Also append records (uncoloured) to this file
+    pub file: Option<PathBuf>,
+}
+
+impl Default for RenderLogConfig {
+    fn default() -> Self {
+        RenderLogConfig {
+            level: LevelFilter::Info,
+            module_levels: Vec::new(),
+            console: true,
+            colors: true,
+            timestamps: true,
+            file: None,
+        }
+    }
+}
+
+impl RenderLogConfig {
+    pub fn new(level: LevelFilter) -> Self {
+        RenderLogConfig {
+            level,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the level of `module` (and its children), replacing any previous filter for it.
+    pub fn set_module_level(&mut self, module: &str, level: LevelFilter) {
+        self.module_levels.retain(|(name, _)| name != module);
+        self.module_levels.push((module.to_string(), level));
+    }
+
+    /// Applies comma separated filters, each either a level or `module=level`.
+    pub fn apply_filters(&mut self, filters: &str) -> Result<()> {
+        for filter in filters.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+            let mut parts = filter.splitn(2, '=');
+            let name = parts.next().unwrap_or_default().trim();
+            match parts.next() {
+                Some(level) => self.set_module_level(name, parse_level(level.trim())?),
+                None => self.level = parse_level(name)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies `RENDER_LOG` filters, if set.
+    pub fn apply_env(&mut self) -> Result<()> {
+        match env::var(RENDER_LOG_ENV) {
+            Ok(filters) => self.apply_filters(&filters),
+            Err(_) => Ok(()),
+        }
+    }
+
+    /// Most verbose level any record can pass with.
+    pub fn max_level(&self) -> LevelFilter {
+        self.module_levels
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.level, ::std::cmp::max)
+    }
+}
+
+fn parse_level(level: &str) -> Result<LevelFilter> {
+    level
+        .parse()
+        .map_err(|_| Error::parse(format!("invalid log level {:?}", level)))
+}
+
+fn format_prefix(timestamps: bool) -> String {
+    if timestamps {
+        format!("[{}]", chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f"))
+    } else {
+        String::new()
+    }
+}
+
+/// Installs the global logger described by `config`.
+///
+/// Fails if a logger was already installed, since the `log` facade only allows one per process.
+pub fn init_logging(config: &RenderLogConfig) -> Result<()> {
+    let mut dispatch = fern::Dispatch::new().level(config.level);
+    for (module, level) in &config.module_levels {
+        dispatch = dispatch.level_for(module.clone(), *level);
+    }
+
+    if config.console {
+        let colors = ColoredLevelConfig::new()
+            .error(Color::Red)
+            .warn(Color::Yellow)
+            .info(Color::Green)
+            .debug(Color::Blue)
+            .trace(Color::Magenta);
+        let use_colors = config.colors;
+        let timestamps = config.timestamps;
+        dispatch = dispatch.chain(
+            fern::Dispatch::new()
+                .format(move |out, message, record| {
+                    if use_colors {
+                        out.finish(format_args!(
+                            "{}[{}][{}] {}",
+                            format_prefix(timestamps),
+                            record.target(),
+                            colors.color(record.level()),
+                            message
+                        ))
+                    } else {
+                        out.finish(format_args!(
+                            "{}[{}][{}] {}",
+                            format_prefix(timestamps),
+                            record.target(),
+                            record.level(),
+                            message
+                        ))
+                    }
+                })
+                .chain(io::stdout()),
+        );
+    }
+
+    if let Some(ref path) = config.file {
+        let file = fern::log_file(path).map_err(|err| {
+            Error::config(format!(
+                "failed to open log file {} - {}",
+                path.display(),
+                err
+            ))
+        })?;
+        let timestamps = config.timestamps;
+        dispatch = dispatch.chain(
+            fern::Dispatch::new()
+                .format(move |out, message, record| {
+                    out.finish(format_args!(
+                        "{}[{}][{}] {}",
+                        format_prefix(timestamps),
+                        record.target(),
+                        record.level(),
+                        message
+                    ))
+                })
+                .chain(file),
+        );
+    }
+
+    dispatch
+        .apply()
+        .map_err(|_| Error::config("a global logger is already installed"))?;
+    log::set_max_level(config.max_level());
+    Ok(())
+}
+
+/// Forwards records to whichever logger the host has installed at the time of the call,
+/// so modules can be loaded before (or without) `init_logging`.
+struct HostLogger;
+
+impl Log for HostLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level() && log::logger().enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            log::logger().log(record);
+        }
+    }
+
+    fn flush(&self) {
+        log::logger().flush();
+    }
+}
+
+static HOST_LOGGER: HostLogger = HostLogger;
+static HOST_LOGGER_REF: &dyn Log = &HOST_LOGGER;
+
+/// Logger handed to dynamically loaded backend modules (see `render_backend_module!`).
+pub fn host_logger() -> &'static dyn Log {
+    &HOST_LOGGER
+}
+
+/// The host logger behind a thin pointer, since `dyn` references have no stable C layout.
+pub(crate) fn host_logger_ref() -> &'static &'static dyn Log {
+    &HOST_LOGGER_REF
+}
+
+/// Installs `logger` as this module's logger. Each backend library has its own copy of the
+/// `log` facade, so without this its records would be dropped.
+///
+/// Filtering is left to the host, since its level may change after the module is loaded.
+pub fn set_module_logger(logger: &'static dyn Log) {
+    if log::set_logger(logger).is_ok() {
+        log::set_max_level(LevelFilter::Trace);
+    }
+}
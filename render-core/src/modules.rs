@@ -1,13 +1,163 @@
-use crate::backend::RenderBackendModule;
+use crate::backend::{RenderBackendModule, RenderBackendSettings};
+use crate::device::RenderDeviceInfo;
+use crate::encoder::RenderCommandList;
 use crate::error::{Error, Result};
+use crate::handles::RenderResourceHandle;
+use crate::logging::{host_logger_ref, Log};
 use failure::Fail;
 use glob::glob;
 use libloading::{Library, Symbol};
 use std::borrow::Borrow;
 use std::env;
-use std::path::Path;
+use std::ffi::CStr;
+use std::mem;
+use std::os::raw::c_char;
+use std::path::{Path, PathBuf};
 
 pub(crate) type FactoryFunc = extern "C" fn() -> Box<dyn RenderBackendModule>;
+pub(crate) type DescriptorFunc = extern "C" fn() -> RenderBackendModuleDesc;
+pub(crate) type LoggerFunc = extern "C" fn(&'static &'static dyn Log);
+
+/// Must be bumped whenever the backend module interface changes incompatibly.
+pub const RENDER_BACKEND_ABI_VERSION: u32 = 3;
+
+const RENDER_CORE_VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), "\0");
+const RENDER_CORE_RUSTC_VERSION: &str = concat!(env!("RENDER_CORE_RUSTC_VERSION"), "\0");
+
+/// Exported by every backend module (see `render_backend_module!`), and checked against
+/// the host before any Rust types are passed across the library boundary.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct RenderBackendModuleDesc {
+    pub abi_version: u32,
+    pub layout_fingerprint: u64,
+    pub core_version: *const c_char,
+    pub rustc_version: *const c_char,
+    pub api: *const c_char,
+}
+
+impl RenderBackendModuleDesc {
+    /// Describes the render-core this function was compiled into; `api` must be nul terminated.
+    pub fn current(api: &'static str) -> Self {
+        assert!(
+            api.ends_with('\0'),
+            "backend module api must be nul terminated"
+        );
+        RenderBackendModuleDesc {
+            abi_version: RENDER_BACKEND_ABI_VERSION,
+            layout_fingerprint: layout_fingerprint(),
+            core_version: RENDER_CORE_VERSION.as_ptr() as *const c_char,
+            rustc_version: RENDER_CORE_RUSTC_VERSION.as_ptr() as *const c_char,
+            api: api.as_ptr() as *const c_char,
+        }
+    }
+
+    pub fn core_version(&self) -> String {
+        desc_string(self.core_version)
+    }
+
+    pub fn rustc_version(&self) -> String {
+        desc_string(self.rustc_version)
+    }
+
+    pub fn api(&self) -> String {
+        desc_string(self.api)
+    }
+
+    /// Checks that a module described by `self` can be safely used by this render-core.
+    pub fn validate(&self) -> Result<()> {
+        let host = RenderBackendModuleDesc::current("\0");
+        if self.abi_version != host.abi_version {
+            Err(Error::backend(format!(
+                "backend module \"{}\" uses abi version {} (expected {})",
+                self.api(),
+                self.abi_version,
+                host.abi_version
+            )))
+        } else if self.core_version() != host.core_version() {
+            Err(Error::backend(format!(
+                "backend module \"{}\" was built against render-core {} (expected {})",
+                self.api(),
+                self.core_version(),
+                host.core_version()
+            )))
+        } else if self.rustc_version() != host.rustc_version() {
+            Err(Error::backend(format!(
+                "backend module \"{}\" was built with {} (expected {})",
+                self.api(),
+                self.rustc_version(),
+                host.rustc_version()
+            )))
+        } else if self.layout_fingerprint != host.layout_fingerprint {
+            Err(Error::backend(format!(
+                "backend module \"{}\" has layout fingerprint {:016x} (expected {:016x})",
+                self.api(),
+                self.layout_fingerprint,
+                host.layout_fingerprint
+            )))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn desc_string(value: *const c_char) -> String {
+    if value.is_null() {
+        String::new()
+    } else {
+        unsafe { CStr::from_ptr(value) }
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+/// Hash (FNV-1a) of the sizes and alignments of types shared with backend modules.
+pub fn layout_fingerprint() -> u64 {
+    let layouts = [
+        mem::size_of::<RenderResourceHandle>(),
+        mem::align_of::<RenderResourceHandle>(),
+        mem::size_of::<RenderBackendSettings>(),
+        mem::align_of::<RenderBackendSettings>(),
+        mem::size_of::<RenderDeviceInfo>(),
+        mem::align_of::<RenderDeviceInfo>(),
+        mem::size_of::<RenderCommandList>(),
+        mem::align_of::<RenderCommandList>(),
+        mem::size_of::<Error>(),
+        mem::size_of::<Box<dyn RenderBackendModule>>(),
+    ];
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for layout in layouts.iter() {
+        for byte in (*layout as u64).to_le_bytes().iter() {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+/// Exports the entry points used to discover a backend module at runtime.
+#[macro_export]
+macro_rules! render_backend_module {
+    ($api:expr, $module:expr) => {
+        #[no_mangle]
+        pub extern "C" fn render_backend_descriptor() -> $crate::modules::RenderBackendModuleDesc {
+            $crate::modules::RenderBackendModuleDesc::current(concat!($api, "\0"))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn render_backend_factory() -> Box<dyn $crate::backend::RenderBackendModule>
+        {
+            Box::new($module)
+        }
+
+        #[no_mangle]
+        pub extern "C" fn render_backend_logger(
+            logger: &'static &'static dyn $crate::logging::Log,
+        ) {
+            $crate::logging::set_module_logger(*logger)
+        }
+    };
+}
 
 type LibResult<T> = ::std::io::Result<T>;
 
@@ -30,17 +180,58 @@ pub(crate) fn is_backend_module(module_path: &Path) -> bool {
     }
 }
 
-pub fn create_backend_module(library: &Box<Library>) -> Result<Box<dyn RenderBackendModule>> {
+pub fn get_backend_module_desc(library: &Library) -> Result<RenderBackendModuleDesc> {
+    let descriptor_func: LibResult<Symbol<DescriptorFunc>> =
+        unsafe { library.get(b"render_backend_descriptor") };
+    if let Ok(descriptor_func) = descriptor_func {
+        Ok(descriptor_func())
+    } else {
+        Err(Error::backend(
+            "backend module does not export a descriptor (stale module?)",
+        ))
+    }
+}
+
+pub fn create_backend_module(library: &Library) -> Result<Box<dyn RenderBackendModule>> {
+    let desc = get_backend_module_desc(library)?;
+    desc.validate()?;
     let factory_func: LibResult<Symbol<FactoryFunc>> =
         unsafe { library.get(b"render_backend_factory") };
     if let Ok(factory_func) = factory_func {
+        // Optional, so modules built before log forwarding still load
+        let logger_func: LibResult<Symbol<LoggerFunc>> =
+            unsafe { library.get(b"render_backend_logger") };
+        if let Ok(logger_func) = logger_func {
+            logger_func(host_logger_ref());
+        }
+
         let backend_module = factory_func();
-        Ok(backend_module)
+        if backend_module.api() != desc.api() {
+            Err(Error::backend(format!(
+                "backend module api \"{}\" does not match its descriptor \"{}\"",
+                backend_module.api(),
+                desc.api()
+            )))
+        } else {
+            Ok(backend_module)
+        }
     } else {
         Err(Error::backend("create backend module failed"))
     }
 }
 
+/// Checks the descriptor of the module at `module_path`, logging why it's rejected if so.
+pub fn accept_backend_module(module_path: &Path, desc: Result<RenderBackendModuleDesc>) -> bool {
+    match desc.and_then(|desc| desc.validate()) {
+        Ok(()) => true,
+        Err(err) => {
+            error!("rejected backend module {:?} - {}", module_path, err);
+            false
+        }
+    }
+}
+
+/// Loads the backend modules found in `module_path`, skipping those that fail the handshake.
 pub fn load_backend_modules(module_path: &Path) -> Result<Vec<Box<Library>>> {
     info!("Loading render backend modules from {:?}", module_path);
     let path_str = module_path
@@ -51,19 +242,34 @@ pub fn load_backend_modules(module_path: &Path) -> Result<Vec<Box<Library>>> {
     let path_glob = format!("{}/*.{}", path_str, LIBRARY_EXT);
     //info!("path glob: {}", path_glob);
 
-    let mut modules: Vec<Box<Library>> = vec![];
+    let mut candidates: Vec<(PathBuf, Box<Library>)> = vec![];
 
     for entry in glob(&path_glob).expect("Failed to read glob pattern") {
         match entry {
             Ok(path) => {
                 let valid_module = is_backend_module(&path);
                 if valid_module {
-                    modules.push(Box::new(Library::new(&path).unwrap()));
+                    let library = Box::new(Library::new(&path).unwrap());
+                    candidates.push((path, library));
                 }
             }
             Err(e) => error!("{:?}", e),
         }
     }
 
-    Ok(modules)
+    Ok(select_backend_modules(candidates, |library| {
+        get_backend_module_desc(library)
+    }))
+}
+
+/// Keeps the `candidates` whose descriptor (from `desc`) is accepted, in discovery order.
+pub fn select_backend_modules<T, F>(candidates: Vec<(PathBuf, T)>, desc: F) -> Vec<T>
+where
+    F: Fn(&T) -> Result<RenderBackendModuleDesc>,
+{
+    candidates
+        .into_iter()
+        .filter(|(path, module)| accept_backend_module(path, desc(module)))
+        .map(|(_, module)| module)
+        .collect()
 }
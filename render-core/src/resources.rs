@@ -1,5 +1,5 @@
 use crate::error::{Error, Result};
-use crate::handles::RenderResourceHandle;
+use crate::handles::{RenderResourceCookie, RenderResourceHandle};
 use crate::types::RenderResourceType;
 use downcast_rs::Downcast;
 use std::collections::HashMap;
@@ -13,8 +13,14 @@ pub trait RenderResourceBase: Downcast + fmt::Debug {
 
 impl_downcast!(RenderResourceBase);
 
+/// Cookie of the creating handle, and the resource
+type RenderResourceEntry<T> = (RenderResourceCookie, Arc<RwLock<T>>);
+
+/// Resources keyed by handle type and index, along with the cookie of the handle they were
+/// created with. Lookups with an older cookie fail with a stale handle error rather than
+/// resolving to whatever resource now occupies the index.
 pub struct RenderResourceStorage<T> {
-    resources: Arc<RwLock<HashMap<RenderResourceHandle, Arc<RwLock<T>>>>>,
+    resources: Arc<RwLock<HashMap<u64, RenderResourceEntry<T>>>>,
 }
 
 unsafe impl<T> Send for RenderResourceStorage<T> {}
@@ -28,49 +34,65 @@ impl<T> RenderResourceStorage<T> {
     }
 
     #[inline(always)]
-    pub fn put(&self, handle: RenderResourceHandle, resource: Arc<RwLock<T>>) -> Result<()> {
+    pub fn put<H: Into<RenderResourceHandle>>(
+        &self,
+        handle: H,
+        resource: Arc<RwLock<T>>,
+    ) -> Result<()> {
+        let handle = handle.into();
         let resources = Arc::clone(&self.resources);
         let mut resources_write = resources.write().unwrap();
-        resources_write.insert(handle, resource);
+        resources_write.insert(handle.get_packed(), (handle.get_cookie(), resource));
         Ok(())
     }
 
     #[inline(always)]
-    pub fn get(&self, handle: RenderResourceHandle) -> Result<Arc<RwLock<T>>> {
+    pub fn get<H: Into<RenderResourceHandle>>(&self, handle: H) -> Result<Arc<RwLock<T>>> {
+        let handle = handle.into();
         let resources = Arc::clone(&self.resources);
         let resources_read = resources.read().unwrap();
-        match resources_read.get(&handle) {
-            Some(ref resource) => Ok(Arc::clone(&resource)),
+        match resources_read.get(&handle.get_packed()) {
+            Some((cookie, resource)) if *cookie == handle.get_cookie() => Ok(Arc::clone(resource)),
+            Some(_) => Err(Error::stale_handle(handle)),
             _ => Err(Error::backend(format!("resource not found: {:?}", handle))),
         }
     }
 
     #[inline(always)]
-    pub fn get_or_none(&self, handle: RenderResourceHandle) -> Option<Arc<RwLock<T>>> {
+    pub fn get_or_none<H: Into<RenderResourceHandle>>(&self, handle: H) -> Option<Arc<RwLock<T>>> {
+        let handle = handle.into();
         let resources = Arc::clone(&self.resources);
         let resources_read = resources.read().unwrap();
-        match resources_read.get(&handle) {
-            Some(ref resource) => Some(Arc::clone(&resource)),
+        match resources_read.get(&handle.get_packed()) {
+            Some((cookie, resource)) if *cookie == handle.get_cookie() => {
+                Some(Arc::clone(resource))
+            }
             _ => None,
         }
     }
 
     #[inline(always)]
-    pub fn remove(&self, handle: RenderResourceHandle) -> Result<Arc<RwLock<T>>> {
+    pub fn remove<H: Into<RenderResourceHandle>>(&self, handle: H) -> Result<Arc<RwLock<T>>> {
+        let handle = handle.into();
         let resources = Arc::clone(&self.resources);
         let mut resources_write = resources.write().unwrap();
-        match resources_write.remove(&handle) {
-            Some(ref resource) => Ok(Arc::clone(&resource)),
+        match resources_write.get(&handle.get_packed()) {
+            Some((cookie, _)) if *cookie == handle.get_cookie() => {
+                let (_, resource) = resources_write.remove(&handle.get_packed()).unwrap();
+                Ok(resource)
+            }
+            Some(_) => Err(Error::stale_handle(handle)),
             _ => Err(Error::backend(format!("resource not found: {:?}", handle))),
         }
     }
 
     #[inline(always)]
-    pub fn valid(&self, handle: RenderResourceHandle) -> bool {
+    pub fn valid<H: Into<RenderResourceHandle>>(&self, handle: H) -> bool {
+        let handle = handle.into();
         let resources = Arc::clone(&self.resources);
         let resources_read = resources.read().unwrap();
-        match resources_read.get(&handle) {
-            Some(_) => true,
+        match resources_read.get(&handle.get_packed()) {
+            Some((cookie, _)) => *cookie == handle.get_cookie(),
             _ => false,
         }
         /*match handle.get_type() {
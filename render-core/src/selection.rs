@@ -0,0 +1,112 @@
+use crate::backend::RenderBackendSettings;
+use crate::device::{RenderDeviceEntry, RenderDeviceInfo, RenderDeviceType, RenderDeviceVendor};
+use std::fmt;
+
+/// Outcome of scoring a single enumerated device.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RenderDeviceScore {
+    /// The device is usable; higher scores are preferred
+    Accept(u32),
+
+    /// The device must not be selected, with the reason why
+    Reject(String),
+}
+
+pub type RenderDeviceScoreFunc = fn(&RenderDeviceInfo) -> RenderDeviceScore;
+
+/// Scores devices by type first, then vendor and capabilities.
+pub fn default_device_score(info: &RenderDeviceInfo) -> RenderDeviceScore {
+    let type_score = match info.device_type {
+        RenderDeviceType::Discrete => 4,
+        RenderDeviceType::Integrated => 3,
+        RenderDeviceType::Virtual => 2,
+        RenderDeviceType::Cpu => 1,
+        RenderDeviceType::Other => 0,
+    };
+
+    let vendor_score = match info.vendor {
+        RenderDeviceVendor::Unknown => 0,
+        _ => 1,
+    };
+
+    let caps = &info.caps;
+    let caps_score = [
+        caps.supports_quads,
+        caps.supports_rect_list,
+        caps.supports_row_major_cross_adapter,
+        caps.supports_typed_uav_load_r11g11b10_float,
+        caps.supports_typed_uav_load_r16g16b16a16_float,
+        caps.max_texture_array_size > 0,
+    ]
+    .iter()
+    .filter(|supported| **supported)
+    .count() as u32;
+
+    let dimension_score = (info.caps.max_texture_dimension / 1024).min(99);
+
+    RenderDeviceScore::Accept(
+        type_score * 100_000 + vendor_score * 10_000 + caps_score * 100 + dimension_score,
+    )
+}
+
+/// Ordered backend preferences and device scoring used by `RenderSystem::select_device`.
+///
+/// Backends are tried in order, and the first backend with an accepted device wins; within
+/// a backend, the highest scoring device is created (falling back to the next best device
+/// if creation fails).
+#[derive(Clone, Debug)]
+pub struct RenderDeviceSelectionPolicy {
+    /// Backends in order of preference (i.e. prefer "vk", fall back to "mock")
+    pub backends: Vec<RenderBackendSettings>,
+
+    /// Allow software (i.e. WARP or CPU) devices during enumeration
+    pub allow_software: bool,
+
+    /// Scoring function applied to every enumerated device
+    pub score: RenderDeviceScoreFunc,
+}
+
+impl RenderDeviceSelectionPolicy {
+    pub fn new(backends: &[RenderBackendSettings]) -> Self {
+        RenderDeviceSelectionPolicy {
+            backends: backends.to_vec(),
+            allow_software: true,
+            score: default_device_score,
+        }
+    }
+}
+
+/// A backend or device that was not selected, and why.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenderDeviceRejection {
+    pub api: String,
+    pub device: Option<RenderDeviceInfo>,
+    pub reason: String,
+}
+
+impl fmt::Display for RenderDeviceRejection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.device {
+            Some(ref device) => write!(
+                f,
+                "{}: {} [{:?}] - {}",
+                self.api, device.name, device.device_type, self.reason
+            ),
+            None => write!(f, "{} - {}", self.api, self.reason),
+        }
+    }
+}
+
+/// The device created by `RenderSystem::select_device`.
+#[derive(Debug)]
+pub struct RenderDeviceSelection {
+    /// Index of the backend within the render system registry
+    pub registry_index: usize,
+    pub settings: RenderBackendSettings,
+    pub info: RenderDeviceInfo,
+    pub score: u32,
+    pub device: RenderDeviceEntry,
+
+    /// Every candidate considered before (or instead of) the selected device
+    pub rejected: Vec<RenderDeviceRejection>,
+}
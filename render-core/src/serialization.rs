@@ -0,0 +1,126 @@
+use crate::commands::RenderCommandEntry;
+use crate::encoder::RenderCommandList;
+use crate::error::{Error, Result};
+use crate::handles::RenderConcurrentHandleAllocator;
+use std::io::{Read, Write};
+use std::sync::Arc;
+
+/// Identifies a serialized command list stream.
+pub const COMMAND_STREAM_MAGIC: [u8; 4] = *b"RCLS";
+
+/// Must be bumped whenever a command or payload type changes incompatibly.
+pub const COMMAND_STREAM_VERSION: u32 = 2;
+
+/// A recorded command, with the data of update commands copied out of the command list.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RenderSerializedCommand {
+    pub command: RenderCommandEntry,
+    pub data: Option<Vec<u8>>,
+}
+
+/// A command list that can be saved and loaded independently of the recording process.
+///
+/// The stream is `COMMAND_STREAM_MAGIC`, then `COMMAND_STREAM_VERSION` as a little endian
+/// `u32`, then the commands encoded with bincode. Handles are stored as recorded, so loading a
+/// list only makes sense against resources created with the same handles (i.e. from a capture).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RenderSerializedCommandList {
+    pub commands: Vec<RenderSerializedCommand>,
+}
+
+impl RenderSerializedCommandList {
+    /// Copies the commands (and their data) out of a recorded command list.
+    pub fn from_command_list(command_list: &RenderCommandList) -> Result<Self> {
+        let commands = command_list
+            .get_commands()
+            .iter()
+            .map(|entry| {
+                Ok(RenderSerializedCommand {
+                    command: entry.clone(),
+                    data: command_list
+                        .get_entry_data(entry)?
+                        .map(|data| data.to_vec()),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(RenderSerializedCommandList { commands })
+    }
+
+    /// Records the commands into a new command list.
+    pub fn to_command_list(
+        &self,
+        handles: Arc<RenderConcurrentHandleAllocator>,
+    ) -> Result<RenderCommandList> {
+        let data_size: usize = self
+            .commands
+            .iter()
+            .filter_map(|command| command.data.as_ref())
+            .map(|data| data.len() + 16)
+            .sum();
+        let mut command_list =
+            RenderCommandList::new(handles, 4096 + data_size, self.commands.len())?;
+        for command in &self.commands {
+            command_list.record_entry(
+                command.command.clone(),
+                command.data.as_ref().map(|data| &data[..]),
+            )?;
+        }
+        Ok(command_list)
+    }
+
+    pub fn write<W: Write + ?Sized>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(&COMMAND_STREAM_MAGIC).map_err(Error::io)?;
+        writer
+            .write_all(&COMMAND_STREAM_VERSION.to_le_bytes())
+            .map_err(Error::io)?;
+        bincode::serialize_into(writer, self)
+            .map_err(|err| Error::bug(format!("failed to serialize command list - {}", err)))
+    }
+
+    pub fn read<R: Read + ?Sized>(reader: &mut R) -> Result<Self> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic).map_err(Error::io)?;
+        if magic != COMMAND_STREAM_MAGIC {
+            return Err(Error::parse("not a serialized command list"));
+        }
+
+        let mut version = [0u8; 4];
+        reader.read_exact(&mut version).map_err(Error::io)?;
+        let version = u32::from_le_bytes(version);
+        if version != COMMAND_STREAM_VERSION {
+            return Err(Error::parse(format!(
+                "unsupported command list version {} (expected {})",
+                version, COMMAND_STREAM_VERSION
+            )));
+        }
+
+        bincode::deserialize_from(reader)
+            .map_err(|err| Error::parse(format!("invalid command list - {}", err)))
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        self.write(&mut data)?;
+        Ok(data)
+    }
+
+    pub fn from_bytes(mut data: &[u8]) -> Result<Self> {
+        Self::read(&mut data)
+    }
+}
+
+/// Writes a recorded command list as a versioned binary stream.
+pub fn write_command_list<W: Write + ?Sized>(
+    writer: &mut W,
+    command_list: &RenderCommandList,
+) -> Result<()> {
+    RenderSerializedCommandList::from_command_list(command_list)?.write(writer)
+}
+
+/// Reads a command list written by `write_command_list`, recording it again.
+pub fn read_command_list<R: Read + ?Sized>(
+    reader: &mut R,
+    handles: Arc<RenderConcurrentHandleAllocator>,
+) -> Result<RenderCommandList> {
+    RenderSerializedCommandList::read(reader)?.to_command_list(handles)
+}
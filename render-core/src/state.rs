@@ -2,7 +2,9 @@ use crate::constants::*;
 use crate::handles::RenderResourceHandle;
 use crate::types::*;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount, Serialize, Deserialize,
+)]
 pub enum RenderSamplerFilter {
     MinMagMipPoint = 0,
     MinMagPointMipLinear = 1,
@@ -92,7 +94,9 @@ pub fn is_min_max_filter(filter: RenderSamplerFilter) -> bool {
     is_min_filter(filter) || is_max_filter(filter)
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount, Serialize, Deserialize,
+)]
 pub enum RenderBorderColor {
     /// Transparent black (0,0,0,0)
     BlackA0 = 0,
@@ -104,14 +108,18 @@ pub enum RenderBorderColor {
     WhiteA1 = 2,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount, Serialize, Deserialize,
+)]
 pub enum RenderCullMode {
     None = 0,
     Front = 1,
     Back = 2,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount, Serialize, Deserialize,
+)]
 pub enum RenderBlendMode {
     Zero = 0,
     One = 1,
@@ -132,7 +140,9 @@ pub enum RenderBlendMode {
     InvSource1Alpha = 16,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount, Serialize, Deserialize,
+)]
 pub enum RenderBlendOp {
     /// Add source 1 and source 2.
     Add = 0,
@@ -151,6 +161,7 @@ pub enum RenderBlendOp {
 }
 
 bitflags! {
+    #[derive(Serialize, Deserialize)]
     pub struct RenderWriteMask: u8 {
         const NONE = 0;
         const RED = 0x1;
@@ -168,13 +179,15 @@ impl Default for RenderWriteMask {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount, Serialize, Deserialize,
+)]
 pub enum RenderFillMode {
     Solid = 0,
     WireFrame = 1,
 }
 
-#[derive(Clone, Default, Debug)]
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct RenderGraphicsPipelineStateDesc {
     pub shaders: [RenderResourceHandle; MAX_SHADER_TYPE],
     pub shader_signature: RenderShaderSignatureDesc,
@@ -189,13 +202,15 @@ pub struct RenderGraphicsPipelineStateDesc {
     pub depth_stencil_format: RenderFormat,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RenderComputePipelineStateDesc {
     pub shader: RenderResourceHandle,
     pub shader_signature: RenderShaderSignatureDesc,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount, Serialize, Deserialize,
+)]
 pub enum RenderClearMask {
     Color0 = 1,
     Color1 = 2,
@@ -213,7 +228,9 @@ pub enum RenderClearMask {
     None = 0,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount, Serialize, Deserialize,
+)]
 pub enum RenderCompareFunc {
     Never = 0,
     Less = 1,
@@ -225,7 +242,9 @@ pub enum RenderCompareFunc {
     Always = 7,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount, Serialize, Deserialize,
+)]
 pub enum RenderStencilOp {
     Keep = 0,
     Zero = 1,
@@ -237,7 +256,9 @@ pub enum RenderStencilOp {
     DecrementWrap = 7,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount, Serialize, Deserialize,
+)]
 pub enum RenderSamplerAddressMode {
     Wrap = 0,
     Mirror = 1,
@@ -246,7 +267,9 @@ pub enum RenderSamplerAddressMode {
     MirrorOnce = 4,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount, Serialize, Deserialize,
+)]
 pub enum RenderStencilMode {
     /// Stenciling is disabled
     Disabled = 0,
@@ -258,7 +281,7 @@ pub enum RenderStencilMode {
     DoubleSided = 2,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct RenderStencilSide {
     pub func: RenderCompareFunc,
     pub fail_op: RenderStencilOp,
@@ -277,7 +300,7 @@ impl Default for RenderStencilSide {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct RenderStencilState {
     pub mode: RenderStencilMode,
     pub read_mask: u8,
@@ -298,14 +321,14 @@ impl Default for RenderStencilState {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub struct RenderDrawState {
     pub viewport: Option<RenderViewportRect>,
     pub scissor: Option<RenderScissorRect>,
     pub stencil_ref: u32,
 }
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub struct RenderViewportRect {
     pub x: f32,
     pub y: f32,
@@ -315,7 +338,7 @@ pub struct RenderViewportRect {
     pub max_z: f32,
 }
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Serialize, Deserialize)]
 pub struct RenderScissorRect {
     pub x: i32,
     pub y: i32,
@@ -323,7 +346,7 @@ pub struct RenderScissorRect {
     pub height: i32,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Serialize, Deserialize)]
 pub struct RenderSamplerState {
     pub filter: RenderSamplerFilter,
     pub address_u: RenderSamplerAddressMode,
@@ -355,7 +378,7 @@ impl Default for RenderSamplerState {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct RenderBlendState {
     pub source_color: RenderBlendMode,
     pub source_alpha: RenderBlendMode,
@@ -380,7 +403,7 @@ impl Default for RenderBlendState {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct RenderState {
     pub blend_states: [RenderBlendState; MAX_RENDER_TARGET_COUNT],
     pub stencil: RenderStencilState,
@@ -414,7 +437,7 @@ impl Default for RenderState {
 }
 
 enum_from_primitive! {
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount, Serialize, Deserialize)]
     #[repr(u8)]
     pub enum RenderVertexUsage {
         Unknown = 0,
@@ -429,7 +452,7 @@ enum_from_primitive! {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct RenderVertexElement {
     pub stream: u32,
     pub offset: u32,
@@ -451,7 +474,7 @@ impl Default for RenderVertexElement {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
 pub struct RenderBindingBuffer {
     pub resource: RenderResourceHandle,
     pub offset: usize,
@@ -461,14 +484,14 @@ pub struct RenderBindingBuffer {
 
 pub type RenderBindingConstantBuffer = RenderBindingBuffer;
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
 pub struct RenderBindingView {
     pub resource: RenderResourceHandle,
     pub format: RenderFormat,
     pub dimension: RenderViewDimension,
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
 pub struct RenderBindingRenderTargetView {
     pub base: RenderBindingView,
     pub mip_slice: u32,
@@ -478,7 +501,7 @@ pub struct RenderBindingRenderTargetView {
     pub w_size: u32,
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
 pub struct RenderBindingDepthStencilView {
     pub base: RenderBindingView,
     pub flags: RenderDepthStencilViewFlags,
@@ -487,7 +510,7 @@ pub struct RenderBindingDepthStencilView {
     pub array_size: u32,
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
 pub struct RenderBindingShaderResourceView {
     pub base: RenderBindingView,
     pub most_detailed_mip_first_element: u32, // TODO: Use either-or enum
@@ -499,7 +522,7 @@ pub struct RenderBindingShaderResourceView {
     pub resource_min_lod_clamp: f32,
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
 pub struct RenderBindingUnorderedAccessView {
     pub base: RenderBindingView,
     pub mip_slice_first_element: u32, // TODO: Use either-or enum
@@ -508,13 +531,13 @@ pub struct RenderBindingUnorderedAccessView {
     pub struct_byte_stride: u32,
 }
 
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RenderShaderTableEntry {
     pub program: RenderResourceHandle,
     pub shader_arguments: Vec<RenderShaderArgument>,
 }
 
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RenderShaderTableUpdateDesc {
     // Must be less than or equal to the counts set at creation time
     pub ray_gen_entries: Vec<RenderShaderTableEntry>,
@@ -522,14 +545,14 @@ pub struct RenderShaderTableUpdateDesc {
     pub miss_entries: Vec<RenderShaderTableEntry>,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum RenderAccelerationPerformanceHint {
     Balanced,
     PreferFastTrace,
     PreferFastBuild,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RenderAccelerationBottomDesc {
     pub geometry: RenderResourceHandle,
     pub acceleration_buffer: RenderResourceHandle,
@@ -551,7 +574,7 @@ impl Default for RenderAccelerationBottomDesc {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct RenderAccelerationInstance {
     pub transform: [f32; 12],
     pub id: u32,
@@ -560,7 +583,7 @@ pub struct RenderAccelerationInstance {
     pub acceleration_buffer: RenderResourceHandle,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct RenderAccelerationTopDesc {
     pub instances: Vec<RenderAccelerationInstance>,
     pub instances_buffer: RenderResourceHandle,
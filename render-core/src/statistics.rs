@@ -0,0 +1,178 @@
+use crate::commands::*;
+use crate::encoder::{RenderCommandFilterStats, RenderCommandList};
+use crate::format::primitive_count_from_vertex_count;
+use crate::handles::RenderResourceHandle;
+use crate::state::RenderGraphicsPipelineStateDesc;
+use crate::types::RenderPrimitiveType;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, RwLock};
+
+/// Counts of a recorded `RenderCommandList`, or of every list compiled during a frame.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenderCommandListStats {
+    pub command_counts: BTreeMap<RenderCommandType, usize>,
+    pub queue_type: RenderCommandQueueType,
+
+    /// Bytes of update data held by the command list's allocator
+    pub allocator_bytes: usize,
+
+    /// Direct and indirect draws
+    pub draw_count: usize,
+
+    /// Direct and indirect dispatches
+    pub dispatch_count: usize,
+
+    /// Primitives of the direct draws, across all instances
+    pub primitive_count: u64,
+
+    /// State left out of the draws and dispatches as already bound
+    pub filter: RenderCommandFilterStats,
+}
+
+impl Default for RenderCommandListStats {
+    fn default() -> Self {
+        RenderCommandListStats {
+            command_counts: BTreeMap::new(),
+            queue_type: RenderCommandQueueType::NONE,
+            allocator_bytes: 0,
+            draw_count: 0,
+            dispatch_count: 0,
+            primitive_count: 0,
+            filter: Default::default(),
+        }
+    }
+}
+
+impl RenderCommandListStats {
+    /// Collects the statistics of a command list, looking up the primitive type of each
+    /// graphics pipeline state; unknown pipeline states count triangle lists.
+    pub fn collect(
+        command_list: &RenderCommandList,
+        primitive_type: impl Fn(RenderResourceHandle) -> Option<RenderPrimitiveType>,
+    ) -> Self {
+        let mut stats = RenderCommandListStats {
+            queue_type: command_list.get_queue_type(),
+            allocator_bytes: command_list.get_allocator_stats().allocated,
+            filter: command_list.get_filter_stats(),
+            ..Default::default()
+        };
+        for entry in command_list.get_commands() {
+            *stats.command_counts.entry(entry.get_type()).or_insert(0) += 1;
+            match entry {
+                RenderCommandEntry::Draw(draw) => {
+                    let primitive_type = primitive_type(draw.pipeline_state)
+                        .unwrap_or(RenderPrimitiveType::TriangleList);
+                    let primitive_count = primitive_count_from_vertex_count(
+                        draw.draw_packet.vertex_count,
+                        primitive_type,
+                    );
+                    stats.draw_count += 1;
+                    stats.primitive_count +=
+                        u64::from(primitive_count) * u64::from(draw.draw_packet.instance_count);
+                }
+                RenderCommandEntry::DrawIndirect(_) => stats.draw_count += 1,
+                RenderCommandEntry::Dispatch(_) | RenderCommandEntry::DispatchIndirect(_) => {
+                    stats.dispatch_count += 1
+                }
+                _ => {}
+            }
+        }
+        stats
+    }
+
+    pub fn command_count(&self, command_type: RenderCommandType) -> usize {
+        self.command_counts.get(&command_type).cloned().unwrap_or(0)
+    }
+
+    /// Adds the counts of another command list.
+    pub fn accumulate(&mut self, other: &RenderCommandListStats) {
+        for (&command_type, &count) in &other.command_counts {
+            *self.command_counts.entry(command_type).or_insert(0) += count;
+        }
+        self.queue_type.insert(other.queue_type);
+        self.allocator_bytes += other.allocator_bytes;
+        self.draw_count += other.draw_count;
+        self.dispatch_count += other.dispatch_count;
+        self.primitive_count += other.primitive_count;
+        self.filter.redundant_pipelines += other.filter.redundant_pipelines;
+        self.filter.redundant_shader_arguments += other.filter.redundant_shader_arguments;
+        self.filter.redundant_draw_bindings += other.filter.redundant_draw_bindings;
+        self.filter.redundant_draw_states += other.filter.redundant_draw_states;
+        self.filter.filtered_bytes += other.filter.filtered_bytes;
+    }
+}
+
+/// Statistics of the command lists a device compiled between two `advance_frame` calls.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenderFrameStats {
+    pub frame_index: u64,
+    pub command_list_count: usize,
+    pub commands: RenderCommandListStats,
+}
+
+/// Aggregates compiled command lists per frame.
+///
+/// Backends record lists from `RenderDevice::compile_command_list(s)`, advance the collector
+/// from `RenderDevice::advance_frame`, and report the last completed frame from
+/// `RenderDevice::get_frame_stats`.
+#[derive(Debug, Default)]
+pub struct RenderFrameStatsCollector {
+    primitive_types: RwLock<HashMap<RenderResourceHandle, RenderPrimitiveType>>,
+    current: Mutex<RenderFrameStats>,
+    completed: Mutex<RenderFrameStats>,
+}
+
+impl RenderFrameStatsCollector {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Remembers the primitive type of a graphics pipeline state, to count its primitives.
+    pub fn register_pipeline(
+        &self,
+        handle: RenderResourceHandle,
+        desc: &RenderGraphicsPipelineStateDesc,
+    ) {
+        self.primitive_types
+            .write()
+            .unwrap()
+            .insert(handle, desc.primitive_type);
+    }
+
+    /// Forgets a destroyed pipeline state; other handles are ignored.
+    pub fn unregister_pipeline(&self, handle: RenderResourceHandle) {
+        self.primitive_types.write().unwrap().remove(&handle);
+    }
+
+    pub fn record(&self, command_list: &RenderCommandList) {
+        let stats = {
+            let primitive_types = self.primitive_types.read().unwrap();
+            RenderCommandListStats::collect(command_list, |handle| {
+                primitive_types.get(&handle).cloned()
+            })
+        };
+        let mut current = self.current.lock().unwrap();
+        current.command_list_count += 1;
+        current.commands.accumulate(&stats);
+    }
+
+    /// Completes the current frame, starting `frame_index`.
+    pub fn advance(&self, frame_index: u64) {
+        let mut current = self.current.lock().unwrap();
+        let next = RenderFrameStats {
+            frame_index,
+            ..Default::default()
+        };
+        *self.completed.lock().unwrap() = std::mem::replace(&mut *current, next);
+    }
+
+    /// Statistics of the frame being recorded.
+    pub fn current(&self) -> RenderFrameStats {
+        self.current.lock().unwrap().clone()
+    }
+
+    /// Statistics of the last completed frame.
+    pub fn completed(&self) -> RenderFrameStats {
+        self.completed.lock().unwrap().clone()
+    }
+}
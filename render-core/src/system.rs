@@ -1,77 +1,276 @@
-use crate::backend::{RenderBackendModule, RenderBackendRegistry, RenderBackendSettings};
-use crate::device::{RenderDevice, RenderDeviceId, RenderDeviceInfo};
+use crate::backend::{
+    RenderBackend, RenderBackendModule, RenderBackendRegistry, RenderBackendSettings,
+    RenderDebugFlags,
+};
+use crate::config::RenderSystemConfig;
+use crate::deferred::{RenderDestroyedFn, RenderOwnedResource};
+use crate::device::{
+    RenderDevice, RenderDeviceEntry, RenderDeviceGroup, RenderDeviceId, RenderDeviceInfo,
+};
 use crate::error::{Error, Result};
-use crate::handles::RenderResourceHandle;
-use crate::handles::RenderResourceHandleAllocator;
+use crate::handles::*;
 use crate::modules::{create_backend_module, load_backend_modules};
+use crate::selection::{
+    RenderDeviceRejection, RenderDeviceScore, RenderDeviceSelection, RenderDeviceSelectionPolicy,
+};
 use crate::types::RenderResourceType;
+use crate::validation::RenderBackendValidation;
 use failure::Fail;
 use libloading::Library;
 use std::borrow::Cow;
+use std::cmp::Reverse;
 use std::collections::HashMap;
+use std::fmt;
 use std::path::Path;
 use std::sync::{Arc, RwLock};
+use std::thread;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenderResourceInventoryEntry {
+    pub handle: RenderResourceHandle,
+
+    /// Name given to `create_handle`, or None if allocated directly (i.e. by a command list)
+    pub name: Option<Cow<'static, str>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenderResourceInventory {
+    pub resource_type: RenderResourceType,
+
+    /// Number of live handles
+    pub count: usize,
+
+    /// Number of handle slots ever allocated (high water mark)
+    pub max: usize,
+
+    /// Live handles, ordered by id
+    pub entries: Vec<RenderResourceInventoryEntry>,
+}
+
+/// What to do with handles still alive when the render system is released.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RenderLeakPolicy {
+    /// Leaked handles are silently dropped
+    Ignore,
+
+    /// Leaked handles are logged as a warning
+    #[default]
+    Log,
+
+    /// `release` returns an error listing the leaked handles
+    Error,
+
+    /// `release` panics with the list of leaked handles
+    Panic,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RenderLeakReport {
+    pub leaks: Vec<RenderResourceInventoryEntry>,
+}
+
+impl RenderLeakReport {
+    pub fn is_empty(&self) -> bool {
+        self.leaks.is_empty()
+    }
+}
+
+impl fmt::Display for RenderLeakReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "leaked {} render resource handle(s)", self.leaks.len())?;
+        for leak in &self.leaks {
+            write!(
+                f,
+                "\n    {:?} [id: {}, cookie: {}] - {}",
+                leak.handle.get_type(),
+                leak.handle.get_id(),
+                leak.handle.get_cookie(),
+                leak.name.as_ref().map_or("<unnamed>", |name| name.as_ref())
+            )?;
+        }
+        Ok(())
+    }
+}
 
 pub struct RenderSystem {
-    handles: Arc<RwLock<RenderResourceHandleAllocator>>,
+    handles: Arc<RenderConcurrentHandleAllocator>,
     registry: Arc<RwLock<Vec<RenderBackendRegistry>>>,
-    libraries: Vec<Box<Library>>,
     modules: Vec<Box<dyn RenderBackendModule>>,
+    static_modules: Vec<Box<dyn RenderBackendModule>>,
     names: Arc<RwLock<HashMap<RenderResourceHandle, Cow<'static, str>>>>,
+    leak_policy: RenderLeakPolicy,
     // TODO: RenderResourceHeap<std::string> resourceNames[int32(RenderResourceType::Count)];
+    /// Declared last, so the code of loaded modules outlives the backends created from them
+    libraries: Vec<Box<Library>>,
 }
 
 impl Drop for RenderSystem {
+    /// Always tears the system down, since a release failed by the leak policy would leave
+    /// backends to be dropped after unloading their modules. Leaks are logged instead of
+    /// failing the release, and `RenderLeakPolicy::Panic` panics once everything is released.
     fn drop(&mut self) {
-        self.release()
-            .expect("failed to called release on drop of render system");
+        let report = match self.leak_policy {
+            // Avoid a double panic (abort) from the leak policy while unwinding
+            RenderLeakPolicy::Panic if !thread::panicking() && self.is_initialized() => {
+                self.get_leak_report().ok()
+            }
+            _ => None,
+        };
+        if self.leak_policy != RenderLeakPolicy::Ignore {
+            self.leak_policy = RenderLeakPolicy::Log;
+        }
+        if let Err(err) = self.release() {
+            error!("failed to release render system on drop: {}", err);
+        }
+        if let Some(report) = report.filter(|report| !report.is_empty()) {
+            panic!("{}", report);
+        }
     }
 }
 
 impl RenderSystem {
     pub fn new() -> Self {
         RenderSystem {
-            handles: Arc::new(RwLock::new(RenderResourceHandleAllocator::new())),
+            handles: Arc::new(RenderConcurrentHandleAllocator::new()),
             registry: Arc::new(RwLock::new(Vec::new())),
-            libraries: Vec::new(),
             modules: Vec::new(),
+            static_modules: Vec::new(),
             names: Arc::new(RwLock::new(HashMap::new())),
+            leak_policy: Default::default(),
+            libraries: Vec::new(),
         }
     }
 
+    pub fn set_leak_policy(&mut self, policy: RenderLeakPolicy) {
+        self.leak_policy = policy;
+    }
+
+    pub fn get_leak_policy(&self) -> RenderLeakPolicy {
+        self.leak_policy
+    }
+
     // System Management
+    /// Registers an in-process backend module (i.e. statically linked into the binary).
+    ///
+    /// Registered modules persist across `initialize` and `release`, and take priority
+    /// over dynamically loaded modules exposing the same api.
+    pub fn register_module(&mut self, module: Box<dyn RenderBackendModule>) -> Result<()> {
+        if self.is_initialized() {
+            Err(Error::backend(
+                "render system must not be initialized when calling register_module",
+            ))
+        } else if self
+            .static_modules
+            .iter()
+            .any(|registered| registered.api() == module.api())
+        {
+            Err(Error::backend(format!(
+                "a render backend module is already registered for api \"{}\"",
+                module.api()
+            )))
+        } else {
+            self.static_modules.push(module);
+            Ok(())
+        }
+    }
+
+    /// Removes the registered module for `api`, handing it back to the caller.
+    ///
+    /// Dynamically loaded modules exposing the same api are no longer shadowed afterwards.
+    pub fn unregister_module(&mut self, api: &str) -> Result<Box<dyn RenderBackendModule>> {
+        if self.is_initialized() {
+            Err(Error::backend(
+                "render system must not be initialized when calling unregister_module",
+            ))
+        } else {
+            match self
+                .static_modules
+                .iter()
+                .position(|registered| registered.api() == api)
+            {
+                Some(index) => Ok(self.static_modules.remove(index)),
+                None => Err(Error::backend(format!(
+                    "no render backend module is registered for api \"{}\"",
+                    api
+                ))),
+            }
+        }
+    }
+
+    /// Initializes backends from registered modules, and modules discovered in `module_path`.
+    ///
+    /// Backends are created in the order of `params`, so the first entry of the registry
+    /// is the most preferred backend. A setting with an empty api matches every module,
+    /// with registered modules tried before dynamically loaded ones.
     pub fn initialize(
         &mut self,
         module_path: &Path,
         params: &[RenderBackendSettings],
+    ) -> Result<()> {
+        self.initialize_modules(Some(module_path), params)
+    }
+
+    /// Initializes backends from registered modules only, skipping dynamic discovery.
+    pub fn initialize_static(&mut self, params: &[RenderBackendSettings]) -> Result<()> {
+        self.initialize_modules(None, params)
+    }
+
+    /// Initializes backends as described by `config`, discovering modules in its module path.
+    pub fn initialize_config(&mut self, config: &RenderSystemConfig) -> Result<()> {
+        self.initialize_modules(config.module_path.as_deref(), &config.backends)
+    }
+
+    fn initialize_modules(
+        &mut self,
+        module_path: Option<&Path>,
+        params: &[RenderBackendSettings],
     ) -> Result<()> {
         if self.is_initialized() {
             self.release()?;
         }
 
-        self.libraries = load_backend_modules(&module_path)?;
-        for library in &self.libraries {
-            self.modules.push(create_backend_module(&library)?);
+        if let Some(module_path) = module_path {
+            self.libraries = load_backend_modules(module_path)?;
+            for library in &self.libraries {
+                self.modules.push(create_backend_module(library)?);
+            }
         }
 
-        if self.modules.len() == 0 {
+        if self.static_modules.is_empty() && self.modules.is_empty() {
             return Err(Error::backend("no render backend modules found"));
         }
 
         let registry_arc = Arc::clone(&self.registry);
         let mut registry_write = registry_arc.write().unwrap();
 
-        // Create matching backends
-        for module in &self.modules {
+        // Registered modules shadow dynamically loaded modules of the same api
+        let mut candidates: Vec<&Box<dyn RenderBackendModule>> = Vec::new();
+        for module in self.static_modules.iter().chain(self.modules.iter()) {
             let module_api = module.api();
-            if module_api.len() > 0 {
-                for settings in params.iter() {
-                    if settings.api.len() == 0 || settings.api == module_api {
-                        registry_write.push(RenderBackendRegistry {
-                            settings: settings.clone(),
-                            backend: Arc::new(RwLock::new(module.create())),
-                        });
+            if !module_api.is_empty()
+                && !candidates
+                    .iter()
+                    .any(|candidate| candidate.api() == module_api)
+            {
+                candidates.push(module);
+            }
+        }
+
+        // Create matching backends, in order of preference
+        for settings in params.iter() {
+            for module in &candidates {
+                if settings.api.is_empty() || settings.api == module.api() {
+                    let mut backend = module.create_with_settings(settings);
+                    if settings
+                        .debug_flags
+                        .contains(RenderDebugFlags::CPU_VALIDATION)
+                    {
+                        backend = Box::new(RenderBackendValidation::new(backend));
                     }
+                    registry_write.push(RenderBackendRegistry {
+                        settings: settings.clone(),
+                        backend: Arc::new(RwLock::new(backend)),
+                    });
                 }
             }
         }
@@ -79,22 +278,57 @@ impl RenderSystem {
         if registry_write.len() == 0 {
             return Err(Error::backend(format!(
                 "no render backend was created - available: {:?}",
-                self.modules
+                candidates
             )));
         }
 
         Ok(())
     }
 
+    /// Releases all backends, applying the leak policy to any handles still alive.
+    ///
+    /// The policy is applied before anything is torn down, so a system whose release fails
+    /// (`RenderLeakPolicy::Error`) is left as it was, with its backends and handles.
     pub fn release(&mut self) -> Result<()> {
-        let registry_arc = Arc::clone(&self.registry);
-        let mut registry_write = registry_arc.write().unwrap();
-        registry_write.clear();
+        if self.is_initialized() {
+            let report = self.get_leak_report()?;
+            if !report.is_empty() {
+                match self.leak_policy {
+                    RenderLeakPolicy::Ignore => {}
+                    RenderLeakPolicy::Log => warn!("{}", report),
+                    RenderLeakPolicy::Error => return Err(Error::backend(report.to_string())),
+                    RenderLeakPolicy::Panic => panic!("{}", report),
+                }
+            }
+        }
+
+        {
+            let registry_arc = Arc::clone(&self.registry);
+            let mut registry_write = registry_arc.write().unwrap();
+            registry_write.clear();
+        }
         self.modules.clear();
         self.libraries.clear();
+
+        // Command lists may still share the old allocator, so replace it rather than reset it
+        self.handles = Arc::new(RenderConcurrentHandleAllocator::new());
+        {
+            let lock = Arc::clone(&self.names);
+            let mut write = lock.write().unwrap();
+            write.clear();
+        }
         Ok(())
     }
 
+    /// Lists every live handle, i.e. those that would leak if the system was released now.
+    pub fn get_leak_report(&self) -> Result<RenderLeakReport> {
+        let mut leaks = Vec::new();
+        for inventory in self.get_inventories()? {
+            leaks.extend(inventory.entries);
+        }
+        Ok(RenderLeakReport { leaks })
+    }
+
     pub fn is_initialized(&self) -> bool {
         let registry_arc = Arc::clone(&self.registry);
         let registry_read = registry_arc.read().unwrap();
@@ -194,161 +428,361 @@ impl RenderSystem {
         }
     }
 
+    /// Creates `primary` and `secondaries` on one backend and groups them for multi-GPU use.
+    pub fn create_device_group(
+        &mut self,
+        registry: &RenderBackendRegistry,
+        primary: RenderDeviceId,
+        secondaries: &[RenderDeviceId],
+    ) -> Result<RenderDeviceGroup> {
+        let primary = self.create_device(registry, primary)?;
+        let secondaries = secondaries
+            .iter()
+            .map(|&device_index| self.create_device(registry, device_index))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(RenderDeviceGroup::new(primary, &secondaries))
+    }
+
+    /// Creates the best device allowed by `policy`, walking its backend preferences in order.
+    ///
+    /// Only backends already present in the registry are considered, so the system is
+    /// typically initialized with `policy.backends` first. Every backend or device passed
+    /// over is reported in `RenderDeviceSelection::rejected`.
+    pub fn select_device(
+        &mut self,
+        policy: &RenderDeviceSelectionPolicy,
+    ) -> Result<RenderDeviceSelection> {
+        if !self.is_initialized() {
+            return Err(Error::backend(
+                "render system must be initialized before calling select_device",
+            ));
+        }
+
+        if policy.backends.is_empty() {
+            return Err(Error::backend(
+                "device selection policy must list at least one backend",
+            ));
+        }
+
+        let entries: Vec<(usize, RenderBackendSettings, _)> = {
+            let registry_arc = Arc::clone(&self.registry);
+            let registry_read = registry_arc.read().unwrap();
+            registry_read
+                .iter()
+                .enumerate()
+                .map(|(index, entry)| (index, entry.settings.clone(), Arc::clone(&entry.backend)))
+                .collect()
+        };
+
+        let mut rejected = Vec::new();
+        for preference in &policy.backends {
+            let matching: Vec<_> = entries
+                .iter()
+                .filter(|(_, settings, _)| {
+                    preference.api.is_empty() || preference.api == settings.api
+                })
+                .collect();
+
+            if matching.is_empty() {
+                rejected.push(RenderDeviceRejection {
+                    api: preference.api.clone(),
+                    device: None,
+                    reason: "backend is not available".to_string(),
+                });
+                continue;
+            }
+
+            for (registry_index, settings, backend) in matching {
+                let mut backend_write = backend.write().unwrap();
+                let devices =
+                    match backend_write.enumerate_devices(u32::MAX, 0, policy.allow_software) {
+                        Ok(devices) => devices,
+                        Err(err) => {
+                            rejected.push(RenderDeviceRejection {
+                                api: settings.api.clone(),
+                                device: None,
+                                reason: format!("failed to enumerate devices - {}", err),
+                            });
+                            continue;
+                        }
+                    };
+
+                if devices.is_empty() {
+                    rejected.push(RenderDeviceRejection {
+                        api: settings.api.clone(),
+                        device: None,
+                        reason: "no devices found".to_string(),
+                    });
+                    continue;
+                }
+
+                let mut candidates = Vec::new();
+                for info in devices {
+                    match (policy.score)(&info) {
+                        RenderDeviceScore::Accept(score) => candidates.push((score, info)),
+                        RenderDeviceScore::Reject(reason) => rejected.push(RenderDeviceRejection {
+                            api: settings.api.clone(),
+                            device: Some(info),
+                            reason,
+                        }),
+                    }
+                }
+
+                // Highest score first, keeping enumeration order for ties
+                candidates.sort_by_key(|(score, _)| Reverse(*score));
+
+                let mut candidates = candidates.into_iter();
+                while let Some((score, info)) = candidates.next() {
+                    let created = backend_write
+                        .create_device(info.device_index)
+                        .and_then(|_| backend_write.get_device(info.device_index));
+                    match created {
+                        Ok(device) => {
+                            for (lower_score, lower_info) in candidates {
+                                rejected.push(RenderDeviceRejection {
+                                    api: settings.api.clone(),
+                                    device: Some(lower_info),
+                                    reason: format!(
+                                        "score {} is lower than selected score {}",
+                                        lower_score, score
+                                    ),
+                                });
+                            }
+
+                            return Ok(RenderDeviceSelection {
+                                registry_index: *registry_index,
+                                settings: settings.clone(),
+                                info,
+                                score,
+                                device,
+                                rejected,
+                            });
+                        }
+                        Err(err) => rejected.push(RenderDeviceRejection {
+                            api: settings.api.clone(),
+                            device: Some(info),
+                            reason: format!("failed to create device - {}", err),
+                        }),
+                    }
+                }
+            }
+        }
+
+        let reasons: Vec<String> = rejected.iter().map(|r| r.to_string()).collect();
+        Err(Error::backend(format!(
+            "no render device could be selected:\n    {}",
+            reasons.join("\n    ")
+        )))
+    }
+
     // Handle Management
-    pub fn get_handle_allocator(&self) -> Result<Arc<RwLock<RenderResourceHandleAllocator>>> {
+    pub fn get_handle_allocator(&self) -> Result<Arc<RenderConcurrentHandleAllocator>> {
         if !self.is_initialized() {
             Err(Error::backend(
                 "render system must be initialized before calling get_handle_allocator",
             ))
         } else {
-            let lock = Arc::clone(&self.handles);
-            Ok(lock)
+            Ok(Arc::clone(&self.handles))
         }
     }
 
-    pub fn is_handle_valid(&self, handle: RenderResourceHandle) -> Result<bool> {
+    pub fn is_handle_valid<H: Into<RenderResourceHandle>>(&self, handle: H) -> Result<bool> {
+        let handle = handle.into();
         if !self.is_initialized() {
             Err(Error::backend(
                 "render system must be initialized before calling is_handle_valid",
             ))
         } else {
-            let lock = Arc::clone(&self.handles);
-            let read = lock.read().unwrap();
-            Ok(read.is_valid(&handle))
+            Ok(self.handles.is_valid(&handle))
         }
     }
 
     pub fn create_swap_chain_handle(
         &self,
         resource_name: Cow<'static, str>,
-    ) -> Result<RenderResourceHandle> {
-        self.create_handle(RenderResourceType::SwapChain, resource_name)
+    ) -> Result<RenderSwapChainHandle> {
+        self.create_typed_handle(resource_name)
     }
 
     pub fn create_buffer_handle(
         &self,
         resource_name: Cow<'static, str>,
-    ) -> Result<RenderResourceHandle> {
-        self.create_handle(RenderResourceType::Buffer, resource_name)
+    ) -> Result<RenderBufferHandle> {
+        self.create_typed_handle(resource_name)
     }
 
     pub fn create_texture_handle(
         &self,
         resource_name: Cow<'static, str>,
-    ) -> Result<RenderResourceHandle> {
-        self.create_handle(RenderResourceType::Texture, resource_name)
+    ) -> Result<RenderTextureHandle> {
+        self.create_typed_handle(resource_name)
     }
 
     pub fn create_sampler_state_handle(
         &self,
         resource_name: Cow<'static, str>,
-    ) -> Result<RenderResourceHandle> {
-        self.create_handle(RenderResourceType::SamplerState, resource_name)
+    ) -> Result<RenderSamplerStateHandle> {
+        self.create_typed_handle(resource_name)
     }
 
     pub fn create_shader_handle(
         &self,
         resource_name: Cow<'static, str>,
-    ) -> Result<RenderResourceHandle> {
-        self.create_handle(RenderResourceType::Shader, resource_name)
+    ) -> Result<RenderShaderHandle> {
+        self.create_typed_handle(resource_name)
     }
 
     pub fn create_shader_views_handle(
         &self,
         resource_name: Cow<'static, str>,
-    ) -> Result<RenderResourceHandle> {
-        self.create_handle(RenderResourceType::ShaderViews, resource_name)
+    ) -> Result<RenderShaderViewsHandle> {
+        self.create_typed_handle(resource_name)
     }
 
     pub fn create_ray_tracing_program_handle(
         &self,
         resource_name: Cow<'static, str>,
-    ) -> Result<RenderResourceHandle> {
-        self.create_handle(RenderResourceType::RayTracingProgram, resource_name)
+    ) -> Result<RenderRayTracingProgramHandle> {
+        self.create_typed_handle(resource_name)
     }
 
     pub fn create_ray_tracing_geometry_handle(
         &self,
         resource_name: Cow<'static, str>,
-    ) -> Result<RenderResourceHandle> {
-        self.create_handle(RenderResourceType::RayTracingGeometry, resource_name)
+    ) -> Result<RenderRayTracingGeometryHandle> {
+        self.create_typed_handle(resource_name)
     }
 
     pub fn create_ray_tracing_acceleration_handle(
         &self,
         resource_name: Cow<'static, str>,
-    ) -> Result<RenderResourceHandle> {
-        self.create_handle(RenderResourceType::RayTracingAcceleration, resource_name)
+    ) -> Result<RenderRayTracingAccelerationHandle> {
+        self.create_typed_handle(resource_name)
     }
 
     pub fn create_ray_tracing_pipeline_state_handle(
         &self,
         resource_name: Cow<'static, str>,
-    ) -> Result<RenderResourceHandle> {
-        self.create_handle(RenderResourceType::RayTracingPipelineState, resource_name)
+    ) -> Result<RenderRayTracingPipelineStateHandle> {
+        self.create_typed_handle(resource_name)
     }
 
     pub fn create_ray_tracing_shader_table_handle(
         &self,
         resource_name: Cow<'static, str>,
-    ) -> Result<RenderResourceHandle> {
-        self.create_handle(RenderResourceType::RayTracingShaderTable, resource_name)
+    ) -> Result<RenderRayTracingShaderTableHandle> {
+        self.create_typed_handle(resource_name)
     }
 
     pub fn create_graphics_pipeline_state_handle(
         &self,
         resource_name: Cow<'static, str>,
-    ) -> Result<RenderResourceHandle> {
-        self.create_handle(RenderResourceType::GraphicsPipelineState, resource_name)
+    ) -> Result<RenderGraphicsPipelineStateHandle> {
+        self.create_typed_handle(resource_name)
     }
 
     pub fn create_compute_pipeline_state_handle(
         &self,
         resource_name: Cow<'static, str>,
-    ) -> Result<RenderResourceHandle> {
-        self.create_handle(RenderResourceType::ComputePipelineState, resource_name)
+    ) -> Result<RenderComputePipelineStateHandle> {
+        self.create_typed_handle(resource_name)
     }
 
     pub fn create_draw_binding_set_handle(
         &self,
         resource_name: Cow<'static, str>,
-    ) -> Result<RenderResourceHandle> {
-        self.create_handle(RenderResourceType::DrawBindingSet, resource_name)
+    ) -> Result<RenderDrawBindingSetHandle> {
+        self.create_typed_handle(resource_name)
     }
 
     pub fn create_frame_binding_set_handle(
         &self,
         resource_name: Cow<'static, str>,
-    ) -> Result<RenderResourceHandle> {
-        self.create_handle(RenderResourceType::FrameBindingSet, resource_name)
+    ) -> Result<RenderFrameBindingSetHandle> {
+        self.create_typed_handle(resource_name)
     }
 
     pub fn create_render_pass_handle(
         &self,
         resource_name: Cow<'static, str>,
-    ) -> Result<RenderResourceHandle> {
-        self.create_handle(RenderResourceType::RenderPass, resource_name)
+    ) -> Result<RenderPassHandle> {
+        self.create_typed_handle(resource_name)
     }
 
     pub fn create_command_list_handle(
         &self,
         resource_name: Cow<'static, str>,
-    ) -> Result<RenderResourceHandle> {
-        self.create_handle(RenderResourceType::CommandList, resource_name)
+    ) -> Result<RenderCommandListHandle> {
+        self.create_typed_handle(resource_name)
     }
 
     pub fn create_fence_handle(
         &self,
         resource_name: Cow<'static, str>,
-    ) -> Result<RenderResourceHandle> {
-        self.create_handle(RenderResourceType::Fence, resource_name)
+    ) -> Result<RenderFenceHandle> {
+        self.create_typed_handle(resource_name)
     }
 
     pub fn create_timing_heap_handle(
         &self,
         resource_name: Cow<'static, str>,
-    ) -> Result<RenderResourceHandle> {
-        self.create_handle(RenderResourceType::TimingHeap, resource_name)
+    ) -> Result<RenderTimingHeapHandle> {
+        self.create_typed_handle(resource_name)
+    }
+
+    /// Creates a handle of the resource type of `H`.
+    pub fn create_typed_handle<H: RenderTypedHandle>(
+        &self,
+        resource_name: Cow<'static, str>,
+    ) -> Result<H> {
+        H::from_handle(self.create_handle(H::RESOURCE_TYPE, resource_name)?)
+    }
+
+    /// Creates a handle and its resource on `device` (with `create`), returning an owner that
+    /// destroys both once dropped and the frames in flight have completed.
+    pub fn create_owned<H: RenderTypedHandle>(
+        &self,
+        device: &RenderDeviceEntry,
+        resource_name: Cow<'static, str>,
+        create: impl FnOnce(&dyn RenderDevice, H, Cow<'static, str>) -> Result<()>,
+    ) -> Result<RenderOwnedResource<H>> {
+        let handle: H = self.create_typed_handle(resource_name.clone())?;
+        let result = {
+            let device_read = device.read().unwrap();
+            match *device_read {
+                Some(ref device) => create(device.as_ref(), handle, resource_name),
+                None => Err(Error::device(
+                    "device must be created before calling create_owned",
+                )),
+            }
+        };
+        match result {
+            Ok(()) => Ok(RenderOwnedResource::new(
+                handle,
+                device,
+                Some(self.handle_releaser()),
+            )),
+            Err(err) => {
+                // Report the creation failure, even if releasing the handle fails too
+                if let Err(release_err) = self.destroy_handle(handle) {
+                    error!("failed to release handle {:?}: {}", handle, release_err);
+                }
+                Err(err)
+            }
+        }
+    }
+
+    /// Returns a callback that releases a handle (and its name), for passing to
+    /// `RenderDevice::destroy_resource_deferred`.
+    pub fn handle_releaser(&self) -> RenderDestroyedFn {
+        let handles = Arc::clone(&self.handles);
+        let names = Arc::clone(&self.names);
+        Box::new(move |handle| {
+            if let Err(err) = handles.release(handle) {
+                warn!("failed to release destroyed resource handle: {}", err);
+            }
+            names.write().unwrap().remove(&handle);
+        })
     }
 
     pub fn create_handle(
@@ -366,11 +800,7 @@ impl RenderSystem {
                     "resource name must be valid when calling create_handle",
                 ))
             } else {
-                let handle = {
-                    let lock = Arc::clone(&self.handles);
-                    let mut write = lock.write().unwrap();
-                    write.allocate(resource_type)
-                };
+                let handle = self.handles.allocate(resource_type)?;
 
                 let lock = Arc::clone(&self.names);
                 let mut write = lock.write().unwrap();
@@ -380,49 +810,147 @@ impl RenderSystem {
         }
     }
 
-    pub fn destroy_handle(&self, handle: RenderResourceHandle) -> Result<()> {
+    pub fn destroy_handle<H: Into<RenderResourceHandle>>(&self, handle: H) -> Result<()> {
+        let handle = handle.into();
         if !self.is_initialized() {
             Err(Error::backend(
                 "render system must be initialized before calling destroy_handle",
             ))
         } else {
-            if !self.is_handle_valid(handle)? {
-                Err(Error::backend(
-                    "resource handle must be valid when calling destroy_handle",
-                ))
-            } else {
-                {
-                    let lock = Arc::clone(&self.handles);
-                    let mut write = lock.write().unwrap();
-                    write.release(handle);
-                }
+            self.handles.release(handle)?;
 
-                let lock = Arc::clone(&self.names);
-                let mut write = lock.write().unwrap();
-                write.remove(&handle);
-                Ok(())
+            let lock = Arc::clone(&self.names);
+            let mut write = lock.write().unwrap();
+            write.remove(&handle);
+            Ok(())
+        }
+    }
+
+    pub fn get_handle_name<H: Into<RenderResourceHandle>>(&self, handle: H) -> Result<String> {
+        let handle = handle.into();
+        if !self.is_initialized() {
+            Err(Error::backend(
+                "render system must be initialized before calling get_handle_name",
+            ))
+        } else {
+            self.handles.check(&handle)?;
+
+            let lock = Arc::clone(&self.names);
+            let read = lock.read().unwrap();
+            match read.get(&handle) {
+                Some(name) => Ok(name.to_string()),
+                None => Err(Error::backend(format!(
+                    "no name recorded for resource handle: {:?}",
+                    handle
+                ))),
             }
         }
     }
 
-    pub fn get_handle_name(&self, _handle: RenderResourceHandle) -> Result<String> {
-        unimplemented!()
+    /// Lists the live handles of a resource type, along with their names.
+    pub fn get_inventory(
+        &self,
+        resource_type: RenderResourceType,
+    ) -> Result<RenderResourceInventory> {
+        if !self.is_initialized() {
+            Err(Error::backend(
+                "render system must be initialized before calling get_inventory",
+            ))
+        } else {
+            let (count, max, handles) = (
+                self.handles.get_count(resource_type),
+                self.handles.get_max(resource_type),
+                self.handles.get_handles(resource_type),
+            );
+
+            let lock = Arc::clone(&self.names);
+            let read = lock.read().unwrap();
+            let entries = handles
+                .into_iter()
+                .map(|handle| RenderResourceInventoryEntry {
+                    handle,
+                    name: read.get(&handle).cloned(),
+                })
+                .collect();
+
+            Ok(RenderResourceInventory {
+                resource_type,
+                count,
+                max,
+                entries,
+            })
+        }
+    }
+
+    /// Lists the live handles of every resource type, skipping types with no live handles.
+    pub fn get_inventories(&self) -> Result<Vec<RenderResourceInventory>> {
+        let mut inventories = Vec::new();
+        for resource_type in RenderResourceType::iter() {
+            let inventory = self.get_inventory(*resource_type)?;
+            if inventory.count > 0 {
+                inventories.push(inventory);
+            }
+        }
+        Ok(inventories)
     }
 
     // Diagnostics
-    pub fn begin_debug_capture(&self, _name: &str) -> Result<()> {
-        unimplemented!()
+    pub fn begin_debug_capture(&self, name: &str) -> Result<()> {
+        self.for_each_capture_backend("begin_debug_capture", |backend| {
+            backend.begin_debug_capture(name)
+        })
     }
 
     pub fn finish_debug_capture(&self) -> Result<()> {
-        unimplemented!()
+        self.for_each_capture_backend("finish_debug_capture", |backend| {
+            backend.finish_debug_capture()
+        })
     }
 
     pub fn trigger_debug_capture(&self) -> Result<()> {
-        unimplemented!()
+        self.for_each_capture_backend("trigger_debug_capture", |backend| {
+            backend.trigger_debug_capture()
+        })
     }
 
-    pub fn launch_debug_capture(&self, _quit: bool) -> Result<()> {
-        unimplemented!()
+    pub fn launch_debug_capture(&self, quit: bool) -> Result<()> {
+        self.for_each_capture_backend("launch_debug_capture", |backend| {
+            backend.launch_debug_capture(quit)
+        })
+    }
+
+    /// Forwards a capture call to every registered backend with `RENDER_DOC` or `PIX` enabled.
+    fn for_each_capture_backend<F>(&self, call: &str, mut func: F) -> Result<()>
+    where
+        F: FnMut(&dyn RenderBackend) -> Result<()>,
+    {
+        if !self.is_initialized() {
+            return Err(Error::backend(format!(
+                "render system must be initialized before calling {}",
+                call
+            )));
+        }
+
+        let capture_flags = RenderDebugFlags::RENDER_DOC | RenderDebugFlags::PIX;
+        let registry_arc = Arc::clone(&self.registry);
+        let registry_read = registry_arc.read().unwrap();
+        let mut forwarded = false;
+        for entry in registry_read.iter() {
+            if entry.settings.debug_flags.intersects(capture_flags) {
+                let backend_arc = Arc::clone(&entry.backend);
+                let backend_read = backend_arc.read().unwrap();
+                func(backend_read.as_ref())?;
+                forwarded = true;
+            }
+        }
+
+        if forwarded {
+            Ok(())
+        } else {
+            Err(Error::backend(format!(
+                "{} requires a backend with RENDER_DOC or PIX debug flags",
+                call
+            )))
+        }
     }
 }
@@ -10,7 +10,9 @@ use winit;
 
 pub type RenderTransitionRecord = (RenderResourceHandle, RenderResourceStates);
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount, Serialize, Deserialize,
+)]
 pub enum RenderQueueType {
     Universal = 0,
     Compute = 1,
@@ -18,7 +20,7 @@ pub enum RenderQueueType {
 }
 
 enum_from_primitive! {
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount, Serialize, Deserialize)]
     #[repr(u32)]
     pub enum RenderFormat {
         Unknown = 0,
@@ -133,7 +135,7 @@ impl Default for RenderFormat {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RenderFormatInfo {
     pub block_width: u32,
     pub block_height: u32,
@@ -149,7 +151,7 @@ pub struct RenderFormatInfo {
 }
 
 enum_from_primitive! {
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount, Serialize, Deserialize)]
     #[repr(u32)]
     pub enum RenderNumericFormat {
         Unknown = 0,
@@ -175,7 +177,7 @@ enum_from_primitive! {
 }
 
 enum_from_primitive! {
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount, Serialize, Deserialize)]
     #[repr(u32)]
     pub enum RenderChannelFormat {
         Unknown = 0,
@@ -230,7 +232,9 @@ enum_from_primitive! {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount, Serialize, Deserialize,
+)]
 pub enum RenderTextureType {
     Tex1d = 0,
     Tex1dArray = 1,
@@ -247,7 +251,9 @@ impl Default for RenderTextureType {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount, Serialize, Deserialize,
+)]
 pub enum RenderViewDimension {
     Unknown = 0,
     Buffer = 1,
@@ -269,7 +275,9 @@ impl Default for RenderViewDimension {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount, Serialize, Deserialize,
+)]
 pub enum RenderPrimitiveType {
     PointList = 0,
     LineList = 1,
@@ -287,14 +295,14 @@ impl Default for RenderPrimitiveType {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RenderPoint {
     pub x: i32,
     pub y: i32,
     pub z: i32,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RenderBox {
     pub x: i32,
     pub y: i32,
@@ -305,7 +313,7 @@ pub struct RenderBox {
 }
 
 enum_from_primitive! {
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount, Serialize, Deserialize)]
     #[repr(u16)]
     pub enum RenderResourceType {
         SwapChain = 0,
@@ -364,6 +372,7 @@ impl RenderResourceType {
 }
 
 bitflags! {
+    #[derive(Serialize, Deserialize)]
     pub struct RenderBindFlags: u16 {
         const NONE = 0x0000;
         const VERTEX_BUFFER = 0x0001;
@@ -387,6 +396,7 @@ impl Default for RenderBindFlags {
 }
 
 bitflags! {
+    #[derive(Serialize, Deserialize)]
     pub struct RenderResourceStates: u16 {
         const COMMON = 0;
         const VERTEX_AND_CONSTANT_BUFFER = 0x1;
@@ -408,7 +418,7 @@ bitflags! {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct RenderDrawPacket {
     pub index_offset: u32,
     pub vertex_offset: i32,
@@ -445,7 +455,9 @@ pub struct RenderSwapChainWindow {
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct RenderSwapChainWindow {}
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
 pub struct RenderShaderParameter {
     pub shader_resource_count: u32,
     pub unordered_access_count: u32,
@@ -477,7 +489,7 @@ impl RenderShaderParameter {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Serialize, Deserialize)]
 pub struct RenderShaderArgument {
     /// Allowed to be None if not used by shader
     pub constant_buffer: Option<RenderResourceHandle>,
@@ -489,7 +501,7 @@ pub struct RenderShaderArgument {
     pub constant_buffer_offset: usize,
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
 pub struct RenderShaderSignatureDesc {
     pub parameters: [RenderShaderParameter; MAX_SHADER_PARAMETERS],
     pub parameter_count: u32,
@@ -525,7 +537,7 @@ impl RenderShaderSignatureDesc {
 }
 
 enum_from_primitive! {
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount, Serialize, Deserialize)]
     #[repr(u8)]
     pub enum RenderShaderType {
         Vertex = 0,
@@ -538,7 +550,7 @@ enum_from_primitive! {
 }
 
 enum_from_primitive! {
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount, Serialize, Deserialize)]
     #[repr(u8)]
     pub enum RenderCommandListType {
         Invalid = 0,
@@ -550,7 +562,7 @@ enum_from_primitive! {
 }
 
 enum_from_primitive! {
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount, Serialize, Deserialize)]
     #[repr(u8)]
     pub enum RayTracingShaderType {
         RayGen = 0,
@@ -562,7 +574,7 @@ enum_from_primitive! {
 }
 
 enum_from_primitive! {
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount, Serialize, Deserialize)]
     #[repr(u8)]
     pub enum RayTracingProgramType {
         RayGen = 0,
@@ -572,7 +584,7 @@ enum_from_primitive! {
 }
 
 enum_from_primitive! {
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount, Serialize, Deserialize)]
     #[repr(u8)]
     pub enum RayTracingGeometryType {
         Triangle = 0,
@@ -581,7 +593,7 @@ enum_from_primitive! {
 }
 
 enum_from_primitive! {
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount, Serialize, Deserialize)]
     #[repr(u16)]
     pub enum RenderLoadOp {
         Discard = 0,
@@ -591,7 +603,7 @@ enum_from_primitive! {
 }
 
 enum_from_primitive! {
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount, Serialize, Deserialize)]
     #[repr(u16)]
     pub enum RenderStoreOp {
         Discard = 0,
@@ -600,6 +612,7 @@ enum_from_primitive! {
 }
 
 bitflags! {
+    #[derive(Serialize, Deserialize)]
     pub struct RenderDepthStencilViewFlags: u16 {
         const NONE = 0x0;
         const READ_ONLY_DEPTH = 0x1;
@@ -613,19 +626,19 @@ impl Default for RenderDepthStencilViewFlags {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Serialize, Deserialize)]
 pub struct RenderShaderDesc {
     pub shader_type: RenderShaderType,
     pub shader_data: Vec<u8>,
 }
 
-#[derive(Clone, Default, Debug)]
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct RenderBufferDesc {
     pub bind_flags: RenderBindFlags,
     pub size: usize,
 }
 
-#[derive(Clone, Default, Debug)]
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct RenderTextureDesc {
     pub texture_type: RenderTextureType,
     pub bind_flags: RenderBindFlags,
@@ -645,7 +658,7 @@ pub struct RenderTextureSubResourceData<'a> {
     pub slice_pitch: u32,
 }
 
-#[derive(Clone, Default, Debug)]
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct RenderShaderViewsDesc {
     // No CBV here as it is passed in dynamically at bind time
     pub shader_resource_views: Vec<RenderBindingShaderResourceView>,
@@ -653,26 +666,26 @@ pub struct RenderShaderViewsDesc {
 }
 
 // TODO: Should make this just use generic shader and program desc types
-#[derive(Clone, Default, Debug)]
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct RayTracingShaderDesc {
     pub entry_point: String,
     pub shader_data: Vec<u8>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RayTracingProgramDesc {
     pub program_type: RayTracingProgramType,
     pub shaders: [RayTracingShaderDesc; MAX_RAY_TRACING_SHADER_TYPE],
     pub signature: RenderShaderSignatureDesc,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RayTracingGeometryPart {
     pub index_count: u32,
     pub index_offset: u32,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RayTracingGeometryDesc {
     pub geometry_type: RayTracingGeometryType,
     pub vertex_buffer: RenderBindingBuffer,
@@ -680,37 +693,37 @@ pub struct RayTracingGeometryDesc {
     pub parts: Vec<RayTracingGeometryPart>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RayTracingTopAccelerationDesc {}
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RayTracingBottomAccelerationDesc {}
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RayTracingPipelineStateDesc {
     pub programs: Vec<RenderResourceHandle>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RayTracingShaderTableDesc {
     pub raygen_entry_count: u32,
     pub hit_entry_count: u32,
     pub miss_entry_count: u32,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RenderDrawBindingSetDesc {
     pub vertex_buffers: [Option<RenderBindingBuffer>; MAX_VERTEX_STREAMS],
     pub index_buffer: Option<RenderBindingBuffer>,
 }
 
-#[derive(Clone, Default, Debug)]
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct RenderFrameBindingSetDesc {
     pub render_target_views: [Option<RenderBindingRenderTargetView>; MAX_RENDER_TARGET_COUNT],
     pub depth_stencil_view: Option<RenderBindingDepthStencilView>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RenderTargetInfo {
     pub load_op: RenderLoadOp,
     pub store_op: RenderStoreOp,
@@ -727,7 +740,7 @@ impl Default for RenderTargetInfo {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DepthStencilTargetInfo {
     pub load_op: RenderLoadOp,
     pub store_op: RenderStoreOp,
@@ -746,25 +759,25 @@ impl Default for DepthStencilTargetInfo {
     }
 }
 
-#[derive(Clone, Default, Debug)]
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct RenderPassDesc {
     pub frame_binding: RenderResourceHandle,
     pub render_target_info: [RenderTargetInfo; MAX_RENDER_TARGET_COUNT],
     pub depth_stencil_target_info: DepthStencilTargetInfo,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RenderFenceDesc {
     pub cross_device: bool,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RenderTimingHeapDesc {
     /// Number of begin/end pairs
     pub region_count: usize,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RenderDrawArguments {
     pub vertex_count_per_instance: u32,
     pub instance_count: u32,
@@ -772,7 +785,7 @@ pub struct RenderDrawArguments {
     pub start_instance_location: u32,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RenderDrawIndexedArguments {
     pub index_count_per_instance: u32,
     pub instance_count: u32,
@@ -781,14 +794,14 @@ pub struct RenderDrawIndexedArguments {
     pub start_instance_location: u32,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RenderDispatchArguments {
     pub thread_group_count_x: u32,
     pub thread_group_count_y: u32,
     pub thread_group_count_z: u32,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RenderUploadHeapDesc {
     pub heap_size: usize,
 }
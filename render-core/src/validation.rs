@@ -0,0 +1,722 @@
+use crate::backend::{RenderBackend, RenderBackendLayer};
+use crate::commands::*;
+use crate::deferred::RenderDestroyedFn;
+use crate::device::*;
+use crate::encoder::{RenderCommandId, RenderCommandList};
+use crate::error::{Error, Result};
+use crate::handles::*;
+use crate::state::*;
+use crate::types::*;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::RwLock;
+
+/// What a `RenderValidationDiagnostic` reports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RenderValidationKind {
+    /// A render pass or event ended without a matching begin
+    UnmatchedEnd,
+
+    /// A render pass or event is still open at the end of the list
+    UnclosedScope,
+
+    /// A render pass began inside another render pass
+    NestedRenderPass,
+
+    /// A draw was recorded outside of a render pass
+    DrawOutsideRenderPass,
+
+    /// A command that isn't allowed in a render pass (i.e. a dispatch or copy) was recorded in one
+    InvalidInRenderPass,
+
+    /// A handle is null, or refers to the wrong type of resource
+    ResourceType,
+
+    /// The number of shader arguments doesn't match the pipeline's shader signature
+    ShaderArgumentCount,
+
+    /// A draw left out its draw state as unchanged, but no draw state is bound
+    UnboundDrawState,
+}
+
+/// A problem found in a recorded command list.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenderValidationDiagnostic {
+    pub command_id: RenderCommandId,
+    pub command_type: RenderCommandType,
+    pub kind: RenderValidationKind,
+    pub message: String,
+}
+
+impl fmt::Display for RenderValidationDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "command {} ({:?}): {}",
+            self.command_id, self.command_type, self.message
+        )
+    }
+}
+
+/// Where a command may be recorded relative to render passes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RenderPassPlacement {
+    /// Only inside a render pass, i.e. draws
+    Inside,
+
+    /// Only outside of render passes, i.e. dispatches and copies
+    Outside,
+
+    /// Either inside or outside of a render pass
+    Anywhere,
+}
+
+pub fn render_pass_placement(command_type: RenderCommandType) -> RenderPassPlacement {
+    match command_type {
+        RenderCommandType::Draw | RenderCommandType::DrawIndirect => RenderPassPlacement::Inside,
+        RenderCommandType::Dispatch
+        | RenderCommandType::DispatchIndirect
+        | RenderCommandType::UpdateBuffer
+        | RenderCommandType::UpdateTexture
+        | RenderCommandType::CopyBuffer
+        | RenderCommandType::CopyTexture
+        | RenderCommandType::ResolveTimings
+        | RenderCommandType::RayTrace
+        | RenderCommandType::UpdateTopLevelAcceleration
+        | RenderCommandType::UpdateBottomLevelAcceleration
+        | RenderCommandType::UpdateShaderTable => RenderPassPlacement::Outside,
+        RenderCommandType::Barriers
+        | RenderCommandType::Transitions
+        | RenderCommandType::BeginTiming
+        | RenderCommandType::EndTiming
+        | RenderCommandType::BeginEvent
+        | RenderCommandType::EndEvent
+        | RenderCommandType::BeginRenderPass
+        | RenderCommandType::EndRenderPass => RenderPassPlacement::Anywhere,
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RenderValidationScope {
+    Event,
+    RenderPass,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RenderValidationBindPoint {
+    Graphics,
+    Compute,
+}
+
+/// Collects the diagnostics of a single command list.
+struct RenderValidationPass<'a> {
+    validator: &'a RenderCommandValidator,
+    diagnostics: Vec<RenderValidationDiagnostic>,
+    scopes: Vec<(RenderValidationScope, RenderCommandId)>,
+    graphics_pipeline: Option<RenderResourceHandle>,
+    compute_pipeline: Option<RenderResourceHandle>,
+    draw_state_bound: bool,
+    command_id: RenderCommandId,
+    command_type: RenderCommandType,
+}
+
+impl<'a> RenderValidationPass<'a> {
+    fn report(&mut self, kind: RenderValidationKind, message: String) {
+        self.diagnostics.push(RenderValidationDiagnostic {
+            command_id: self.command_id,
+            command_type: self.command_type,
+            kind,
+            message,
+        });
+    }
+
+    fn in_render_pass(&self) -> bool {
+        self.scopes
+            .iter()
+            .any(|&(scope, _)| scope == RenderValidationScope::RenderPass)
+    }
+
+    fn begin_scope(&mut self, scope: RenderValidationScope) {
+        self.scopes.push((scope, self.command_id));
+    }
+
+    fn end_scope(&mut self, scope: RenderValidationScope) {
+        match self.scopes.last() {
+            Some(&(open_scope, _)) if open_scope == scope => {
+                self.scopes.pop();
+            }
+            Some(&(open_scope, begin_id)) => self.report(
+                RenderValidationKind::UnmatchedEnd,
+                format!(
+                    "end of {:?} while the {:?} begun by command {} is open",
+                    scope, open_scope, begin_id
+                ),
+            ),
+            None => self.report(
+                RenderValidationKind::UnmatchedEnd,
+                format!("end of {:?} without a matching begin", scope),
+            ),
+        }
+    }
+
+    fn expect_handle(
+        &mut self,
+        field: &str,
+        handle: RenderResourceHandle,
+        expected: RenderResourceType,
+    ) {
+        if !handle.is_valid() {
+            self.report(
+                RenderValidationKind::ResourceType,
+                format!("{} is a null handle, expected {:?}", field, expected),
+            );
+        } else if handle.get_type() != expected {
+            self.report(
+                RenderValidationKind::ResourceType,
+                format!(
+                    "{} is a {:?} handle, expected {:?}",
+                    field,
+                    handle.get_type(),
+                    expected
+                ),
+            );
+        }
+    }
+
+    /// Checks `handle` if it is set; null handles mark unused optional resources.
+    fn expect_optional_handle(
+        &mut self,
+        field: &str,
+        handle: Option<RenderResourceHandle>,
+        expected: RenderResourceType,
+    ) {
+        if let Some(handle) = handle.filter(|handle| handle.is_valid()) {
+            self.expect_handle(field, handle, expected);
+        }
+    }
+
+    fn expect_shader_arguments(
+        &mut self,
+        bind_point: RenderValidationBindPoint,
+        pipeline_state: RenderResourceHandle,
+        shader_arguments: Option<&[RenderShaderArgument]>,
+    ) {
+        let bound_pipeline = match bind_point {
+            RenderValidationBindPoint::Graphics => &mut self.graphics_pipeline,
+            RenderValidationBindPoint::Compute => &mut self.compute_pipeline,
+        };
+        let rebound = *bound_pipeline == Some(pipeline_state);
+        *bound_pipeline = Some(pipeline_state);
+
+        // Arguments left out by the encoder are those already bound to the pipeline state
+        let shader_arguments = match shader_arguments {
+            Some(shader_arguments) => shader_arguments,
+            None if rebound => return,
+            None => &[],
+        };
+
+        for (index, argument) in shader_arguments.iter().enumerate() {
+            self.expect_optional_handle(
+                &format!("shader argument {} constant buffer", index),
+                argument.constant_buffer,
+                RenderResourceType::Buffer,
+            );
+            self.expect_optional_handle(
+                &format!("shader argument {} shader views", index),
+                argument.shader_views,
+                RenderResourceType::ShaderViews,
+            );
+        }
+
+        let parameter_count = match self.validator.signatures.get(&pipeline_state) {
+            Some(signature) => signature.parameter_count as usize,
+            None => return,
+        };
+        if shader_arguments.len() != parameter_count {
+            self.report(
+                RenderValidationKind::ShaderArgumentCount,
+                format!(
+                    "{} shader argument(s) recorded, the pipeline signature has {} parameter(s)",
+                    shader_arguments.len(),
+                    parameter_count
+                ),
+            );
+        }
+    }
+
+    fn expect_draw_state(&mut self, draw_state: Option<&RenderDrawState>) {
+        // Draw states left out by the encoder are the same as the bound one. Like unbound
+        // shader arguments, a missing draw state is only reported on the first draw after it
+        // was lost, rather than again for every draw that follows
+        if draw_state.is_none() && !self.draw_state_bound {
+            self.report(
+                RenderValidationKind::UnboundDrawState,
+                "draw state left out as unchanged, but none is bound".to_string(),
+            );
+        }
+        self.draw_state_bound = true;
+    }
+
+    fn expect_placement(&mut self, placement: RenderPassPlacement) {
+        match placement {
+            RenderPassPlacement::Inside if !self.in_render_pass() => self.report(
+                RenderValidationKind::DrawOutsideRenderPass,
+                "draws must be recorded inside a render pass".to_string(),
+            ),
+            RenderPassPlacement::Outside if self.in_render_pass() => self.report(
+                RenderValidationKind::InvalidInRenderPass,
+                format!(
+                    "{:?} is not allowed inside a render pass",
+                    self.command_type
+                ),
+            ),
+            _ => {}
+        }
+    }
+
+    fn validate(&mut self, entry: &RenderCommandEntry) {
+        self.expect_placement(render_pass_placement(entry.get_type()));
+        if entry.get_type().invalidates_bindings() {
+            self.graphics_pipeline = None;
+            self.compute_pipeline = None;
+            self.draw_state_bound = false;
+        }
+        match entry {
+            RenderCommandEntry::Draw(command) => {
+                self.expect_handle(
+                    "pipeline_state",
+                    command.pipeline_state,
+                    RenderResourceType::GraphicsPipelineState,
+                );
+                self.expect_optional_handle(
+                    "draw_binding",
+                    command.draw_binding,
+                    RenderResourceType::DrawBindingSet,
+                );
+                self.expect_shader_arguments(
+                    RenderValidationBindPoint::Graphics,
+                    command.pipeline_state,
+                    command.shader_arguments.as_deref(),
+                );
+                self.expect_draw_state(command.draw_state.as_ref());
+            }
+            RenderCommandEntry::DrawIndirect(command) => {
+                self.expect_handle(
+                    "pipeline_state",
+                    command.pipeline_state,
+                    RenderResourceType::GraphicsPipelineState,
+                );
+                self.expect_handle(
+                    "draw_binding",
+                    command.draw_binding,
+                    RenderResourceType::DrawBindingSet,
+                );
+                self.expect_handle(
+                    "indirect_buffer",
+                    command.indirect_buffer,
+                    RenderResourceType::Buffer,
+                );
+                self.expect_optional_handle(
+                    "count_buffer",
+                    Some(command.count_buffer),
+                    RenderResourceType::Buffer,
+                );
+                self.expect_shader_arguments(
+                    RenderValidationBindPoint::Graphics,
+                    command.pipeline_state,
+                    command.shader_arguments.as_deref(),
+                );
+                self.expect_draw_state(command.draw_state.as_ref());
+            }
+            RenderCommandEntry::Dispatch(command) => {
+                self.expect_handle(
+                    "pipeline_state",
+                    command.pipeline_state,
+                    RenderResourceType::ComputePipelineState,
+                );
+                self.expect_shader_arguments(
+                    RenderValidationBindPoint::Compute,
+                    command.pipeline_state,
+                    command.shader_arguments.as_deref(),
+                );
+            }
+            RenderCommandEntry::DispatchIndirect(command) => {
+                self.expect_handle(
+                    "pipeline_state",
+                    command.pipeline_state,
+                    RenderResourceType::ComputePipelineState,
+                );
+                self.expect_handle(
+                    "indirect_buffer",
+                    command.indirect_buffer,
+                    RenderResourceType::Buffer,
+                );
+                self.expect_optional_handle(
+                    "count_buffer",
+                    Some(command.count_buffer),
+                    RenderResourceType::Buffer,
+                );
+                self.expect_shader_arguments(
+                    RenderValidationBindPoint::Compute,
+                    command.pipeline_state,
+                    command.shader_arguments.as_deref(),
+                );
+            }
+            RenderCommandEntry::UpdateBuffer(command) => {
+                self.expect_handle("buffer", command.buffer, RenderResourceType::Buffer);
+            }
+            RenderCommandEntry::UpdateTexture(command) => {
+                self.expect_handle("texture", command.texture, RenderResourceType::Texture);
+            }
+            RenderCommandEntry::CopyBuffer(command) => {
+                self.expect_handle("src_buffer", command.src_buffer, RenderResourceType::Buffer);
+                self.expect_handle("dst_buffer", command.dst_buffer, RenderResourceType::Buffer);
+            }
+            RenderCommandEntry::CopyTexture(command) => {
+                self.expect_handle(
+                    "src_texture",
+                    command.src_texture,
+                    RenderResourceType::Texture,
+                );
+                self.expect_handle(
+                    "dst_texture",
+                    command.dst_texture,
+                    RenderResourceType::Texture,
+                );
+            }
+            RenderCommandEntry::Barriers(_) | RenderCommandEntry::Transitions(_) => {}
+            RenderCommandEntry::BeginTiming(command) => {
+                self.expect_handle(
+                    "timing_heap",
+                    command.timing_heap,
+                    RenderResourceType::TimingHeap,
+                );
+            }
+            RenderCommandEntry::EndTiming(command) => {
+                self.expect_handle(
+                    "timing_heap",
+                    command.timing_heap,
+                    RenderResourceType::TimingHeap,
+                );
+            }
+            RenderCommandEntry::ResolveTimings(command) => {
+                self.expect_handle(
+                    "timing_heap",
+                    command.timing_heap,
+                    RenderResourceType::TimingHeap,
+                );
+            }
+            RenderCommandEntry::BeginEvent(_) => self.begin_scope(RenderValidationScope::Event),
+            RenderCommandEntry::EndEvent(_) => self.end_scope(RenderValidationScope::Event),
+            RenderCommandEntry::BeginRenderPass(command) => {
+                if self.in_render_pass() {
+                    self.report(
+                        RenderValidationKind::NestedRenderPass,
+                        "render passes can't be nested".to_string(),
+                    );
+                }
+                self.expect_handle(
+                    "render_pass",
+                    command.render_pass,
+                    RenderResourceType::RenderPass,
+                );
+                self.begin_scope(RenderValidationScope::RenderPass);
+            }
+            RenderCommandEntry::EndRenderPass(_) => {
+                self.end_scope(RenderValidationScope::RenderPass)
+            }
+            RenderCommandEntry::RayTrace(command) => {
+                self.expect_handle(
+                    "pipeline_state",
+                    command.pipeline_state,
+                    RenderResourceType::RayTracingPipelineState,
+                );
+                self.expect_handle(
+                    "shader_table",
+                    command.shader_table,
+                    RenderResourceType::RayTracingShaderTable,
+                );
+                self.expect_handle("rt_output", command.rt_output, RenderResourceType::Texture);
+            }
+            RenderCommandEntry::UpdateTopLevelAcceleration(command) => {
+                self.expect_handle(
+                    "acceleration",
+                    command.acceleration,
+                    RenderResourceType::RayTracingAcceleration,
+                );
+            }
+            RenderCommandEntry::UpdateBottomLevelAcceleration(command) => {
+                self.expect_handle(
+                    "acceleration",
+                    command.acceleration,
+                    RenderResourceType::RayTracingAcceleration,
+                );
+            }
+            RenderCommandEntry::UpdateShaderTable(command) => {
+                self.expect_handle(
+                    "shader_table",
+                    command.shader_table,
+                    RenderResourceType::RayTracingShaderTable,
+                );
+            }
+        }
+    }
+}
+
+/// Backend independent checks of recorded command lists.
+///
+/// Scopes, render pass rules and handle types are checked for every list. Shader argument
+/// counts are only checked for pipelines registered with their shader signature.
+#[derive(Clone, Debug, Default)]
+pub struct RenderCommandValidator {
+    signatures: HashMap<RenderResourceHandle, RenderShaderSignatureDesc>,
+}
+
+impl RenderCommandValidator {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn register_pipeline<H: Into<RenderResourceHandle>>(
+        &mut self,
+        pipeline_state: H,
+        signature: &RenderShaderSignatureDesc,
+    ) {
+        self.signatures.insert(pipeline_state.into(), *signature);
+    }
+
+    pub fn unregister_pipeline<H: Into<RenderResourceHandle>>(&mut self, pipeline_state: H) {
+        self.signatures.remove(&pipeline_state.into());
+    }
+
+    /// Returns every problem found, in command order; an empty list is valid.
+    pub fn validate(&self, command_list: &RenderCommandList) -> Vec<RenderValidationDiagnostic> {
+        self.validate_commands(command_list.get_commands())
+    }
+
+    pub fn validate_commands(
+        &self,
+        commands: &[RenderCommandEntry],
+    ) -> Vec<RenderValidationDiagnostic> {
+        let mut pass = RenderValidationPass {
+            validator: self,
+            diagnostics: Vec::new(),
+            scopes: Vec::new(),
+            graphics_pipeline: None,
+            compute_pipeline: None,
+            draw_state_bound: false,
+            command_id: 0,
+            command_type: RenderCommandType::Draw,
+        };
+
+        for (command_id, entry) in commands.iter().enumerate() {
+            pass.command_id = command_id;
+            pass.command_type = entry.get_type();
+            pass.validate(entry);
+        }
+
+        for (scope, begin_id) in pass.scopes.split_off(0) {
+            pass.command_id = begin_id;
+            pass.command_type = commands[begin_id].get_type();
+            pass.report(
+                RenderValidationKind::UnclosedScope,
+                format!("{:?} is never ended", scope),
+            );
+        }
+
+        pass.diagnostics
+    }
+
+    /// Fails with an encoder error listing the diagnostics, if there are any.
+    pub fn check(&self, command_list: &RenderCommandList) -> Result<()> {
+        let diagnostics = self.validate(command_list);
+        if diagnostics.is_empty() {
+            return Ok(());
+        }
+
+        let mut message = format!(
+            "command list failed validation with {} error(s)",
+            diagnostics.len()
+        );
+        for diagnostic in &diagnostics {
+            message.push_str(&format!("\n    {}", diagnostic));
+        }
+        Err(Error::encoder(message))
+    }
+}
+
+/// Validates every command list before the backend compiles it, for backends created with
+/// `RenderDebugFlags::CPU_VALIDATION`.
+///
+/// Every other call is forwarded to the wrapped backend and its devices unchanged.
+#[derive(Debug)]
+pub struct RenderBackendValidation {
+    layer: RenderBackendLayer,
+}
+
+impl RenderBackendValidation {
+    pub fn new(inner: Box<dyn RenderBackend>) -> Self {
+        RenderBackendValidation {
+            layer: RenderBackendLayer::new(inner),
+        }
+    }
+}
+
+impl RenderBackend for RenderBackendValidation {
+    fn is_initialized(&self) -> bool {
+        self.layer.inner().is_initialized()
+    }
+
+    fn enumerate_devices(
+        &mut self,
+        max_devices: u32,
+        mirror_count: u32,
+        software: bool,
+    ) -> Result<Vec<RenderDeviceInfo>> {
+        self.layer
+            .enumerate_devices(max_devices, mirror_count, software)
+    }
+
+    fn create_device(&mut self, device_id: RenderDeviceId) -> Result<()> {
+        self.layer.create_device(device_id, |inner| {
+            Ok(Box::new(RenderDeviceValidation {
+                device_id,
+                inner,
+                validator: RwLock::new(RenderCommandValidator::new()),
+            }))
+        })
+    }
+
+    fn destroy_device(&mut self, device_id: RenderDeviceId) -> Result<()> {
+        self.layer.destroy_device(device_id)
+    }
+
+    fn get_device(&self, device_id: RenderDeviceId) -> Result<RenderDeviceEntry> {
+        self.layer.get_device(device_id)
+    }
+
+    fn begin_debug_capture(&self, name: &str) -> Result<()> {
+        self.layer.inner().begin_debug_capture(name)
+    }
+
+    fn finish_debug_capture(&self) -> Result<()> {
+        self.layer.inner().finish_debug_capture()
+    }
+
+    fn trigger_debug_capture(&self) -> Result<()> {
+        self.layer.inner().trigger_debug_capture()
+    }
+
+    fn launch_debug_capture(&self, quit: bool) -> Result<()> {
+        self.layer.inner().launch_debug_capture(quit)
+    }
+}
+
+#[derive(Debug)]
+struct RenderDeviceValidation {
+    device_id: RenderDeviceId,
+    inner: RenderDeviceEntry,
+    /// Signatures of the pipelines created on this device
+    validator: RwLock<RenderCommandValidator>,
+}
+
+impl RenderDeviceValidation {
+    fn check(&self, command_list: &RenderCommandList) -> Result<()> {
+        let result = self.validator.read().unwrap().check(command_list);
+        if let Err(ref err) = result {
+            error!("{}", err);
+        }
+        result
+    }
+}
+
+impl RenderDeviceLayer for RenderDeviceValidation {
+    fn layer_device_id(&self) -> RenderDeviceId {
+        self.device_id
+    }
+
+    fn layer_entry(&self) -> &RenderDeviceEntry {
+        &self.inner
+    }
+
+    fn destroy_resource(&self, handle: RenderResourceHandle) -> Result<()> {
+        self.validator.write().unwrap().unregister_pipeline(handle);
+        self.layer_inner()?.destroy_resource(handle)
+    }
+
+    fn destroy_resource_deferred(
+        &self,
+        handle: RenderResourceHandle,
+        on_destroyed: Option<RenderDestroyedFn>,
+    ) -> Result<()> {
+        self.validator.write().unwrap().unregister_pipeline(handle);
+        let (inner, on_destroyed) = self.layer_inner_or_release(handle, on_destroyed)?;
+        inner.destroy_resource_deferred(handle, on_destroyed)
+    }
+
+    fn create_graphics_pipeline_state(
+        &self,
+        handle: RenderGraphicsPipelineStateHandle,
+        desc: &RenderGraphicsPipelineStateDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.layer_inner()?
+            .create_graphics_pipeline_state(handle, desc, debug_name)?;
+        self.validator
+            .write()
+            .unwrap()
+            .register_pipeline(handle, &desc.shader_signature);
+        Ok(())
+    }
+
+    fn create_compute_pipeline_state(
+        &self,
+        handle: RenderComputePipelineStateHandle,
+        desc: &RenderComputePipelineStateDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.layer_inner()?
+            .create_compute_pipeline_state(handle, desc, debug_name)?;
+        self.validator
+            .write()
+            .unwrap()
+            .register_pipeline(handle, &desc.shader_signature);
+        Ok(())
+    }
+
+    fn compile_command_list(
+        &self,
+        handle: RenderCommandListHandle,
+        command_list: &RenderCommandList,
+    ) -> Result<()> {
+        self.check(command_list)?;
+        self.layer_inner()?
+            .compile_command_list(handle, command_list)
+    }
+
+    fn compile_command_lists(
+        &self,
+        handle: RenderCommandListHandle,
+        command_lists: &[RenderCommandList],
+    ) -> Result<()> {
+        for command_list in command_lists {
+            self.check(command_list)?;
+        }
+        self.layer_inner()?
+            .compile_command_lists(handle, command_lists)
+    }
+
+    // Cross-Node Transfer [Prototype]
+    fn device_transfer(
+        &self,
+        wait_value: u64,
+        signal_value: u64,
+        fence: RenderFenceHandle,
+        command_list: &RenderCommandList,
+    ) -> Result<()> {
+        self.check(command_list)?;
+        self.layer_inner()?
+            .device_transfer(wait_value, signal_value, fence, command_list)
+    }
+}
@@ -1,43 +1,160 @@
-extern crate render_core;
-use render_core::allocator::{LinearAllocator, LinearAllocatorMark};
-use render_core::utilities::*;
-
-#[test]
-fn allocator_is_empty() {
-    let allocator = LinearAllocator::new(64);
-    assert_eq!(allocator.is_empty(), true);
-    assert_eq!(allocator.size(), 0);
-    assert_eq!(allocator.capacity(), 64);
-    assert_eq!(allocator.mark(), 0 as LinearAllocatorMark);
-}
-
-#[test]
-fn allocator_mark_insert() {
-    let mut allocator = LinearAllocator::new(64);
-    let mut test_data: Vec<f32> = Vec::with_capacity(12);
-    for i in 0..12 {
-        test_data.push(((i as f32) * 1234f32) + ((i as f32) * 1000f32));
-    }
-
-    let byte_length = std::mem::size_of::<f32>() * 12;
-
-    // Test slices going out of scope
-    let data_mark = {
-        let test_bytes = typed_to_bytes(&test_data);
-        assert_eq!(test_bytes.len(), byte_length);
-
-        let data_mark = allocator.allocate_raw(byte_length, 8, 0).unwrap();
-        allocator.mark_insert(data_mark, &test_bytes).unwrap();
-
-        assert_eq!(byte_length, allocator.size());
-        assert_eq!(allocator.mark(), byte_length);
-        data_mark
-    };
-
-    let test_bytes2 = typed_to_bytes(&test_data);
-    assert_eq!(test_bytes2.len(), byte_length);
-
-    let placed_data = allocator.mark_data(data_mark, byte_length).unwrap();
-    assert_eq!(test_bytes2, placed_data);
-    assert_eq!(allocator.capacity(), 64);
-}
+extern crate render_core;
+use render_core::allocator::{
+    LinearAllocator, LinearAllocatorMark, LinearAllocatorMode, LINEAR_ALLOCATOR_MIN_PAGE_SIZE,
+};
+use render_core::error::ErrorKind;
+use render_core::utilities::*;
+
+#[test]
+fn allocator_is_empty() {
+    let allocator = LinearAllocator::new(64);
+    assert!(allocator.is_empty());
+    assert_eq!(allocator.size(), 0);
+    assert_eq!(allocator.capacity(), 64);
+    assert_eq!(allocator.mark(), 0 as LinearAllocatorMark);
+}
+
+#[test]
+fn allocator_mark_insert() {
+    let mut allocator = LinearAllocator::new(64);
+    let mut test_data: Vec<f32> = Vec::with_capacity(12);
+    for i in 0..12 {
+        test_data.push(((i as f32) * 1234f32) + ((i as f32) * 1000f32));
+    }
+
+    let byte_length = std::mem::size_of::<f32>() * 12;
+
+    // Test slices going out of scope
+    let data_mark = {
+        let test_bytes = typed_to_bytes(&test_data);
+        assert_eq!(test_bytes.len(), byte_length);
+
+        let data_mark = allocator.allocate_raw(byte_length, 8, 0).unwrap();
+        allocator.mark_insert(data_mark, test_bytes).unwrap();
+
+        assert_eq!(byte_length, allocator.size());
+        assert_eq!(allocator.mark(), byte_length);
+        data_mark
+    };
+
+    let test_bytes2 = typed_to_bytes(&test_data);
+    assert_eq!(test_bytes2.len(), byte_length);
+
+    let placed_data = allocator.mark_data(data_mark, byte_length).unwrap();
+    assert_eq!(test_bytes2, placed_data);
+    assert_eq!(allocator.capacity(), 64);
+}
+
+#[test]
+fn allocator_grow() {
+    let mut allocator = LinearAllocator::new(64);
+    let first = allocator.allocate_raw(48, 8, 0).unwrap();
+    allocator.mark_insert(first, &[1u8; 48]).unwrap();
+    assert_eq!(allocator.available(), 16);
+
+    // Doesn't fit in the first page, so a second one is chained
+    let second = allocator.allocate_raw(32, 8, 0).unwrap();
+    allocator.mark_insert(second, &[2u8; 32]).unwrap();
+    assert!(second >= 64);
+    assert_eq!(allocator.capacity(), 128);
+    assert_eq!(allocator.stats().page_count, 2);
+
+    // Larger than a page
+    let large = allocator.allocate_raw(256, 8, 0).unwrap();
+    allocator.mark_insert(large, &[3u8; 256]).unwrap();
+    assert_eq!(allocator.stats().page_count, 3);
+
+    // Earlier allocations are left intact
+    assert_eq!(allocator.mark_data(first, 48).unwrap(), &[1u8; 48][..]);
+    assert_eq!(allocator.mark_data(second, 32).unwrap(), &[2u8; 32][..]);
+    assert_eq!(allocator.mark_data(large, 256).unwrap(), &[3u8; 256][..]);
+
+    // Rewinding keeps the pages for reuse
+    allocator.rewind(0).unwrap();
+    assert!(allocator.is_empty());
+    allocator.allocate_raw(48, 8, 0).unwrap();
+    allocator.allocate_raw(32, 8, 0).unwrap();
+    assert_eq!(allocator.stats().page_count, 3);
+}
+
+#[test]
+fn allocator_grow_empty() {
+    let mut allocator = LinearAllocator::new(0);
+    assert_eq!(allocator.capacity(), 0);
+
+    let first = allocator.allocate_raw(16, 8, 0).unwrap();
+    allocator.mark_insert(first, &[1u8; 16]).unwrap();
+    let second = allocator.allocate_raw(64, 8, 0).unwrap();
+    allocator.mark_insert(second, &[2u8; 64]).unwrap();
+    assert_eq!(allocator.mark_data(first, 16).unwrap(), &[1u8; 16][..]);
+    assert_eq!(allocator.mark_data(second, 64).unwrap(), &[2u8; 64][..]);
+
+    // The empty first page is filled in rather than followed by a page at the same base,
+    // and sized for more than the allocation that filled it
+    assert_eq!(allocator.stats().page_count, 1);
+    assert_eq!(allocator.capacity(), LINEAR_ALLOCATOR_MIN_PAGE_SIZE);
+
+    // Later pages are at least as large as the filled in one
+    for _ in 0..LINEAR_ALLOCATOR_MIN_PAGE_SIZE / 16 {
+        allocator.allocate_raw(16, 8, 0).unwrap();
+    }
+    assert_eq!(allocator.stats().page_count, 2);
+    assert_eq!(allocator.capacity(), 2 * LINEAR_ALLOCATOR_MIN_PAGE_SIZE);
+    allocator.rewind(0).unwrap();
+    assert!(allocator.is_empty());
+}
+
+#[test]
+fn allocator_bounded() {
+    let mut allocator = LinearAllocator::with_mode(64, LinearAllocatorMode::Bounded);
+    allocator.allocate_raw(48, 8, 0).unwrap();
+    let err = allocator.allocate_raw(32, 8, 0).unwrap_err();
+    match err.kind() {
+        ErrorKind::Memory(_) => {}
+        kind => panic!("unexpected error kind {:?}", kind),
+    }
+
+    // The failed allocation leaves the allocator untouched
+    assert_eq!(allocator.size(), 48);
+    assert_eq!(allocator.capacity(), 64);
+    assert_eq!(allocator.stats().page_count, 1);
+    assert!(allocator.allocate_raw(16, 8, 0).is_ok());
+    assert_eq!(allocator.available(), 0);
+}
+
+#[test]
+fn allocator_alignment() {
+    let mut allocator = LinearAllocator::new(256);
+    allocator.allocate_raw(3, 1, 0).unwrap();
+    for &alignment in &[2usize, 4, 16, 64] {
+        let mark = allocator.allocate_raw(8, alignment, 0).unwrap();
+        let address = allocator.mark_data(mark, 8).unwrap().as_ptr() as usize;
+        assert!(is_aligned(address, alignment));
+        allocator.allocate_raw(1, 1, 0).unwrap();
+    }
+
+    let mark = allocator.allocate_typed::<u64>().unwrap();
+    let value: &u64 = allocator.mark_place(mark, 0x0123_4567_89ab_cdef).unwrap();
+    assert_eq!(*value, 0x0123_4567_89ab_cdef);
+    assert!(allocator.allocate_raw(8, 3, 0).is_err());
+}
+
+#[test]
+fn allocator_stats() {
+    let mut allocator = LinearAllocator::new(64);
+    allocator.allocate_raw(4, 1, 0).unwrap();
+    allocator.allocate_raw(16, 16, 0).unwrap();
+    let stats = allocator.stats();
+    assert_eq!(stats.allocation_count, 2);
+    assert_eq!(stats.allocated, 20);
+    assert_eq!(stats.allocated + stats.wasted, allocator.size());
+    assert_eq!(stats.peak, allocator.size());
+    assert_eq!(stats.capacity, 64);
+    assert_eq!(stats.utilisation(), 0.3125);
+
+    allocator.rewind(0).unwrap();
+    let stats = allocator.stats();
+    assert_eq!(stats.allocation_count, 0);
+    assert_eq!(stats.allocated, 0);
+    assert!(stats.peak >= 20);
+}
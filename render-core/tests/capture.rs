@@ -0,0 +1,318 @@
+extern crate render_core;
+extern crate render_hal_mock;
+extern crate render_hal_proxy;
+use render_core::backend::*;
+use render_core::commands::*;
+use render_core::encoder::*;
+use render_core::error::ErrorKind;
+use render_core::handles::*;
+use render_core::serialization::RenderSerializedCommandList;
+use render_core::state::*;
+use render_core::system::*;
+use render_core::types::*;
+use render_hal_mock::RenderBackendModuleMock;
+use render_hal_proxy::*;
+use std::env;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::sync::Arc;
+mod common;
+
+fn capture_system(settings: RenderCaptureSettings) -> RenderSystem {
+    common::static_render_system(
+        Box::new(RenderBackendModuleCapture::new(
+            Box::new(RenderBackendModuleMock::new()),
+            settings,
+        )),
+        &[common::mock_backend_settings(RenderDebugFlags::NONE)],
+    )
+}
+
+fn capture_path(test_name: &str) -> PathBuf {
+    env::temp_dir().join(format!("render-core-{}.capture", test_name))
+}
+
+/// Creates a buffer, then updates and submits it once per frame.
+fn record_frames(render_system: &mut RenderSystem, frame_count: u64) -> RenderBufferHandle {
+    let device = common::create_render_device(render_system);
+    let device_read = device.read().unwrap();
+    let device_read = device_read.as_ref().unwrap();
+
+    let buffer_handle = render_system
+        .create_buffer_handle("Capture Buffer".into())
+        .unwrap();
+    device_read
+        .create_buffer(
+            buffer_handle,
+            &RenderBufferDesc {
+                bind_flags: RenderBindFlags::SHADER_RESOURCE,
+                size: 64,
+            },
+            Some(&[0u8; 64]),
+            "Capture Buffer".into(),
+        )
+        .unwrap();
+
+    let command_list_handle = render_system
+        .create_command_list_handle("Capture Commands".into())
+        .unwrap();
+    device_read
+        .create_command_list(command_list_handle, "Capture Commands".into())
+        .unwrap();
+
+    let handles = render_system.get_handle_allocator().unwrap();
+    for frame in 0..frame_count {
+        let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
+        command_list
+            .update_buffer(buffer_handle, 0, &[frame as u8; 16])
+            .unwrap();
+        device_read
+            .compile_command_list(command_list_handle, &command_list)
+            .unwrap();
+        device_read
+            .submit_command_list(command_list_handle, false, None, None)
+            .unwrap();
+        device_read.advance_frame().unwrap();
+    }
+
+    device_read
+        .destroy_resource(command_list_handle.into())
+        .unwrap();
+    buffer_handle
+}
+
+#[test]
+fn capture_replay() {
+    let path = capture_path("capture_replay");
+    let mut render_system = capture_system(RenderCaptureSettings::new(&path));
+    let buffer_handle = record_frames(&mut render_system, 3);
+    render_system.release().unwrap();
+
+    let mut replay = RenderCaptureReplay::new(&RenderBackendModuleMock::new());
+    let summary = replay.replay_file(&path).unwrap();
+    assert_eq!(summary.api, "mock");
+    assert_eq!(summary.resources_created, 2);
+    assert_eq!(summary.command_lists_compiled, 3);
+    assert_eq!(summary.submits, 3);
+    assert_eq!(summary.frames, 3);
+
+    // Resources keep their captured handles
+    let device = replay.backend().get_device(0).unwrap();
+    let device_read = device.read().unwrap();
+    let device_read = device_read.as_ref().unwrap();
+    assert!(device_read.valid_resource(buffer_handle.into()));
+    assert_eq!(device_read.get_frame_index(), 3);
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn capture_frame_range() {
+    let path = capture_path("capture_frame_range");
+    let mut render_system = capture_system(RenderCaptureSettings::new(&path).with_frames(1, 2));
+    record_frames(&mut render_system, 4);
+    render_system.release().unwrap();
+
+    // Resources are captured regardless of the range, frame work only within it
+    let mut replay = RenderCaptureReplay::new(&RenderBackendModuleMock::new());
+    let summary = replay.replay_file(&path).unwrap();
+    assert_eq!(summary.resources_created, 2);
+    assert_eq!(summary.command_lists_compiled, 2);
+    assert_eq!(summary.submits, 2);
+    assert_eq!(summary.frames, 2);
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn capture_compiled_before_frame_range() {
+    let path = capture_path("capture_compiled_before_frame_range");
+    let mut render_system = capture_system(RenderCaptureSettings::new(&path).with_frames(1, 2));
+    {
+        let device = common::create_render_device(&mut render_system);
+        let device_read = device.read().unwrap();
+        let device_read = device_read.as_ref().unwrap();
+
+        let command_list_handle = render_system
+            .create_command_list_handle("Capture Commands".into())
+            .unwrap();
+        device_read
+            .create_command_list(command_list_handle, "Capture Commands".into())
+            .unwrap();
+
+        // Compiled twice before the range, then only submitted within it
+        let handles = render_system.get_handle_allocator().unwrap();
+        for _ in 0..2 {
+            let command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
+            device_read
+                .compile_command_list(command_list_handle, &command_list)
+                .unwrap();
+        }
+        for _ in 0..3 {
+            device_read
+                .submit_command_list(command_list_handle, false, None, None)
+                .unwrap();
+            device_read.advance_frame().unwrap();
+        }
+    }
+    render_system.release().unwrap();
+
+    // Only the latest compile is captured, ahead of the first captured submit
+    let mut reader = BufReader::new(File::open(&path).unwrap());
+    read_capture_header(&mut reader).unwrap();
+    let mut compiled = false;
+    while let Some(call) = read_capture_call(&mut reader).unwrap() {
+        match call {
+            RenderCaptureCall::CompileCommandList { .. } => compiled = true,
+            call => assert!(compiled || !format!("{:?}", call).contains("SubmitCommandList")),
+        }
+    }
+
+    let mut replay = RenderCaptureReplay::new(&RenderBackendModuleMock::new());
+    let summary = replay.replay_file(&path).unwrap();
+    assert_eq!(summary.command_lists_compiled, 1);
+    assert_eq!(summary.submits, 2);
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn capture_write_failure() {
+    // Writes to /dev/full fail once they spill out of the capture stream's buffer, here on
+    // the 64 KiB of initial data; device creation and the header stay buffered until then
+    let mut render_system = capture_system(RenderCaptureSettings::new("/dev/full"));
+    let device = common::create_render_device(&mut render_system);
+    let device_read = device.read().unwrap();
+    let device_read = device_read.as_ref().unwrap();
+
+    let buffer_handle = render_system
+        .create_buffer_handle("Capture Buffer".into())
+        .unwrap();
+    device_read
+        .create_buffer(
+            buffer_handle,
+            &RenderBufferDesc {
+                bind_flags: RenderBindFlags::SHADER_RESOURCE,
+                size: 64 * 1024,
+            },
+            Some(&vec![0u8; 64 * 1024]),
+            "Capture Buffer".into(),
+        )
+        .unwrap();
+    assert!(device_read.valid_resource(buffer_handle.into()));
+    device_read.destroy_resource(buffer_handle.into()).unwrap();
+    device_read.advance_frame().unwrap();
+}
+
+#[test]
+fn capture_filtered_command_list() {
+    let path = capture_path("capture_filtered_command_list");
+    let mut render_system = capture_system(RenderCaptureSettings::new(&path));
+    {
+        let device = common::create_render_device(&mut render_system);
+        let device_read = device.read().unwrap();
+        let device_read = device_read.as_ref().unwrap();
+
+        let pipeline_state = render_system
+            .create_graphics_pipeline_state_handle("Capture Pipeline".into())
+            .unwrap();
+        let command_list_handle = render_system
+            .create_command_list_handle("Capture Commands".into())
+            .unwrap();
+        device_read
+            .create_command_list(command_list_handle, "Capture Commands".into())
+            .unwrap();
+
+        let handles = render_system.get_handle_allocator().unwrap();
+        let mut command_list = RenderCommandList::new(handles, 8 * 1024, 16).unwrap();
+        let draw_state = RenderDrawState {
+            stencil_ref: 7,
+            ..Default::default()
+        };
+        for _ in 0..2 {
+            command_list
+                .draw(
+                    pipeline_state,
+                    &[],
+                    None,
+                    &draw_state,
+                    &RenderDrawPacket {
+                        index_offset: 0,
+                        vertex_offset: 0,
+                        vertex_count: 3,
+                        first_instance: 0,
+                        instance_count: 1,
+                    },
+                )
+                .unwrap();
+        }
+        device_read
+            .compile_command_list(command_list_handle, &command_list)
+            .unwrap();
+    }
+    render_system.release().unwrap();
+
+    // Command lists are captured verbatim, including the draw state left out as unchanged
+    let mut reader = BufReader::new(File::open(&path).unwrap());
+    read_capture_header(&mut reader).unwrap();
+    let mut command_lists = Vec::new();
+    while let Some(call) = read_capture_call(&mut reader).unwrap() {
+        if let RenderCaptureCall::CompileCommandList { command_list, .. } = call {
+            command_lists.push(RenderSerializedCommandList::from_bytes(&command_list).unwrap());
+        }
+    }
+    assert_eq!(command_lists.len(), 1);
+    let draw_states: Vec<Option<RenderDrawState>> = command_lists[0]
+        .commands
+        .iter()
+        .map(|command| {
+            command
+                .command
+                .downcast_ref::<RenderCommandDraw>()
+                .unwrap()
+                .draw_state
+        })
+        .collect();
+    assert_eq!(draw_states.len(), 2);
+    assert_eq!(draw_states[0].unwrap().stencil_ref, 7);
+    assert_eq!(draw_states[1], None);
+
+    let mut replay = RenderCaptureReplay::new(&RenderBackendModuleMock::new());
+    let summary = replay.replay_file(&path).unwrap();
+    assert_eq!(summary.command_lists_compiled, 1);
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn capture_invalid() {
+    let header = read_capture_header(&mut &b"RCLS\x01\x00\x00\x00"[..]).unwrap_err();
+    match header.kind() {
+        ErrorKind::Parse(_) => {}
+        kind => panic!("unexpected error kind {:?}", kind),
+    }
+
+    let mut data = CAPTURE_MAGIC.to_vec();
+    data.extend_from_slice(&(CAPTURE_VERSION + 1).to_le_bytes());
+    let mut replay = RenderCaptureReplay::new(&RenderBackendModuleMock::new());
+    assert!(replay.replay(&mut &data[..]).is_err());
+
+    // Oversized and truncated calls fail without reading (or allocating) the advertised size
+    let oversized = ((PROXY_MAX_MESSAGE_SIZE + 1) as u32).to_le_bytes();
+    assert!(read_capture_call(&mut &oversized[..]).is_err());
+    let mut truncated = (PROXY_MAX_MESSAGE_SIZE as u32).to_le_bytes().to_vec();
+    truncated.extend_from_slice(&[0u8; 16]);
+    let err = read_capture_call(&mut &truncated[..]).unwrap_err();
+    assert!(err.to_string().contains("truncated after 16"));
+
+    // A capture that can't be created leaves the backend working, without capturing
+    let directory = env::temp_dir().join("render-core-capture-missing");
+    let _ = fs::remove_dir_all(&directory);
+    let path = directory.join("frame.capture");
+    let mut render_system = capture_system(RenderCaptureSettings::new(&path));
+    assert!(render_system.is_initialized());
+    record_frames(&mut render_system, 1);
+    assert!(!path.exists());
+}
@@ -1,14 +1,19 @@
+// Each test crate only uses some of the helpers
+#![allow(dead_code)]
+
 use render_core::backend::*;
+use render_core::config::*;
 //use render_core::commands::*;
 use render_core::device::*;
 //use render_core::encoder::*;
-use render_core::handles::*;
 use render_core::system::*;
+use render_hal_mock::RenderBackendModuleMock;
 //use render_core::types::*;
 //use render_core::utilities::*;
 use std::env;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
+use std::thread;
 
 pub fn get_render_debug_flags() -> RenderDebugFlags {
     RenderDebugFlags::NONE
@@ -35,32 +40,98 @@ pub fn get_render_module_path() -> PathBuf {
     module_path.to_path_buf()
 }
 
+/// Default test configuration, with RENDER_BACKEND_API etc. overrides applied on top.
+pub fn get_render_system_config() -> RenderSystemConfig {
+    let mut config = RenderSystemConfig::new(&get_render_backend_settings());
+    config.module_path = Some(get_render_module_path());
+    config
+        .apply_env()
+        .expect("invalid render system environment overrides");
+    config
+}
+
+/// Render system with only `module` registered, initialized for `settings` without loading
+/// backend modules.
+pub fn static_render_system(
+    module: Box<dyn RenderBackendModule>,
+    settings: &[RenderBackendSettings],
+) -> RenderSystem {
+    let mut render_system = RenderSystem::new();
+    render_system.register_module(module).unwrap();
+    render_system.initialize_static(settings).unwrap();
+    render_system
+}
+
+pub fn mock_backend_settings(debug_flags: RenderDebugFlags) -> RenderBackendSettings {
+    RenderBackendSettings {
+        api: "mock".to_string(),
+        address: None,
+        debug_flags,
+    }
+}
+
+/// Render system with a single mock backend.
+pub fn mock_render_system(debug_flags: RenderDebugFlags) -> RenderSystem {
+    static_render_system(
+        Box::new(RenderBackendModuleMock::new()),
+        &[mock_backend_settings(debug_flags)],
+    )
+}
+
+/// Creates the first device of the first backend.
+pub fn create_render_device(render_system: &mut RenderSystem) -> RenderDeviceEntry {
+    let registry = render_system.get_registry().unwrap();
+    let registry_read = registry.read().unwrap();
+    let entry = &registry_read[0];
+    render_system
+        .enumerate_devices(entry, false, None, None)
+        .unwrap();
+    render_system.create_device(entry, 0).unwrap()
+}
+
 pub struct SystemHarness {
     pub render_system: Arc<RwLock<RenderSystem>>,
     pub device_info: Arc<Vec<RenderDeviceInfo>>,
-    pub handles: Arc<RwLock<RenderResourceHandleAllocator>>,
     pub device: Arc<RwLock<Option<Box<dyn RenderDevice>>>>,
 }
 
 impl SystemHarness {
+    /// Harness for the configured backends, with the mock module available to
+    /// RENDER_BACKEND_API overrides.
     pub fn new() -> SystemHarness {
-        let render_system = Arc::new(RwLock::new(RenderSystem::new()));
-        let mut harness = SystemHarness {
-            render_system,
-            device_info: Arc::new(Vec::new()),
-            handles: Arc::new(RwLock::new(RenderResourceHandleAllocator::new())),
-            device: Arc::new(RwLock::new(None)),
-        };
+        let mut render_system = RenderSystem::new();
+        render_system
+            .register_module(Box::new(RenderBackendModuleMock::new()))
+            .unwrap();
+        let mut harness = SystemHarness::empty(render_system);
+        harness.initialize(&get_render_system_config());
+        harness
+    }
 
-        harness.initialize(&get_render_module_path(), &get_render_backend_settings());
+    /// Harness with a single mock backend, for tests that don't need a real device.
+    pub fn mock() -> SystemHarness {
+        let mut render_system = RenderSystem::new();
+        render_system
+            .register_module(Box::new(RenderBackendModuleMock::new()))
+            .unwrap();
+        let mut harness = SystemHarness::empty(render_system);
+        harness.initialize(&RenderSystemConfig::new(&[mock_backend_settings(
+            get_render_debug_flags(),
+        )]));
         harness
     }
 
-    pub fn initialize(&mut self, module_path: &Path, backend_settings: &[RenderBackendSettings]) {
+    fn empty(render_system: RenderSystem) -> SystemHarness {
+        SystemHarness {
+            render_system: Arc::new(RwLock::new(render_system)),
+            device_info: Arc::new(Vec::new()),
+            device: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    pub fn initialize(&mut self, config: &RenderSystemConfig) {
         let mut rs_write = self.render_system.write().unwrap();
-        rs_write
-            .initialize(&module_path, &backend_settings)
-            .unwrap();
+        rs_write.initialize_config(config).unwrap();
         assert!(rs_write.is_initialized());
         let registry = Arc::clone(&rs_write.get_registry().unwrap());
         let registry_read = registry.read().unwrap();
@@ -88,6 +159,10 @@ impl SystemHarness {
 
 impl Drop for SystemHarness {
     fn drop(&mut self) {
+        // The render system lock is poisoned if initialization panicked
+        if thread::panicking() {
+            return;
+        }
         self.release();
     }
 }
@@ -0,0 +1,158 @@
+extern crate render_core;
+extern crate render_hal_mock;
+use render_core::backend::*;
+use render_core::config::*;
+use render_core::error::ErrorKind;
+use render_core::system::*;
+use render_hal_mock::RenderBackendModuleMock;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+const CONFIG_JSON: &str = r#"{
+    "module_path": "modules",
+    "backends": [
+        { "api": "vk", "debug_flags": ["RENDER_DOC", "CPU_VALIDATION"] },
+        { "api": "mock", "address": "127.0.0.1:50080", "debug_flags": "PIX|gpu_validation" },
+        { "api": "dx12", "debug_flags": 16 }
+    ]
+}"#;
+
+fn overrides(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let vars: HashMap<String, String> = vars
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+    move |name| vars.get(name).cloned()
+}
+
+#[test]
+fn debug_flags_parse() {
+    assert_eq!(
+        "RENDER_DOC|CPU_VALIDATION"
+            .parse::<RenderDebugFlags>()
+            .unwrap(),
+        RenderDebugFlags::RENDER_DOC | RenderDebugFlags::CPU_VALIDATION
+    );
+    assert_eq!(
+        "pix, gpu_validation".parse::<RenderDebugFlags>().unwrap(),
+        RenderDebugFlags::PIX | RenderDebugFlags::GPU_VALIDATION
+    );
+    assert_eq!(
+        "NONE".parse::<RenderDebugFlags>().unwrap(),
+        RenderDebugFlags::NONE
+    );
+    assert_eq!(
+        "3".parse::<RenderDebugFlags>().unwrap(),
+        RenderDebugFlags::RENDER_DOC | RenderDebugFlags::PIX
+    );
+    assert!("RENDER_DOCS".parse::<RenderDebugFlags>().is_err());
+    assert!("256".parse::<RenderDebugFlags>().is_err());
+}
+
+#[test]
+fn config_from_json() {
+    let config = RenderSystemConfig::from_json(CONFIG_JSON).unwrap();
+    assert_eq!(config.module_path, Some(PathBuf::from("modules")));
+    assert_eq!(config.backends.len(), 3);
+    assert_eq!(config.backends[0].api, "vk");
+    assert_eq!(config.backends[0].address, None);
+    assert_eq!(
+        config.backends[0].debug_flags,
+        RenderDebugFlags::RENDER_DOC | RenderDebugFlags::CPU_VALIDATION
+    );
+    assert_eq!(
+        config.backends[1].address,
+        Some("127.0.0.1:50080".to_string())
+    );
+    assert_eq!(
+        config.backends[1].debug_flags,
+        RenderDebugFlags::PIX | RenderDebugFlags::GPU_VALIDATION
+    );
+    assert_eq!(
+        config.backends[2].debug_flags,
+        RenderDebugFlags::POST_CRASH_ANALYSIS
+    );
+
+    let round_trip = RenderSystemConfig::from_json(&config.to_json().unwrap()).unwrap();
+    assert_eq!(round_trip, config);
+
+    let empty = RenderSystemConfig::from_json("{}").unwrap();
+    assert_eq!(empty, RenderSystemConfig::default());
+}
+
+#[test]
+fn config_invalid() {
+    let err = RenderSystemConfig::from_json(
+        r#"{ "backends": [{ "api": "vk", "debug_flags": ["FOO"] }] }"#,
+    )
+    .unwrap_err();
+    match err.kind() {
+        ErrorKind::Parse(msg) => assert!(msg.contains("FOO")),
+        _ => panic!("unexpected error kind: {:?}", err.kind()),
+    }
+
+    let err = RenderSystemConfig::load("does/not/exist.json").unwrap_err();
+    match err.kind() {
+        ErrorKind::Config(_) => {}
+        _ => panic!("unexpected error kind: {:?}", err.kind()),
+    }
+}
+
+#[test]
+fn config_overrides() {
+    let mut config = RenderSystemConfig::from_json(CONFIG_JSON).unwrap();
+    config
+        .apply_overrides(overrides(&[
+            (RENDER_BACKEND_API_ENV, "mock, proxy"),
+            (RENDER_DEBUG_FLAGS_ENV, "CPU_VALIDATION"),
+            (RENDER_MODULE_PATH_ENV, ""),
+        ]))
+        .unwrap();
+
+    assert_eq!(config.module_path, None);
+    assert_eq!(config.backends.len(), 2);
+    assert_eq!(config.backends[0].api, "mock");
+    assert_eq!(
+        config.backends[0].address,
+        Some("127.0.0.1:50080".to_string())
+    );
+    assert_eq!(config.backends[1].api, "proxy");
+    for settings in &config.backends {
+        assert_eq!(settings.debug_flags, RenderDebugFlags::CPU_VALIDATION);
+    }
+
+    assert!(config
+        .apply_overrides(overrides(&[(RENDER_DEBUG_FLAGS_ENV, "BOGUS")]))
+        .is_err());
+    assert!(config
+        .apply_overrides(overrides(&[(RENDER_BACKEND_API_ENV, " , ")]))
+        .is_err());
+}
+
+#[test]
+fn config_load_and_initialize() {
+    let path = env::temp_dir().join("render-core-config.json");
+    fs::write(
+        &path,
+        r#"{ "backends": [{ "api": "vk" }, { "api": "mock", "debug_flags": ["RENDER_DOC"] }] }"#,
+    )
+    .unwrap();
+
+    let config = RenderSystemConfig::load(&path).unwrap();
+    let mut render_system = RenderSystem::new();
+    render_system
+        .register_module(Box::new(RenderBackendModuleMock::new()))
+        .unwrap();
+    render_system.initialize_config(&config).unwrap();
+
+    let registry = render_system.get_registry().unwrap();
+    let registry_read = registry.read().unwrap();
+    assert_eq!(registry_read.len(), 1);
+    assert_eq!(registry_read[0].settings.api, "mock");
+    assert_eq!(
+        registry_read[0].settings.debug_flags,
+        RenderDebugFlags::RENDER_DOC
+    );
+}
@@ -0,0 +1,163 @@
+extern crate render_core;
+extern crate render_hal_mock;
+use render_core::backend::*;
+use render_core::deferred::*;
+use render_core::device::*;
+use render_core::handles::*;
+use render_core::system::*;
+use render_core::types::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+mod common;
+
+const BUFFER_DESC: RenderBufferDesc = RenderBufferDesc {
+    bind_flags: RenderBindFlags::CONSTANT_BUFFER,
+    size: 64,
+};
+
+fn mock_system() -> (RenderSystem, RenderDeviceEntry) {
+    let mut render_system = common::mock_render_system(RenderDebugFlags::NONE);
+    let device = common::create_render_device(&mut render_system);
+    (render_system, device)
+}
+
+fn advance_frame(device: &RenderDeviceEntry) {
+    device
+        .read()
+        .unwrap()
+        .as_ref()
+        .unwrap()
+        .advance_frame()
+        .unwrap();
+}
+
+fn device_valid(device: &RenderDeviceEntry, handle: RenderResourceHandle) -> bool {
+    device
+        .read()
+        .unwrap()
+        .as_ref()
+        .unwrap()
+        .valid_resource(handle)
+}
+
+#[test]
+fn deferred_destroy_after_frames_in_flight() {
+    let (render_system, device) = mock_system();
+    let handle = render_system
+        .create_buffer_handle("Deferred Buffer".into())
+        .unwrap();
+    let destroyed = Arc::new(AtomicBool::new(false));
+    {
+        let device_read = device.read().unwrap();
+        let device_read = device_read.as_ref().unwrap();
+        device_read
+            .create_buffer(handle, &BUFFER_DESC, None, "Deferred Buffer".into())
+            .unwrap();
+        assert_eq!(device_read.get_frame_index(), 0);
+
+        let destroyed = Arc::clone(&destroyed);
+        device_read
+            .destroy_resource_deferred(
+                handle.into(),
+                Some(Box::new(move |_| destroyed.store(true, Ordering::SeqCst))),
+            )
+            .unwrap();
+    }
+
+    // The mock keeps two frames in flight
+    advance_frame(&device);
+    assert!(device_valid(&device, handle.into()));
+    assert!(!destroyed.load(Ordering::SeqCst));
+
+    advance_frame(&device);
+    assert!(!device_valid(&device, handle.into()));
+    assert!(destroyed.load(Ordering::SeqCst));
+
+    let device_read = device.read().unwrap();
+    let device_read = device_read.as_ref().unwrap();
+    assert_eq!(device_read.get_frame_index(), 2);
+    assert!(device_read
+        .destroy_resource_deferred(handle.into(), None)
+        .is_err());
+}
+
+#[test]
+fn owned_resource_drop() {
+    let (render_system, device) = mock_system();
+    let owned: RenderOwnedResource<RenderBufferHandle> = render_system
+        .create_owned(&device, "Owned Buffer".into(), |device, handle, name| {
+            device.create_buffer(handle, &BUFFER_DESC, None, name)
+        })
+        .unwrap();
+    let handle = owned.handle();
+    assert_eq!(*owned, handle);
+    assert_eq!(
+        render_system.get_handle_name(handle).unwrap(),
+        "Owned Buffer"
+    );
+
+    drop(owned);
+    advance_frame(&device);
+    assert!(device_valid(&device, handle.into()));
+    assert!(render_system.is_handle_valid(handle).unwrap());
+
+    advance_frame(&device);
+    assert!(!device_valid(&device, handle.into()));
+    assert!(!render_system.is_handle_valid(handle).unwrap());
+    assert!(render_system.get_handle_name(handle).is_err());
+}
+
+#[test]
+fn owned_resource_drop_after_destroy() {
+    let (render_system, device) = mock_system();
+    let owned: RenderOwnedResource<RenderBufferHandle> = render_system
+        .create_owned(
+            &device,
+            "Destroyed Buffer".into(),
+            |device, handle, name| device.create_buffer(handle, &BUFFER_DESC, None, name),
+        )
+        .unwrap();
+    let handle = owned.handle();
+    device
+        .read()
+        .unwrap()
+        .as_ref()
+        .unwrap()
+        .destroy_resource(handle.into())
+        .unwrap();
+
+    // The device rejects the deferred destruction, but the handle is still released
+    drop(owned);
+    assert!(!render_system.is_handle_valid(handle).unwrap());
+    assert!(render_system.get_handle_name(handle).is_err());
+}
+
+#[test]
+fn owned_resource_into_handle() {
+    let (render_system, device) = mock_system();
+    let owned: RenderOwnedResource<RenderBufferHandle> = render_system
+        .create_owned(&device, "Kept Buffer".into(), |device, handle, name| {
+            device.create_buffer(handle, &BUFFER_DESC, None, name)
+        })
+        .unwrap();
+    let handle = owned.into_handle();
+    advance_frame(&device);
+    advance_frame(&device);
+    assert!(device_valid(&device, handle.into()));
+    assert!(render_system.is_handle_valid(handle).unwrap());
+
+    // A failed creation releases the handle again
+    let failed = render_system.create_owned::<RenderTextureHandle>(
+        &device,
+        "Failed Texture".into(),
+        |_, _, _| Err(render_core::error::Error::backend("creation failed")),
+    );
+    assert!(failed.is_err());
+    assert_eq!(
+        render_system
+            .get_inventory(RenderResourceType::Texture)
+            .unwrap()
+            .count,
+        0
+    );
+}
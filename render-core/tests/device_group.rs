@@ -0,0 +1,214 @@
+extern crate render_core;
+extern crate render_hal_mock;
+use render_core::backend::*;
+use render_core::device::*;
+use render_core::error::{Error, ErrorKind, Result};
+use render_core::handles::*;
+use render_core::types::*;
+use render_hal_mock::RenderBackendModuleMock;
+use std::sync::{Arc, RwLock};
+mod common;
+
+fn mock_device() -> RenderDeviceEntry {
+    let mut backend = RenderBackendModuleMock::new().create();
+    backend.enumerate_devices(1, 0, false).unwrap();
+    backend.create_device(0).unwrap();
+    backend.get_device(0).unwrap()
+}
+
+fn mock_group(secondary_count: usize) -> RenderDeviceGroup {
+    let secondaries: Vec<RenderDeviceEntry> = (0..secondary_count).map(|_| mock_device()).collect();
+    RenderDeviceGroup::new(mock_device(), &secondaries)
+}
+
+fn create_buffer(group: &RenderDeviceGroup, handle: RenderBufferHandle) -> Result<()> {
+    let desc = RenderBufferDesc {
+        bind_flags: RenderBindFlags::CONSTANT_BUFFER,
+        size: 256,
+    };
+    group.create_resource(handle, |device| {
+        device.create_buffer(handle, &desc, None, "Group Buffer".into())
+    })
+}
+
+#[test]
+fn group_create_mirrored() {
+    let mut handles = RenderResourceHandleAllocator::new();
+    let handle: RenderBufferHandle = handles.allocate_typed().unwrap();
+    let group = mock_group(2);
+    assert_eq!(group.device_count(), 3);
+
+    create_buffer(&group, handle).unwrap();
+    assert!(group.valid_resource(handle));
+    let valid: Vec<bool> = group
+        .call_each_ref(|device| Ok(device.valid_resource(handle.into())))
+        .into_iter()
+        .map(|result| result.unwrap())
+        .collect();
+    assert_eq!(valid, vec![true, true, true]);
+
+    group.destroy_resource(handle).unwrap();
+    assert!(!group.valid_resource(handle));
+}
+
+#[test]
+fn group_per_device_errors() {
+    let mut handles = RenderResourceHandleAllocator::new();
+    let handle: RenderBufferHandle = handles.allocate_typed().unwrap();
+    let group = mock_group(1);
+
+    // Only the primary owns the resource, so destroying it fails on the secondary alone
+    group
+        .call_device_ref(0, |device| {
+            device.create_buffer(
+                handle,
+                &RenderBufferDesc {
+                    bind_flags: RenderBindFlags::CONSTANT_BUFFER,
+                    size: 16,
+                },
+                None,
+                "Primary Buffer".into(),
+            )
+        })
+        .unwrap();
+    assert!(!group.valid_resource(handle));
+
+    let err = group.destroy_resource(handle).unwrap_err();
+    match err.kind() {
+        ErrorKind::DeviceGroup(errors) => {
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].0, 1);
+        }
+        kind => panic!("unexpected error kind {:?}", kind),
+    }
+    assert!(err.to_string().contains("[device 1]"));
+
+    // The primary was still visited
+    let valid = group.call_device_ref(0, |device| Ok(device.valid_resource(handle.into())));
+    assert!(!valid.unwrap());
+
+    assert!(group.call_device_ref(2, |_| Ok(())).is_err());
+}
+
+#[test]
+fn group_create_rollback() {
+    let mut handles = RenderResourceHandleAllocator::new();
+    let handle: RenderBufferHandle = handles.allocate_typed().unwrap();
+    let group = RenderDeviceGroup::new(mock_device(), &[Arc::new(RwLock::new(None))]);
+
+    let err = create_buffer(&group, handle).unwrap_err();
+    match err.kind() {
+        ErrorKind::DeviceGroup(errors) => {
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].0, 1);
+            match errors[0].1 {
+                ErrorKind::Device(_) => {}
+                ref kind => panic!("unexpected device error kind {:?}", kind),
+            }
+        }
+        kind => panic!("unexpected error kind {:?}", kind),
+    }
+
+    // The primary copy was destroyed again
+    let valid = group.call_device_ref(0, |device| Ok(device.valid_resource(handle.into())));
+    assert!(!valid.unwrap());
+}
+
+/// Device whose resources can't be destroyed.
+#[derive(Debug)]
+struct IndestructibleDevice {
+    inner: RenderDeviceEntry,
+}
+
+impl RenderDeviceLayer for IndestructibleDevice {
+    fn layer_device_id(&self) -> RenderDeviceId {
+        0
+    }
+
+    fn layer_entry(&self) -> &RenderDeviceEntry {
+        &self.inner
+    }
+
+    fn destroy_resource(&self, _handle: RenderResourceHandle) -> Result<()> {
+        Err(Error::device("resource is indestructible"))
+    }
+}
+
+#[test]
+fn group_create_rollback_failure() {
+    let mut handles = RenderResourceHandleAllocator::new();
+    let handle: RenderBufferHandle = handles.allocate_typed().unwrap();
+    let primary: Box<dyn RenderDevice> = Box::new(IndestructibleDevice {
+        inner: mock_device(),
+    });
+    let group = RenderDeviceGroup::new(
+        Arc::new(RwLock::new(Some(primary))),
+        &[Arc::new(RwLock::new(None))],
+    );
+
+    // The primary copy that couldn't be destroyed again is reported with the failed creation
+    let err = create_buffer(&group, handle).unwrap_err();
+    match err.kind() {
+        ErrorKind::DeviceGroup(errors) => {
+            let indices: Vec<usize> = errors.iter().map(|(index, _)| *index).collect();
+            assert_eq!(indices, vec![0, 1]);
+        }
+        kind => panic!("unexpected error kind {:?}", kind),
+    }
+    assert!(err.to_string().contains("resource is indestructible"));
+}
+
+#[test]
+fn group_call_mut() {
+    let mut handles = RenderResourceHandleAllocator::new();
+    let swap_chain: RenderSwapChainHandle = handles.allocate_typed().unwrap();
+    let texture: RenderTextureHandle = handles.allocate_typed().unwrap();
+    let group = mock_group(1);
+
+    let mut visited = 0;
+    group
+        .call_mut(|device| {
+            visited += 1;
+            device.advance_frame()
+        })
+        .unwrap();
+    assert_eq!(visited, 2);
+
+    group.present_swap_chain(swap_chain, texture).unwrap();
+}
+
+#[test]
+fn group_frame_helpers() {
+    let group = mock_group(2);
+
+    let indices: Vec<usize> = (0..6)
+        .map(|frame_index| group.alternate_frame_index(frame_index))
+        .collect();
+    assert_eq!(indices, vec![0, 1, 2, 0, 1, 2]);
+    let name = group
+        .call_alternate_frame(4, |device| Ok(device.get_device_info()?.name))
+        .unwrap();
+    assert_eq!(name, "MockDevice");
+
+    assert_eq!(
+        group.split_frame_bands(1081),
+        vec![(0, 360), (360, 360), (720, 361)]
+    );
+}
+
+#[test]
+fn system_device_group() {
+    let mut render_system = common::mock_render_system(RenderDebugFlags::NONE);
+    let registry = render_system.get_registry().unwrap();
+    let registry_read = registry.read().unwrap();
+    let entry = &registry_read[0];
+    render_system
+        .enumerate_devices(entry, false, None, None)
+        .unwrap();
+
+    let group = render_system.create_device_group(entry, 0, &[]).unwrap();
+    assert_eq!(group.device_count(), 1);
+    group.advance_frame().unwrap();
+
+    assert!(render_system.create_device_group(entry, 0, &[7]).is_err());
+}
@@ -0,0 +1,192 @@
+extern crate render_core;
+extern crate render_hal_mock;
+use render_core::backend::*;
+use render_core::disassembly::*;
+use render_core::encoder::RenderCommandList;
+use render_core::handles::*;
+use render_core::state::*;
+use render_core::system::*;
+use render_core::types::*;
+use std::sync::Arc;
+mod common;
+
+/// Records an event around an update, a transition and a render pass with a draw.
+fn record_frame(render_system: &RenderSystem) -> RenderCommandList {
+    let buffer = render_system
+        .create_buffer_handle("Scene Constants".into())
+        .unwrap();
+    let texture = render_system
+        .create_texture_handle("Scene Color".into())
+        .unwrap();
+    let render_pass = render_system
+        .create_render_pass_handle("Main Pass".into())
+        .unwrap();
+    let pipeline_state = render_system
+        .create_graphics_pipeline_state_handle("Opaque".into())
+        .unwrap();
+
+    let handles = render_system.get_handle_allocator().unwrap();
+    let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
+    command_list.begin_event(0, "Frame".into()).unwrap();
+    command_list.update_buffer(buffer, 0, &[0u8; 16]).unwrap();
+    command_list
+        .transitions(&[(
+            texture.into(),
+            RenderResourceStates::RENDER_TARGET | RenderResourceStates::PIXEL_SHADER_RESOURCE,
+        )])
+        .unwrap();
+    command_list.begin_render_pass(render_pass).unwrap();
+    command_list
+        .draw(
+            pipeline_state,
+            &[RenderShaderArgument {
+                constant_buffer: Some(buffer.into()),
+                shader_views: None,
+                constant_buffer_offset: 256,
+            }],
+            None,
+            &RenderDrawState::default(),
+            &RenderDrawPacket {
+                index_offset: 0,
+                vertex_offset: 0,
+                vertex_count: 3,
+                first_instance: 0,
+                instance_count: 1,
+            },
+        )
+        .unwrap();
+    command_list.end_render_pass().unwrap();
+    command_list.end_event().unwrap();
+    command_list
+}
+
+#[test]
+fn disassembly_listing() {
+    let render_system = common::mock_render_system(RenderDebugFlags::NONE);
+    let command_list = record_frame(&render_system);
+    let disassembly =
+        RenderCommandDisassembler::with_names(&render_system).disassemble(&command_list);
+
+    // Events and render passes nest
+    let depths: Vec<usize> = disassembly
+        .commands
+        .iter()
+        .map(|command| command.depth)
+        .collect();
+    assert_eq!(depths, vec![0, 1, 1, 1, 2, 1, 0]);
+    assert_eq!(disassembly.update_count, 1);
+    assert_eq!(disassembly.payload_size, 16);
+    assert_eq!(disassembly.commands[1].payload_size, Some(16));
+
+    let text = disassembly.to_text();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 8);
+    assert_eq!(lines[0], "    0 BeginEvent message=\"Frame\", user_data=0");
+    assert!(lines[1].starts_with("    1   UpdateBuffer buffer=Buffer#"));
+    assert!(lines[1].contains("\"Scene Constants\", offset=0, size=16"));
+    assert!(lines[2].contains("\"Scene Color\" -> RenderTarget|PixelShaderResource]"));
+    assert!(lines[4].starts_with("    4     Draw pipeline_state=GraphicsPipelineState#"));
+    assert!(lines[4].contains("\"Opaque\""));
+    assert!(lines[4].contains("\"Scene Constants\"+256, views none}]"));
+    assert!(lines[4].contains("draw_binding=none, draw_state={stencil_ref 0}"));
+    assert_eq!(lines[6], "    6 EndEvent");
+    assert_eq!(
+        lines[7],
+        "; 7 command(s), 1 update(s) with 16 byte(s) of data"
+    );
+}
+
+#[test]
+fn disassembly_unnamed() {
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
+    let buffer = handles.allocate_typed::<RenderBufferHandle>().unwrap();
+    let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
+    command_list.end_event().unwrap();
+    command_list
+        .barriers(&[
+            buffer.into(),
+            RenderResourceHandle::new(0, RenderResourceType::Buffer, 0),
+        ])
+        .unwrap();
+
+    // Handles fall back to their type and index, and unmatched ends don't underflow
+    let disassembler = RenderCommandDisassembler::new();
+    let disassembly = disassembler.disassemble(&command_list);
+    assert_eq!(disassembly.commands[1].depth, 0);
+    assert_eq!(
+        disassembly.commands[1].operands,
+        vec![RenderDisassemblyOperand {
+            name: "barriers".to_string(),
+            value: format!("[Buffer#{}, null]", buffer.get_id()),
+        }]
+    );
+    assert_eq!(
+        disassembler.handle_text(buffer.into()),
+        format!("Buffer#{}", buffer.get_id())
+    );
+}
+
+#[test]
+fn disassembly_json() {
+    let render_system = common::mock_render_system(RenderDebugFlags::NONE);
+    let command_list = record_frame(&render_system);
+    let json = RenderCommandDisassembler::with_names(&render_system)
+        .disassemble(&command_list)
+        .to_json()
+        .unwrap();
+    assert!(json.contains("\"command_type\": \"Draw\""));
+    assert!(json.contains("\"depth\": 2"));
+    assert!(json.contains("\"payload_size\": 16"));
+    assert!(json.contains("\"value\": \"\\\"Frame\\\"\""));
+}
+
+#[test]
+fn disassembly_filtered_draw() {
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
+    let pipeline_state = handles
+        .allocate_typed::<RenderGraphicsPipelineStateHandle>()
+        .unwrap();
+    let buffer = handles.allocate_typed::<RenderBufferHandle>().unwrap();
+    let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
+    let draw_packet = RenderDrawPacket {
+        index_offset: 0,
+        vertex_offset: 0,
+        vertex_count: 3,
+        first_instance: 0,
+        instance_count: 1,
+    };
+    let shader_arguments = [RenderShaderArgument {
+        constant_buffer: Some(buffer.into()),
+        shader_views: None,
+        constant_buffer_offset: 0,
+    }];
+    for _ in 0..2 {
+        command_list
+            .draw(
+                pipeline_state,
+                &shader_arguments,
+                None,
+                &RenderDrawState::default(),
+                &draw_packet,
+            )
+            .unwrap();
+    }
+    command_list
+        .draw(
+            pipeline_state,
+            &[],
+            None,
+            &RenderDrawState::default(),
+            &draw_packet,
+        )
+        .unwrap();
+
+    // State left out as already bound reads as unchanged, unlike binding no arguments
+    let disassembly = RenderCommandDisassembler::new().disassemble(&command_list);
+    let text = disassembly.to_text();
+    let lines: Vec<&str> = text.lines().collect();
+    assert!(lines[0].contains(", views none}]"));
+    assert!(lines[1].contains("shader_arguments=unchanged"));
+    assert!(lines[1].contains("draw_state=unchanged"));
+    assert!(lines[2].contains("shader_arguments=[]"));
+}
@@ -1,29 +1,37 @@
 extern crate render_core;
+use render_core::allocator::LinearAllocatorMode;
 use render_core::commands::*;
-use render_core::encoder::RenderCommandList;
-use render_core::handles::{RenderResourceHandle, RenderResourceHandleAllocator};
+use render_core::encoder::{RenderCommandList, RenderCommandListPool};
+use render_core::error::{ErrorKind, Result};
+use render_core::handles::*;
 use render_core::state::*;
 use render_core::types::*;
 use render_core::utilities::typed_to_bytes;
-use std::sync::{Arc, RwLock};
+use render_core::validation::RenderCommandValidator;
+use std::sync::Arc;
 
 #[test]
 fn record_commands() {
-    let handles = Arc::new(RwLock::new(RenderResourceHandleAllocator::new()));
-    let mut handles_write = handles.write().unwrap();
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
     let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
 
     // Dummy render resources
-    let graphics_state_handle = handles_write.allocate(RenderResourceType::GraphicsPipelineState);
-    let compute_state_handle = handles_write.allocate(RenderResourceType::ComputePipelineState);
-    let draw_binding_handle = handles_write.allocate(RenderResourceType::DrawBindingSet);
-    let buffer1_handle = handles_write.allocate(RenderResourceType::Buffer);
-    let buffer2_handle = handles_write.allocate(RenderResourceType::Buffer);
-    let texture1_handle = handles_write.allocate(RenderResourceType::Texture);
-    let texture2_handle = handles_write.allocate(RenderResourceType::Texture);
-    let render_pass_handle = handles_write.allocate(RenderResourceType::RenderPass);
-    let indirect_buffer_handle = handles_write.allocate(RenderResourceType::Buffer);
-    let count_buffer_handle = handles_write.allocate(RenderResourceType::Buffer);
+    let graphics_state_handle = handles
+        .allocate_typed::<RenderGraphicsPipelineStateHandle>()
+        .unwrap();
+    let compute_state_handle = handles
+        .allocate_typed::<RenderComputePipelineStateHandle>()
+        .unwrap();
+    let draw_binding_handle = handles
+        .allocate_typed::<RenderDrawBindingSetHandle>()
+        .unwrap();
+    let buffer1_handle = handles.allocate_typed::<RenderBufferHandle>().unwrap();
+    let buffer2_handle = handles.allocate_typed::<RenderBufferHandle>().unwrap();
+    let texture1_handle = handles.allocate_typed::<RenderTextureHandle>().unwrap();
+    let texture2_handle = handles.allocate_typed::<RenderTextureHandle>().unwrap();
+    let render_pass_handle = handles.allocate_typed::<RenderPassHandle>().unwrap();
+    let indirect_buffer_handle = handles.allocate_typed::<RenderBufferHandle>().unwrap();
+    let count_buffer_handle = handles.allocate_typed::<RenderBufferHandle>().unwrap();
 
     // Draws
     {
@@ -173,14 +181,17 @@ fn record_commands() {
 
 #[test]
 fn record_draw() {
-    let handles = Arc::new(RwLock::new(RenderResourceHandleAllocator::new()));
-    let mut handles_write = handles.write().unwrap();
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
     let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
 
     // Dummy render resources
-    let pipeline_state_handle = handles_write.allocate(RenderResourceType::GraphicsPipelineState);
-    let draw_binding_handle = handles_write.allocate(RenderResourceType::DrawBindingSet);
-    let render_pass_handle = handles_write.allocate(RenderResourceType::RenderPass);
+    let pipeline_state_handle = handles
+        .allocate_typed::<RenderGraphicsPipelineStateHandle>()
+        .unwrap();
+    let draw_binding_handle = handles
+        .allocate_typed::<RenderDrawBindingSetHandle>()
+        .unwrap();
+    let render_pass_handle = handles.allocate_typed::<RenderPassHandle>().unwrap();
 
     let draw_state = RenderDrawState::default();
 
@@ -223,7 +234,10 @@ fn record_draw() {
 
     assert_eq!(command0_typed.render_pass, render_pass_handle);
     assert_eq!(command1_typed.pipeline_state, pipeline_state_handle);
-    assert_eq!(command1_typed.draw_binding, Some(draw_binding_handle));
+    assert_eq!(
+        command1_typed.draw_binding,
+        Some(draw_binding_handle.into())
+    );
     assert_eq!(command1_typed.draw_state, Some(draw_state));
     assert_eq!(command1_typed.draw_packet.index_offset, 1);
     assert_eq!(command1_typed.draw_packet.vertex_offset, 2);
@@ -239,16 +253,19 @@ fn record_draw() {
 
 #[test]
 fn record_draw_indirect() {
-    let handles = Arc::new(RwLock::new(RenderResourceHandleAllocator::new()));
-    let mut handles_write = handles.write().unwrap();
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
     let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
 
     // Dummy render resources
-    let pipeline_state_handle = handles_write.allocate(RenderResourceType::GraphicsPipelineState);
-    let draw_binding_handle = handles_write.allocate(RenderResourceType::DrawBindingSet);
-    let indirect_buffer_handle = handles_write.allocate(RenderResourceType::Buffer);
-    let count_buffer_handle = handles_write.allocate(RenderResourceType::Buffer);
-    let render_pass_handle = handles_write.allocate(RenderResourceType::RenderPass);
+    let pipeline_state_handle = handles
+        .allocate_typed::<RenderGraphicsPipelineStateHandle>()
+        .unwrap();
+    let draw_binding_handle = handles
+        .allocate_typed::<RenderDrawBindingSetHandle>()
+        .unwrap();
+    let indirect_buffer_handle = handles.allocate_typed::<RenderBufferHandle>().unwrap();
+    let count_buffer_handle = handles.allocate_typed::<RenderBufferHandle>().unwrap();
+    let render_pass_handle = handles.allocate_typed::<RenderPassHandle>().unwrap();
 
     let draw_state = RenderDrawState::default();
 
@@ -308,11 +325,12 @@ fn record_draw_indirect() {
 
 #[test]
 fn record_dispatch() {
-    let handles = Arc::new(RwLock::new(RenderResourceHandleAllocator::new()));
-    let mut handles_write = handles.write().unwrap();
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
     let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
 
-    let compute_state_handle = handles_write.allocate(RenderResourceType::ComputePipelineState);
+    let compute_state_handle = handles
+        .allocate_typed::<RenderComputePipelineStateHandle>()
+        .unwrap();
 
     assert!(command_list
         .dispatch(
@@ -344,13 +362,14 @@ fn record_dispatch() {
 
 #[test]
 fn record_dispatch_indirect() {
-    let handles = Arc::new(RwLock::new(RenderResourceHandleAllocator::new()));
-    let mut handles_write = handles.write().unwrap();
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
     let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
 
-    let compute_state_handle = handles_write.allocate(RenderResourceType::ComputePipelineState);
-    let indirect_buffer_handle = handles_write.allocate(RenderResourceType::Buffer);
-    let count_buffer_handle = handles_write.allocate(RenderResourceType::Buffer);
+    let compute_state_handle = handles
+        .allocate_typed::<RenderComputePipelineStateHandle>()
+        .unwrap();
+    let indirect_buffer_handle = handles.allocate_typed::<RenderBufferHandle>().unwrap();
+    let count_buffer_handle = handles.allocate_typed::<RenderBufferHandle>().unwrap();
 
     assert!(command_list
         .dispatch_indirect(
@@ -388,12 +407,11 @@ fn record_dispatch_indirect() {
 
 #[test]
 fn record_update_buffer() {
-    let handles = Arc::new(RwLock::new(RenderResourceHandleAllocator::new()));
-    let mut handles_write = handles.write().unwrap();
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
     let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
 
     // Dummy render resources
-    let buffer_handle = handles_write.allocate(RenderResourceType::Buffer);
+    let buffer_handle = handles.allocate_typed::<RenderBufferHandle>().unwrap();
 
     let offset = 32 * 1024;
     let data: [u8; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
@@ -429,12 +447,11 @@ fn record_update_buffer() {
 
 #[test]
 fn record_update_texture() {
-    let handles = Arc::new(RwLock::new(RenderResourceHandleAllocator::new()));
-    let mut handles_write = handles.write().unwrap();
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
     let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
 
     // Dummy render resources
-    let texture_handle = handles_write.allocate(RenderResourceType::Texture);
+    let texture_handle = handles.allocate_typed::<RenderTextureHandle>().unwrap();
 
     let dword_values: [u32; 4] = [1, 2, 3, 4];
     let sub_data = &typed_to_bytes(&dword_values);
@@ -483,13 +500,12 @@ fn record_update_texture() {
 
 #[test]
 fn record_copy_buffer() {
-    let handles = Arc::new(RwLock::new(RenderResourceHandleAllocator::new()));
-    let mut handles_write = handles.write().unwrap();
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
     let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
 
     // Dummy render resources
-    let src_buffer_handle = handles_write.allocate(RenderResourceType::Buffer);
-    let dst_buffer_handle = handles_write.allocate(RenderResourceType::Buffer);
+    let src_buffer_handle = handles.allocate_typed::<RenderBufferHandle>().unwrap();
+    let dst_buffer_handle = handles.allocate_typed::<RenderBufferHandle>().unwrap();
 
     assert!(command_list
         .copy_buffer(src_buffer_handle, 12, 34, dst_buffer_handle, 56)
@@ -516,13 +532,12 @@ fn record_copy_buffer() {
 
 #[test]
 fn record_copy_texture() {
-    let handles = Arc::new(RwLock::new(RenderResourceHandleAllocator::new()));
-    let mut handles_write = handles.write().unwrap();
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
     let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
 
     // Dummy render resources
-    let src_texture_handle = handles_write.allocate(RenderResourceType::Texture);
-    let dst_texture_handle = handles_write.allocate(RenderResourceType::Texture);
+    let src_texture_handle = handles.allocate_typed::<RenderTextureHandle>().unwrap();
+    let dst_texture_handle = handles.allocate_typed::<RenderTextureHandle>().unwrap();
 
     let dst_point = RenderPoint { x: 1, y: 2, z: 3 };
 
@@ -568,13 +583,12 @@ fn record_copy_texture() {
 
 #[test]
 fn record_barriers() {
-    let handles = Arc::new(RwLock::new(RenderResourceHandleAllocator::new()));
-    let mut handles_write = handles.write().unwrap();
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
     let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
 
     // Dummy render resources
-    let dst_buffer_handle_1 = handles_write.allocate(RenderResourceType::Buffer);
-    let dst_buffer_handle_2 = handles_write.allocate(RenderResourceType::Buffer);
+    let dst_buffer_handle_1 = handles.allocate(RenderResourceType::Buffer).unwrap();
+    let dst_buffer_handle_2 = handles.allocate(RenderResourceType::Buffer).unwrap();
 
     let barriers: Vec<RenderResourceHandle> = vec![dst_buffer_handle_1, dst_buffer_handle_2];
 
@@ -599,13 +613,12 @@ fn record_barriers() {
 
 #[test]
 fn record_transitions() {
-    let handles = Arc::new(RwLock::new(RenderResourceHandleAllocator::new()));
-    let mut handles_write = handles.write().unwrap();
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
     let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
 
     // Dummy render resources
-    let dst_buffer_handle_1 = handles_write.allocate(RenderResourceType::Buffer);
-    let dst_buffer_handle_2 = handles_write.allocate(RenderResourceType::Buffer);
+    let dst_buffer_handle_1 = handles.allocate(RenderResourceType::Buffer).unwrap();
+    let dst_buffer_handle_2 = handles.allocate(RenderResourceType::Buffer).unwrap();
 
     let transitions: Vec<RenderTransitionRecord> = vec![
         (dst_buffer_handle_1, RenderResourceStates::GENERIC_READ),
@@ -633,12 +646,11 @@ fn record_transitions() {
 
 #[test]
 fn record_begin_timing() {
-    let handles = Arc::new(RwLock::new(RenderResourceHandleAllocator::new()));
-    let mut handles_write = handles.write().unwrap();
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
     let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
 
     // Dummy render resources
-    let timing_heap_handle = handles_write.allocate(RenderResourceType::TimingHeap);
+    let timing_heap_handle = handles.allocate_typed::<RenderTimingHeapHandle>().unwrap();
 
     assert!(command_list.begin_timing(timing_heap_handle, 123).is_ok());
 
@@ -660,12 +672,11 @@ fn record_begin_timing() {
 
 #[test]
 fn record_end_timing() {
-    let handles = Arc::new(RwLock::new(RenderResourceHandleAllocator::new()));
-    let mut handles_write = handles.write().unwrap();
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
     let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
 
     // Dummy render resources
-    let timing_heap_handle = handles_write.allocate(RenderResourceType::TimingHeap);
+    let timing_heap_handle = handles.allocate_typed::<RenderTimingHeapHandle>().unwrap();
 
     assert!(command_list.end_timing(timing_heap_handle, 123).is_ok());
 
@@ -687,12 +698,11 @@ fn record_end_timing() {
 
 #[test]
 fn record_resolve_timings() {
-    let handles = Arc::new(RwLock::new(RenderResourceHandleAllocator::new()));
-    let mut handles_write = handles.write().unwrap();
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
     let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
 
     // Dummy render resources
-    let timing_heap_handle = handles_write.allocate(RenderResourceType::TimingHeap);
+    let timing_heap_handle = handles.allocate_typed::<RenderTimingHeapHandle>().unwrap();
 
     assert!(command_list
         .resolve_timings(timing_heap_handle, 123, 456)
@@ -720,7 +730,7 @@ fn record_resolve_timings() {
 
 #[test]
 fn record_begin_event() {
-    let handles = Arc::new(RwLock::new(RenderResourceHandleAllocator::new()));
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
     let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
 
     assert!(command_list
@@ -745,7 +755,7 @@ fn record_begin_event() {
 
 #[test]
 fn record_end_event() {
-    let handles = Arc::new(RwLock::new(RenderResourceHandleAllocator::new()));
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
     let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
 
     assert!(command_list.end_event().is_ok());
@@ -766,12 +776,11 @@ fn record_end_event() {
 
 #[test]
 fn record_begin_render_pass() {
-    let handles = Arc::new(RwLock::new(RenderResourceHandleAllocator::new()));
-    let mut handles_write = handles.write().unwrap();
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
     let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
 
     // Dummy render resources
-    let render_pass_handle = handles_write.allocate(RenderResourceType::RenderPass);
+    let render_pass_handle = handles.allocate_typed::<RenderPassHandle>().unwrap();
 
     assert!(command_list.begin_render_pass(render_pass_handle).is_ok());
 
@@ -795,7 +804,7 @@ fn record_begin_render_pass() {
 
 #[test]
 fn record_end_render_pass() {
-    let handles = Arc::new(RwLock::new(RenderResourceHandleAllocator::new()));
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
     let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
 
     assert!(command_list.end_render_pass().is_ok());
@@ -818,14 +827,17 @@ fn record_end_render_pass() {
 
 #[test]
 fn record_ray_trace() {
-    let handles = Arc::new(RwLock::new(RenderResourceHandleAllocator::new()));
-    let mut handles_write = handles.write().unwrap();
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
     let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
 
     // Dummy render resources
-    let pipeline_state_handle = handles_write.allocate(RenderResourceType::RayTracingPipelineState);
-    let shader_table_handle = handles_write.allocate(RenderResourceType::RayTracingShaderTable);
-    let rt_output_handle = handles_write.allocate(RenderResourceType::Texture);
+    let pipeline_state_handle = handles
+        .allocate_typed::<RenderRayTracingPipelineStateHandle>()
+        .unwrap();
+    let shader_table_handle = handles
+        .allocate_typed::<RenderRayTracingShaderTableHandle>()
+        .unwrap();
+    let rt_output_handle = handles.allocate(RenderResourceType::Texture).unwrap();
 
     assert!(command_list
         .ray_trace(
@@ -860,12 +872,13 @@ fn record_ray_trace() {
 
 #[test]
 fn record_update_top_level_acceleration() {
-    let handles = Arc::new(RwLock::new(RenderResourceHandleAllocator::new()));
-    let mut handles_write = handles.write().unwrap();
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
     let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
 
     // Dummy render resources
-    let acceleration_handle = handles_write.allocate(RenderResourceType::RayTracingAcceleration);
+    let acceleration_handle = handles
+        .allocate_typed::<RenderRayTracingAccelerationHandle>()
+        .unwrap();
 
     let desc = RenderAccelerationTopDesc::default();
 
@@ -896,12 +909,13 @@ fn record_update_top_level_acceleration() {
 
 #[test]
 fn record_update_bottom_level_acceleration() {
-    let handles = Arc::new(RwLock::new(RenderResourceHandleAllocator::new()));
-    let mut handles_write = handles.write().unwrap();
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
     let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
 
     // Dummy render resources
-    let acceleration_handle = handles_write.allocate(RenderResourceType::RayTracingAcceleration);
+    let acceleration_handle = handles
+        .allocate_typed::<RenderRayTracingAccelerationHandle>()
+        .unwrap();
 
     assert!(command_list
         .update_bottom_level_acceleration(acceleration_handle, true)
@@ -931,12 +945,13 @@ fn record_update_bottom_level_acceleration() {
 
 #[test]
 fn record_update_shader_table() {
-    let handles = Arc::new(RwLock::new(RenderResourceHandleAllocator::new()));
-    let mut handles_write = handles.write().unwrap();
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
     let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
 
     // Dummy render resources
-    let shader_table_handle = handles_write.allocate(RenderResourceType::RayTracingShaderTable);
+    let shader_table_handle = handles
+        .allocate_typed::<RenderRayTracingShaderTableHandle>()
+        .unwrap();
 
     let desc = RenderShaderTableUpdateDesc::default();
 
@@ -961,3 +976,561 @@ fn record_update_shader_table() {
         .get_queue_type()
         .contains(command_typed.get_queue()));
 }
+
+#[test]
+fn record_past_initial_size() {
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
+    let mut command_list = RenderCommandList::new(Arc::clone(&handles), 256, 16).unwrap();
+    let buffer_handle = handles.allocate_typed::<RenderBufferHandle>().unwrap();
+
+    for index in 0..64u8 {
+        command_list
+            .update_buffer(buffer_handle, 0, &[index; 64])
+            .unwrap();
+    }
+    let stats = command_list.get_allocator_stats();
+    assert!(stats.page_count > 1);
+    assert!(stats.capacity > 256);
+
+    // Commands recorded into earlier pages are still intact
+    let commands = command_list.get_commands();
+    assert_eq!(commands.len(), 64);
+    for (index, command) in commands.iter().enumerate() {
+        let command_typed = command.downcast_ref::<RenderCommandUpdateBuffer>().unwrap();
+        let command_data = command_list
+            .get_command_data(command_typed.data, command_typed.size)
+            .unwrap();
+        assert_eq!(command_data, &[index as u8; 64][..]);
+    }
+}
+
+#[test]
+fn record_bounded() {
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
+    let mut command_list =
+        RenderCommandList::with_mode(Arc::clone(&handles), 256, 16, LinearAllocatorMode::Bounded)
+            .unwrap();
+    let buffer_handle = handles.allocate_typed::<RenderBufferHandle>().unwrap();
+
+    let err = (0..64)
+        .map(|_| command_list.update_buffer(buffer_handle, 0, &[0u8; 64]))
+        .find(|result| result.is_err())
+        .unwrap()
+        .unwrap_err();
+    match err.kind() {
+        ErrorKind::Memory(_) => {}
+        kind => panic!("unexpected error kind {:?}", kind),
+    }
+    assert!(!command_list.get_commands().is_empty());
+    assert_eq!(command_list.get_allocator_stats().page_count, 1);
+}
+
+#[test]
+fn record_reset() {
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
+    let mut command_list = RenderCommandList::new(Arc::clone(&handles), 256, 16).unwrap();
+    let buffer_handle = handles.allocate_typed::<RenderBufferHandle>().unwrap();
+
+    for _ in 0..16 {
+        command_list
+            .update_buffer(buffer_handle, 0, &[1u8; 64])
+            .unwrap();
+        command_list.begin_event(0, "Reset Event".into()).unwrap();
+    }
+    let capacity = command_list.get_allocator_stats().capacity;
+    assert!(capacity > 256);
+
+    command_list.reset().unwrap();
+    assert!(command_list.is_empty());
+    assert_eq!(command_list.get_queue_type(), RenderCommandQueueType::NONE);
+
+    // The same recording fits in the storage kept from before
+    for _ in 0..16 {
+        command_list
+            .update_buffer(buffer_handle, 0, &[2u8; 64])
+            .unwrap();
+        command_list.begin_event(0, "Reset Event".into()).unwrap();
+    }
+    assert_eq!(command_list.get_commands().len(), 32);
+    assert_eq!(command_list.get_allocator_stats().capacity, capacity);
+}
+
+#[test]
+fn record_rewind() {
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
+    let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
+    let buffer_handle = handles.allocate_typed::<RenderBufferHandle>().unwrap();
+
+    command_list.barriers(&[buffer_handle.into()]).unwrap();
+    let mark = command_list.mark();
+    command_list
+        .update_buffer(buffer_handle, 0, &[1u8; 16])
+        .unwrap();
+    command_list.end_event().unwrap();
+    assert_eq!(command_list.get_commands().len(), 3);
+
+    command_list.rewind(mark).unwrap();
+    assert_eq!(command_list.mark(), mark);
+    let commands = command_list.get_commands();
+    assert_eq!(commands.len(), 1);
+    assert_eq!(commands[0].get_type(), RenderCommandType::Barriers);
+
+    // Marks can't move the recording forward
+    let empty = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16)
+        .unwrap()
+        .mark();
+    command_list.rewind(empty).unwrap();
+    assert!(command_list.is_empty());
+    assert!(command_list.rewind(mark).is_err());
+}
+
+#[test]
+fn command_list_pool() {
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
+    let mut pool = RenderCommandListPool::new(Arc::clone(&handles), 256, 16);
+    let buffer_handle = handles.allocate_typed::<RenderBufferHandle>().unwrap();
+
+    let mut command_list = pool.acquire().unwrap();
+    for _ in 0..16 {
+        command_list
+            .update_buffer(buffer_handle, 0, &[1u8; 64])
+            .unwrap();
+    }
+    let capacity = command_list.get_allocator_stats().capacity;
+    pool.release(command_list).unwrap();
+    assert_eq!(pool.free_count(), 1);
+
+    // The released list comes back empty, with its storage
+    let command_list = pool.acquire().unwrap();
+    assert_eq!(pool.free_count(), 0);
+    assert!(command_list.is_empty());
+    assert_eq!(command_list.get_allocator_stats().capacity, capacity);
+    pool.release(command_list).unwrap();
+
+    let other_handles = Arc::new(RenderConcurrentHandleAllocator::new());
+    let other_list = RenderCommandList::new(other_handles, 256, 16).unwrap();
+    assert!(pool.release(other_list).is_err());
+
+    pool.trim(0);
+    assert_eq!(pool.free_count(), 0);
+}
+
+#[test]
+fn record_entries() {
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
+    let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
+    let buffer_handle = handles.allocate(RenderResourceType::Buffer).unwrap();
+
+    let barriers_id = command_list.barriers(&[buffer_handle]).unwrap();
+    let event_id = command_list
+        .begin_event(7, String::from("Owned Event").into())
+        .unwrap();
+    assert_eq!((barriers_id, event_id), (0, 1));
+
+    // Commands own their payloads, and can be matched by value
+    let commands = command_list.get_commands();
+    match commands[0] {
+        RenderCommandEntry::Barriers(ref command) => assert_eq!(command.barriers, [buffer_handle]),
+        ref entry => panic!("unexpected command {:?}", entry),
+    }
+    assert_eq!(
+        commands[1],
+        RenderCommandEntry::from(RenderCommandBeginEvent::new(7, "Owned Event"))
+    );
+    assert_eq!(
+        commands[1].as_command().get_type(),
+        RenderCommandType::BeginEvent
+    );
+}
+
+#[test]
+fn record_reuses_payloads() {
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
+    let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
+    let barriers = [
+        handles.allocate(RenderResourceType::Buffer).unwrap(),
+        handles.allocate(RenderResourceType::Texture).unwrap(),
+    ];
+
+    let barriers_ptr = |command_list: &RenderCommandList| {
+        command_list.get_commands()[0]
+            .downcast_ref::<RenderCommandBarriers>()
+            .unwrap()
+            .barriers
+            .as_ptr()
+    };
+
+    command_list.barriers(&barriers).unwrap();
+    let first = barriers_ptr(&command_list);
+
+    // A reset keeps the payload storage for the next recording
+    command_list.reset().unwrap();
+    command_list.barriers(&barriers[..1]).unwrap();
+    assert_eq!(barriers_ptr(&command_list), first);
+
+    let mark = command_list.mark();
+    command_list.barriers(&barriers).unwrap();
+    command_list.rewind(mark).unwrap();
+    assert_eq!(command_list.get_commands().len(), 1);
+}
+
+#[test]
+fn record_filters_redundant_state() {
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
+    let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
+    let graphics_state1 = handles
+        .allocate_typed::<RenderGraphicsPipelineStateHandle>()
+        .unwrap();
+    let graphics_state2 = handles
+        .allocate_typed::<RenderGraphicsPipelineStateHandle>()
+        .unwrap();
+    let compute_state = handles
+        .allocate_typed::<RenderComputePipelineStateHandle>()
+        .unwrap();
+    let draw_binding = handles
+        .allocate_typed::<RenderDrawBindingSetHandle>()
+        .unwrap();
+    let buffer_handle = handles.allocate(RenderResourceType::Buffer).unwrap();
+
+    let shader_arguments = [RenderShaderArgument {
+        constant_buffer: Some(buffer_handle),
+        shader_views: None,
+        constant_buffer_offset: 0,
+    }];
+    let draw_state = RenderDrawState::default();
+    let stencil_state = RenderDrawState {
+        stencil_ref: 1,
+        ..Default::default()
+    };
+    let draw_packet = RenderDrawPacket {
+        index_offset: 0,
+        vertex_offset: 0,
+        vertex_count: 3,
+        first_instance: 0,
+        instance_count: 1,
+    };
+
+    let draw = |command_list: &mut RenderCommandList,
+                pipeline_state: RenderGraphicsPipelineStateHandle,
+                draw_state: &RenderDrawState| {
+        let id = command_list
+            .draw(
+                pipeline_state,
+                &shader_arguments,
+                Some(draw_binding),
+                draw_state,
+                &draw_packet,
+            )
+            .unwrap();
+        let command = command_list.get_commands()[id]
+            .downcast_ref::<RenderCommandDraw>()
+            .unwrap();
+        (
+            command.shader_arguments.as_ref().map(Vec::len),
+            command.draw_state,
+        )
+    };
+
+    assert_eq!(
+        draw(&mut command_list, graphics_state1, &draw_state),
+        (Some(1), Some(draw_state))
+    );
+    assert_eq!(
+        draw(&mut command_list, graphics_state1, &draw_state),
+        (None, None)
+    );
+    assert_eq!(
+        draw(&mut command_list, graphics_state1, &stencil_state),
+        (None, Some(stencil_state))
+    );
+
+    // Arguments are bound again for a different pipeline state
+    assert_eq!(
+        draw(&mut command_list, graphics_state2, &stencil_state),
+        (Some(1), None)
+    );
+
+    let mark = command_list.mark();
+    let stats = command_list.get_filter_stats();
+    assert_eq!(stats.redundant_pipelines, 2);
+    assert_eq!(stats.redundant_shader_arguments, 2);
+    assert_eq!(stats.redundant_draw_bindings, 3);
+    assert_eq!(stats.redundant_draw_states, 2);
+    assert_eq!(
+        stats.filtered_bytes,
+        2 * std::mem::size_of::<RenderShaderArgument>()
+            + 2 * std::mem::size_of::<RenderDrawState>()
+    );
+
+    let dispatch_arguments = |command_list: &mut RenderCommandList| {
+        let id = command_list
+            .dispatch(compute_state, &shader_arguments, 1, 1, 1)
+            .unwrap();
+        command_list.get_commands()[id]
+            .downcast_ref::<RenderCommandDispatch>()
+            .unwrap()
+            .shader_arguments
+            .as_ref()
+            .map(Vec::len)
+    };
+    assert_eq!(dispatch_arguments(&mut command_list), Some(1));
+    assert_eq!(dispatch_arguments(&mut command_list), None);
+
+    // Rewinding restores the statistics and records the full state again
+    command_list.rewind(mark).unwrap();
+    assert_eq!(command_list.get_filter_stats(), stats);
+    assert_eq!(
+        draw(&mut command_list, graphics_state2, &stencil_state),
+        (Some(1), Some(stencil_state))
+    );
+
+    // Commands that may change resource states have the full state recorded again
+    command_list
+        .transitions(&[(buffer_handle, RenderResourceStates::UNORDERED_ACCESS)])
+        .unwrap();
+    assert_eq!(
+        draw(&mut command_list, graphics_state2, &stencil_state),
+        (Some(1), Some(stencil_state))
+    );
+    command_list.begin_event(0, "Event".into()).unwrap();
+    assert_eq!(
+        draw(&mut command_list, graphics_state2, &stencil_state),
+        (None, None)
+    );
+
+    // Binding no arguments is recorded, rather than left out
+    let id = command_list
+        .draw(
+            graphics_state2,
+            &[],
+            Some(draw_binding),
+            &stencil_state,
+            &draw_packet,
+        )
+        .unwrap();
+    let command = command_list.get_commands()[id]
+        .downcast_ref::<RenderCommandDraw>()
+        .unwrap();
+    assert_eq!(command.shader_arguments, Some(Vec::new()));
+
+    command_list.reset().unwrap();
+    assert_eq!(command_list.get_filter_stats(), Default::default());
+    assert_eq!(
+        draw(&mut command_list, graphics_state2, &stencil_state),
+        (Some(1), Some(stencil_state))
+    );
+}
+
+fn command_types(command_list: &RenderCommandList) -> Vec<RenderCommandType> {
+    command_list
+        .get_commands()
+        .iter()
+        .map(|command| command.get_type())
+        .collect()
+}
+
+fn record_scoped_pass(
+    command_list: &mut RenderCommandList,
+    render_pass_handle: RenderPassHandle,
+    buffer_handle: RenderBufferHandle,
+) -> Result<()> {
+    let mut event = command_list.scoped_event(0, "Scoped Event".into())?;
+    let mut render_pass = event.scoped_render_pass(render_pass_handle)?;
+    render_pass.begin_event(1, "Unbalanced Event".into())?;
+    render_pass.update_buffer(buffer_handle, 0, &[1u8; 16])?;
+    render_pass.end_event()?;
+    Ok(())
+}
+
+#[test]
+fn record_scopes() {
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
+    let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
+    let render_pass_handle = handles.allocate_typed::<RenderPassHandle>().unwrap();
+    let buffer_handle = handles.allocate_typed::<RenderBufferHandle>().unwrap();
+
+    {
+        let mut event = command_list.scoped_event(0, "Outer Event".into()).unwrap();
+        let mut inner = event.scoped_event(1, "Inner Event".into()).unwrap();
+        inner.barriers(&[buffer_handle.into()]).unwrap();
+    }
+    assert_eq!(
+        command_types(&command_list),
+        vec![
+            RenderCommandType::BeginEvent,
+            RenderCommandType::BeginEvent,
+            RenderCommandType::Barriers,
+            RenderCommandType::EndEvent,
+            RenderCommandType::EndEvent,
+        ]
+    );
+
+    // Returning early still ends the render pass, the event begun in it, and the outer event
+    command_list.reset().unwrap();
+    assert!(record_scoped_pass(&mut command_list, render_pass_handle, buffer_handle).is_err());
+    assert_eq!(
+        command_types(&command_list),
+        vec![
+            RenderCommandType::BeginEvent,
+            RenderCommandType::BeginRenderPass,
+            RenderCommandType::BeginEvent,
+            RenderCommandType::EndEvent,
+            RenderCommandType::EndRenderPass,
+            RenderCommandType::EndEvent,
+        ]
+    );
+    assert_eq!(command_list.get_allocator_stats().allocated, 0);
+}
+
+#[test]
+fn record_scope_blocks_invalid_commands() {
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
+    let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
+    let render_pass_handle = handles.allocate_typed::<RenderPassHandle>().unwrap();
+    let graphics_state = handles
+        .allocate_typed::<RenderGraphicsPipelineStateHandle>()
+        .unwrap();
+    let compute_state = handles
+        .allocate_typed::<RenderComputePipelineStateHandle>()
+        .unwrap();
+    let buffer_handle = handles.allocate_typed::<RenderBufferHandle>().unwrap();
+
+    command_list.begin_event(0, "Manual Event".into()).unwrap();
+    {
+        let mut render_pass = command_list.scoped_render_pass(render_pass_handle).unwrap();
+        render_pass
+            .draw(
+                graphics_state,
+                &[],
+                None,
+                &RenderDrawState::default(),
+                &RenderDrawPacket {
+                    index_offset: 0,
+                    vertex_offset: 0,
+                    vertex_count: 3,
+                    first_instance: 0,
+                    instance_count: 1,
+                },
+            )
+            .unwrap();
+        assert!(render_pass.dispatch(compute_state, &[], 1, 1, 1).is_err());
+        assert!(render_pass
+            .copy_buffer(buffer_handle, 0, 16, buffer_handle, 16)
+            .is_err());
+        assert!(render_pass
+            .record_entry(
+                RenderCommandEntry::Dispatch(RenderCommandDispatch {
+                    pipeline_state: compute_state.into(),
+                    shader_arguments: None,
+                    dispatch_x: 1,
+                    dispatch_y: 1,
+                    dispatch_z: 1,
+                }),
+                None,
+            )
+            .is_err());
+
+        // Scopes can only be ended by their guard
+        assert!(render_pass.begin_render_pass(render_pass_handle).is_err());
+        assert!(render_pass.end_render_pass().is_err());
+        assert!(render_pass.end_event().is_err());
+        assert!(render_pass.reset().is_err());
+        let begin = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16)
+            .unwrap()
+            .mark();
+        assert!(render_pass.rewind(begin).is_err());
+        assert_eq!(render_pass.get_commands().len(), 3);
+    }
+
+    // Dispatches are allowed again once the render pass ends
+    command_list.dispatch(compute_state, &[], 1, 1, 1).unwrap();
+    command_list.end_event().unwrap();
+    assert_eq!(
+        command_types(&command_list),
+        vec![
+            RenderCommandType::BeginEvent,
+            RenderCommandType::BeginRenderPass,
+            RenderCommandType::Draw,
+            RenderCommandType::EndRenderPass,
+            RenderCommandType::Dispatch,
+            RenderCommandType::EndEvent,
+        ]
+    );
+    assert_eq!(command_list.get_stats(|_| None).dispatch_count, 1);
+}
+
+#[test]
+fn record_scope_blocks_manual_render_pass() {
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
+    let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
+    let render_pass_handle = handles.allocate_typed::<RenderPassHandle>().unwrap();
+
+    {
+        let mut event = command_list.scoped_event(0, "Scoped Event".into()).unwrap();
+        assert!(event.begin_render_pass(render_pass_handle).is_err());
+        assert!(event.end_render_pass().is_err());
+        event.scoped_render_pass(render_pass_handle).unwrap();
+    }
+
+    // A render pass begun before the scope can't end inside it
+    command_list.begin_render_pass(render_pass_handle).unwrap();
+    {
+        let mut event = command_list.scoped_event(1, "Pass Event".into()).unwrap();
+        assert!(event.end_render_pass().is_err());
+    }
+    command_list.end_render_pass().unwrap();
+
+    assert_eq!(
+        command_types(&command_list),
+        vec![
+            RenderCommandType::BeginEvent,
+            RenderCommandType::BeginRenderPass,
+            RenderCommandType::EndRenderPass,
+            RenderCommandType::EndEvent,
+            RenderCommandType::BeginRenderPass,
+            RenderCommandType::BeginEvent,
+            RenderCommandType::EndEvent,
+            RenderCommandType::EndRenderPass,
+        ]
+    );
+    assert!(RenderCommandValidator::new()
+        .validate(&command_list)
+        .is_empty());
+}
+
+#[test]
+fn record_scoped_render_pass_inside_manual() {
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
+    let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
+    let render_pass_handle = handles.allocate_typed::<RenderPassHandle>().unwrap();
+
+    // A scoped render pass can't begin inside a manual one, or inside an event scope in it
+    command_list.begin_render_pass(render_pass_handle).unwrap();
+    assert!(command_list.scoped_render_pass(render_pass_handle).is_err());
+    {
+        let mut event = command_list.scoped_event(0, "Pass Event".into()).unwrap();
+        assert!(event.scoped_render_pass(render_pass_handle).is_err());
+    }
+    command_list.end_render_pass().unwrap();
+
+    // Rewinding past the manual begin allows it again
+    let mark = command_list.mark();
+    command_list.begin_render_pass(render_pass_handle).unwrap();
+    command_list.rewind(mark).unwrap();
+    command_list.scoped_render_pass(render_pass_handle).unwrap();
+
+    assert_eq!(
+        command_types(&command_list),
+        vec![
+            RenderCommandType::BeginRenderPass,
+            RenderCommandType::BeginEvent,
+            RenderCommandType::EndEvent,
+            RenderCommandType::EndRenderPass,
+            RenderCommandType::BeginRenderPass,
+            RenderCommandType::EndRenderPass,
+        ]
+    );
+    assert!(RenderCommandValidator::new()
+        .validate(&command_list)
+        .is_empty());
+}
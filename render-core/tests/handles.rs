@@ -1,6 +1,12 @@
 extern crate render_core;
-use render_core::handles::{RenderResourceHandle, RenderResourceHandleAllocator};
+use render_core::error::ErrorKind;
+use render_core::handles::*;
+use render_core::resources::RenderResourceStorage;
 use render_core::types::RenderResourceType;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::sync::{Arc, RwLock};
+use std::thread;
 
 #[test]
 fn bogus_handle() {
@@ -36,7 +42,7 @@ fn set_cookie() {
 #[test]
 fn alloc_swap_chain() {
     let mut alloc = RenderResourceHandleAllocator::new();
-    let handle = alloc.allocate(RenderResourceType::SwapChain);
+    let handle = alloc.allocate(RenderResourceType::SwapChain).unwrap();
     assert!(handle.get_type() == RenderResourceType::SwapChain);
     assert!(alloc.is_valid(&handle));
     alloc.release(handle);
@@ -46,7 +52,7 @@ fn alloc_swap_chain() {
 #[test]
 fn alloc_buffer() {
     let mut alloc = RenderResourceHandleAllocator::new();
-    let handle = alloc.allocate(RenderResourceType::Buffer);
+    let handle = alloc.allocate(RenderResourceType::Buffer).unwrap();
     assert!(handle.get_type() == RenderResourceType::Buffer);
     assert!(alloc.is_valid(&handle));
     alloc.release(handle);
@@ -56,7 +62,7 @@ fn alloc_buffer() {
 #[test]
 fn alloc_texture() {
     let mut alloc = RenderResourceHandleAllocator::new();
-    let handle = alloc.allocate(RenderResourceType::Texture);
+    let handle = alloc.allocate(RenderResourceType::Texture).unwrap();
     assert!(handle.get_type() == RenderResourceType::Texture);
     assert!(alloc.is_valid(&handle));
     alloc.release(handle);
@@ -66,7 +72,7 @@ fn alloc_texture() {
 #[test]
 fn alloc_sampler_state() {
     let mut alloc = RenderResourceHandleAllocator::new();
-    let handle = alloc.allocate(RenderResourceType::SamplerState);
+    let handle = alloc.allocate(RenderResourceType::SamplerState).unwrap();
     assert!(handle.get_type() == RenderResourceType::SamplerState);
     assert!(alloc.is_valid(&handle));
     alloc.release(handle);
@@ -76,7 +82,7 @@ fn alloc_sampler_state() {
 #[test]
 fn alloc_shader() {
     let mut alloc = RenderResourceHandleAllocator::new();
-    let handle = alloc.allocate(RenderResourceType::Shader);
+    let handle = alloc.allocate(RenderResourceType::Shader).unwrap();
     assert!(handle.get_type() == RenderResourceType::Shader);
     assert!(alloc.is_valid(&handle));
     alloc.release(handle);
@@ -86,7 +92,7 @@ fn alloc_shader() {
 #[test]
 fn alloc_shader_views() {
     let mut alloc = RenderResourceHandleAllocator::new();
-    let handle = alloc.allocate(RenderResourceType::ShaderViews);
+    let handle = alloc.allocate(RenderResourceType::ShaderViews).unwrap();
     assert!(handle.get_type() == RenderResourceType::ShaderViews);
     assert!(alloc.is_valid(&handle));
     alloc.release(handle);
@@ -96,7 +102,9 @@ fn alloc_shader_views() {
 #[test]
 fn alloc_graphics_pipeline_state() {
     let mut alloc = RenderResourceHandleAllocator::new();
-    let handle = alloc.allocate(RenderResourceType::GraphicsPipelineState);
+    let handle = alloc
+        .allocate(RenderResourceType::GraphicsPipelineState)
+        .unwrap();
     assert!(handle.get_type() == RenderResourceType::GraphicsPipelineState);
     assert!(alloc.is_valid(&handle));
     alloc.release(handle);
@@ -106,7 +114,9 @@ fn alloc_graphics_pipeline_state() {
 #[test]
 fn alloc_compute_pipeline_state() {
     let mut alloc = RenderResourceHandleAllocator::new();
-    let handle = alloc.allocate(RenderResourceType::ComputePipelineState);
+    let handle = alloc
+        .allocate(RenderResourceType::ComputePipelineState)
+        .unwrap();
     assert!(handle.get_type() == RenderResourceType::ComputePipelineState);
     assert!(alloc.is_valid(&handle));
     alloc.release(handle);
@@ -116,7 +126,7 @@ fn alloc_compute_pipeline_state() {
 #[test]
 fn alloc_draw_binding_set() {
     let mut alloc = RenderResourceHandleAllocator::new();
-    let handle = alloc.allocate(RenderResourceType::DrawBindingSet);
+    let handle = alloc.allocate(RenderResourceType::DrawBindingSet).unwrap();
     assert!(handle.get_type() == RenderResourceType::DrawBindingSet);
     assert!(alloc.is_valid(&handle));
     alloc.release(handle);
@@ -126,7 +136,7 @@ fn alloc_draw_binding_set() {
 #[test]
 fn alloc_frame_binding_set() {
     let mut alloc = RenderResourceHandleAllocator::new();
-    let handle = alloc.allocate(RenderResourceType::FrameBindingSet);
+    let handle = alloc.allocate(RenderResourceType::FrameBindingSet).unwrap();
     assert!(handle.get_type() == RenderResourceType::FrameBindingSet);
     assert!(alloc.is_valid(&handle));
     alloc.release(handle);
@@ -136,7 +146,7 @@ fn alloc_frame_binding_set() {
 #[test]
 fn alloc_render_pass() {
     let mut alloc = RenderResourceHandleAllocator::new();
-    let handle = alloc.allocate(RenderResourceType::RenderPass);
+    let handle = alloc.allocate(RenderResourceType::RenderPass).unwrap();
     assert!(handle.get_type() == RenderResourceType::RenderPass);
     assert!(alloc.is_valid(&handle));
     alloc.release(handle);
@@ -146,7 +156,7 @@ fn alloc_render_pass() {
 #[test]
 fn alloc_command_list() {
     let mut alloc = RenderResourceHandleAllocator::new();
-    let handle = alloc.allocate(RenderResourceType::CommandList);
+    let handle = alloc.allocate(RenderResourceType::CommandList).unwrap();
     assert!(handle.get_type() == RenderResourceType::CommandList);
     assert!(alloc.is_valid(&handle));
     alloc.release(handle);
@@ -156,7 +166,7 @@ fn alloc_command_list() {
 #[test]
 fn alloc_fence() {
     let mut alloc = RenderResourceHandleAllocator::new();
-    let handle = alloc.allocate(RenderResourceType::Fence);
+    let handle = alloc.allocate(RenderResourceType::Fence).unwrap();
     assert!(handle.get_type() == RenderResourceType::Fence);
     assert!(alloc.is_valid(&handle));
     alloc.release(handle);
@@ -166,7 +176,7 @@ fn alloc_fence() {
 #[test]
 fn alloc_timing_heap() {
     let mut alloc = RenderResourceHandleAllocator::new();
-    let handle = alloc.allocate(RenderResourceType::TimingHeap);
+    let handle = alloc.allocate(RenderResourceType::TimingHeap).unwrap();
     assert!(handle.get_type() == RenderResourceType::TimingHeap);
     assert!(alloc.is_valid(&handle));
     alloc.release(handle);
@@ -177,15 +187,19 @@ fn alloc_timing_heap() {
 fn release_randomly() {
     let mut alloc = RenderResourceHandleAllocator::new();
 
-    let handle1 = alloc.allocate(RenderResourceType::Buffer);
-    let handle2 = alloc.allocate(RenderResourceType::Texture);
-    let handle3 = alloc.allocate(RenderResourceType::SwapChain);
-    let handle4 = alloc.allocate(RenderResourceType::GraphicsPipelineState);
-    let handle5 = alloc.allocate(RenderResourceType::ComputePipelineState);
-    let handle6 = alloc.allocate(RenderResourceType::Buffer);
-    let handle7 = alloc.allocate(RenderResourceType::DrawBindingSet);
-    let handle8 = alloc.allocate(RenderResourceType::RenderPass);
-    let handle9 = alloc.allocate(RenderResourceType::FrameBindingSet);
+    let handle1 = alloc.allocate(RenderResourceType::Buffer).unwrap();
+    let handle2 = alloc.allocate(RenderResourceType::Texture).unwrap();
+    let handle3 = alloc.allocate(RenderResourceType::SwapChain).unwrap();
+    let handle4 = alloc
+        .allocate(RenderResourceType::GraphicsPipelineState)
+        .unwrap();
+    let handle5 = alloc
+        .allocate(RenderResourceType::ComputePipelineState)
+        .unwrap();
+    let handle6 = alloc.allocate(RenderResourceType::Buffer).unwrap();
+    let handle7 = alloc.allocate(RenderResourceType::DrawBindingSet).unwrap();
+    let handle8 = alloc.allocate(RenderResourceType::RenderPass).unwrap();
+    let handle9 = alloc.allocate(RenderResourceType::FrameBindingSet).unwrap();
 
     assert!(alloc.is_valid(&handle1));
     assert!(alloc.is_valid(&handle2));
@@ -301,10 +315,10 @@ fn release_randomly() {
 fn multi_type() {
     let mut alloc = RenderResourceHandleAllocator::new();
 
-    let handle1 = alloc.allocate(RenderResourceType::Buffer);
-    let handle2 = alloc.allocate(RenderResourceType::Buffer);
-    let handle3 = alloc.allocate(RenderResourceType::Buffer);
-    let handle4 = alloc.allocate(RenderResourceType::Buffer);
+    let handle1 = alloc.allocate(RenderResourceType::Buffer).unwrap();
+    let handle2 = alloc.allocate(RenderResourceType::Buffer).unwrap();
+    let handle3 = alloc.allocate(RenderResourceType::Buffer).unwrap();
+    let handle4 = alloc.allocate(RenderResourceType::Buffer).unwrap();
 
     assert!(alloc.is_valid(&handle1));
     assert!(alloc.is_valid(&handle2));
@@ -334,19 +348,19 @@ fn count_type() {
     assert_eq!(alloc.get_count(RenderResourceType::Buffer), 0);
     assert_eq!(alloc.get_max(RenderResourceType::Buffer), 0);
 
-    let handle1 = alloc.allocate(RenderResourceType::Buffer);
+    let handle1 = alloc.allocate(RenderResourceType::Buffer).unwrap();
     assert_eq!(alloc.get_count(RenderResourceType::Buffer), 1);
     assert_eq!(alloc.get_max(RenderResourceType::Buffer), 1);
 
-    let handle2 = alloc.allocate(RenderResourceType::Buffer);
+    let handle2 = alloc.allocate(RenderResourceType::Buffer).unwrap();
     assert_eq!(alloc.get_count(RenderResourceType::Buffer), 2);
     assert_eq!(alloc.get_max(RenderResourceType::Buffer), 2);
 
-    let handle3 = alloc.allocate(RenderResourceType::Buffer);
+    let handle3 = alloc.allocate(RenderResourceType::Buffer).unwrap();
     assert_eq!(alloc.get_count(RenderResourceType::Buffer), 3);
     assert_eq!(alloc.get_max(RenderResourceType::Buffer), 3);
 
-    let handle4 = alloc.allocate(RenderResourceType::Buffer);
+    let handle4 = alloc.allocate(RenderResourceType::Buffer).unwrap();
     assert_eq!(alloc.get_count(RenderResourceType::Buffer), 4);
     assert_eq!(alloc.get_max(RenderResourceType::Buffer), 4);
 
@@ -367,16 +381,37 @@ fn count_type() {
     assert_eq!(alloc.get_max(RenderResourceType::Buffer), 4);
 }
 
+#[test]
+fn live_handles() {
+    let mut alloc = RenderResourceHandleAllocator::new();
+    assert!(alloc.get_handles(RenderResourceType::Texture).is_empty());
+
+    let handle1 = alloc.allocate(RenderResourceType::Texture).unwrap();
+    let handle2 = alloc.allocate(RenderResourceType::Texture).unwrap();
+    let handle3 = alloc.allocate(RenderResourceType::Texture).unwrap();
+    let _buffer = alloc.allocate(RenderResourceType::Buffer).unwrap();
+    assert_eq!(
+        alloc.get_handles(RenderResourceType::Texture),
+        vec![handle1, handle2, handle3]
+    );
+
+    alloc.release(handle2);
+    assert_eq!(
+        alloc.get_handles(RenderResourceType::Texture),
+        vec![handle1, handle3]
+    );
+}
+
 #[test]
 fn allocated_not_valid() {
     let mut alloc = RenderResourceHandleAllocator::new();
 
-    let handle1 = alloc.allocate(RenderResourceType::Buffer);
+    let handle1 = alloc.allocate(RenderResourceType::Buffer).unwrap();
     assert!(alloc.is_valid(&handle1));
     assert!(alloc.is_allocated(handle1.get_id(), handle1.get_type()));
 
     alloc.release(handle1);
-    let handle2 = alloc.allocate(RenderResourceType::Buffer);
+    let handle2 = alloc.allocate(RenderResourceType::Buffer).unwrap();
 
     assert!(!alloc.is_valid(&handle1));
     assert!(alloc.is_allocated(handle1.get_id(), handle1.get_type()));
@@ -420,7 +455,209 @@ fn handle_packing() {
     let handle1 = RenderResourceHandle::new(123, RenderResourceType::GraphicsPipelineState, 12345);
     let packed = handle1.get_packed();
     assert_ne!(packed, 0);
-    assert_ne!(packed, u32::max_value());
-    let handle2 = RenderResourceHandle::from_packed(packed, handle1.get_cookie());
+    assert_ne!(packed, u64::MAX);
+    let handle2 = RenderResourceHandle::from_packed(packed, handle1.get_cookie()).unwrap();
     assert_eq!(handle1, handle2);
 }
+
+#[test]
+fn handle_packing_wide() {
+    let handle1 =
+        RenderResourceHandle::new(0x0012_3456, RenderResourceType::TimingHeap, 0x0001_0002);
+    let packed = handle1.get_packed();
+    assert_eq!(packed >> 32, RenderResourceType::TimingHeap as u64);
+    let handle2 = RenderResourceHandle::from_packed(packed, handle1.get_cookie()).unwrap();
+    assert_eq!(handle1, handle2);
+    assert_eq!(handle2.get_id(), 0x0012_3456);
+}
+
+#[test]
+fn handle_packing_invalid_type() {
+    let packed = (u64::from(u16::MAX) << 32) | 7;
+    assert!(RenderResourceHandle::from_packed(packed, 1).is_err());
+    assert!(RenderResourceHandle::from_packed(u64::MAX, 1).is_err());
+}
+
+#[test]
+fn alloc_past_u16() {
+    let mut alloc = RenderResourceHandleAllocator::new();
+    let handles: Vec<RenderResourceHandle> = (0..70_000)
+        .map(|_| alloc.allocate(RenderResourceType::Buffer).unwrap())
+        .collect();
+    assert_eq!(alloc.get_count(RenderResourceType::Buffer), 70_000);
+    let last = handles[69_999];
+    assert_eq!(last.get_id(), 69_999);
+    assert!(alloc.is_valid(&last));
+    alloc.release(last);
+    assert!(!alloc.is_valid(&last));
+}
+
+#[test]
+fn alloc_limit() {
+    let mut alloc = RenderResourceHandleAllocator::with_limit(2);
+    let handle = alloc.allocate(RenderResourceType::Fence).unwrap();
+    alloc.allocate(RenderResourceType::Fence).unwrap();
+    match alloc
+        .allocate(RenderResourceType::Fence)
+        .unwrap_err()
+        .kind()
+    {
+        ErrorKind::Memory(_) => {}
+        kind => panic!("unexpected error kind {:?}", kind),
+    }
+    assert!(alloc.allocate_typed::<RenderFenceHandle>().is_err());
+
+    // Limits are per resource type, and released indices are reused
+    assert!(alloc.allocate(RenderResourceType::Buffer).is_ok());
+    alloc.release(handle);
+    assert_eq!(
+        alloc.allocate(RenderResourceType::Fence).unwrap().get_id(),
+        handle.get_id()
+    );
+}
+
+#[test]
+fn typed_handle_conversions() {
+    let mut alloc = RenderResourceHandleAllocator::new();
+    let buffer: RenderBufferHandle = alloc.allocate_typed().unwrap();
+    assert_eq!(buffer.get_type(), RenderResourceType::Buffer);
+    assert!(alloc.is_valid(&buffer));
+
+    let untyped: RenderResourceHandle = buffer.into();
+    assert_eq!(untyped, buffer);
+    assert_eq!(RenderBufferHandle::try_from(untyped).unwrap(), buffer);
+    assert!(RenderTextureHandle::try_from(untyped).is_err());
+    assert!(RenderTextureHandle::from_handle(untyped).is_err());
+}
+
+#[test]
+fn stale_handle_rejected() {
+    let mut alloc = RenderResourceHandleAllocator::new();
+    let stale = alloc.allocate(RenderResourceType::Buffer).unwrap();
+    alloc.release(stale);
+    let handle = alloc.allocate(RenderResourceType::Buffer).unwrap();
+    assert_eq!(handle.get_id(), stale.get_id());
+    assert_ne!(handle.get_cookie(), stale.get_cookie());
+
+    assert!(!alloc.is_valid(&stale));
+    assert!(alloc.check(&handle).is_ok());
+    let err = alloc.check(&stale).unwrap_err();
+    assert_eq!(*err.kind(), ErrorKind::StaleHandle(stale));
+    assert!(err.to_string().contains("stale Buffer handle"));
+
+    let unallocated = RenderResourceHandle::new(7, RenderResourceType::Buffer, 1);
+    match alloc.check(&unallocated).unwrap_err().kind() {
+        ErrorKind::Backend(_) => {}
+        kind => panic!("unexpected error kind {:?}", kind),
+    }
+}
+
+#[test]
+fn stale_handle_storage() {
+    let mut alloc = RenderResourceHandleAllocator::new();
+    let storage = RenderResourceStorage::new();
+    let stale = alloc.allocate(RenderResourceType::Texture).unwrap();
+    storage.put(stale, Arc::new(RwLock::new("old"))).unwrap();
+    assert!(storage.valid(stale));
+    storage.remove(stale).unwrap();
+    alloc.release(stale);
+
+    let handle = alloc.allocate(RenderResourceType::Texture).unwrap();
+    storage.put(handle, Arc::new(RwLock::new("new"))).unwrap();
+    assert!(storage.valid(handle));
+    assert!(!storage.valid(stale));
+    assert!(storage.get_or_none(stale).is_none());
+    assert_eq!(
+        *storage.get(stale).unwrap_err().kind(),
+        ErrorKind::StaleHandle(stale)
+    );
+    assert_eq!(
+        *storage.remove(stale).unwrap_err().kind(),
+        ErrorKind::StaleHandle(stale)
+    );
+    assert_eq!(*storage.get(handle).unwrap().read().unwrap(), "new");
+}
+
+#[test]
+fn concurrent_alloc_release() {
+    let alloc = RenderConcurrentHandleAllocator::new();
+    let stale = alloc.allocate(RenderResourceType::Buffer).unwrap();
+    assert!(alloc.is_valid(&stale));
+    assert_eq!(alloc.get_count(RenderResourceType::Buffer), 1);
+    alloc.release(stale).unwrap();
+    assert!(!alloc.is_valid(&stale));
+    assert_eq!(alloc.get_count(RenderResourceType::Buffer), 0);
+
+    // Released indices are reused with a new cookie
+    let handle = alloc.allocate(RenderResourceType::Buffer).unwrap();
+    assert_eq!(handle.get_id(), stale.get_id());
+    assert_ne!(handle.get_cookie(), stale.get_cookie());
+    assert_eq!(alloc.get_max(RenderResourceType::Buffer), 1);
+    assert_eq!(alloc.get_handles(RenderResourceType::Buffer), vec![handle]);
+
+    assert_eq!(
+        *alloc.release(stale).unwrap_err().kind(),
+        ErrorKind::StaleHandle(stale)
+    );
+    assert!(alloc.check(&handle).is_ok());
+    let bogus = RenderResourceHandle::new(12, RenderResourceType::Buffer, 1);
+    assert!(!alloc.is_valid(&bogus));
+    assert!(alloc.release(bogus).is_err());
+
+    let texture: RenderTextureHandle = alloc.allocate_typed().unwrap();
+    assert!(alloc.is_valid(&texture));
+}
+
+#[test]
+fn concurrent_alloc_limit() {
+    let alloc = RenderConcurrentHandleAllocator::with_limit(100);
+    for _ in 0..100 {
+        alloc.allocate(RenderResourceType::Shader).unwrap();
+    }
+    match alloc
+        .allocate(RenderResourceType::Shader)
+        .unwrap_err()
+        .kind()
+    {
+        ErrorKind::Memory(_) => {}
+        kind => panic!("unexpected error kind {:?}", kind),
+    }
+    assert!(alloc.allocate(RenderResourceType::Texture).is_ok());
+}
+
+#[test]
+fn concurrent_alloc_threads() {
+    let alloc = Arc::new(RenderConcurrentHandleAllocator::new());
+    let threads: Vec<_> = (0..8)
+        .map(|_| {
+            let alloc = Arc::clone(&alloc);
+            thread::spawn(move || {
+                let mut kept = Vec::new();
+                for round in 0..2_000 {
+                    let handle = alloc.allocate(RenderResourceType::Buffer).unwrap();
+                    assert!(alloc.is_valid(&handle));
+                    if round % 2 == 0 {
+                        alloc.release(handle).unwrap();
+                    } else {
+                        kept.push(handle);
+                    }
+                }
+                kept
+            })
+        })
+        .collect();
+
+    let mut ids = HashSet::new();
+    for thread in threads {
+        for handle in thread.join().unwrap() {
+            assert!(alloc.is_valid(&handle));
+            assert!(ids.insert(handle.get_id()), "handle allocated twice");
+        }
+    }
+    assert_eq!(ids.len(), 8 * 1_000);
+    assert_eq!(alloc.get_count(RenderResourceType::Buffer), 8 * 1_000);
+    assert_eq!(
+        alloc.get_handles(RenderResourceType::Buffer).len(),
+        8 * 1_000
+    );
+}
@@ -0,0 +1,67 @@
+extern crate render_core;
+#[macro_use]
+extern crate log;
+use render_core::logging::*;
+use std::env;
+use std::fs;
+
+#[test]
+fn log_filters() {
+    let mut config = RenderLogConfig::default();
+    config
+        .apply_filters("warn, render_core::system=debug,render_hal_vk=trace")
+        .unwrap();
+    assert_eq!(config.level, LevelFilter::Warn);
+    assert_eq!(
+        config.module_levels,
+        vec![
+            ("render_core::system".to_string(), LevelFilter::Debug),
+            ("render_hal_vk".to_string(), LevelFilter::Trace),
+        ]
+    );
+    assert_eq!(config.max_level(), LevelFilter::Trace);
+
+    config.apply_filters("render_hal_vk=off").unwrap();
+    assert_eq!(config.module_levels.len(), 2);
+    assert_eq!(config.module_levels[1].1, LevelFilter::Off);
+    assert_eq!(config.max_level(), LevelFilter::Debug);
+
+    assert!(config.apply_filters("loud").is_err());
+    assert!(config.apply_filters("render_core=loud").is_err());
+}
+
+#[test]
+fn log_init_file() {
+    let log_path = env::temp_dir().join("render-core-logging.log");
+    let _ = fs::remove_file(&log_path);
+
+    let mut config = RenderLogConfig::new(LevelFilter::Warn);
+    config.console = false;
+    config.timestamps = false;
+    config.file = Some(log_path.clone());
+    config.set_module_level("logging::verbose", LevelFilter::Debug);
+    init_logging(&config).unwrap();
+
+    warn!("kept warning");
+    info!("dropped info");
+    debug!(target: "logging::verbose", "kept debug");
+    trace!(target: "logging::verbose", "dropped trace");
+
+    // Records from modules go through the host logger
+    let record = Record::builder()
+        .args(format_args!("forwarded error"))
+        .level(log::Level::Error)
+        .target("render_hal_mock")
+        .build();
+    host_logger().log(&record);
+    host_logger().flush();
+
+    let contents = fs::read_to_string(&log_path).unwrap();
+    assert!(contents.contains("[logging][WARN] kept warning"));
+    assert!(contents.contains("[logging::verbose][DEBUG] kept debug"));
+    assert!(contents.contains("[render_hal_mock][ERROR] forwarded error"));
+    assert!(!contents.contains("dropped"));
+
+    // Only one global logger per process
+    assert!(init_logging(&config).is_err());
+}
@@ -0,0 +1,90 @@
+extern crate render_core;
+use render_core::error::{Error, ErrorKind};
+use render_core::modules::*;
+use std::env;
+use std::path::PathBuf;
+
+#[test]
+fn module_desc_current() {
+    let desc = RenderBackendModuleDesc::current("mock\0");
+    assert_eq!(desc.abi_version, RENDER_BACKEND_ABI_VERSION);
+    assert_eq!(desc.layout_fingerprint, layout_fingerprint());
+    assert_eq!(desc.api(), "mock");
+    assert_eq!(desc.core_version(), env!("CARGO_PKG_VERSION"));
+    assert!(desc.validate().is_ok());
+}
+
+#[test]
+fn module_desc_abi_mismatch() {
+    let mut desc = RenderBackendModuleDesc::current("mock\0");
+    desc.abi_version += 1;
+    match desc.validate() {
+        Err(err) => match err.kind() {
+            ErrorKind::Backend(_) => {}
+            _ => panic!("unexpected error kind: {:?}", err.kind()),
+        },
+        Ok(_) => panic!("abi mismatch was not rejected"),
+    }
+}
+
+#[test]
+fn module_desc_version_mismatch() {
+    let mut desc = RenderBackendModuleDesc::current("mock\0");
+    desc.core_version = "0.0.0\0".as_ptr() as *const _;
+    assert!(desc.validate().is_err());
+
+    let mut desc = RenderBackendModuleDesc::current("mock\0");
+    desc.rustc_version = "rustc 0.0.0\0".as_ptr() as *const _;
+    assert!(desc.validate().is_err());
+}
+
+#[test]
+fn module_desc_layout_mismatch() {
+    let mut desc = RenderBackendModuleDesc::current("mock\0");
+    desc.layout_fingerprint ^= 1;
+    assert!(desc.validate().is_err());
+}
+
+#[test]
+fn module_discovery_skips_rejected() {
+    let mut stale_abi = RenderBackendModuleDesc::current("stale_abi\0");
+    stale_abi.abi_version -= 1;
+    let mut stale_layout = RenderBackendModuleDesc::current("stale_layout\0");
+    stale_layout.layout_fingerprint ^= 1;
+    let mut stale_rustc = RenderBackendModuleDesc::current("stale_rustc\0");
+    stale_rustc.rustc_version = "rustc 0.0.0\0".as_ptr() as _;
+    let candidates = vec![
+        (PathBuf::from("stale_abi.so"), Some(stale_abi)),
+        (PathBuf::from("missing.so"), None),
+        (
+            PathBuf::from("mock.so"),
+            Some(RenderBackendModuleDesc::current("mock\0")),
+        ),
+        (PathBuf::from("stale_layout.so"), Some(stale_layout)),
+        (PathBuf::from("stale_rustc.so"), Some(stale_rustc)),
+        (
+            PathBuf::from("proxy.so"),
+            Some(RenderBackendModuleDesc::current("proxy\0")),
+        ),
+    ];
+
+    // Rejected modules are skipped, rather than failing discovery of the others
+    let accepted = select_backend_modules(candidates, |desc| {
+        desc.ok_or_else(|| Error::backend("no descriptor"))
+    });
+    let apis: Vec<String> = accepted.iter().map(|desc| desc.unwrap().api()).collect();
+    assert_eq!(apis, vec!["mock", "proxy"]);
+}
+
+#[test]
+fn module_discovery_loads_modules() {
+    // Backend modules are built next to the test executables
+    let exe_path = env::current_exe().unwrap();
+    let module_path = exe_path.parent().unwrap();
+    let libraries = load_backend_modules(module_path).unwrap();
+    let apis: Vec<String> = libraries
+        .iter()
+        .map(|library| get_backend_module_desc(library).unwrap().api())
+        .collect();
+    assert!(apis.iter().any(|api| api == "mock"));
+}
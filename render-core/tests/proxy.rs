@@ -0,0 +1,184 @@
+extern crate render_core;
+extern crate render_hal_mock;
+extern crate render_hal_proxy;
+use render_core::backend::*;
+use render_core::encoder::*;
+use render_core::error::ErrorKind;
+use render_core::system::*;
+use render_core::types::*;
+use render_hal_mock::RenderBackendModuleMock;
+use render_hal_proxy::*;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+mod common;
+
+/// Serves a single client connection from a mock backend on a background thread.
+fn spawn_server(address: &str) -> (ProxyAddress, thread::JoinHandle<()>) {
+    let address = address.to_string();
+    let (sender, receiver) = mpsc::channel();
+    let server_thread = thread::spawn(move || {
+        let server =
+            RenderProxyServer::bind(Box::new(RenderBackendModuleMock::new()), &address).unwrap();
+        sender.send(server.local_address().unwrap()).unwrap();
+        server.serve_connection().unwrap();
+    });
+    (receiver.recv().unwrap(), server_thread)
+}
+
+fn proxy_system(address: &ProxyAddress) -> RenderSystem {
+    common::static_render_system(
+        Box::new(RenderBackendModuleProxy::new()),
+        &[RenderBackendSettings {
+            api: "proxy".to_string(),
+            address: Some(address.to_string()),
+            debug_flags: RenderDebugFlags::NONE,
+        }],
+    )
+}
+
+fn exercise_device(render_system: &mut RenderSystem) {
+    let registry = render_system.get_registry().unwrap();
+    let registry_read = registry.read().unwrap();
+    assert_eq!(registry_read.len(), 1);
+    let entry = &registry_read[0];
+
+    let device_info = render_system
+        .enumerate_devices(entry, false, None, None)
+        .unwrap();
+    assert!(!device_info.is_empty());
+
+    let device = render_system.create_device(entry, 0).unwrap();
+    let device_read = device.read().unwrap();
+    let device_read = device_read.as_ref().unwrap();
+    assert_eq!(
+        device_read.get_device_info().unwrap().name,
+        device_info[0].name
+    );
+
+    let buffer_handle = render_system
+        .create_buffer_handle("Proxy Buffer".into())
+        .unwrap();
+    device_read
+        .create_buffer(
+            buffer_handle,
+            &RenderBufferDesc {
+                bind_flags: RenderBindFlags::SHADER_RESOURCE,
+                size: 64,
+            },
+            Some(&[0u8; 64]),
+            "Proxy Buffer".into(),
+        )
+        .unwrap();
+    assert!(device_read.valid_resource(buffer_handle.into()));
+
+    let command_list_handle = render_system
+        .create_command_list_handle("Proxy Commands".into())
+        .unwrap();
+    device_read
+        .create_command_list(command_list_handle, "Proxy Commands".into())
+        .unwrap();
+
+    let handles = render_system.get_handle_allocator().unwrap();
+    let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
+    command_list
+        .update_buffer(buffer_handle, 0, &[1u8; 16])
+        .unwrap();
+    device_read
+        .compile_command_list(command_list_handle, &command_list)
+        .unwrap();
+    device_read
+        .submit_command_list(command_list_handle, true, None, None)
+        .unwrap();
+
+    device_read
+        .destroy_resource(command_list_handle.into())
+        .unwrap();
+    device_read.destroy_resource(buffer_handle.into()).unwrap();
+    assert!(!device_read.valid_resource(buffer_handle.into()));
+}
+
+#[test]
+fn proxy_address_parse() {
+    assert_eq!(
+        ProxyAddress::parse("127.0.0.1:50080").unwrap(),
+        ProxyAddress::Tcp("127.0.0.1:50080".to_string())
+    );
+    assert_eq!(
+        ProxyAddress::parse("tcp://localhost:1234").unwrap(),
+        ProxyAddress::Tcp("localhost:1234".to_string())
+    );
+    assert_eq!(
+        ProxyAddress::parse("unix:///tmp/render.sock").unwrap(),
+        ProxyAddress::Unix("/tmp/render.sock".into())
+    );
+    assert!(ProxyAddress::parse("").is_err());
+}
+
+#[test]
+fn proxy_tcp() {
+    let (address, server_thread) = spawn_server("127.0.0.1:0");
+    let mut render_system = proxy_system(&address);
+    exercise_device(&mut render_system);
+    render_system.release().unwrap();
+    server_thread.join().unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn proxy_unix() {
+    let socket_path = std::env::temp_dir().join("render-core-proxy.sock");
+    let (address, server_thread) = spawn_server(&format!("unix:{}", socket_path.display()));
+    let mut render_system = proxy_system(&address);
+    exercise_device(&mut render_system);
+    render_system.release().unwrap();
+    server_thread.join().unwrap();
+    assert!(!socket_path.exists());
+}
+
+#[test]
+fn proxy_error_forwarding() {
+    let (address, server_thread) = spawn_server("127.0.0.1:0");
+    let mut render_system = proxy_system(&address);
+    {
+        let registry = render_system.get_registry().unwrap();
+        let registry_read = registry.read().unwrap();
+        let entry = &registry_read[0];
+        render_system
+            .enumerate_devices(entry, false, None, None)
+            .unwrap();
+
+        // Devices must be created before use
+        let device = render_system.get_device(entry, 0).unwrap();
+        assert!(device.read().unwrap().is_none());
+
+        // The mock backend panics on flush; the server reports it as a backend error
+        let device = render_system.create_device(entry, 0).unwrap();
+        let device_read = device.read().unwrap();
+        let err = device_read.as_ref().unwrap().device_flush().unwrap_err();
+        match err.kind() {
+            ErrorKind::Backend(_) => {}
+            kind => panic!("unexpected error kind {:?}", kind),
+        }
+
+        // The connection survives the failed call
+        assert!(device_read.as_ref().unwrap().get_device_info().is_ok());
+    }
+    render_system.release().unwrap();
+    server_thread.join().unwrap();
+}
+
+#[test]
+fn proxy_connection_refused() {
+    // Bind and drop a listener to find a port nothing is listening on
+    let address = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let mut render_system = proxy_system(&ProxyAddress::Tcp(address.to_string()));
+    let registry = render_system.get_registry().unwrap();
+    let registry_read = registry.read().unwrap();
+    assert!(render_system
+        .enumerate_devices(&registry_read[0], false, None, None)
+        .is_err());
+}
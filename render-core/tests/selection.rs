@@ -0,0 +1,104 @@
+extern crate render_core;
+extern crate render_hal_mock;
+use render_core::backend::*;
+use render_core::device::*;
+use render_core::selection::*;
+use render_core::system::*;
+use render_hal_mock::RenderBackendModuleMock;
+mod common;
+
+fn settings(api: &str) -> RenderBackendSettings {
+    RenderBackendSettings {
+        api: api.to_string(),
+        address: None,
+        debug_flags: RenderDebugFlags::NONE,
+    }
+}
+
+fn device_info(device_type: RenderDeviceType, vendor: RenderDeviceVendor) -> RenderDeviceInfo {
+    RenderDeviceInfo {
+        name: format!("{:?}", device_type),
+        vendor,
+        device_id: 0,
+        device_index: 0,
+        device_type,
+        caps: Default::default(),
+    }
+}
+
+fn score_of(info: &RenderDeviceInfo) -> u32 {
+    match default_device_score(info) {
+        RenderDeviceScore::Accept(score) => score,
+        RenderDeviceScore::Reject(reason) => panic!("unexpected rejection: {}", reason),
+    }
+}
+
+fn reject_cpu(info: &RenderDeviceInfo) -> RenderDeviceScore {
+    if info.device_type == RenderDeviceType::Cpu {
+        RenderDeviceScore::Reject("cpu devices are not allowed".to_string())
+    } else {
+        default_device_score(info)
+    }
+}
+
+fn mock_system(policy: &RenderDeviceSelectionPolicy) -> RenderSystem {
+    common::static_render_system(Box::new(RenderBackendModuleMock::new()), &policy.backends)
+}
+
+#[test]
+fn default_score_order() {
+    let discrete = score_of(&device_info(
+        RenderDeviceType::Discrete,
+        RenderDeviceVendor::Unknown,
+    ));
+    let integrated = score_of(&device_info(
+        RenderDeviceType::Integrated,
+        RenderDeviceVendor::Intel,
+    ));
+    let cpu = score_of(&device_info(
+        RenderDeviceType::Cpu,
+        RenderDeviceVendor::Nvidia,
+    ));
+    assert!(discrete > integrated);
+    assert!(integrated > cpu);
+
+    let mut caps_info = device_info(RenderDeviceType::Cpu, RenderDeviceVendor::Nvidia);
+    caps_info.caps.supports_quads = true;
+    assert!(score_of(&caps_info) > cpu);
+}
+
+#[test]
+fn select_fallback() {
+    let policy = RenderDeviceSelectionPolicy::new(&[settings("vk"), settings("mock")]);
+    let mut render_system = mock_system(&policy);
+
+    let selection = render_system.select_device(&policy).unwrap();
+    assert_eq!(selection.registry_index, 0);
+    assert_eq!(selection.settings.api, "mock");
+    assert_eq!(selection.info.name, "MockDevice");
+    assert!(selection.device.read().unwrap().is_some());
+
+    assert_eq!(selection.rejected.len(), 1);
+    assert_eq!(selection.rejected[0].api, "vk");
+    assert!(selection.rejected[0].device.is_none());
+}
+
+#[test]
+fn select_rejected() {
+    let mut policy = RenderDeviceSelectionPolicy::new(&[settings("mock")]);
+    policy.score = reject_cpu;
+    let mut render_system = mock_system(&policy);
+
+    let err = render_system.select_device(&policy).unwrap_err();
+    assert!(err.to_string().contains("cpu devices are not allowed"));
+}
+
+#[test]
+fn select_invalid() {
+    let policy = RenderDeviceSelectionPolicy::new(&[]);
+    let mut render_system = RenderSystem::new();
+    assert!(render_system.select_device(&policy).is_err());
+
+    let mut render_system = mock_system(&RenderDeviceSelectionPolicy::new(&[settings("mock")]));
+    assert!(render_system.select_device(&policy).is_err());
+}
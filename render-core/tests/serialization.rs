@@ -0,0 +1,222 @@
+extern crate render_core;
+use render_core::commands::*;
+use render_core::encoder::RenderCommandList;
+use render_core::error::ErrorKind;
+use render_core::handles::*;
+use render_core::serialization::*;
+use render_core::state::*;
+use render_core::types::*;
+use std::sync::Arc;
+
+fn record_all(handles: &Arc<RenderConcurrentHandleAllocator>) -> RenderCommandList {
+    let mut command_list = RenderCommandList::new(Arc::clone(handles), 8 * 1024, 32).unwrap();
+
+    let graphics_state = handles
+        .allocate_typed::<RenderGraphicsPipelineStateHandle>()
+        .unwrap();
+    let compute_state = handles
+        .allocate_typed::<RenderComputePipelineStateHandle>()
+        .unwrap();
+    let ray_tracing_state = handles
+        .allocate_typed::<RenderRayTracingPipelineStateHandle>()
+        .unwrap();
+    let draw_binding = handles
+        .allocate_typed::<RenderDrawBindingSetHandle>()
+        .unwrap();
+    let buffer = handles.allocate_typed::<RenderBufferHandle>().unwrap();
+    let texture = handles.allocate_typed::<RenderTextureHandle>().unwrap();
+    let render_pass = handles.allocate_typed::<RenderPassHandle>().unwrap();
+    let timing_heap = handles.allocate_typed::<RenderTimingHeapHandle>().unwrap();
+    let shader_table = handles
+        .allocate_typed::<RenderRayTracingShaderTableHandle>()
+        .unwrap();
+    let acceleration = handles
+        .allocate_typed::<RenderRayTracingAccelerationHandle>()
+        .unwrap();
+
+    let shader_arguments = [RenderShaderArgument {
+        constant_buffer: Some(buffer.into()),
+        shader_views: None,
+        constant_buffer_offset: 256,
+    }];
+    let draw_packet = RenderDrawPacket {
+        index_offset: 1,
+        vertex_offset: -2,
+        vertex_count: 3,
+        first_instance: 4,
+        instance_count: 5,
+    };
+    let src_box = RenderBox {
+        x: 1,
+        y: 2,
+        z: 3,
+        w: 4,
+        h: 5,
+        d: 6,
+    };
+
+    command_list
+        .begin_event(1, String::from("Serialized Frame").into())
+        .unwrap();
+    command_list.begin_timing(timing_heap, 0).unwrap();
+    command_list.begin_render_pass(render_pass).unwrap();
+    command_list
+        .draw(
+            graphics_state,
+            &shader_arguments,
+            Some(draw_binding),
+            &RenderDrawState::default(),
+            &draw_packet,
+        )
+        .unwrap();
+    command_list
+        .draw_indirect(
+            graphics_state,
+            &shader_arguments,
+            draw_binding,
+            &RenderDrawState::default(),
+            RenderPrimitiveType::TriangleList,
+            buffer,
+            12,
+            buffer,
+            34,
+            56,
+        )
+        .unwrap();
+    command_list.end_render_pass().unwrap();
+    command_list
+        .dispatch(compute_state, &shader_arguments, 1, 2, 3)
+        .unwrap();
+    command_list
+        .dispatch_indirect(compute_state, &[], buffer, 12, buffer, 34, 56)
+        .unwrap();
+    command_list.update_buffer(buffer, 64, &[7u8; 10]).unwrap();
+    command_list
+        .update_texture(texture, 1, 4, 16, &[9u8; 16])
+        .unwrap();
+    command_list.copy_buffer(buffer, 0, 16, buffer, 32).unwrap();
+    command_list
+        .copy_texture(
+            texture,
+            0,
+            src_box,
+            texture,
+            1,
+            RenderPoint { x: 1, y: 2, z: 3 },
+        )
+        .unwrap();
+    command_list.barriers(&[buffer.into()]).unwrap();
+    command_list
+        .transitions(&[(texture.into(), RenderResourceStates::COPY_DEST)])
+        .unwrap();
+    command_list
+        .ray_trace(ray_tracing_state, shader_table, texture.into(), 64, 32, 0)
+        .unwrap();
+    command_list
+        .update_top_level_acceleration(acceleration, RenderAccelerationTopDesc::default())
+        .unwrap();
+    command_list
+        .update_bottom_level_acceleration(acceleration, true)
+        .unwrap();
+    command_list
+        .update_shader_table(shader_table, RenderShaderTableUpdateDesc::default())
+        .unwrap();
+    command_list.end_timing(timing_heap, 0).unwrap();
+    command_list.resolve_timings(timing_heap, 0, 1).unwrap();
+    command_list.end_event().unwrap();
+    command_list
+}
+
+#[test]
+fn command_list_round_trip() {
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
+    let command_list = record_all(&handles);
+    assert_eq!(command_list.get_commands().len(), 21);
+
+    let mut stream = Vec::new();
+    write_command_list(&mut stream, &command_list).unwrap();
+    assert_eq!(&stream[..4], &COMMAND_STREAM_MAGIC);
+
+    let loaded = read_command_list(&mut &stream[..], Arc::clone(&handles)).unwrap();
+    assert_eq!(loaded.get_queue_type(), command_list.get_queue_type());
+    assert_eq!(
+        loaded.get_commands().len(),
+        command_list.get_commands().len()
+    );
+    for (original, loaded_entry) in command_list
+        .get_commands()
+        .iter()
+        .zip(loaded.get_commands())
+    {
+        assert_eq!(original.get_type(), loaded_entry.get_type());
+        let data = command_list.get_entry_data(original).unwrap();
+        assert_eq!(data, loaded.get_entry_data(loaded_entry).unwrap());
+        if data.is_none() {
+            assert_eq!(original, loaded_entry);
+        }
+    }
+
+    match loaded.get_commands()[0] {
+        RenderCommandEntry::BeginEvent(ref command) => {
+            assert_eq!(command.message, "Serialized Frame")
+        }
+        ref entry => panic!("unexpected command {:?}", entry),
+    }
+}
+
+#[test]
+fn command_list_stream_versioned() {
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
+    let serialized = RenderSerializedCommandList::from_command_list(&record_all(&handles)).unwrap();
+    let bytes = serialized.to_bytes().unwrap();
+    assert_eq!(
+        RenderSerializedCommandList::from_bytes(&bytes).unwrap(),
+        serialized
+    );
+
+    let expect_parse_error = |bytes: &[u8]| match RenderSerializedCommandList::from_bytes(bytes)
+        .unwrap_err()
+        .kind()
+    {
+        ErrorKind::Parse(_) => {}
+        kind => panic!("unexpected error kind {:?}", kind),
+    };
+
+    let mut bad_magic = bytes.clone();
+    bad_magic[0] = b'X';
+    expect_parse_error(&bad_magic);
+
+    let mut bad_version = bytes.clone();
+    bad_version[4..8].copy_from_slice(&(COMMAND_STREAM_VERSION + 1).to_le_bytes());
+    expect_parse_error(&bad_version);
+
+    expect_parse_error(&bytes[..bytes.len() / 2]);
+}
+
+#[test]
+fn command_list_record_entry() {
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
+    let buffer = handles.allocate(RenderResourceType::Buffer).unwrap();
+    let mut command_list = RenderCommandList::new(Arc::clone(&handles), 1024, 4).unwrap();
+
+    // Update commands need data matching their size, and other commands can't have any
+    let update = RenderCommandEntry::from(RenderCommandUpdateBuffer::new(buffer, 0, 8, 0));
+    assert!(command_list.record_entry(update.clone(), None).is_err());
+    assert!(command_list
+        .record_entry(update.clone(), Some(&[0u8; 4]))
+        .is_err());
+    let barriers = RenderCommandEntry::from(RenderCommandBarriers::new(&[buffer]));
+    assert!(command_list
+        .record_entry(barriers.clone(), Some(&[0u8; 4]))
+        .is_err());
+    assert!(command_list.is_empty());
+
+    command_list.record_entry(update, Some(&[3u8; 8])).unwrap();
+    command_list.record_entry(barriers, None).unwrap();
+    let commands = command_list.get_commands();
+    assert_eq!(
+        command_list.get_entry_data(&commands[0]).unwrap(),
+        Some(&[3u8; 8][..])
+    );
+    assert_eq!(command_list.get_entry_data(&commands[1]).unwrap(), None);
+}
@@ -0,0 +1,136 @@
+extern crate render_core;
+extern crate render_hal_mock;
+use render_core::backend::*;
+use render_core::commands::*;
+use render_core::encoder::RenderCommandList;
+use render_core::handles::*;
+use render_core::state::*;
+use render_core::types::*;
+use std::sync::Arc;
+mod common;
+
+fn draw(
+    command_list: &mut RenderCommandList,
+    pipeline_state: RenderGraphicsPipelineStateHandle,
+    vertex_count: u32,
+    instance_count: u32,
+) {
+    command_list
+        .draw(
+            pipeline_state,
+            &[],
+            None,
+            &RenderDrawState::default(),
+            &RenderDrawPacket {
+                index_offset: 0,
+                vertex_offset: 0,
+                vertex_count,
+                first_instance: 0,
+                instance_count,
+            },
+        )
+        .unwrap();
+}
+
+#[test]
+fn statistics_command_list() {
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
+    let pipeline_state = handles
+        .allocate_typed::<RenderGraphicsPipelineStateHandle>()
+        .unwrap();
+    let compute_state = handles
+        .allocate_typed::<RenderComputePipelineStateHandle>()
+        .unwrap();
+    let render_pass = handles.allocate_typed::<RenderPassHandle>().unwrap();
+    let buffer = handles.allocate_typed::<RenderBufferHandle>().unwrap();
+
+    let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
+    command_list.update_buffer(buffer, 0, &[0u8; 16]).unwrap();
+    command_list.begin_render_pass(render_pass).unwrap();
+    draw(&mut command_list, pipeline_state, 6, 2);
+    draw(&mut command_list, pipeline_state, 3, 1);
+    command_list.end_render_pass().unwrap();
+    command_list.dispatch(compute_state, &[], 1, 1, 1).unwrap();
+
+    let lines = RenderResourceHandle::from(pipeline_state);
+    let stats = command_list.get_stats(|handle| {
+        if handle == lines {
+            Some(RenderPrimitiveType::LineList)
+        } else {
+            None
+        }
+    });
+    assert_eq!(stats.command_count(RenderCommandType::Draw), 2);
+    assert_eq!(stats.command_count(RenderCommandType::UpdateBuffer), 1);
+    assert_eq!(stats.command_count(RenderCommandType::CopyBuffer), 0);
+    assert_eq!(stats.command_counts.values().sum::<usize>(), 6);
+    assert_eq!(stats.queue_type, command_list.get_queue_type());
+    assert!(stats.queue_type.contains(RenderCommandQueueType::COMPUTE));
+    assert_eq!(stats.allocator_bytes, 16);
+    assert_eq!(stats.draw_count, 2);
+    assert_eq!(stats.dispatch_count, 1);
+
+    assert_eq!(stats.primitive_count, 3 * 2 + 1);
+    assert_eq!(stats.filter.redundant_draw_states, 1);
+
+    // Unknown pipeline states count triangles
+    let stats = command_list.get_stats(|_| None);
+    assert_eq!(stats.primitive_count, 2 * 2 + 1);
+}
+
+#[test]
+fn statistics_frame() {
+    let mut render_system = common::mock_render_system(RenderDebugFlags::NONE);
+    let device = common::create_render_device(&mut render_system);
+    let device_read = device.read().unwrap();
+    let device_read = device_read.as_ref().unwrap();
+
+    let pipeline_state = render_system
+        .create_graphics_pipeline_state_handle("Lines".into())
+        .unwrap();
+    device_read
+        .create_graphics_pipeline_state(
+            pipeline_state,
+            &RenderGraphicsPipelineStateDesc {
+                primitive_type: RenderPrimitiveType::LineList,
+                ..Default::default()
+            },
+            "Lines".into(),
+        )
+        .unwrap();
+    let command_list_handle = render_system
+        .create_command_list_handle("Statistics Commands".into())
+        .unwrap();
+    device_read
+        .create_command_list(command_list_handle, "Statistics Commands".into())
+        .unwrap();
+
+    let handles = render_system.get_handle_allocator().unwrap();
+    let mut command_list = RenderCommandList::new(handles, 8 * 1024, 16).unwrap();
+    draw(&mut command_list, pipeline_state, 8, 1);
+    device_read
+        .compile_command_list(command_list_handle, &command_list)
+        .unwrap();
+    device_read
+        .compile_command_lists(command_list_handle, &[command_list])
+        .unwrap();
+
+    // Nothing is reported until the frame completes
+    assert_eq!(device_read.get_frame_stats().command_list_count, 0);
+    device_read.advance_frame().unwrap();
+    let stats = device_read.get_frame_stats();
+    assert_eq!(stats.frame_index, 0);
+    assert_eq!(stats.command_list_count, 2);
+    assert_eq!(stats.commands.draw_count, 2);
+    assert_eq!(stats.commands.primitive_count, 2 * 4);
+    assert!(stats
+        .commands
+        .queue_type
+        .contains(RenderCommandQueueType::GRAPHICS));
+
+    device_read.advance_frame().unwrap();
+    let stats = device_read.get_frame_stats();
+    assert_eq!(stats.frame_index, 1);
+    assert_eq!(stats.command_list_count, 0);
+    assert_eq!(stats.commands.draw_count, 0);
+}
@@ -1,16 +1,46 @@
 extern crate render_core;
-//use render_core::backend::*;
+extern crate render_hal_mock;
+use render_core::backend::*;
+use render_core::error::ErrorKind;
+use render_core::modules::*;
 //use render_core::commands::*;
 //use render_core::device::*;
 //use render_core::encoder::*;
 //use render_core::handles::*;
 use render_core::system::*;
-//use render_core::types::*;
+use render_core::types::*;
 //use render_core::utilities::*;
+use render_hal_mock::RenderBackendModuleMock;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
 //use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, RwLock};
 mod common;
 
+/// Registered "mock" module that counts the backends created through it.
+#[derive(Debug)]
+struct CountingModuleMock {
+    inner: RenderBackendModuleMock,
+    created: Arc<AtomicUsize>,
+}
+
+impl RenderBackendModule for CountingModuleMock {
+    fn name(&self) -> &'static str {
+        "Counting Mock"
+    }
+
+    fn api(&self) -> &'static str {
+        self.inner.api()
+    }
+
+    fn create(&self) -> Box<dyn RenderBackend> {
+        self.created.fetch_add(1, Ordering::SeqCst);
+        self.inner.create()
+    }
+}
+
 #[test]
 fn system_initialize() {
     let render_system = Arc::new(RwLock::new(RenderSystem::new()));
@@ -95,3 +125,343 @@ fn system_devices() {
         }
     }
 }
+
+fn mock_settings(api: &str, debug_flags: RenderDebugFlags) -> RenderBackendSettings {
+    RenderBackendSettings {
+        api: api.to_string(),
+        address: None,
+        debug_flags,
+    }
+}
+
+#[test]
+fn system_initialize_static() {
+    let mut render_system = RenderSystem::new();
+    render_system
+        .register_module(Box::new(RenderBackendModuleMock::new()))
+        .unwrap();
+    render_system
+        .initialize_static(&[mock_settings("mock", RenderDebugFlags::NONE)])
+        .unwrap();
+    assert!(render_system.is_initialized());
+
+    // Registered modules survive a release
+    render_system.release().unwrap();
+    assert!(!render_system.is_initialized());
+    render_system
+        .initialize_static(&[mock_settings("mock", RenderDebugFlags::NONE)])
+        .unwrap();
+    assert!(render_system.is_initialized());
+}
+
+#[test]
+fn system_initialize_static_empty() {
+    let mut render_system = RenderSystem::new();
+    assert!(render_system
+        .initialize_static(&[mock_settings("mock", RenderDebugFlags::NONE)])
+        .is_err());
+    assert!(!render_system.is_initialized());
+}
+
+#[test]
+fn system_register_module() {
+    let mut render_system = RenderSystem::new();
+    assert!(render_system
+        .register_module(Box::new(RenderBackendModuleMock::new()))
+        .is_ok());
+    assert!(render_system
+        .register_module(Box::new(RenderBackendModuleMock::new()))
+        .is_err());
+    assert!(render_system.unregister_module("mock").is_ok());
+    assert!(render_system.unregister_module("mock").is_err());
+}
+
+#[test]
+fn system_backend_order() {
+    let mut render_system = RenderSystem::new();
+    render_system
+        .register_module(Box::new(RenderBackendModuleMock::new()))
+        .unwrap();
+    render_system
+        .initialize_static(&[
+            mock_settings("vk", RenderDebugFlags::NONE),
+            mock_settings("", RenderDebugFlags::CPU_VALIDATION),
+            mock_settings("mock", RenderDebugFlags::GPU_VALIDATION),
+        ])
+        .unwrap();
+
+    let registry = render_system.get_registry().unwrap();
+    let registry_read = registry.read().unwrap();
+    assert_eq!(registry_read.len(), 2);
+    assert_eq!(
+        registry_read[0].settings.debug_flags,
+        RenderDebugFlags::CPU_VALIDATION
+    );
+    assert_eq!(
+        registry_read[1].settings.debug_flags,
+        RenderDebugFlags::GPU_VALIDATION
+    );
+}
+
+#[test]
+fn system_static_shadows_dynamic() {
+    // The dynamically built mock module is discovered next to the test executable
+    let libraries = load_backend_modules(&common::get_render_module_path()).unwrap();
+    let dynamic_apis: Vec<&str> = libraries
+        .iter()
+        .map(|library| create_backend_module(library).unwrap().api())
+        .collect();
+    assert!(dynamic_apis.contains(&"mock"));
+
+    let created = Arc::new(AtomicUsize::new(0));
+    let mut render_system = RenderSystem::new();
+    render_system
+        .register_module(Box::new(CountingModuleMock {
+            inner: RenderBackendModuleMock::new(),
+            created: Arc::clone(&created),
+        }))
+        .unwrap();
+    render_system
+        .initialize(
+            &common::get_render_module_path(),
+            &[mock_settings("mock", RenderDebugFlags::NONE)],
+        )
+        .unwrap();
+
+    // Only one backend per api, created by the registered module
+    {
+        let registry = render_system.get_registry().unwrap();
+        let registry_read = registry.read().unwrap();
+        assert_eq!(registry_read.len(), 1);
+    }
+    assert_eq!(created.load(Ordering::SeqCst), 1);
+
+    // Once unregistered, the dynamic module creates the backend instead
+    render_system.release().unwrap();
+    render_system.unregister_module("mock").unwrap();
+    render_system
+        .initialize(
+            &common::get_render_module_path(),
+            &[mock_settings("mock", RenderDebugFlags::NONE)],
+        )
+        .unwrap();
+    {
+        let registry = render_system.get_registry().unwrap();
+        let registry_read = registry.read().unwrap();
+        assert_eq!(registry_read.len(), 1);
+    }
+    assert_eq!(created.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn system_handle_names() {
+    let harness = common::SystemHarness::mock();
+    let rs_read = harness.render_system.read().unwrap();
+
+    let buffer = rs_read
+        .create_buffer_handle("Vertex Buffer".into())
+        .unwrap();
+    let texture = rs_read.create_texture_handle("Albedo".into()).unwrap();
+    assert_eq!(rs_read.get_handle_name(buffer).unwrap(), "Vertex Buffer");
+    assert_eq!(rs_read.get_handle_name(texture).unwrap(), "Albedo");
+
+    rs_read.destroy_handle(buffer).unwrap();
+    assert!(rs_read.get_handle_name(buffer).is_err());
+    assert_eq!(rs_read.get_handle_name(texture).unwrap(), "Albedo");
+    rs_read.destroy_handle(texture).unwrap();
+}
+
+#[test]
+fn system_inventory() {
+    let harness = common::SystemHarness::mock();
+    let rs_read = harness.render_system.read().unwrap();
+
+    let buffer1 = rs_read.create_buffer_handle("Buffer 1".into()).unwrap();
+    let buffer2 = rs_read.create_buffer_handle("Buffer 2".into()).unwrap();
+    let fence = rs_read.create_fence_handle("Fence".into()).unwrap();
+
+    let inventory = rs_read.get_inventory(RenderResourceType::Buffer).unwrap();
+    assert_eq!(inventory.resource_type, RenderResourceType::Buffer);
+    assert_eq!(inventory.count, 2);
+    assert_eq!(inventory.max, 2);
+    assert_eq!(inventory.entries.len(), 2);
+    assert_eq!(inventory.entries[0].handle, buffer1);
+    assert_eq!(inventory.entries[0].name, Some("Buffer 1".into()));
+    assert_eq!(inventory.entries[1].handle, buffer2);
+    assert_eq!(inventory.entries[1].name, Some("Buffer 2".into()));
+
+    let inventories = rs_read.get_inventories().unwrap();
+    assert_eq!(inventories.len(), 2);
+    assert_eq!(inventories[0].resource_type, RenderResourceType::Buffer);
+    assert_eq!(inventories[1].resource_type, RenderResourceType::Fence);
+
+    rs_read.destroy_handle(buffer1).unwrap();
+    let inventory = rs_read.get_inventory(RenderResourceType::Buffer).unwrap();
+    assert_eq!(inventory.count, 1);
+    assert_eq!(inventory.max, 2);
+    assert_eq!(inventory.entries[0].handle, buffer2);
+
+    rs_read.destroy_handle(buffer2).unwrap();
+    rs_read.destroy_handle(fence).unwrap();
+    assert!(rs_read.get_inventories().unwrap().is_empty());
+}
+
+fn leaky_system(policy: RenderLeakPolicy) -> RenderSystem {
+    let mut render_system = common::mock_render_system(RenderDebugFlags::NONE);
+    render_system.set_leak_policy(policy);
+    render_system
+        .create_buffer_handle("Leaky Buffer".into())
+        .unwrap();
+    render_system
+}
+
+#[test]
+fn system_leak_report() {
+    let render_system = leaky_system(RenderLeakPolicy::Ignore);
+    let fence = render_system.create_fence_handle("Fence".into()).unwrap();
+
+    let report = render_system.get_leak_report().unwrap();
+    assert_eq!(report.leaks.len(), 2);
+    assert_eq!(
+        report.leaks[0].handle.get_type(),
+        RenderResourceType::Buffer
+    );
+    assert_eq!(report.leaks[0].name, Some("Leaky Buffer".into()));
+    assert_eq!(report.leaks[1].handle, fence);
+
+    render_system.destroy_handle(fence).unwrap();
+    let report = render_system.get_leak_report().unwrap();
+    assert_eq!(report.leaks.len(), 1);
+    assert!(report.to_string().contains("Leaky Buffer"));
+}
+
+#[test]
+fn system_leak_policy_log() {
+    let mut render_system = leaky_system(RenderLeakPolicy::Log);
+    assert!(render_system.release().is_ok());
+    assert!(!render_system.is_initialized());
+}
+
+#[test]
+fn system_leak_policy_error() {
+    let mut render_system = leaky_system(RenderLeakPolicy::Error);
+    let err = render_system.release().unwrap_err();
+    match err.kind() {
+        ErrorKind::Backend(msg) => assert!(msg.contains("Leaky Buffer")),
+        _ => panic!("unexpected error kind: {:?}", err.kind()),
+    }
+
+    // The system is left intact, so the leaked handles can still be destroyed
+    assert!(render_system.is_initialized());
+    let report = render_system.get_leak_report().unwrap();
+    assert_eq!(report.leaks.len(), 1);
+    render_system
+        .destroy_handle(report.leaks[0].handle)
+        .unwrap();
+    assert!(render_system.release().is_ok());
+    assert!(!render_system.is_initialized());
+}
+
+#[test]
+fn system_leak_policy_reinitialize() {
+    let mut render_system = leaky_system(RenderLeakPolicy::Error);
+    assert!(render_system
+        .initialize_static(&[mock_settings("mock", RenderDebugFlags::NONE)])
+        .is_err());
+    assert!(render_system.is_initialized());
+    assert_eq!(render_system.get_leak_report().unwrap().leaks.len(), 1);
+
+    render_system.set_leak_policy(RenderLeakPolicy::Log);
+    assert!(render_system
+        .initialize_static(&[mock_settings("mock", RenderDebugFlags::NONE)])
+        .is_ok());
+    assert!(render_system.get_leak_report().unwrap().is_empty());
+}
+
+#[test]
+#[should_panic(expected = "leaked 1 render resource handle(s)")]
+fn system_leak_policy_panic() {
+    let mut render_system = leaky_system(RenderLeakPolicy::Panic);
+    render_system.release().unwrap();
+}
+
+#[test]
+fn system_leak_policy_drop() {
+    // Dropping releases the system regardless of the leak policy
+    let render_system = leaky_system(RenderLeakPolicy::Error);
+    let registry = render_system.get_registry().unwrap();
+    drop(render_system);
+    assert!(registry.read().unwrap().is_empty());
+}
+
+#[test]
+#[should_panic(expected = "leaked 1 render resource handle(s)")]
+fn system_leak_policy_drop_panic() {
+    leaky_system(RenderLeakPolicy::Panic);
+}
+
+#[test]
+fn system_no_leaks() {
+    let mut render_system = leaky_system(RenderLeakPolicy::Error);
+    let report = render_system.get_leak_report().unwrap();
+    for leak in &report.leaks {
+        render_system.destroy_handle(leak.handle).unwrap();
+    }
+    assert!(render_system.release().is_ok());
+}
+
+fn capture_system(test_name: &str, debug_flags: RenderDebugFlags) -> (RenderSystem, PathBuf) {
+    let capture_path = env::temp_dir().join(format!("render-core-{}", test_name));
+    let _ = fs::remove_dir_all(&capture_path);
+
+    let render_system = common::static_render_system(
+        Box::new(RenderBackendModuleMock::new().with_capture_path(&capture_path)),
+        &[common::mock_backend_settings(debug_flags)],
+    );
+    (render_system, capture_path)
+}
+
+#[test]
+fn system_debug_capture() {
+    let (render_system, capture_path) =
+        capture_system("debug_capture", RenderDebugFlags::RENDER_DOC);
+
+    render_system.begin_debug_capture("Frame 0").unwrap();
+    assert!(render_system.begin_debug_capture("Frame 1").is_err());
+    render_system.trigger_debug_capture().unwrap();
+    render_system.finish_debug_capture().unwrap();
+    assert!(render_system.finish_debug_capture().is_err());
+
+    let summary = fs::read_to_string(capture_path.join("Frame_0.capture.txt")).unwrap();
+    assert!(summary.contains("name: Frame 0"));
+    assert!(summary.contains("api: mock"));
+    assert!(summary.contains("triggers: 1"));
+
+    render_system.launch_debug_capture(false).unwrap();
+}
+
+#[test]
+fn system_debug_capture_trigger() {
+    let (render_system, capture_path) =
+        capture_system("debug_capture_trigger", RenderDebugFlags::PIX);
+
+    assert!(render_system.launch_debug_capture(false).is_err());
+    render_system.trigger_debug_capture().unwrap();
+    render_system.trigger_debug_capture().unwrap();
+    assert!(capture_path.join("trigger-0.capture.txt").exists());
+    assert!(capture_path.join("trigger-1.capture.txt").exists());
+    render_system.launch_debug_capture(true).unwrap();
+}
+
+#[test]
+fn system_debug_capture_disabled() {
+    let (render_system, capture_path) =
+        capture_system("debug_capture_disabled", RenderDebugFlags::CPU_VALIDATION);
+
+    assert!(render_system.begin_debug_capture("Frame 0").is_err());
+    assert!(render_system.trigger_debug_capture().is_err());
+    assert!(!capture_path.exists());
+
+    let render_system = RenderSystem::new();
+    assert!(render_system.begin_debug_capture("Frame 0").is_err());
+}
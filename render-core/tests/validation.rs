@@ -0,0 +1,394 @@
+extern crate render_core;
+extern crate render_hal_mock;
+use render_core::backend::*;
+use render_core::commands::*;
+use render_core::encoder::RenderCommandList;
+use render_core::error::ErrorKind;
+use render_core::handles::*;
+use render_core::state::*;
+use render_core::system::*;
+use render_core::types::*;
+use render_core::validation::*;
+use std::sync::Arc;
+mod common;
+
+struct Resources {
+    graphics_state: RenderGraphicsPipelineStateHandle,
+    compute_state: RenderComputePipelineStateHandle,
+    buffer: RenderBufferHandle,
+    texture: RenderTextureHandle,
+    render_pass: RenderPassHandle,
+}
+
+fn allocate_resources(handles: &RenderConcurrentHandleAllocator) -> Resources {
+    Resources {
+        graphics_state: handles.allocate_typed().unwrap(),
+        compute_state: handles.allocate_typed().unwrap(),
+        buffer: handles.allocate_typed().unwrap(),
+        texture: handles.allocate_typed().unwrap(),
+        render_pass: handles.allocate_typed().unwrap(),
+    }
+}
+
+fn draw(command_list: &mut RenderCommandList, resources: &Resources, argument_count: usize) {
+    let shader_arguments = vec![
+        RenderShaderArgument {
+            constant_buffer: Some(resources.buffer.into()),
+            shader_views: None,
+            constant_buffer_offset: 0,
+        };
+        argument_count
+    ];
+    command_list
+        .draw(
+            resources.graphics_state,
+            &shader_arguments,
+            None,
+            &RenderDrawState::default(),
+            &RenderDrawPacket {
+                index_offset: 0,
+                vertex_offset: 0,
+                vertex_count: 3,
+                first_instance: 0,
+                instance_count: 1,
+            },
+        )
+        .unwrap();
+}
+
+fn null_handle() -> RenderResourceHandle {
+    RenderResourceHandle::new(0, RenderResourceType::Buffer, 0)
+}
+
+fn kinds(diagnostics: &[RenderValidationDiagnostic]) -> Vec<(usize, RenderValidationKind)> {
+    diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.command_id, diagnostic.kind))
+        .collect()
+}
+
+#[test]
+fn validation_valid() {
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
+    let resources = allocate_resources(&handles);
+    let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
+
+    command_list.begin_event(0, "Frame".into()).unwrap();
+    command_list
+        .update_buffer(resources.buffer, 0, &[0u8; 16])
+        .unwrap();
+    command_list
+        .begin_render_pass(resources.render_pass)
+        .unwrap();
+    command_list.begin_event(0, "Draws".into()).unwrap();
+    draw(&mut command_list, &resources, 1);
+    command_list.end_event().unwrap();
+    command_list.end_render_pass().unwrap();
+    command_list
+        .dispatch(resources.compute_state, &[], 1, 1, 1)
+        .unwrap();
+    command_list.end_event().unwrap();
+
+    let validator = RenderCommandValidator::new();
+    assert!(validator.validate(&command_list).is_empty());
+    assert!(validator.check(&command_list).is_ok());
+}
+
+#[test]
+fn validation_scopes() {
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
+    let resources = allocate_resources(&handles);
+    let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
+
+    command_list.end_event().unwrap();
+    command_list
+        .begin_render_pass(resources.render_pass)
+        .unwrap();
+    command_list.begin_event(0, "Unclosed".into()).unwrap();
+    command_list.end_render_pass().unwrap();
+    command_list
+        .begin_render_pass(resources.render_pass)
+        .unwrap();
+
+    let diagnostics = RenderCommandValidator::new().validate(&command_list);
+    assert_eq!(
+        kinds(&diagnostics),
+        vec![
+            (0, RenderValidationKind::UnmatchedEnd),
+            (3, RenderValidationKind::UnmatchedEnd),
+            (4, RenderValidationKind::NestedRenderPass),
+            (1, RenderValidationKind::UnclosedScope),
+            (2, RenderValidationKind::UnclosedScope),
+            (4, RenderValidationKind::UnclosedScope),
+        ]
+    );
+    assert_eq!(diagnostics[0].command_type, RenderCommandType::EndEvent);
+    assert_eq!(
+        diagnostics[3].command_type,
+        RenderCommandType::BeginRenderPass
+    );
+}
+
+#[test]
+fn validation_render_pass_rules() {
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
+    let resources = allocate_resources(&handles);
+    let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
+
+    draw(&mut command_list, &resources, 0);
+    command_list
+        .begin_render_pass(resources.render_pass)
+        .unwrap();
+    command_list
+        .dispatch(resources.compute_state, &[], 1, 1, 1)
+        .unwrap();
+    command_list
+        .copy_buffer(resources.buffer, 0, 16, resources.buffer, 16)
+        .unwrap();
+    command_list.barriers(&[resources.buffer.into()]).unwrap();
+    command_list.end_render_pass().unwrap();
+
+    let diagnostics = RenderCommandValidator::new().validate(&command_list);
+    assert_eq!(
+        kinds(&diagnostics),
+        vec![
+            (0, RenderValidationKind::DrawOutsideRenderPass),
+            (2, RenderValidationKind::InvalidInRenderPass),
+            (3, RenderValidationKind::InvalidInRenderPass),
+        ]
+    );
+    assert!(diagnostics[1]
+        .to_string()
+        .starts_with("command 2 (Dispatch)"));
+}
+
+#[test]
+fn validation_resource_types() {
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
+    let resources = allocate_resources(&handles);
+    let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
+
+    // Entries bypass the typed encoder methods
+    command_list
+        .record_entry(
+            RenderCommandDispatch::new(resources.graphics_state.into(), &[], 1, 1, 1).into(),
+            None,
+        )
+        .unwrap();
+    command_list
+        .record_entry(
+            RenderCommandDispatch::new(
+                resources.compute_state.into(),
+                &[RenderShaderArgument {
+                    constant_buffer: Some(resources.texture.into()),
+                    shader_views: Some(null_handle()),
+                    constant_buffer_offset: 0,
+                }],
+                1,
+                1,
+                1,
+            )
+            .into(),
+            None,
+        )
+        .unwrap();
+    command_list
+        .record_entry(
+            RenderCommandCopyBuffer::new(resources.buffer.into(), 0, 16, null_handle(), 0).into(),
+            None,
+        )
+        .unwrap();
+
+    let diagnostics = RenderCommandValidator::new().validate(&command_list);
+    assert_eq!(
+        kinds(&diagnostics),
+        vec![
+            (0, RenderValidationKind::ResourceType),
+            (1, RenderValidationKind::ResourceType),
+            (2, RenderValidationKind::ResourceType),
+        ]
+    );
+    assert!(diagnostics[0].message.contains("GraphicsPipelineState"));
+    assert!(diagnostics[1].message.contains("constant buffer"));
+    assert!(diagnostics[2].message.contains("null"));
+}
+
+#[test]
+fn validation_shader_argument_count() {
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
+    let resources = allocate_resources(&handles);
+    let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
+
+    command_list
+        .begin_render_pass(resources.render_pass)
+        .unwrap();
+    draw(&mut command_list, &resources, 1);
+    draw(&mut command_list, &resources, 2);
+    command_list.end_render_pass().unwrap();
+
+    // Pipelines without a registered signature aren't checked
+    let mut validator = RenderCommandValidator::new();
+    assert!(validator.validate(&command_list).is_empty());
+
+    let signature = RenderShaderSignatureDesc {
+        parameter_count: 2,
+        ..Default::default()
+    };
+    validator.register_pipeline(resources.graphics_state, &signature);
+    assert_eq!(
+        kinds(&validator.validate(&command_list)),
+        vec![(1, RenderValidationKind::ShaderArgumentCount)]
+    );
+
+    // Arguments left out as already bound aren't counted, unlike an empty argument list
+    command_list.reset().unwrap();
+    command_list
+        .begin_render_pass(resources.render_pass)
+        .unwrap();
+    draw(&mut command_list, &resources, 2);
+    draw(&mut command_list, &resources, 2);
+    draw(&mut command_list, &resources, 0);
+    command_list.end_render_pass().unwrap();
+    let filtered = command_list.get_commands()[2]
+        .downcast_ref::<RenderCommandDraw>()
+        .unwrap();
+    assert!(filtered.shader_arguments.is_none());
+    assert_eq!(
+        kinds(&validator.validate(&command_list)),
+        vec![(3, RenderValidationKind::ShaderArgumentCount)]
+    );
+
+    // Arguments are recorded again after commands that may change resource states
+    command_list.reset().unwrap();
+    command_list
+        .begin_render_pass(resources.render_pass)
+        .unwrap();
+    draw(&mut command_list, &resources, 2);
+    command_list.end_render_pass().unwrap();
+    let shader_arguments = [RenderShaderArgument {
+        constant_buffer: Some(resources.buffer.into()),
+        shader_views: None,
+        constant_buffer_offset: 0,
+    }];
+    command_list
+        .dispatch(resources.compute_state, &shader_arguments, 1, 1, 1)
+        .unwrap();
+    command_list
+        .begin_render_pass(resources.render_pass)
+        .unwrap();
+    draw(&mut command_list, &resources, 2);
+    command_list.end_render_pass().unwrap();
+    let draw = command_list.get_commands()[5]
+        .downcast_ref::<RenderCommandDraw>()
+        .unwrap();
+    assert_eq!(draw.shader_arguments.as_ref().map(Vec::len), Some(2));
+    assert!(validator.validate(&command_list).is_empty());
+
+    // Arguments can't be left out once bindings are invalidated
+    let mut commands = command_list.get_commands().to_vec();
+    if let RenderCommandEntry::Draw(ref mut draw) = commands[5] {
+        draw.shader_arguments = None;
+    }
+    assert_eq!(
+        kinds(&validator.validate_commands(&commands)),
+        vec![(5, RenderValidationKind::ShaderArgumentCount)]
+    );
+
+    validator.unregister_pipeline(resources.graphics_state);
+    assert!(validator.validate(&command_list).is_empty());
+}
+
+#[test]
+fn validation_unbound_draw_state() {
+    let handles = Arc::new(RenderConcurrentHandleAllocator::new());
+    let resources = allocate_resources(&handles);
+    let mut command_list = RenderCommandList::new(Arc::clone(&handles), 8 * 1024, 16).unwrap();
+
+    command_list
+        .begin_render_pass(resources.render_pass)
+        .unwrap();
+    draw(&mut command_list, &resources, 0);
+    draw(&mut command_list, &resources, 0);
+    command_list.end_render_pass().unwrap();
+    command_list
+        .begin_render_pass(resources.render_pass)
+        .unwrap();
+    draw(&mut command_list, &resources, 0);
+    command_list.end_render_pass().unwrap();
+
+    let validator = RenderCommandValidator::new();
+    let filtered = command_list.get_commands()[2]
+        .downcast_ref::<RenderCommandDraw>()
+        .unwrap();
+    assert!(filtered.draw_state.is_none());
+    assert!(validator.validate(&command_list).is_empty());
+
+    // Draw states can't be left out on the first draw, or once bindings are invalidated;
+    // the filtered draw 2 after it is not reported again for the same loss
+    let mut commands = command_list.get_commands().to_vec();
+    for &index in &[1, 5] {
+        if let RenderCommandEntry::Draw(ref mut draw) = commands[index] {
+            draw.draw_state = None;
+        }
+    }
+    assert_eq!(
+        kinds(&validator.validate_commands(&commands)),
+        vec![
+            (1, RenderValidationKind::UnboundDrawState),
+            (5, RenderValidationKind::UnboundDrawState)
+        ]
+    );
+}
+
+/// Compiles a dispatch without arguments for a pipeline whose signature expects one.
+fn compile_mismatched_dispatch(render_system: &mut RenderSystem) -> render_core::Result<()> {
+    let device = common::create_render_device(render_system);
+    let device_read = device.read().unwrap();
+    let device_read = device_read.as_ref().unwrap();
+
+    let shader = render_system
+        .create_shader_handle("Validated Shader".into())
+        .unwrap();
+    let pipeline_state = render_system
+        .create_compute_pipeline_state_handle("Validated Pipeline".into())
+        .unwrap();
+    device_read
+        .create_compute_pipeline_state(
+            pipeline_state,
+            &RenderComputePipelineStateDesc {
+                shader: shader.into(),
+                shader_signature: RenderShaderSignatureDesc {
+                    parameter_count: 1,
+                    ..Default::default()
+                },
+            },
+            "Validated Pipeline".into(),
+        )
+        .unwrap();
+
+    let command_list_handle = render_system
+        .create_command_list_handle("Validated Commands".into())
+        .unwrap();
+    device_read
+        .create_command_list(command_list_handle, "Validated Commands".into())
+        .unwrap();
+
+    let handles = render_system.get_handle_allocator().unwrap();
+    let mut command_list = RenderCommandList::new(handles, 8 * 1024, 16).unwrap();
+    command_list.dispatch(pipeline_state, &[], 1, 1, 1).unwrap();
+    device_read.compile_command_list(command_list_handle, &command_list)
+}
+
+#[test]
+fn validation_cpu_validation_flag() {
+    let mut render_system = common::mock_render_system(RenderDebugFlags::CPU_VALIDATION);
+    let err = compile_mismatched_dispatch(&mut render_system).unwrap_err();
+    match err.kind() {
+        ErrorKind::Encoder(msg) => assert!(msg.contains("command 0 (Dispatch)")),
+        kind => panic!("unexpected error kind {:?}", kind),
+    }
+
+    // Lists are passed to the backend unchecked without the flag
+    let mut render_system = common::mock_render_system(RenderDebugFlags::NONE);
+    assert!(compile_mismatched_dispatch(&mut render_system).is_ok());
+}
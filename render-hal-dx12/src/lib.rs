@@ -26,7 +26,4 @@ mod backend;
 mod module;
 
 #[cfg(windows)]
-#[no_mangle]
-pub extern "C" fn render_backend_factory() -> Box<dyn RenderBackendModule> {
-    Box::new(RenderBackendModuleDx12::new())
-}
+render_core::render_backend_module!("dx12", RenderBackendModuleDx12::new());
@@ -16,24 +16,85 @@ use render_core::error::{Error, Result};
 use std::collections::HashMap;
 use std::ffi::{CStr, CString};
 use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::ptr;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
+
+#[derive(Debug)]
+struct RenderCaptureMock {
+    name: String,
+    started: Instant,
+    triggers: u32,
+}
 
 #[derive(Debug)]
 pub(crate) struct RenderBackendMock {
     device_info: Vec<RenderDeviceInfo>,
     device_map: HashMap<u32, u32>,
     devices: Vec<Arc<RwLock<Option<Box<dyn RenderDevice>>>>>,
+    capture_path: PathBuf,
+    capture: Mutex<Option<RenderCaptureMock>>,
+    capture_count: Mutex<u32>,
 }
 
 impl RenderBackendMock {
-    pub fn new() -> Result<Self> {
+    pub fn new(capture_path: &Path) -> Result<Self> {
         Ok(RenderBackendMock {
             device_info: Vec::new(),
             device_map: HashMap::new(),
             devices: Vec::new(),
+            capture_path: capture_path.to_path_buf(),
+            capture: Mutex::new(None),
+            capture_count: Mutex::new(0),
         })
     }
+
+    fn write_capture_summary(&self, capture: &RenderCaptureMock) -> Result<PathBuf> {
+        let file_name: String = capture
+            .name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let path = self.capture_path.join(format!("{}.capture.txt", file_name));
+
+        let devices: Vec<String> = self
+            .devices
+            .iter()
+            .enumerate()
+            .filter(|(_, device)| device.read().unwrap().is_some())
+            .map(|(index, _)| self.device_info[index].name.clone())
+            .collect();
+
+        let mut summary = String::new();
+        summary.push_str(&format!("name: {}\n", capture.name));
+        summary.push_str("api: mock\n");
+        summary.push_str(&format!("devices: {}\n", devices.join(", ")));
+        summary.push_str(&format!("triggers: {}\n", capture.triggers));
+        summary.push_str(&format!(
+            "duration_ms: {}\n",
+            capture.started.elapsed().as_millis()
+        ));
+
+        fs::create_dir_all(&self.capture_path)
+            .and_then(|_| fs::File::create(&path))
+            .and_then(|mut file| file.write_all(summary.as_bytes()))
+            .map_err(|err| {
+                Error::backend(format!(
+                    "failed to write capture summary {:?} - {}",
+                    path, err
+                ))
+            })?;
+        Ok(path)
+    }
 }
 
 impl Drop for RenderBackendMock {
@@ -126,18 +187,59 @@ impl RenderBackend for RenderBackendMock {
     }
 
     fn begin_debug_capture(&self, name: &str) -> Result<()> {
-        unimplemented!()
+        let mut capture = self.capture.lock().unwrap();
+        if let Some(ref active) = *capture {
+            Err(Error::backend(format!(
+                "debug capture {:?} is already in progress",
+                active.name
+            )))
+        } else {
+            *capture = Some(RenderCaptureMock {
+                name: name.to_string(),
+                started: Instant::now(),
+                triggers: 0,
+            });
+            Ok(())
+        }
     }
 
     fn finish_debug_capture(&self) -> Result<()> {
-        unimplemented!()
+        let capture = self.capture.lock().unwrap().take();
+        match capture {
+            Some(capture) => {
+                self.write_capture_summary(&capture)?;
+                *self.capture_count.lock().unwrap() += 1;
+                Ok(())
+            }
+            None => Err(Error::backend("no debug capture is in progress")),
+        }
     }
 
     fn trigger_debug_capture(&self) -> Result<()> {
-        unimplemented!()
+        // A trigger outside of an explicit capture records a single frame capture
+        let mut capture = self.capture.lock().unwrap();
+        if let Some(ref mut active) = *capture {
+            active.triggers += 1;
+            Ok(())
+        } else {
+            let mut capture_count = self.capture_count.lock().unwrap();
+            self.write_capture_summary(&RenderCaptureMock {
+                name: format!("trigger-{}", *capture_count),
+                started: Instant::now(),
+                triggers: 1,
+            })?;
+            *capture_count += 1;
+            Ok(())
+        }
     }
 
     fn launch_debug_capture(&self, quit: bool) -> Result<()> {
-        unimplemented!()
+        // There is no capture UI to launch for the mock backend
+        let capture_count = *self.capture_count.lock().unwrap();
+        if capture_count == 0 {
+            Err(Error::backend("no debug captures have been recorded"))
+        } else {
+            Ok(())
+        }
     }
 }
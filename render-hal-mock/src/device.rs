@@ -3,12 +3,14 @@
 
 use crate::backend::RenderBackendMock;
 use crate::types::*;
+use render_core::deferred::*;
 use render_core::device::*;
 use render_core::encoder::*;
 use render_core::error::{Error, Result};
-use render_core::handles::RenderResourceHandle;
+use render_core::handles::*;
 use render_core::resources::{RenderResourceBase, RenderResourceStorage};
 use render_core::state::*;
+use render_core::statistics::*;
 use render_core::types::*;
 use std::{
     borrow::Cow,
@@ -18,14 +20,21 @@ use std::{
     mem,
     mem::align_of,
     ptr,
+    sync::atomic::{AtomicU64, Ordering},
     sync::{Arc, RwLock},
     u32,
 };
 
+/// Frames the mock pretends the GPU runs behind the CPU
+pub const MOCK_FRAMES_IN_FLIGHT: u64 = 2;
+
 #[derive(Debug)]
 pub struct RenderDeviceMock {
     device_info: RenderDeviceInfo,
     storage: RenderResourceStorage<Box<dyn RenderResourceBase>>,
+    frame_index: AtomicU64,
+    destroy_queue: RenderDestroyQueue,
+    frame_stats: RenderFrameStatsCollector,
 }
 
 impl RenderDeviceMock {
@@ -33,6 +42,9 @@ impl RenderDeviceMock {
         Ok(RenderDeviceMock {
             device_info,
             storage: RenderResourceStorage::new(),
+            frame_index: AtomicU64::new(0),
+            destroy_queue: RenderDestroyQueue::new(MOCK_FRAMES_IN_FLIGHT),
+            frame_stats: RenderFrameStatsCollector::new(),
         })
     }
 
@@ -41,6 +53,14 @@ impl RenderDeviceMock {
     }
 }
 
+impl Drop for RenderDeviceMock {
+    fn drop(&mut self) {
+        if let Err(err) = self.destroy_queue.flush_all(self) {
+            error!("failed to destroy deferred resources on drop: {}", err);
+        }
+    }
+}
+
 impl RenderDevice for RenderDeviceMock {
     fn valid_resource(&self, handle: RenderResourceHandle) -> bool {
         self.storage.valid(handle)
@@ -49,7 +69,7 @@ impl RenderDevice for RenderDeviceMock {
     fn destroy_resource(&self, handle: RenderResourceHandle) -> Result<()> {
         let resource_lock = self.storage.remove(handle)?;
         let resource = resource_lock.write().unwrap();
-        println!(
+        trace!(
             "Destroying resource - name: {}, handle: {:?}",
             resource.get_name(),
             handle
@@ -78,6 +98,7 @@ impl RenderDevice for RenderDeviceMock {
                 let mut _resource = resource
                     .downcast_ref::<RenderGraphicsPipelineStateMock>()
                     .unwrap();
+                self.frame_stats.unregister_pipeline(handle);
             }
             RenderResourceType::ComputePipelineState => {
                 let mut _resource = resource
@@ -133,16 +154,36 @@ impl RenderDevice for RenderDeviceMock {
         Ok(())
     }
 
+    fn destroy_resource_deferred(
+        &self,
+        handle: RenderResourceHandle,
+        on_destroyed: Option<RenderDestroyedFn>,
+    ) -> Result<()> {
+        if !self.storage.valid(handle) {
+            if let Some(on_destroyed) = on_destroyed {
+                on_destroyed(handle);
+            }
+            return Err(Error::backend(format!("resource not found: {:?}", handle)));
+        }
+        self.destroy_queue
+            .push(self.get_frame_index(), handle, on_destroyed);
+        Ok(())
+    }
+
     // Resource Management
     fn create_swap_chain(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderSwapChainHandle,
         desc: &RenderSwapChainDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
-        println!(
+        trace!(
             "Creating swap chain: name:{}, format:{:?}, width:{}, height:{}, buffers:{}",
-            debug_name, desc.format, desc.width, desc.height, desc.buffer_count
+            debug_name,
+            desc.format,
+            desc.width,
+            desc.height,
+            desc.buffer_count
         );
 
         let tex_desc = RenderTextureDesc {
@@ -177,12 +218,12 @@ impl RenderDevice for RenderDeviceMock {
 
     fn create_buffer(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderBufferHandle,
         desc: &RenderBufferDesc,
         initial_data: Option<&[u8]>,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
-        println!("Creating buffer: {}, {:?}", debug_name, desc);
+        trace!("Creating buffer: {}, {:?}", debug_name, desc);
 
         let data_size: u64 = match initial_data {
             Some(data) => mem::size_of_val(&data) as u64,
@@ -203,12 +244,12 @@ impl RenderDevice for RenderDeviceMock {
 
     fn create_texture(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderTextureHandle,
         desc: &RenderTextureDesc,
         initial_data: Option<RenderTextureSubResourceData>,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
-        println!("Creating texture: {}, {:?}", debug_name, desc);
+        trace!("Creating texture: {}, {:?}", debug_name, desc);
 
         let resource: Arc<RwLock<Box<dyn RenderResourceBase>>> =
             Arc::new(RwLock::new(Box::new(RenderTextureMock {
@@ -222,11 +263,11 @@ impl RenderDevice for RenderDeviceMock {
 
     fn create_sampler_state(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderSamplerStateHandle,
         desc: &RenderSamplerState,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
-        println!("Creating sampler: {}, {:?}", debug_name, desc);
+        trace!("Creating sampler: {}, {:?}", debug_name, desc);
 
         let resource: Arc<RwLock<Box<dyn RenderResourceBase>>> =
             Arc::new(RwLock::new(Box::new(RenderSamplerStateMock {
@@ -240,11 +281,11 @@ impl RenderDevice for RenderDeviceMock {
 
     fn create_shader(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderShaderHandle,
         desc: &RenderShaderDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
-        println!("Creating shader: {}, {:?}", debug_name, desc.shader_type);
+        trace!("Creating shader: {}, {:?}", debug_name, desc.shader_type);
 
         let resource: Arc<RwLock<Box<dyn RenderResourceBase>>> =
             Arc::new(RwLock::new(Box::new(RenderShaderMock {
@@ -257,11 +298,11 @@ impl RenderDevice for RenderDeviceMock {
 
     fn create_shader_views(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderShaderViewsHandle,
         desc: &RenderShaderViewsDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
-        println!("Creating shader views: {}, {:?}", debug_name, desc);
+        trace!("Creating shader views: {}, {:?}", debug_name, desc);
 
         let resource: Arc<RwLock<Box<dyn RenderResourceBase>>> =
             Arc::new(RwLock::new(Box::new(RenderShaderViewsMock {
@@ -276,11 +317,11 @@ impl RenderDevice for RenderDeviceMock {
     // Ray tracing features are only supported on some devices
     fn create_ray_tracing_program(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderRayTracingProgramHandle,
         desc: &RayTracingProgramDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
-        println!("Creating ray tracing program: {}, {:?}", debug_name, desc);
+        trace!("Creating ray tracing program: {}, {:?}", debug_name, desc);
 
         let resource: Arc<RwLock<Box<dyn RenderResourceBase>>> =
             Arc::new(RwLock::new(Box::new(RenderRayTracingProgramMock {
@@ -294,11 +335,11 @@ impl RenderDevice for RenderDeviceMock {
 
     fn create_ray_tracing_geometry(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderRayTracingGeometryHandle,
         desc: &RayTracingGeometryDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
-        println!("Creating ray tracing geometry: {}, {:?}", debug_name, desc);
+        trace!("Creating ray tracing geometry: {}, {:?}", debug_name, desc);
 
         let resource: Arc<RwLock<Box<dyn RenderResourceBase>>> =
             Arc::new(RwLock::new(Box::new(RenderRayTracingGeometryMock {
@@ -312,13 +353,14 @@ impl RenderDevice for RenderDeviceMock {
 
     fn create_ray_tracing_top_acceleration(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderRayTracingAccelerationHandle,
         desc: &RayTracingTopAccelerationDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
-        println!(
+        trace!(
             "Creating ray tracing top acceleration: {}, {:?}",
-            debug_name, desc
+            debug_name,
+            desc
         );
 
         let resource: Arc<RwLock<Box<dyn RenderResourceBase>>> =
@@ -333,13 +375,14 @@ impl RenderDevice for RenderDeviceMock {
 
     fn create_ray_tracing_bottom_acceleration(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderRayTracingAccelerationHandle,
         desc: &RayTracingBottomAccelerationDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
-        println!(
+        trace!(
             "Creating ray tracing bottom acceleration: {}, {:?}",
-            debug_name, desc
+            debug_name,
+            desc
         );
 
         let resource: Arc<RwLock<Box<dyn RenderResourceBase>>> =
@@ -354,13 +397,14 @@ impl RenderDevice for RenderDeviceMock {
 
     fn create_ray_tracing_pipeline_state(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderRayTracingPipelineStateHandle,
         desc: &RayTracingPipelineStateDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
-        println!(
+        trace!(
             "Creating ray tracing pipeline state: {}, {:?}",
-            debug_name, desc
+            debug_name,
+            desc
         );
 
         let resource: Arc<RwLock<Box<dyn RenderResourceBase>>> =
@@ -375,13 +419,14 @@ impl RenderDevice for RenderDeviceMock {
 
     fn create_ray_tracing_shader_table(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderRayTracingShaderTableHandle,
         desc: &RayTracingShaderTableDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
-        println!(
+        trace!(
             "Creating ray tracing shader table: {}, {:?}",
-            debug_name, desc
+            debug_name,
+            desc
         );
 
         let resource: Arc<RwLock<Box<dyn RenderResourceBase>>> =
@@ -396,13 +441,14 @@ impl RenderDevice for RenderDeviceMock {
 
     fn create_graphics_pipeline_state(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderGraphicsPipelineStateHandle,
         desc: &RenderGraphicsPipelineStateDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
-        println!(
+        trace!(
             "Creating graphics pipeline state: {}, {:?}",
-            debug_name, desc
+            debug_name,
+            desc
         );
 
         let resource: Arc<RwLock<Box<dyn RenderResourceBase>>> =
@@ -412,18 +458,20 @@ impl RenderDevice for RenderDeviceMock {
             })));
 
         self.storage.put(handle, resource)?;
+        self.frame_stats.register_pipeline(handle.into(), desc);
         Ok(())
     }
 
     fn create_compute_pipeline_state(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderComputePipelineStateHandle,
         desc: &RenderComputePipelineStateDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
-        println!(
+        trace!(
             "Creating compute pipeline state: {}, {:?}",
-            debug_name, desc
+            debug_name,
+            desc
         );
 
         let resource: Arc<RwLock<Box<dyn RenderResourceBase>>> =
@@ -438,11 +486,11 @@ impl RenderDevice for RenderDeviceMock {
 
     fn create_draw_binding_set(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderDrawBindingSetHandle,
         desc: &RenderDrawBindingSetDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
-        println!("Creating draw binding set: {}, {:?}", debug_name, desc);
+        trace!("Creating draw binding set: {}, {:?}", debug_name, desc);
 
         let resource: Arc<RwLock<Box<dyn RenderResourceBase>>> =
             Arc::new(RwLock::new(Box::new(RenderDrawBindingSetMock {
@@ -456,11 +504,11 @@ impl RenderDevice for RenderDeviceMock {
 
     fn create_frame_binding_set(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderFrameBindingSetHandle,
         desc: &RenderFrameBindingSetDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
-        println!("Creating frame binding set: {}, {:?}", debug_name, desc);
+        trace!("Creating frame binding set: {}, {:?}", debug_name, desc);
 
         let resource: Arc<RwLock<Box<dyn RenderResourceBase>>> =
             Arc::new(RwLock::new(Box::new(RenderFrameBindingSetMock {
@@ -474,11 +522,11 @@ impl RenderDevice for RenderDeviceMock {
 
     fn create_render_pass(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderPassHandle,
         desc: &RenderPassDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
-        println!("Creating render pass: {}, {:?}", debug_name, desc);
+        trace!("Creating render pass: {}, {:?}", debug_name, desc);
 
         let resource: Arc<RwLock<Box<dyn RenderResourceBase>>> =
             Arc::new(RwLock::new(Box::new(RenderPassMock {
@@ -492,10 +540,10 @@ impl RenderDevice for RenderDeviceMock {
 
     fn create_command_list(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderCommandListHandle,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
-        println!("Creating command list: {}", debug_name);
+        trace!("Creating command list: {}", debug_name);
 
         let resource: Arc<RwLock<Box<dyn RenderResourceBase>>> =
             Arc::new(RwLock::new(Box::new(RenderCommandListMock {
@@ -511,11 +559,11 @@ impl RenderDevice for RenderDeviceMock {
 
     fn create_fence(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderFenceHandle,
         desc: &RenderFenceDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
-        println!("Creating fence: {}, {:?}", debug_name, desc);
+        trace!("Creating fence: {}, {:?}", debug_name, desc);
 
         let resource: Arc<RwLock<Box<dyn RenderResourceBase>>> =
             Arc::new(RwLock::new(Box::new(RenderFenceMock {
@@ -528,11 +576,11 @@ impl RenderDevice for RenderDeviceMock {
 
     fn create_timing_heap(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderTimingHeapHandle,
         desc: &RenderTimingHeapDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
-        println!("Creating timing heap: {}, {:?}", debug_name, desc);
+        trace!("Creating timing heap: {}, {:?}", debug_name, desc);
 
         let resource: Arc<RwLock<Box<dyn RenderResourceBase>>> =
             Arc::new(RwLock::new(Box::new(RenderTimingHeapMock {
@@ -553,10 +601,10 @@ impl RenderDevice for RenderDeviceMock {
     // CommandList Management
     fn submit_command_list(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderCommandListHandle,
         flush: bool,
-        wait_before: Option<&[RenderResourceHandle]>,
-        signal_after: Option<RenderResourceHandle>,
+        wait_before: Option<&[RenderFenceHandle]>,
+        signal_after: Option<RenderFenceHandle>,
     ) -> Result<()> {
         assert_eq!(handle.get_type(), RenderResourceType::CommandList);
         Ok(())
@@ -564,26 +612,30 @@ impl RenderDevice for RenderDeviceMock {
 
     fn compile_command_list(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderCommandListHandle,
         command_list: &RenderCommandList,
     ) -> Result<()> {
         assert_eq!(handle.get_type(), RenderResourceType::CommandList);
+        self.frame_stats.record(command_list);
         Ok(())
     }
 
     fn compile_command_lists(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderCommandListHandle,
         command_lists: &[RenderCommandList],
     ) -> Result<()> {
+        for command_list in command_lists {
+            self.frame_stats.record(command_list);
+        }
         Ok(())
     }
 
     // Present Management
     fn present_swap_chain(
         &mut self,
-        swap_chain: RenderResourceHandle,
-        source_texture: RenderResourceHandle,
+        swap_chain: RenderSwapChainHandle,
+        source_texture: RenderTextureHandle,
     ) -> Result<()> {
         //println!("Presenting swap chain - {:?}", swap_chain);
         Ok(())
@@ -591,7 +643,7 @@ impl RenderDevice for RenderDeviceMock {
 
     fn resize_swap_chain(
         &self,
-        swap_chain: RenderResourceHandle,
+        swap_chain: RenderSwapChainHandle,
         width: u32,
         height: u32,
     ) -> Result<()> {
@@ -603,8 +655,18 @@ impl RenderDevice for RenderDeviceMock {
     }
 
     fn advance_frame(&self) -> Result<()> {
-        //println!("Advancing device frame");
-        Ok(())
+        let frame_index = self.frame_index.fetch_add(1, Ordering::AcqRel) + 1;
+        trace!("Advancing device frame - index: {}", frame_index);
+        self.frame_stats.advance(frame_index);
+        self.destroy_queue.flush(self, frame_index)
+    }
+
+    fn get_frame_index(&self) -> u64 {
+        self.frame_index.load(Ordering::Acquire)
+    }
+
+    fn get_frame_stats(&self) -> RenderFrameStats {
+        self.frame_stats.completed()
     }
 
     // Cross-Node Transfer [Prototype]
@@ -612,25 +674,25 @@ impl RenderDevice for RenderDeviceMock {
         &self,
         wait_value: u64,
         signal_value: u64,
-        fence: RenderResourceHandle,
+        fence: RenderFenceHandle,
         command_list: &RenderCommandList,
     ) -> Result<()> {
         unimplemented!()
     }
 
-    fn device_graphics_signal(&self, signal_value: u64, fence: RenderResourceHandle) -> Result<()> {
+    fn device_graphics_signal(&self, signal_value: u64, fence: RenderFenceHandle) -> Result<()> {
         unimplemented!()
     }
 
-    fn device_graphics_wait(&self, wait_value: u64, fence: RenderResourceHandle) -> Result<()> {
+    fn device_graphics_wait(&self, wait_value: u64, fence: RenderFenceHandle) -> Result<()> {
         unimplemented!()
     }
 
-    fn device_copy_signal(&self, signal_value: u64, fence: RenderResourceHandle) -> Result<()> {
+    fn device_copy_signal(&self, signal_value: u64, fence: RenderFenceHandle) -> Result<()> {
         unimplemented!()
     }
 
-    fn device_copy_wait(&self, wait_value: u64, fence: RenderResourceHandle) -> Result<()> {
+    fn device_copy_wait(&self, wait_value: u64, fence: RenderFenceHandle) -> Result<()> {
         unimplemented!()
     }
 
@@ -13,7 +13,6 @@ extern crate render_core;
 #[macro_use]
 extern crate downcast_rs;
 
-use crate::module::RenderBackendModuleMock;
 use render_core::backend::{RenderBackend, RenderBackendModule};
 
 mod backend;
@@ -21,7 +20,6 @@ mod device;
 mod module;
 mod types;
 
-#[no_mangle]
-pub extern "C" fn render_backend_factory() -> Box<dyn RenderBackendModule> {
-    Box::new(RenderBackendModuleMock::new())
-}
+pub use crate::module::RenderBackendModuleMock;
+
+render_core::render_backend_module!("mock", RenderBackendModuleMock::new());
@@ -1,12 +1,34 @@
 use crate::backend::RenderBackendMock;
 use render_core::backend::{RenderBackend, RenderBackendModule};
+use std::env;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug)]
-pub(crate) struct RenderBackendModuleMock {}
+pub struct RenderBackendModuleMock {
+    capture_path: PathBuf,
+}
 
 impl RenderBackendModuleMock {
     pub fn new() -> Self {
-        RenderBackendModuleMock {}
+        RenderBackendModuleMock {
+            capture_path: env::temp_dir(),
+        }
+    }
+
+    /// Sets the directory debug capture summaries are written to (defaults to the temp dir).
+    pub fn with_capture_path<P: AsRef<Path>>(mut self, capture_path: P) -> Self {
+        self.capture_path = capture_path.as_ref().to_path_buf();
+        self
+    }
+
+    pub fn capture_path(&self) -> &Path {
+        &self.capture_path
+    }
+}
+
+impl Default for RenderBackendModuleMock {
+    fn default() -> Self {
+        RenderBackendModuleMock::new()
     }
 }
 
@@ -24,6 +46,6 @@ impl RenderBackendModule for RenderBackendModuleMock {
     }
 
     fn create(&self) -> Box<dyn RenderBackend> {
-        Box::new(RenderBackendMock::new().unwrap())
+        Box::new(RenderBackendMock::new(&self.capture_path).unwrap())
     }
 }
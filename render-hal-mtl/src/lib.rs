@@ -19,7 +19,4 @@ mod backend;
 mod module;
 
 #[cfg(target_os = "macos")]
-#[no_mangle]
-pub extern "C" fn render_backend_factory() -> Box<RenderBackendModule> {
-    Box::new(RenderBackendModuleMtl::new())
-}
+render_core::render_backend_module!("mtl", RenderBackendModuleMtl::new());
@@ -0,0 +1,147 @@
+use crate::connection::{unexpected_response, ProxyConnection, ProxyConnectionRef};
+use crate::device::RenderDeviceProxy;
+use crate::protocol::*;
+use crate::transport::ProxyAddress;
+use render_core::backend::RenderBackend;
+use render_core::device::{
+    new_device_entry, RenderDevice, RenderDeviceEntry, RenderDeviceId, RenderDeviceInfo,
+};
+use render_core::error::{Error, Result};
+use std::sync::{Arc, Mutex};
+
+/// Backend that forwards every call to a `RenderProxyServer`.
+///
+/// The connection is made lazily on first use, so a misconfigured address only fails
+/// once devices are enumerated.
+#[derive(Debug)]
+pub(crate) struct RenderBackendProxy {
+    address: Result<ProxyAddress>,
+    connection: Option<ProxyConnectionRef>,
+    device_info: Vec<RenderDeviceInfo>,
+    devices: Vec<RenderDeviceEntry>,
+}
+
+impl RenderBackendProxy {
+    pub fn new(address: &str) -> Self {
+        RenderBackendProxy {
+            address: ProxyAddress::parse(address),
+            connection: None,
+            device_info: Vec::new(),
+            devices: Vec::new(),
+        }
+    }
+
+    fn connection(&mut self) -> Result<ProxyConnectionRef> {
+        if let Some(ref connection) = self.connection {
+            return Ok(Arc::clone(connection));
+        }
+
+        let address = match self.address {
+            Ok(ref address) => address,
+            Err(ref err) => return Err(Error::from(err.kind().clone())),
+        };
+        let connection = ProxyConnection::connect(address)?;
+        info!("Connected to {} proxy at {}", connection.api(), address);
+        let connection = Arc::new(Mutex::new(connection));
+        self.connection = Some(Arc::clone(&connection));
+        Ok(connection)
+    }
+
+    fn call_ok(&self, request: ProxyRequest) -> Result<()> {
+        match self.connection {
+            Some(ref connection) => connection.lock().unwrap().call_ok(request),
+            None => Err(Error::backend("proxy backend is not connected")),
+        }
+    }
+
+    fn device_index(&self, device_id: RenderDeviceId) -> Result<usize> {
+        let device_index = device_id as usize;
+        if device_index >= self.devices.len() {
+            Err(Error::backend(format!(
+                "no device found for id {}",
+                device_index
+            )))
+        } else {
+            Ok(device_index)
+        }
+    }
+}
+
+impl RenderBackend for RenderBackendProxy {
+    fn is_initialized(&self) -> bool {
+        self.address.is_ok()
+    }
+
+    fn enumerate_devices(
+        &mut self,
+        max_devices: u32,
+        mirror_count: u32,
+        software: bool,
+    ) -> Result<Vec<RenderDeviceInfo>> {
+        let connection = self.connection()?;
+        let response = connection
+            .lock()
+            .unwrap()
+            .call(ProxyRequest::EnumerateDevices {
+                max_devices,
+                mirror_count,
+                software,
+            })?;
+        match response {
+            ProxyResponse::Devices(device_info) => {
+                self.devices = (0..device_info.len())
+                    .map(|_| new_device_entry(None))
+                    .collect();
+                self.device_info = device_info;
+                Ok(self.device_info.clone())
+            }
+            response => Err(unexpected_response(&response)),
+        }
+    }
+
+    fn create_device(&mut self, device_id: RenderDeviceId) -> Result<()> {
+        let device_index = self.device_index(device_id)?;
+        let connection = self.connection()?;
+        connection
+            .lock()
+            .unwrap()
+            .call_ok(ProxyRequest::CreateDevice { device_id })?;
+        let device: Box<dyn RenderDevice> = Box::new(RenderDeviceProxy::new(
+            device_id,
+            self.device_info[device_index].clone(),
+            connection,
+        ));
+        self.devices[device_index] = new_device_entry(Some(device));
+        Ok(())
+    }
+
+    fn destroy_device(&mut self, device_id: RenderDeviceId) -> Result<()> {
+        let device_index = self.device_index(device_id)?;
+        self.call_ok(ProxyRequest::DestroyDevice { device_id })?;
+        self.devices[device_index] = new_device_entry(None);
+        Ok(())
+    }
+
+    fn get_device(&self, device_id: RenderDeviceId) -> Result<RenderDeviceEntry> {
+        let device_index = self.device_index(device_id)?;
+        Ok(Arc::clone(&self.devices[device_index]))
+    }
+
+    fn begin_debug_capture(&self, name: &str) -> Result<()> {
+        self.call_ok(ProxyRequest::BeginDebugCapture {
+            name: name.to_string(),
+        })
+    }
+
+    fn finish_debug_capture(&self) -> Result<()> {
+        self.call_ok(ProxyRequest::FinishDebugCapture)
+    }
+
+    fn trigger_debug_capture(&self) -> Result<()> {
+        self.call_ok(ProxyRequest::TriggerDebugCapture)
+    }
+
+    fn launch_debug_capture(&self, quit: bool) -> Result<()> {
+        self.call_ok(ProxyRequest::LaunchDebugCapture { quit })
+    }
+}
@@ -0,0 +1,48 @@
+//! Plays a capture recorded with `RenderBackendModuleCapture` against a render backend.
+//!
+//! Usage: render-replay <capture file> [api] [module path]
+//!
+//! The api defaults to "mock". Any other api is loaded from the backend modules found in the
+//! module path, which defaults to the directory of this executable.
+
+extern crate render_core;
+extern crate render_hal_proxy;
+
+use render_core::error::{Error, Result};
+use render_hal_proxy::{load_backend_module, RenderCaptureReplay};
+use std::env;
+use std::path::Path;
+use std::process;
+
+fn run() -> Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let capture_path = args
+        .first()
+        .ok_or_else(|| Error::config("usage: render-replay <capture file> [api] [module path]"))?;
+    let api = args.get(1).map(|api| api.as_str()).unwrap_or("mock");
+
+    // Libraries must outlive the module created from them
+    let (_libraries, module) = load_backend_module(api, args.get(2).map(Path::new))?;
+
+    let mut replay = RenderCaptureReplay::new(&*module);
+    let summary = replay.replay_file(capture_path)?;
+    println!(
+        "Replayed {} capture on {}: {} calls, {} resources, {} command lists, {} submits, {} presents, {} frames",
+        summary.api,
+        api,
+        summary.calls,
+        summary.resources_created,
+        summary.command_lists_compiled,
+        summary.submits,
+        summary.presents,
+        summary.frames
+    );
+    Ok(())
+}
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("render-replay: {}", err);
+        process::exit(1);
+    }
+}
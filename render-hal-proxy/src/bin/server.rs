@@ -0,0 +1,39 @@
+//! Hosts a render backend for `render-hal-proxy` clients.
+//!
+//! Usage: render-proxy-server [address] [api] [module path]
+//!
+//! The address defaults to 127.0.0.1:50080 ("unix:/path" for a Unix socket) and the api
+//! to "mock". Any other api is loaded from the backend modules found in the module path,
+//! which defaults to the directory of this executable.
+
+extern crate render_core;
+extern crate render_hal_proxy;
+
+use render_core::error::Result;
+use render_hal_proxy::{load_backend_module, RenderProxyServer, DEFAULT_PROXY_ADDRESS};
+use std::env;
+use std::path::Path;
+use std::process;
+
+fn run() -> Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let address = args
+        .first()
+        .map(|address| address.as_str())
+        .unwrap_or(DEFAULT_PROXY_ADDRESS);
+    let api = args.get(1).map(|api| api.as_str()).unwrap_or("mock");
+
+    // Libraries must outlive the module created from them
+    let (_libraries, module) = load_backend_module(api, args.get(2).map(Path::new))?;
+
+    let server = RenderProxyServer::bind(module, address)?;
+    println!("Serving {} backend on {}", api, server.local_address()?);
+    server.run()
+}
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("render-proxy-server: {}", err);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,1244 @@
+use crate::protocol::*;
+use crate::server::ProxySession;
+use render_core::backend::{
+    RenderBackend, RenderBackendLayer, RenderBackendModule, RenderBackendSettings,
+};
+use render_core::deferred::RenderDestroyedFn;
+use render_core::device::*;
+use render_core::encoder::RenderCommandList;
+use render_core::error::{Error, Result};
+use render_core::handles::*;
+use render_core::serialization::RenderSerializedCommandList;
+use render_core::state::*;
+use render_core::types::*;
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Identifies a capture file.
+pub const CAPTURE_MAGIC: [u8; 4] = *b"RCAP";
+
+/// Must be bumped whenever the capture header or `RenderCaptureCall` changes incompatibly.
+/// Other calls are proxy requests, versioned by `PROXY_PROTOCOL_VERSION`, and command lists
+/// are versioned by `COMMAND_STREAM_VERSION`.
+pub const CAPTURE_VERSION: u32 = 1;
+
+/// Written after the magic and version, before the recorded calls.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RenderCaptureHeader {
+    pub protocol_version: u32,
+
+    /// Api of the captured backend, for information only; captures replay against any backend
+    pub api: String,
+}
+
+/// A call recorded in a capture, after the header.
+///
+/// Command lists are stored as `RenderSerializedCommandList` streams, the format shared with
+/// other saved command lists; every other call is stored as the proxy request replaying it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum RenderCaptureCall {
+    Request(ProxyRequest),
+    CompileCommandList {
+        device_id: RenderDeviceId,
+        handle: RenderCommandListHandle,
+        command_list: Vec<u8>,
+    },
+    CompileCommandLists {
+        device_id: RenderDeviceId,
+        handle: RenderCommandListHandle,
+        command_lists: Vec<Vec<u8>>,
+    },
+    DeviceTransfer {
+        device_id: RenderDeviceId,
+        wait_value: u64,
+        signal_value: u64,
+        fence: RenderFenceHandle,
+        command_list: Vec<u8>,
+    },
+}
+
+impl RenderCaptureCall {
+    fn device(device_id: RenderDeviceId, call: ProxyDeviceCall) -> Self {
+        RenderCaptureCall::Request(ProxyRequest::Device {
+            device_id,
+            call: Box::new(call),
+        })
+    }
+}
+
+fn serialize_command_list(command_list: &RenderCommandList) -> Result<Vec<u8>> {
+    RenderSerializedCommandList::from_command_list(command_list)?.to_bytes()
+}
+
+#[derive(Clone, Debug)]
+pub struct RenderCaptureSettings {
+    pub path: PathBuf,
+
+    /// First frame (as counted by the device's `advance_frame`) whose work is captured
+    pub first_frame: u64,
+
+    /// Number of frames to capture, or `None` to capture until the backend is dropped
+    pub frame_count: Option<u64>,
+}
+
+impl RenderCaptureSettings {
+    /// Captures every frame to `path`.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        RenderCaptureSettings {
+            path: path.as_ref().to_path_buf(),
+            first_frame: 0,
+            frame_count: None,
+        }
+    }
+
+    /// Only captures the work of `frame_count` frames, starting at `first_frame`.
+    pub fn with_frames(mut self, first_frame: u64, frame_count: u64) -> Self {
+        self.first_frame = first_frame;
+        self.frame_count = Some(frame_count);
+        self
+    }
+}
+
+#[derive(Debug)]
+struct RenderCaptureWriter {
+    path: PathBuf,
+    stream: Option<BufWriter<File>>,
+    first_frame: u64,
+    end_frame: Option<u64>,
+
+    /// Latest compile of each command list before the first captured frame, written when the
+    /// frames start being captured so lists compiled earlier replay as they were submitted
+    pending_compiles: Vec<(RenderDeviceId, RenderCommandListHandle, RenderCaptureCall)>,
+}
+
+type RenderCaptureWriterRef = Arc<Mutex<RenderCaptureWriter>>;
+
+impl RenderCaptureWriter {
+    /// Starts a capture, or a writer that records nothing if the file can't be written.
+    fn create(settings: &RenderCaptureSettings, api: &str) -> Self {
+        let mut writer = RenderCaptureWriter {
+            path: settings.path.clone(),
+            stream: None,
+            first_frame: settings.first_frame,
+            end_frame: settings
+                .frame_count
+                .map(|frame_count| settings.first_frame + frame_count),
+            pending_compiles: Vec::new(),
+        };
+        match Self::create_stream(settings, api) {
+            Ok(stream) => {
+                info!("Capturing {} backend to {}", api, settings.path.display());
+                writer.stream = Some(stream);
+            }
+            Err(err) => writer.fail(&err),
+        }
+        writer
+    }
+
+    fn create_stream(settings: &RenderCaptureSettings, api: &str) -> Result<BufWriter<File>> {
+        let mut stream = BufWriter::new(File::create(&settings.path).map_err(Error::io)?);
+        stream.write_all(&CAPTURE_MAGIC).map_err(Error::io)?;
+        stream
+            .write_all(&CAPTURE_VERSION.to_le_bytes())
+            .map_err(Error::io)?;
+        write_message(
+            &mut stream,
+            &RenderCaptureHeader {
+                protocol_version: PROXY_PROTOCOL_VERSION,
+                api: api.to_string(),
+            },
+        )?;
+        Ok(stream)
+    }
+
+    fn is_capturing(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    fn is_capturing_frame(&self, frame_index: u64) -> bool {
+        let before_end = match self.end_frame {
+            Some(end_frame) => frame_index < end_frame,
+            None => true,
+        };
+        self.is_capturing() && frame_index >= self.first_frame && before_end
+    }
+
+    /// Capture failures never fail the device call being captured; they are logged and the
+    /// capture stops, leaving the calls written so far.
+    fn record(&mut self, call: Result<RenderCaptureCall>) {
+        let result = match self.stream {
+            Some(ref mut stream) => call.and_then(|call| write_message(stream, &call)),
+            None => return,
+        };
+        if let Err(err) = result {
+            self.fail(&err);
+        }
+    }
+
+    /// Records a call made during a captured frame, after the compiles it may depend on.
+    fn record_frame(&mut self, call: Result<RenderCaptureCall>) {
+        for (_, _, compile) in std::mem::take(&mut self.pending_compiles) {
+            self.record(Ok(compile));
+        }
+        self.record(call);
+    }
+
+    /// Records a compile of `handle` during `frame_index`; before the captured frames only the
+    /// latest compile of each command list is kept.
+    fn record_compile<F>(
+        &mut self,
+        frame_index: u64,
+        device_id: RenderDeviceId,
+        handle: RenderCommandListHandle,
+        call: F,
+    ) where
+        F: FnOnce() -> Result<RenderCaptureCall>,
+    {
+        if !self.is_capturing() || frame_index >= self.first_frame {
+            self.discard_compile(device_id, handle.into());
+            if self.is_capturing_frame(frame_index) {
+                self.record_frame(call());
+            }
+            return;
+        }
+        match call() {
+            Ok(call) => {
+                self.discard_compile(device_id, handle.into());
+                self.pending_compiles.push((device_id, handle, call));
+            }
+            Err(err) => self.fail(&err),
+        }
+    }
+
+    /// Drops the pending compile of a command list that is destroyed before it is written.
+    fn discard_compile(&mut self, device_id: RenderDeviceId, handle: RenderResourceHandle) {
+        self.pending_compiles
+            .retain(|&(pending_device_id, pending_handle, _)| {
+                pending_device_id != device_id || pending_handle != handle
+            });
+    }
+
+    /// Writes out the calls buffered during the previous frame, closing the file once
+    /// `frame_index` is past the last captured frame.
+    fn advance(&mut self, frame_index: u64) {
+        self.flush();
+        let finished = match self.end_frame {
+            Some(end_frame) => frame_index >= end_frame,
+            None => false,
+        };
+        if finished && self.stream.take().is_some() {
+            info!("Finished capture to {}", self.path.display());
+            self.pending_compiles.clear();
+        }
+    }
+
+    fn flush(&mut self) {
+        let result = match self.stream {
+            Some(ref mut stream) => stream.flush().map_err(Error::io),
+            None => return,
+        };
+        if let Err(err) = result {
+            self.fail(&err);
+        }
+    }
+
+    fn fail(&mut self, err: &Error) {
+        error!(
+            "failed to write capture {} - {}, capture stopped",
+            self.path.display(),
+            err
+        );
+        self.stream = None;
+        self.pending_compiles.clear();
+    }
+}
+
+impl Drop for RenderCaptureWriter {
+    /// Reports a failure to write the end of the capture, which dropping the stream ignores.
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// Records every device call made through the backends it creates to a capture file, which
+/// `RenderCaptureReplay` (or the `render-replay` tool) plays back against any backend.
+///
+/// Devices, resources and their initial data are always captured, since replays need them,
+/// as is the latest compile of each command list made before the first captured frame;
+/// command lists, submits, presents and fence operations are only captured for the frames
+/// in the settings. Each backend created from the module starts a new capture, overwriting
+/// the file. Failing to write the capture stops it without failing the device calls.
+#[derive(Debug)]
+pub struct RenderBackendModuleCapture {
+    inner: Box<dyn RenderBackendModule>,
+    settings: RenderCaptureSettings,
+}
+
+impl RenderBackendModuleCapture {
+    pub fn new(inner: Box<dyn RenderBackendModule>, settings: RenderCaptureSettings) -> Self {
+        RenderBackendModuleCapture { inner, settings }
+    }
+
+    pub fn settings(&self) -> &RenderCaptureSettings {
+        &self.settings
+    }
+}
+
+impl RenderBackendModule for RenderBackendModuleCapture {
+    fn name(&self) -> &'static str {
+        "Capture"
+    }
+
+    /// The api of the captured module, so the capture is transparent to backend selection.
+    fn api(&self) -> &'static str {
+        self.inner.api()
+    }
+
+    fn create(&self) -> Box<dyn RenderBackend> {
+        Box::new(RenderBackendCapture::new(
+            self.inner.create(),
+            &self.settings,
+            self.inner.api(),
+        ))
+    }
+
+    fn create_with_settings(&self, settings: &RenderBackendSettings) -> Box<dyn RenderBackend> {
+        Box::new(RenderBackendCapture::new(
+            self.inner.create_with_settings(settings),
+            &self.settings,
+            self.inner.api(),
+        ))
+    }
+}
+
+#[derive(Debug)]
+struct RenderBackendCapture {
+    layer: RenderBackendLayer,
+    writer: RenderCaptureWriterRef,
+}
+
+impl RenderBackendCapture {
+    fn new(inner: Box<dyn RenderBackend>, settings: &RenderCaptureSettings, api: &str) -> Self {
+        RenderBackendCapture {
+            layer: RenderBackendLayer::new(inner),
+            writer: Arc::new(Mutex::new(RenderCaptureWriter::create(settings, api))),
+        }
+    }
+
+    fn record(&self, request: ProxyRequest) {
+        record_request(&self.writer, request);
+    }
+}
+
+fn record_request(writer: &RenderCaptureWriterRef, request: ProxyRequest) {
+    writer
+        .lock()
+        .unwrap()
+        .record(Ok(RenderCaptureCall::Request(request)))
+}
+
+impl RenderBackend for RenderBackendCapture {
+    fn is_initialized(&self) -> bool {
+        self.layer.inner().is_initialized()
+    }
+
+    fn enumerate_devices(
+        &mut self,
+        max_devices: u32,
+        mirror_count: u32,
+        software: bool,
+    ) -> Result<Vec<RenderDeviceInfo>> {
+        let device_info = self
+            .layer
+            .enumerate_devices(max_devices, mirror_count, software)?;
+        self.record(ProxyRequest::EnumerateDevices {
+            max_devices,
+            mirror_count,
+            software,
+        });
+        Ok(device_info)
+    }
+
+    fn create_device(&mut self, device_id: RenderDeviceId) -> Result<()> {
+        let writer = Arc::clone(&self.writer);
+        self.layer.create_device(device_id, |inner| {
+            record_request(&writer, ProxyRequest::CreateDevice { device_id });
+            Ok(Box::new(RenderDeviceCapture {
+                device_id,
+                inner,
+                writer,
+            }))
+        })
+    }
+
+    fn destroy_device(&mut self, device_id: RenderDeviceId) -> Result<()> {
+        self.layer.destroy_device(device_id)?;
+        self.record(ProxyRequest::DestroyDevice { device_id });
+        Ok(())
+    }
+
+    fn get_device(&self, device_id: RenderDeviceId) -> Result<RenderDeviceEntry> {
+        self.layer.get_device(device_id)
+    }
+
+    fn begin_debug_capture(&self, name: &str) -> Result<()> {
+        self.layer.inner().begin_debug_capture(name)
+    }
+
+    fn finish_debug_capture(&self) -> Result<()> {
+        self.layer.inner().finish_debug_capture()
+    }
+
+    fn trigger_debug_capture(&self) -> Result<()> {
+        self.layer.inner().trigger_debug_capture()
+    }
+
+    fn launch_debug_capture(&self, quit: bool) -> Result<()> {
+        self.layer.inner().launch_debug_capture(quit)
+    }
+}
+
+/// Forwards every call to the device being captured, then records the calls that succeeded.
+/// Capture failures are logged by the writer rather than returned from the device calls.
+#[derive(Debug)]
+struct RenderDeviceCapture {
+    device_id: RenderDeviceId,
+    inner: RenderDeviceEntry,
+    writer: RenderCaptureWriterRef,
+}
+
+impl RenderDeviceCapture {
+    /// Records a call that replays need regardless of the captured frames.
+    fn record<F: FnOnce() -> ProxyDeviceCall>(&self, call: F) {
+        let mut writer = self.writer.lock().unwrap();
+        if writer.is_capturing() {
+            writer.record(Ok(RenderCaptureCall::device(self.device_id, call())));
+        }
+    }
+
+    /// Records a call made during `frame_index`, if that frame is being captured.
+    fn record_frame<F: FnOnce() -> ProxyDeviceCall>(&self, frame_index: u64, call: F) {
+        let device_id = self.device_id;
+        self.record_frame_call(frame_index, || {
+            Ok(RenderCaptureCall::device(device_id, call()))
+        })
+    }
+
+    /// Records a call made during `frame_index` that isn't a proxy request (i.e. a transfer).
+    fn record_frame_call<F>(&self, frame_index: u64, call: F)
+    where
+        F: FnOnce() -> Result<RenderCaptureCall>,
+    {
+        let mut writer = self.writer.lock().unwrap();
+        if writer.is_capturing_frame(frame_index) {
+            writer.record_frame(call());
+        }
+    }
+
+    /// Records the destruction of a resource, dropping any compile of it not yet written.
+    fn record_destroy(
+        writer: &RenderCaptureWriterRef,
+        device_id: RenderDeviceId,
+        handle: RenderResourceHandle,
+    ) {
+        let mut writer = writer.lock().unwrap();
+        writer.discard_compile(device_id, handle);
+        writer.record(Ok(RenderCaptureCall::device(
+            device_id,
+            ProxyDeviceCall::DestroyResource(handle),
+        )));
+    }
+}
+
+impl RenderDeviceLayer for RenderDeviceCapture {
+    fn layer_device_id(&self) -> RenderDeviceId {
+        self.device_id
+    }
+
+    fn layer_entry(&self) -> &RenderDeviceEntry {
+        &self.inner
+    }
+
+    fn destroy_resource(&self, handle: RenderResourceHandle) -> Result<()> {
+        self.layer_inner()?.destroy_resource(handle)?;
+        RenderDeviceCapture::record_destroy(&self.writer, self.device_id, handle);
+        Ok(())
+    }
+
+    /// The destruction is recorded once the device performs it, so replays destroy the
+    /// resource at the same point. Unknown resources are passed through unrecorded.
+    fn destroy_resource_deferred(
+        &self,
+        handle: RenderResourceHandle,
+        on_destroyed: Option<RenderDestroyedFn>,
+    ) -> Result<()> {
+        let (inner, on_destroyed) = self.layer_inner_or_release(handle, on_destroyed)?;
+        if !inner.valid_resource(handle) {
+            return inner.destroy_resource_deferred(handle, on_destroyed);
+        }
+        let device_id = self.device_id;
+        let writer = Arc::clone(&self.writer);
+        let on_destroyed: RenderDestroyedFn = Box::new(move |handle| {
+            RenderDeviceCapture::record_destroy(&writer, device_id, handle);
+            if let Some(on_destroyed) = on_destroyed {
+                on_destroyed(handle);
+            }
+        });
+        inner.destroy_resource_deferred(handle, Some(on_destroyed))
+    }
+
+    // Resource Management
+    fn create_swap_chain(
+        &self,
+        handle: RenderSwapChainHandle,
+        desc: &RenderSwapChainDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.layer_inner()?
+            .create_swap_chain(handle, desc, debug_name.clone())?;
+        self.record(|| {
+            ProxyDeviceCall::CreateSwapChain(handle, desc.into(), debug_name.into_owned())
+        });
+        Ok(())
+    }
+
+    fn create_buffer(
+        &self,
+        handle: RenderBufferHandle,
+        desc: &RenderBufferDesc,
+        initial_data: Option<&[u8]>,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.layer_inner()?
+            .create_buffer(handle, desc, initial_data, debug_name.clone())?;
+        self.record(|| {
+            ProxyDeviceCall::CreateBuffer(
+                handle,
+                desc.clone(),
+                initial_data.map(|data| data.to_vec()),
+                debug_name.into_owned(),
+            )
+        });
+        Ok(())
+    }
+
+    fn create_texture(
+        &self,
+        handle: RenderTextureHandle,
+        desc: &RenderTextureDesc,
+        initial_data: Option<RenderTextureSubResourceData>,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        let texture_data = initial_data.as_ref().map(ProxyTextureData::from);
+        self.layer_inner()?
+            .create_texture(handle, desc, initial_data, debug_name.clone())?;
+        self.record(|| {
+            ProxyDeviceCall::CreateTexture(
+                handle,
+                desc.clone(),
+                texture_data,
+                debug_name.into_owned(),
+            )
+        });
+        Ok(())
+    }
+
+    fn create_sampler_state(
+        &self,
+        handle: RenderSamplerStateHandle,
+        state: &RenderSamplerState,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.layer_inner()?
+            .create_sampler_state(handle, state, debug_name.clone())?;
+        self.record(|| {
+            ProxyDeviceCall::CreateSamplerState(handle, *state, debug_name.into_owned())
+        });
+        Ok(())
+    }
+
+    fn create_shader(
+        &self,
+        handle: RenderShaderHandle,
+        desc: &RenderShaderDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.layer_inner()?
+            .create_shader(handle, desc, debug_name.clone())?;
+        self.record(|| {
+            ProxyDeviceCall::CreateShader(handle, desc.clone(), debug_name.into_owned())
+        });
+        Ok(())
+    }
+
+    fn create_shader_views(
+        &self,
+        handle: RenderShaderViewsHandle,
+        desc: &RenderShaderViewsDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.layer_inner()?
+            .create_shader_views(handle, desc, debug_name.clone())?;
+        self.record(|| {
+            ProxyDeviceCall::CreateShaderViews(handle, desc.clone(), debug_name.into_owned())
+        });
+        Ok(())
+    }
+
+    // Ray tracing features are only supported on some devices
+    fn create_ray_tracing_program(
+        &self,
+        handle: RenderRayTracingProgramHandle,
+        desc: &RayTracingProgramDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.layer_inner()?
+            .create_ray_tracing_program(handle, desc, debug_name.clone())?;
+        self.record(|| {
+            ProxyDeviceCall::CreateRayTracingProgram(handle, desc.clone(), debug_name.into_owned())
+        });
+        Ok(())
+    }
+
+    fn create_ray_tracing_geometry(
+        &self,
+        handle: RenderRayTracingGeometryHandle,
+        desc: &RayTracingGeometryDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.layer_inner()?
+            .create_ray_tracing_geometry(handle, desc, debug_name.clone())?;
+        self.record(|| {
+            ProxyDeviceCall::CreateRayTracingGeometry(handle, desc.clone(), debug_name.into_owned())
+        });
+        Ok(())
+    }
+
+    fn create_ray_tracing_top_acceleration(
+        &self,
+        handle: RenderRayTracingAccelerationHandle,
+        desc: &RayTracingTopAccelerationDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.layer_inner()?.create_ray_tracing_top_acceleration(
+            handle,
+            desc,
+            debug_name.clone(),
+        )?;
+        self.record(|| {
+            ProxyDeviceCall::CreateRayTracingTopAcceleration(
+                handle,
+                desc.clone(),
+                debug_name.into_owned(),
+            )
+        });
+        Ok(())
+    }
+
+    fn create_ray_tracing_bottom_acceleration(
+        &self,
+        handle: RenderRayTracingAccelerationHandle,
+        desc: &RayTracingBottomAccelerationDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.layer_inner()?.create_ray_tracing_bottom_acceleration(
+            handle,
+            desc,
+            debug_name.clone(),
+        )?;
+        self.record(|| {
+            ProxyDeviceCall::CreateRayTracingBottomAcceleration(
+                handle,
+                desc.clone(),
+                debug_name.into_owned(),
+            )
+        });
+        Ok(())
+    }
+
+    fn create_ray_tracing_pipeline_state(
+        &self,
+        handle: RenderRayTracingPipelineStateHandle,
+        desc: &RayTracingPipelineStateDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.layer_inner()?
+            .create_ray_tracing_pipeline_state(handle, desc, debug_name.clone())?;
+        self.record(|| {
+            ProxyDeviceCall::CreateRayTracingPipelineState(
+                handle,
+                desc.clone(),
+                debug_name.into_owned(),
+            )
+        });
+        Ok(())
+    }
+
+    fn create_ray_tracing_shader_table(
+        &self,
+        handle: RenderRayTracingShaderTableHandle,
+        desc: &RayTracingShaderTableDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.layer_inner()?
+            .create_ray_tracing_shader_table(handle, desc, debug_name.clone())?;
+        self.record(|| {
+            ProxyDeviceCall::CreateRayTracingShaderTable(
+                handle,
+                desc.clone(),
+                debug_name.into_owned(),
+            )
+        });
+        Ok(())
+    }
+
+    fn create_graphics_pipeline_state(
+        &self,
+        handle: RenderGraphicsPipelineStateHandle,
+        desc: &RenderGraphicsPipelineStateDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.layer_inner()?
+            .create_graphics_pipeline_state(handle, desc, debug_name.clone())?;
+        self.record(|| {
+            ProxyDeviceCall::CreateGraphicsPipelineState(
+                handle,
+                Box::new(desc.clone()),
+                debug_name.into_owned(),
+            )
+        });
+        Ok(())
+    }
+
+    fn create_compute_pipeline_state(
+        &self,
+        handle: RenderComputePipelineStateHandle,
+        desc: &RenderComputePipelineStateDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.layer_inner()?
+            .create_compute_pipeline_state(handle, desc, debug_name.clone())?;
+        self.record(|| {
+            ProxyDeviceCall::CreateComputePipelineState(
+                handle,
+                desc.clone(),
+                debug_name.into_owned(),
+            )
+        });
+        Ok(())
+    }
+
+    fn create_draw_binding_set(
+        &self,
+        handle: RenderDrawBindingSetHandle,
+        desc: &RenderDrawBindingSetDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.layer_inner()?
+            .create_draw_binding_set(handle, desc, debug_name.clone())?;
+        self.record(|| {
+            ProxyDeviceCall::CreateDrawBindingSet(
+                handle,
+                Box::new(desc.clone()),
+                debug_name.into_owned(),
+            )
+        });
+        Ok(())
+    }
+
+    fn create_frame_binding_set(
+        &self,
+        handle: RenderFrameBindingSetHandle,
+        desc: &RenderFrameBindingSetDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.layer_inner()?
+            .create_frame_binding_set(handle, desc, debug_name.clone())?;
+        self.record(|| {
+            ProxyDeviceCall::CreateFrameBindingSet(handle, desc.clone(), debug_name.into_owned())
+        });
+        Ok(())
+    }
+
+    fn create_render_pass(
+        &self,
+        handle: RenderPassHandle,
+        desc: &RenderPassDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.layer_inner()?
+            .create_render_pass(handle, desc, debug_name.clone())?;
+        self.record(|| {
+            ProxyDeviceCall::CreateRenderPass(handle, desc.clone(), debug_name.into_owned())
+        });
+        Ok(())
+    }
+
+    fn create_command_list(
+        &self,
+        handle: RenderCommandListHandle,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.layer_inner()?
+            .create_command_list(handle, debug_name.clone())?;
+        self.record(|| ProxyDeviceCall::CreateCommandList(handle, debug_name.into_owned()));
+        Ok(())
+    }
+
+    fn create_fence(
+        &self,
+        handle: RenderFenceHandle,
+        desc: &RenderFenceDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.layer_inner()?
+            .create_fence(handle, desc, debug_name.clone())?;
+        self.record(|| ProxyDeviceCall::CreateFence(handle, desc.clone(), debug_name.into_owned()));
+        Ok(())
+    }
+
+    fn create_timing_heap(
+        &self,
+        handle: RenderTimingHeapHandle,
+        desc: &RenderTimingHeapDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.layer_inner()?
+            .create_timing_heap(handle, desc, debug_name.clone())?;
+        self.record(|| {
+            ProxyDeviceCall::CreateTimingHeap(handle, desc.clone(), debug_name.into_owned())
+        });
+        Ok(())
+    }
+
+    // CommandList Management
+    fn submit_command_list(
+        &self,
+        handle: RenderCommandListHandle,
+        flush: bool,
+        wait_before: Option<&[RenderFenceHandle]>,
+        signal_after: Option<RenderFenceHandle>,
+    ) -> Result<()> {
+        let frame_index = {
+            let inner = self.layer_inner()?;
+            inner.submit_command_list(handle, flush, wait_before, signal_after)?;
+            inner.get_frame_index()
+        };
+        self.record_frame(frame_index, || ProxyDeviceCall::SubmitCommandList {
+            handle,
+            flush,
+            wait_before: wait_before.map(|handles| handles.to_vec()),
+            signal_after,
+        });
+        Ok(())
+    }
+
+    fn compile_command_list(
+        &self,
+        handle: RenderCommandListHandle,
+        command_list: &RenderCommandList,
+    ) -> Result<()> {
+        let frame_index = {
+            let inner = self.layer_inner()?;
+            inner.compile_command_list(handle, command_list)?;
+            inner.get_frame_index()
+        };
+        let device_id = self.device_id;
+        self.writer
+            .lock()
+            .unwrap()
+            .record_compile(frame_index, device_id, handle, || {
+                Ok(RenderCaptureCall::CompileCommandList {
+                    device_id,
+                    handle,
+                    command_list: serialize_command_list(command_list)?,
+                })
+            });
+        Ok(())
+    }
+
+    fn compile_command_lists(
+        &self,
+        handle: RenderCommandListHandle,
+        command_lists: &[RenderCommandList],
+    ) -> Result<()> {
+        let frame_index = {
+            let inner = self.layer_inner()?;
+            inner.compile_command_lists(handle, command_lists)?;
+            inner.get_frame_index()
+        };
+        let device_id = self.device_id;
+        self.writer
+            .lock()
+            .unwrap()
+            .record_compile(frame_index, device_id, handle, || {
+                Ok(RenderCaptureCall::CompileCommandLists {
+                    device_id,
+                    handle,
+                    command_lists: command_lists
+                        .iter()
+                        .map(serialize_command_list)
+                        .collect::<Result<Vec<_>>>()?,
+                })
+            });
+        Ok(())
+    }
+
+    // Present Management
+    fn present_swap_chain(
+        &mut self,
+        swap_chain: RenderSwapChainHandle,
+        source_texture: RenderTextureHandle,
+    ) -> Result<()> {
+        let frame_index = {
+            let mut inner = self.layer_inner_mut()?;
+            inner.present_swap_chain(swap_chain, source_texture)?;
+            inner.get_frame_index()
+        };
+        self.record_frame(frame_index, || ProxyDeviceCall::PresentSwapChain {
+            swap_chain,
+            source_texture,
+        });
+        Ok(())
+    }
+
+    fn resize_swap_chain(
+        &self,
+        swap_chain: RenderSwapChainHandle,
+        width: u32,
+        height: u32,
+    ) -> Result<()> {
+        let frame_index = {
+            let inner = self.layer_inner()?;
+            inner.resize_swap_chain(swap_chain, width, height)?;
+            inner.get_frame_index()
+        };
+        self.record_frame(frame_index, || ProxyDeviceCall::ResizeSwapChain {
+            swap_chain,
+            width,
+            height,
+        });
+        Ok(())
+    }
+
+    fn advance_frame(&self) -> Result<()> {
+        let (frame_index, next_frame_index) = {
+            let inner = self.layer_inner()?;
+            let frame_index = inner.get_frame_index();
+            inner.advance_frame()?;
+            (frame_index, inner.get_frame_index())
+        };
+        self.record_frame(frame_index, || ProxyDeviceCall::AdvanceFrame);
+        self.writer.lock().unwrap().advance(next_frame_index);
+        Ok(())
+    }
+
+    // Cross-Node Transfer [Prototype]
+    fn device_transfer(
+        &self,
+        wait_value: u64,
+        signal_value: u64,
+        fence: RenderFenceHandle,
+        command_list: &RenderCommandList,
+    ) -> Result<()> {
+        let frame_index = {
+            let inner = self.layer_inner()?;
+            inner.device_transfer(wait_value, signal_value, fence, command_list)?;
+            inner.get_frame_index()
+        };
+        self.record_frame_call(frame_index, || {
+            Ok(RenderCaptureCall::DeviceTransfer {
+                device_id: self.device_id,
+                wait_value,
+                signal_value,
+                fence,
+                command_list: serialize_command_list(command_list)?,
+            })
+        });
+        Ok(())
+    }
+
+    fn device_graphics_signal(&self, signal_value: u64, fence: RenderFenceHandle) -> Result<()> {
+        let frame_index = {
+            let inner = self.layer_inner()?;
+            inner.device_graphics_signal(signal_value, fence)?;
+            inner.get_frame_index()
+        };
+        self.record_frame(frame_index, || {
+            ProxyDeviceCall::DeviceGraphicsSignal(signal_value, fence)
+        });
+        Ok(())
+    }
+
+    fn device_graphics_wait(&self, wait_value: u64, fence: RenderFenceHandle) -> Result<()> {
+        let frame_index = {
+            let inner = self.layer_inner()?;
+            inner.device_graphics_wait(wait_value, fence)?;
+            inner.get_frame_index()
+        };
+        self.record_frame(frame_index, || {
+            ProxyDeviceCall::DeviceGraphicsWait(wait_value, fence)
+        });
+        Ok(())
+    }
+
+    fn device_copy_signal(&self, signal_value: u64, fence: RenderFenceHandle) -> Result<()> {
+        let frame_index = {
+            let inner = self.layer_inner()?;
+            inner.device_copy_signal(signal_value, fence)?;
+            inner.get_frame_index()
+        };
+        self.record_frame(frame_index, || {
+            ProxyDeviceCall::DeviceCopySignal(signal_value, fence)
+        });
+        Ok(())
+    }
+
+    fn device_copy_wait(&self, wait_value: u64, fence: RenderFenceHandle) -> Result<()> {
+        let frame_index = {
+            let inner = self.layer_inner()?;
+            inner.device_copy_wait(wait_value, fence)?;
+            inner.get_frame_index()
+        };
+        self.record_frame(frame_index, || {
+            ProxyDeviceCall::DeviceCopyWait(wait_value, fence)
+        });
+        Ok(())
+    }
+
+    fn device_acquire(&self, resource: RenderResourceHandle) -> Result<()> {
+        let frame_index = {
+            let inner = self.layer_inner()?;
+            inner.device_acquire(resource)?;
+            inner.get_frame_index()
+        };
+        self.record_frame(frame_index, || ProxyDeviceCall::DeviceAcquire(resource));
+        Ok(())
+    }
+
+    fn device_unacquire(&self, resource: RenderResourceHandle) -> Result<()> {
+        let frame_index = {
+            let inner = self.layer_inner()?;
+            inner.device_unacquire(resource)?;
+            inner.get_frame_index()
+        };
+        self.record_frame(frame_index, || ProxyDeviceCall::DeviceUnacquire(resource));
+        Ok(())
+    }
+
+    fn device_flush(&self) -> Result<()> {
+        let frame_index = {
+            let inner = self.layer_inner()?;
+            inner.device_flush()?;
+            inner.get_frame_index()
+        };
+        self.record_frame(frame_index, || ProxyDeviceCall::DeviceFlush);
+        Ok(())
+    }
+}
+
+/// Counts of the calls played back by `RenderCaptureReplay`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RenderReplaySummary {
+    /// Api of the captured backend
+    pub api: String,
+    pub calls: usize,
+    pub resources_created: usize,
+    pub command_lists_compiled: usize,
+    pub submits: usize,
+    pub presents: usize,
+    pub frames: usize,
+}
+
+impl RenderReplaySummary {
+    fn count(&mut self, call: &RenderCaptureCall) {
+        self.calls += 1;
+        let call = match *call {
+            RenderCaptureCall::Request(ProxyRequest::Device { ref call, .. }) => call,
+            RenderCaptureCall::CompileCommandList { .. } => {
+                self.command_lists_compiled += 1;
+                return;
+            }
+            RenderCaptureCall::CompileCommandLists {
+                ref command_lists, ..
+            } => {
+                self.command_lists_compiled += command_lists.len();
+                return;
+            }
+            _ => return,
+        };
+        match **call {
+            ProxyDeviceCall::CreateSwapChain(..)
+            | ProxyDeviceCall::CreateBuffer(..)
+            | ProxyDeviceCall::CreateTexture(..)
+            | ProxyDeviceCall::CreateSamplerState(..)
+            | ProxyDeviceCall::CreateShader(..)
+            | ProxyDeviceCall::CreateShaderViews(..)
+            | ProxyDeviceCall::CreateRayTracingProgram(..)
+            | ProxyDeviceCall::CreateRayTracingGeometry(..)
+            | ProxyDeviceCall::CreateRayTracingTopAcceleration(..)
+            | ProxyDeviceCall::CreateRayTracingBottomAcceleration(..)
+            | ProxyDeviceCall::CreateRayTracingPipelineState(..)
+            | ProxyDeviceCall::CreateRayTracingShaderTable(..)
+            | ProxyDeviceCall::CreateGraphicsPipelineState(..)
+            | ProxyDeviceCall::CreateComputePipelineState(..)
+            | ProxyDeviceCall::CreateDrawBindingSet(..)
+            | ProxyDeviceCall::CreateFrameBindingSet(..)
+            | ProxyDeviceCall::CreateRenderPass(..)
+            | ProxyDeviceCall::CreateCommandList(..)
+            | ProxyDeviceCall::CreateFence(..)
+            | ProxyDeviceCall::CreateTimingHeap(..) => self.resources_created += 1,
+            ProxyDeviceCall::SubmitCommandList { .. } => self.submits += 1,
+            ProxyDeviceCall::PresentSwapChain { .. } => self.presents += 1,
+            ProxyDeviceCall::AdvanceFrame => self.frames += 1,
+            _ => {}
+        }
+    }
+}
+
+/// Plays a capture back against a backend.
+///
+/// Swap chains are created headless, as by the proxy server, so presents don't reach a window.
+pub struct RenderCaptureReplay {
+    session: ProxySession,
+}
+
+impl RenderCaptureReplay {
+    pub fn new(module: &dyn RenderBackendModule) -> Self {
+        RenderCaptureReplay::with_backend(module.api(), module.create())
+    }
+
+    pub fn with_backend(api: &'static str, backend: Box<dyn RenderBackend>) -> Self {
+        RenderCaptureReplay {
+            session: ProxySession::new(api, backend),
+        }
+    }
+
+    /// The backend the capture is played against, i.e. to inspect the replayed devices.
+    pub fn backend(&self) -> &dyn RenderBackend {
+        self.session.backend()
+    }
+
+    /// Plays every call in the capture, stopping at the first that fails.
+    pub fn replay<R: Read + ?Sized>(&mut self, reader: &mut R) -> Result<RenderReplaySummary> {
+        let header = read_capture_header(reader)?;
+        let mut summary = RenderReplaySummary {
+            api: header.api,
+            ..Default::default()
+        };
+        while let Some(call) = read_capture_call(reader)? {
+            summary.count(&call);
+            let result = match call {
+                RenderCaptureCall::Request(request) => match self.session.handle(request) {
+                    ProxyResponse::Error(kind) => Err(Error::from(kind)),
+                    _ => Ok(()),
+                },
+                call => self.replay_command_lists(call),
+            };
+            if let Err(err) = result {
+                return Err(Error::backend(format!(
+                    "capture call {} failed - {}",
+                    summary.calls, err
+                )));
+            }
+        }
+        Ok(summary)
+    }
+
+    /// Compiles (or transfers) captured command lists on the device they were captured from.
+    fn replay_command_lists(&self, call: RenderCaptureCall) -> Result<()> {
+        let device_id = match call {
+            RenderCaptureCall::Request(_) => {
+                return Err(Error::bug("proxy requests are replayed by the session"))
+            }
+            RenderCaptureCall::CompileCommandList { device_id, .. }
+            | RenderCaptureCall::CompileCommandLists { device_id, .. }
+            | RenderCaptureCall::DeviceTransfer { device_id, .. } => device_id,
+        };
+        let entry = self.session.backend().get_device(device_id)?;
+        let entry = entry
+            .read()
+            .map_err(|_| Error::backend("replayed device lock is poisoned"))?;
+        let device = entry
+            .as_ref()
+            .ok_or_else(|| Error::backend(format!("device {} has not been created", device_id)))?;
+        let handles = self.session.handles();
+        let to_command_list = |data: &[u8]| {
+            RenderSerializedCommandList::from_bytes(data)?.to_command_list(Arc::clone(&handles))
+        };
+
+        match call {
+            RenderCaptureCall::Request(_) => Ok(()),
+            RenderCaptureCall::CompileCommandList {
+                handle,
+                command_list,
+                ..
+            } => device.compile_command_list(handle, &to_command_list(&command_list)?),
+            RenderCaptureCall::CompileCommandLists {
+                handle,
+                command_lists,
+                ..
+            } => {
+                let command_lists = command_lists
+                    .iter()
+                    .map(|command_list| to_command_list(command_list))
+                    .collect::<Result<Vec<_>>>()?;
+                device.compile_command_lists(handle, &command_lists)
+            }
+            RenderCaptureCall::DeviceTransfer {
+                wait_value,
+                signal_value,
+                fence,
+                command_list,
+                ..
+            } => device.device_transfer(
+                wait_value,
+                signal_value,
+                fence,
+                &to_command_list(&command_list)?,
+            ),
+        }
+    }
+
+    pub fn replay_file<P: AsRef<Path>>(&mut self, path: P) -> Result<RenderReplaySummary> {
+        let mut reader = BufReader::new(File::open(path).map_err(Error::io)?);
+        self.replay(&mut reader)
+    }
+}
+
+/// Reads the next call of a capture, after its header, returning `None` at the end.
+pub fn read_capture_call<R: Read + ?Sized>(reader: &mut R) -> Result<Option<RenderCaptureCall>> {
+    read_message(reader)
+}
+
+/// Reads and checks the magic, version and header at the start of a capture.
+pub fn read_capture_header<R: Read + ?Sized>(reader: &mut R) -> Result<RenderCaptureHeader> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic).map_err(Error::io)?;
+    if magic != CAPTURE_MAGIC {
+        return Err(Error::parse("not a render capture"));
+    }
+
+    let mut version = [0u8; 4];
+    reader.read_exact(&mut version).map_err(Error::io)?;
+    let version = u32::from_le_bytes(version);
+    if version != CAPTURE_VERSION {
+        return Err(Error::parse(format!(
+            "unsupported capture version {} (expected {})",
+            version, CAPTURE_VERSION
+        )));
+    }
+
+    let header: RenderCaptureHeader =
+        read_message(reader)?.ok_or_else(|| Error::parse("capture is missing its header"))?;
+    if header.protocol_version != PROXY_PROTOCOL_VERSION {
+        return Err(Error::parse(format!(
+            "capture protocol version {} does not match {}",
+            header.protocol_version, PROXY_PROTOCOL_VERSION
+        )));
+    }
+    Ok(header)
+}
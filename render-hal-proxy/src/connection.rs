@@ -0,0 +1,78 @@
+use crate::protocol::*;
+use crate::transport::{ProxyAddress, ProxyStream};
+use render_core::error::{Error, Result};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// Client side of a proxy connection; every call is a blocking request/response round trip.
+pub(crate) struct ProxyConnection {
+    address: ProxyAddress,
+    stream: Box<dyn ProxyStream>,
+    api: String,
+}
+
+pub(crate) type ProxyConnectionRef = Arc<Mutex<ProxyConnection>>;
+
+impl ProxyConnection {
+    pub fn connect(address: &ProxyAddress) -> Result<Self> {
+        let mut connection = ProxyConnection {
+            address: address.clone(),
+            stream: address.connect()?,
+            api: String::new(),
+        };
+
+        match connection.call(ProxyRequest::Hello {
+            version: PROXY_PROTOCOL_VERSION,
+        })? {
+            ProxyResponse::Hello { version, api } => {
+                if version != PROXY_PROTOCOL_VERSION {
+                    return Err(Error::backend(format!(
+                        "proxy protocol mismatch - client {}, server {}",
+                        PROXY_PROTOCOL_VERSION, version
+                    )));
+                }
+                connection.api = api;
+                Ok(connection)
+            }
+            response => Err(unexpected_response(&response)),
+        }
+    }
+
+    /// Api of the backend hosted by the server (i.e. "mock" or "vk").
+    pub fn api(&self) -> &str {
+        &self.api
+    }
+
+    pub fn call(&mut self, request: ProxyRequest) -> Result<ProxyResponse> {
+        write_message(&mut *self.stream, &request)?;
+        flush_messages(&mut *self.stream)?;
+        match read_message(&mut *self.stream)? {
+            Some(ProxyResponse::Error(kind)) => Err(Error::from(kind)),
+            Some(response) => Ok(response),
+            None => Err(Error::backend(format!(
+                "proxy {} closed the connection",
+                self.address
+            ))),
+        }
+    }
+
+    pub fn call_ok(&mut self, request: ProxyRequest) -> Result<()> {
+        match self.call(request)? {
+            ProxyResponse::Ok => Ok(()),
+            response => Err(unexpected_response(&response)),
+        }
+    }
+}
+
+impl fmt::Debug for ProxyConnection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ProxyConnection")
+            .field("address", &self.address)
+            .field("api", &self.api)
+            .finish()
+    }
+}
+
+pub(crate) fn unexpected_response(response: &ProxyResponse) -> Error {
+    Error::backend(format!("unexpected proxy response: {:?}", response))
+}
@@ -0,0 +1,534 @@
+use crate::connection::{unexpected_response, ProxyConnectionRef};
+use crate::protocol::*;
+use render_core::deferred::*;
+use render_core::device::*;
+use render_core::encoder::RenderCommandList;
+use render_core::error::Result;
+use render_core::handles::*;
+use render_core::serialization::RenderSerializedCommandList;
+use render_core::state::*;
+use render_core::statistics::*;
+use render_core::types::*;
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Frames a deferred destruction waits before it is forwarded to the server
+pub const PROXY_FRAMES_IN_FLIGHT: u64 = 2;
+
+/// Forwards every call to the matching device of the backend hosted by a proxy server.
+///
+/// Deferred destruction is queued client side, since completion callbacks can't be sent to
+/// the server.
+pub struct RenderDeviceProxy {
+    device_id: RenderDeviceId,
+    device_info: RenderDeviceInfo,
+    connection: ProxyConnectionRef,
+    frame_index: AtomicU64,
+    destroy_queue: RenderDestroyQueue,
+    frame_stats: RenderFrameStatsCollector,
+}
+
+impl RenderDeviceProxy {
+    pub(crate) fn new(
+        device_id: RenderDeviceId,
+        device_info: RenderDeviceInfo,
+        connection: ProxyConnectionRef,
+    ) -> Self {
+        RenderDeviceProxy {
+            device_id,
+            device_info,
+            connection,
+            frame_index: AtomicU64::new(0),
+            destroy_queue: RenderDestroyQueue::new(PROXY_FRAMES_IN_FLIGHT),
+            frame_stats: RenderFrameStatsCollector::new(),
+        }
+    }
+
+    fn call(&self, call: ProxyDeviceCall) -> Result<ProxyResponse> {
+        let mut connection = self.connection.lock().unwrap();
+        connection.call(ProxyRequest::Device {
+            device_id: self.device_id,
+            call: Box::new(call),
+        })
+    }
+
+    fn call_ok(&self, call: ProxyDeviceCall) -> Result<()> {
+        match self.call(call)? {
+            ProxyResponse::Ok => Ok(()),
+            response => Err(unexpected_response(&response)),
+        }
+    }
+}
+
+impl fmt::Debug for RenderDeviceProxy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RenderDeviceProxy")
+            .field("device_id", &self.device_id)
+            .field("device_info", &self.device_info)
+            .field("destroy_queue", &self.destroy_queue)
+            .finish()
+    }
+}
+
+impl Drop for RenderDeviceProxy {
+    fn drop(&mut self) {
+        if let Err(err) = self.destroy_queue.flush_all(self) {
+            error!("failed to destroy deferred resources on drop: {}", err);
+        }
+    }
+}
+
+impl RenderDevice for RenderDeviceProxy {
+    fn valid_resource(&self, handle: RenderResourceHandle) -> bool {
+        match self.call(ProxyDeviceCall::ValidResource(handle)) {
+            Ok(ProxyResponse::Bool(valid)) => valid,
+            _ => false,
+        }
+    }
+
+    fn destroy_resource(&self, handle: RenderResourceHandle) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::DestroyResource(handle))?;
+        self.frame_stats.unregister_pipeline(handle);
+        Ok(())
+    }
+
+    fn destroy_resource_deferred(
+        &self,
+        handle: RenderResourceHandle,
+        on_destroyed: Option<RenderDestroyedFn>,
+    ) -> Result<()> {
+        self.destroy_queue
+            .push(self.get_frame_index(), handle, on_destroyed);
+        Ok(())
+    }
+
+    // Resource Management
+    fn create_swap_chain(
+        &self,
+        handle: RenderSwapChainHandle,
+        desc: &RenderSwapChainDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        // The window only exists in this process, so the server presents headless
+        self.call_ok(ProxyDeviceCall::CreateSwapChain(
+            handle,
+            desc.into(),
+            debug_name.into_owned(),
+        ))
+    }
+
+    fn create_buffer(
+        &self,
+        handle: RenderBufferHandle,
+        desc: &RenderBufferDesc,
+        initial_data: Option<&[u8]>,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::CreateBuffer(
+            handle,
+            desc.clone(),
+            initial_data.map(|data| data.to_vec()),
+            debug_name.into_owned(),
+        ))
+    }
+
+    fn create_texture(
+        &self,
+        handle: RenderTextureHandle,
+        desc: &RenderTextureDesc,
+        initial_data: Option<RenderTextureSubResourceData>,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::CreateTexture(
+            handle,
+            desc.clone(),
+            initial_data.as_ref().map(ProxyTextureData::from),
+            debug_name.into_owned(),
+        ))
+    }
+
+    fn create_sampler_state(
+        &self,
+        handle: RenderSamplerStateHandle,
+        state: &RenderSamplerState,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::CreateSamplerState(
+            handle,
+            *state,
+            debug_name.into_owned(),
+        ))
+    }
+
+    fn create_shader(
+        &self,
+        handle: RenderShaderHandle,
+        desc: &RenderShaderDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::CreateShader(
+            handle,
+            desc.clone(),
+            debug_name.into_owned(),
+        ))
+    }
+
+    fn create_shader_views(
+        &self,
+        handle: RenderShaderViewsHandle,
+        desc: &RenderShaderViewsDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::CreateShaderViews(
+            handle,
+            desc.clone(),
+            debug_name.into_owned(),
+        ))
+    }
+
+    // Ray tracing features are only supported on some devices
+    fn create_ray_tracing_program(
+        &self,
+        handle: RenderRayTracingProgramHandle,
+        desc: &RayTracingProgramDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::CreateRayTracingProgram(
+            handle,
+            desc.clone(),
+            debug_name.into_owned(),
+        ))
+    }
+
+    fn create_ray_tracing_geometry(
+        &self,
+        handle: RenderRayTracingGeometryHandle,
+        desc: &RayTracingGeometryDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::CreateRayTracingGeometry(
+            handle,
+            desc.clone(),
+            debug_name.into_owned(),
+        ))
+    }
+
+    fn create_ray_tracing_top_acceleration(
+        &self,
+        handle: RenderRayTracingAccelerationHandle,
+        desc: &RayTracingTopAccelerationDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::CreateRayTracingTopAcceleration(
+            handle,
+            desc.clone(),
+            debug_name.into_owned(),
+        ))
+    }
+
+    fn create_ray_tracing_bottom_acceleration(
+        &self,
+        handle: RenderRayTracingAccelerationHandle,
+        desc: &RayTracingBottomAccelerationDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::CreateRayTracingBottomAcceleration(
+            handle,
+            desc.clone(),
+            debug_name.into_owned(),
+        ))
+    }
+
+    fn create_ray_tracing_pipeline_state(
+        &self,
+        handle: RenderRayTracingPipelineStateHandle,
+        desc: &RayTracingPipelineStateDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::CreateRayTracingPipelineState(
+            handle,
+            desc.clone(),
+            debug_name.into_owned(),
+        ))
+    }
+
+    fn create_ray_tracing_shader_table(
+        &self,
+        handle: RenderRayTracingShaderTableHandle,
+        desc: &RayTracingShaderTableDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::CreateRayTracingShaderTable(
+            handle,
+            desc.clone(),
+            debug_name.into_owned(),
+        ))
+    }
+
+    fn create_graphics_pipeline_state(
+        &self,
+        handle: RenderGraphicsPipelineStateHandle,
+        desc: &RenderGraphicsPipelineStateDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::CreateGraphicsPipelineState(
+            handle,
+            Box::new(desc.clone()),
+            debug_name.into_owned(),
+        ))?;
+        self.frame_stats.register_pipeline(handle.into(), desc);
+        Ok(())
+    }
+
+    fn create_compute_pipeline_state(
+        &self,
+        handle: RenderComputePipelineStateHandle,
+        desc: &RenderComputePipelineStateDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::CreateComputePipelineState(
+            handle,
+            desc.clone(),
+            debug_name.into_owned(),
+        ))
+    }
+
+    fn create_draw_binding_set(
+        &self,
+        handle: RenderDrawBindingSetHandle,
+        desc: &RenderDrawBindingSetDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::CreateDrawBindingSet(
+            handle,
+            Box::new(desc.clone()),
+            debug_name.into_owned(),
+        ))
+    }
+
+    fn create_frame_binding_set(
+        &self,
+        handle: RenderFrameBindingSetHandle,
+        desc: &RenderFrameBindingSetDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::CreateFrameBindingSet(
+            handle,
+            desc.clone(),
+            debug_name.into_owned(),
+        ))
+    }
+
+    fn create_render_pass(
+        &self,
+        handle: RenderPassHandle,
+        desc: &RenderPassDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::CreateRenderPass(
+            handle,
+            desc.clone(),
+            debug_name.into_owned(),
+        ))
+    }
+
+    fn create_command_list(
+        &self,
+        handle: RenderCommandListHandle,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::CreateCommandList(
+            handle,
+            debug_name.into_owned(),
+        ))
+    }
+
+    fn create_fence(
+        &self,
+        handle: RenderFenceHandle,
+        desc: &RenderFenceDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::CreateFence(
+            handle,
+            desc.clone(),
+            debug_name.into_owned(),
+        ))
+    }
+
+    fn create_timing_heap(
+        &self,
+        handle: RenderTimingHeapHandle,
+        desc: &RenderTimingHeapDesc,
+        debug_name: Cow<'static, str>,
+    ) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::CreateTimingHeap(
+            handle,
+            desc.clone(),
+            debug_name.into_owned(),
+        ))
+    }
+
+    // Timing Heap Management
+    fn get_timing_frequency(&self) -> Result<f64> {
+        match self.call(ProxyDeviceCall::GetTimingFrequency)? {
+            ProxyResponse::Float(frequency) => Ok(frequency),
+            response => Err(unexpected_response(&response)),
+        }
+    }
+
+    // CommandList Management
+    fn submit_command_list(
+        &self,
+        handle: RenderCommandListHandle,
+        flush: bool,
+        wait_before: Option<&[RenderFenceHandle]>,
+        signal_after: Option<RenderFenceHandle>,
+    ) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::SubmitCommandList {
+            handle,
+            flush,
+            wait_before: wait_before.map(|handles| handles.to_vec()),
+            signal_after,
+        })
+    }
+
+    fn compile_command_list(
+        &self,
+        handle: RenderCommandListHandle,
+        command_list: &RenderCommandList,
+    ) -> Result<()> {
+        let serialized_list = RenderSerializedCommandList::from_command_list(command_list)?;
+        self.call_ok(ProxyDeviceCall::CompileCommandList(handle, serialized_list))?;
+        self.frame_stats.record(command_list);
+        Ok(())
+    }
+
+    fn compile_command_lists(
+        &self,
+        handle: RenderCommandListHandle,
+        command_lists: &[RenderCommandList],
+    ) -> Result<()> {
+        let serialized_lists = command_lists
+            .iter()
+            .map(RenderSerializedCommandList::from_command_list)
+            .collect::<Result<Vec<_>>>()?;
+        self.call_ok(ProxyDeviceCall::CompileCommandLists(
+            handle,
+            serialized_lists,
+        ))?;
+        for command_list in command_lists {
+            self.frame_stats.record(command_list);
+        }
+        Ok(())
+    }
+
+    // Present Management
+    fn present_swap_chain(
+        &mut self,
+        swap_chain: RenderSwapChainHandle,
+        source_texture: RenderTextureHandle,
+    ) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::PresentSwapChain {
+            swap_chain,
+            source_texture,
+        })
+    }
+
+    fn resize_swap_chain(
+        &self,
+        swap_chain: RenderSwapChainHandle,
+        width: u32,
+        height: u32,
+    ) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::ResizeSwapChain {
+            swap_chain,
+            width,
+            height,
+        })
+    }
+
+    fn advance_frame(&self) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::AdvanceFrame)?;
+        let frame_index = self.frame_index.fetch_add(1, Ordering::AcqRel) + 1;
+        self.frame_stats.advance(frame_index);
+        self.destroy_queue.flush(self, frame_index)
+    }
+
+    fn get_frame_index(&self) -> u64 {
+        self.frame_index.load(Ordering::Acquire)
+    }
+
+    fn get_frame_stats(&self) -> RenderFrameStats {
+        self.frame_stats.completed()
+    }
+
+    // Cross-Node Transfer [Prototype]
+    fn device_transfer(
+        &self,
+        wait_value: u64,
+        signal_value: u64,
+        fence: RenderFenceHandle,
+        command_list: &RenderCommandList,
+    ) -> Result<()> {
+        let serialized_list = RenderSerializedCommandList::from_command_list(command_list)?;
+        self.call_ok(ProxyDeviceCall::DeviceTransfer {
+            wait_value,
+            signal_value,
+            fence,
+            command_list: serialized_list,
+        })?;
+        self.frame_stats.record(command_list);
+        Ok(())
+    }
+
+    fn device_graphics_signal(&self, signal_value: u64, fence: RenderFenceHandle) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::DeviceGraphicsSignal(signal_value, fence))
+    }
+
+    fn device_graphics_wait(&self, wait_value: u64, fence: RenderFenceHandle) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::DeviceGraphicsWait(wait_value, fence))
+    }
+
+    fn device_copy_signal(&self, signal_value: u64, fence: RenderFenceHandle) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::DeviceCopySignal(signal_value, fence))
+    }
+
+    fn device_copy_wait(&self, wait_value: u64, fence: RenderFenceHandle) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::DeviceCopyWait(wait_value, fence))
+    }
+
+    fn device_acquire(&self, resource: RenderResourceHandle) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::DeviceAcquire(resource))
+    }
+
+    fn device_unacquire(&self, resource: RenderResourceHandle) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::DeviceUnacquire(resource))
+    }
+
+    fn device_flush(&self) -> Result<()> {
+        self.call_ok(ProxyDeviceCall::DeviceFlush)
+    }
+
+    fn get_device_info(&self) -> Result<RenderDeviceInfo> {
+        match self.call(ProxyDeviceCall::GetDeviceInfo)? {
+            ProxyResponse::DeviceInfo(info) => Ok(info),
+            response => Err(unexpected_response(&response)),
+        }
+    }
+
+    fn shader_format(&self) -> Result<String> {
+        match self.call(ProxyDeviceCall::ShaderFormat)? {
+            ProxyResponse::Text(format) => Ok(format),
+            response => Err(unexpected_response(&response)),
+        }
+    }
+
+    fn ray_tracing_supported(&self) -> bool {
+        match self.call(ProxyDeviceCall::RayTracingSupported) {
+            Ok(ProxyResponse::Bool(supported)) => supported,
+            _ => false,
+        }
+    }
+}
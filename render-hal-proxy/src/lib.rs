@@ -0,0 +1,35 @@
+#![allow(dead_code)]
+
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate serde_derive;
+extern crate bincode;
+extern crate failure;
+extern crate libloading;
+extern crate render_core;
+extern crate render_hal_mock;
+extern crate serde;
+
+mod backend;
+mod capture;
+mod connection;
+mod device;
+mod loader;
+mod module;
+mod protocol;
+mod server;
+mod transport;
+
+pub use crate::capture::{
+    read_capture_call, read_capture_header, RenderBackendModuleCapture, RenderCaptureCall,
+    RenderCaptureHeader, RenderCaptureReplay, RenderCaptureSettings, RenderReplaySummary,
+    CAPTURE_MAGIC, CAPTURE_VERSION,
+};
+pub use crate::loader::{load_backend_module, BackendModuleLibraries};
+pub use crate::module::RenderBackendModuleProxy;
+pub use crate::protocol::{PROXY_MAX_MESSAGE_SIZE, PROXY_PROTOCOL_VERSION};
+pub use crate::server::RenderProxyServer;
+pub use crate::transport::{ProxyAddress, DEFAULT_PROXY_ADDRESS};
+
+render_core::render_backend_module!("proxy", RenderBackendModuleProxy::new());
@@ -0,0 +1,44 @@
+use libloading::Library;
+use render_core::backend::RenderBackendModule;
+use render_core::error::{Error, Result};
+use render_core::modules::{create_backend_module, load_backend_modules};
+use render_hal_mock::RenderBackendModuleMock;
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// Libraries loaded for a backend module, which must outlive it.
+pub type BackendModuleLibraries = Vec<Box<Library>>;
+
+/// Selects the backend module for `api`, for the proxy tools.
+///
+/// "mock" is built in; any other api is loaded from the backend modules found in
+/// `module_path`, which defaults to the directory of the current executable. The libraries
+/// are returned first, so destructuring them into locals drops the module before them.
+pub fn load_backend_module(
+    api: &str,
+    module_path: Option<&Path>,
+) -> Result<(BackendModuleLibraries, Box<dyn RenderBackendModule>)> {
+    if api == "mock" {
+        return Ok((Vec::new(), Box::new(RenderBackendModuleMock::new())));
+    }
+
+    let module_path = match module_path {
+        Some(module_path) => module_path.to_path_buf(),
+        None => env::current_exe()
+            .ok()
+            .and_then(|exe_path| exe_path.parent().map(PathBuf::from))
+            .ok_or_else(|| Error::backend("failed to locate module path"))?,
+    };
+    let libraries = load_backend_modules(&module_path)?;
+    for library in &libraries {
+        let module = create_backend_module(library)?;
+        if module.api() == api {
+            return Ok((libraries, module));
+        }
+    }
+    Err(Error::backend(format!(
+        "no backend module for api {:?} in {}",
+        api,
+        module_path.display()
+    )))
+}
@@ -0,0 +1,46 @@
+use crate::backend::RenderBackendProxy;
+use crate::transport::DEFAULT_PROXY_ADDRESS;
+use render_core::backend::{RenderBackend, RenderBackendModule, RenderBackendSettings};
+
+#[derive(Debug)]
+pub struct RenderBackendModuleProxy {
+    default_address: String,
+}
+
+impl RenderBackendModuleProxy {
+    pub fn new() -> Self {
+        RenderBackendModuleProxy::with_default_address(DEFAULT_PROXY_ADDRESS)
+    }
+
+    /// Address used when the backend settings do not specify one.
+    pub fn with_default_address(address: &str) -> Self {
+        RenderBackendModuleProxy {
+            default_address: address.to_string(),
+        }
+    }
+}
+
+impl Default for RenderBackendModuleProxy {
+    fn default() -> Self {
+        RenderBackendModuleProxy::new()
+    }
+}
+
+impl RenderBackendModule for RenderBackendModuleProxy {
+    fn name(&self) -> &'static str {
+        "Proxy"
+    }
+
+    fn api(&self) -> &'static str {
+        "proxy"
+    }
+
+    fn create(&self) -> Box<dyn RenderBackend> {
+        Box::new(RenderBackendProxy::new(&self.default_address))
+    }
+
+    fn create_with_settings(&self, settings: &RenderBackendSettings) -> Box<dyn RenderBackend> {
+        let address = settings.address.as_deref().unwrap_or(&self.default_address);
+        Box::new(RenderBackendProxy::new(address))
+    }
+}
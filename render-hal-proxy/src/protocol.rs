@@ -0,0 +1,282 @@
+use render_core::device::{RenderDeviceId, RenderDeviceInfo};
+use render_core::error::{Error, ErrorKind, Result};
+use render_core::handles::*;
+use render_core::serialization::RenderSerializedCommandList;
+use render_core::state::*;
+use render_core::types::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::cmp;
+use std::io::{ErrorKind as IoErrorKind, Read, Write};
+
+/// Must be bumped whenever a request or response changes incompatibly.
+pub const PROXY_PROTOCOL_VERSION: u32 = 3;
+
+/// Upper bound on a single message, to reject corrupt length prefixes.
+pub const PROXY_MAX_MESSAGE_SIZE: usize = 1 << 28;
+
+/// Initial buffer for a message body, which only grows as the body is received so a length
+/// prefix alone can't force a large allocation.
+const MESSAGE_CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ProxyRequest {
+    Hello {
+        version: u32,
+    },
+    EnumerateDevices {
+        max_devices: u32,
+        mirror_count: u32,
+        software: bool,
+    },
+    CreateDevice {
+        device_id: RenderDeviceId,
+    },
+    DestroyDevice {
+        device_id: RenderDeviceId,
+    },
+    BeginDebugCapture {
+        name: String,
+    },
+    FinishDebugCapture,
+    TriggerDebugCapture,
+    LaunchDebugCapture {
+        quit: bool,
+    },
+    Device {
+        device_id: RenderDeviceId,
+        call: Box<ProxyDeviceCall>,
+    },
+}
+
+/// Swap chain description without the (process local) window.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProxySwapChainDesc {
+    pub width: u32,
+    pub height: u32,
+    pub format: RenderFormat,
+    pub buffer_count: u32,
+}
+
+impl<'a> From<&'a RenderSwapChainDesc> for ProxySwapChainDesc {
+    fn from(desc: &'a RenderSwapChainDesc) -> Self {
+        ProxySwapChainDesc {
+            width: desc.width,
+            height: desc.height,
+            format: desc.format,
+            buffer_count: desc.buffer_count,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProxyTextureData {
+    pub data: Vec<u8>,
+    pub row_pitch: u32,
+    pub slice_pitch: u32,
+}
+
+impl<'a, 'b> From<&'b RenderTextureSubResourceData<'a>> for ProxyTextureData {
+    fn from(data: &'b RenderTextureSubResourceData<'a>) -> Self {
+        ProxyTextureData {
+            data: data.data.clone(),
+            row_pitch: data.row_pitch,
+            slice_pitch: data.slice_pitch,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ProxyDeviceCall {
+    ValidResource(RenderResourceHandle),
+    DestroyResource(RenderResourceHandle),
+    CreateSwapChain(RenderSwapChainHandle, ProxySwapChainDesc, String),
+    CreateBuffer(
+        RenderBufferHandle,
+        RenderBufferDesc,
+        Option<Vec<u8>>,
+        String,
+    ),
+    CreateTexture(
+        RenderTextureHandle,
+        RenderTextureDesc,
+        Option<ProxyTextureData>,
+        String,
+    ),
+    CreateSamplerState(RenderSamplerStateHandle, RenderSamplerState, String),
+    CreateShader(RenderShaderHandle, RenderShaderDesc, String),
+    CreateShaderViews(RenderShaderViewsHandle, RenderShaderViewsDesc, String),
+    CreateRayTracingProgram(RenderRayTracingProgramHandle, RayTracingProgramDesc, String),
+    CreateRayTracingGeometry(
+        RenderRayTracingGeometryHandle,
+        RayTracingGeometryDesc,
+        String,
+    ),
+    CreateRayTracingTopAcceleration(
+        RenderRayTracingAccelerationHandle,
+        RayTracingTopAccelerationDesc,
+        String,
+    ),
+    CreateRayTracingBottomAcceleration(
+        RenderRayTracingAccelerationHandle,
+        RayTracingBottomAccelerationDesc,
+        String,
+    ),
+    CreateRayTracingPipelineState(
+        RenderRayTracingPipelineStateHandle,
+        RayTracingPipelineStateDesc,
+        String,
+    ),
+    CreateRayTracingShaderTable(
+        RenderRayTracingShaderTableHandle,
+        RayTracingShaderTableDesc,
+        String,
+    ),
+    CreateGraphicsPipelineState(
+        RenderGraphicsPipelineStateHandle,
+        Box<RenderGraphicsPipelineStateDesc>,
+        String,
+    ),
+    CreateComputePipelineState(
+        RenderComputePipelineStateHandle,
+        RenderComputePipelineStateDesc,
+        String,
+    ),
+    CreateDrawBindingSet(
+        RenderDrawBindingSetHandle,
+        Box<RenderDrawBindingSetDesc>,
+        String,
+    ),
+    CreateFrameBindingSet(
+        RenderFrameBindingSetHandle,
+        RenderFrameBindingSetDesc,
+        String,
+    ),
+    CreateRenderPass(RenderPassHandle, RenderPassDesc, String),
+    CreateCommandList(RenderCommandListHandle, String),
+    CreateFence(RenderFenceHandle, RenderFenceDesc, String),
+    CreateTimingHeap(RenderTimingHeapHandle, RenderTimingHeapDesc, String),
+    GetTimingFrequency,
+    SubmitCommandList {
+        handle: RenderCommandListHandle,
+        flush: bool,
+        wait_before: Option<Vec<RenderFenceHandle>>,
+        signal_after: Option<RenderFenceHandle>,
+    },
+    CompileCommandList(RenderCommandListHandle, RenderSerializedCommandList),
+    CompileCommandLists(RenderCommandListHandle, Vec<RenderSerializedCommandList>),
+    PresentSwapChain {
+        swap_chain: RenderSwapChainHandle,
+        source_texture: RenderTextureHandle,
+    },
+    ResizeSwapChain {
+        swap_chain: RenderSwapChainHandle,
+        width: u32,
+        height: u32,
+    },
+    AdvanceFrame,
+    DeviceTransfer {
+        wait_value: u64,
+        signal_value: u64,
+        fence: RenderFenceHandle,
+        command_list: RenderSerializedCommandList,
+    },
+    DeviceGraphicsSignal(u64, RenderFenceHandle),
+    DeviceGraphicsWait(u64, RenderFenceHandle),
+    DeviceCopySignal(u64, RenderFenceHandle),
+    DeviceCopyWait(u64, RenderFenceHandle),
+    DeviceAcquire(RenderResourceHandle),
+    DeviceUnacquire(RenderResourceHandle),
+    DeviceFlush,
+    GetDeviceInfo,
+    ShaderFormat,
+    RayTracingSupported,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ProxyResponse {
+    Ok,
+    Hello { version: u32, api: String },
+    Bool(bool),
+    Float(f64),
+    Text(String),
+    DeviceInfo(RenderDeviceInfo),
+    Devices(Vec<RenderDeviceInfo>),
+    Error(ErrorKind),
+}
+
+impl ProxyResponse {
+    pub fn from_result<T, F>(result: Result<T>, func: F) -> ProxyResponse
+    where
+        F: FnOnce(T) -> ProxyResponse,
+    {
+        match result {
+            Ok(value) => func(value),
+            Err(err) => ProxyResponse::Error(err.kind().clone()),
+        }
+    }
+}
+
+/// Writes a length prefixed message, leaving buffered writers to be flushed by the caller.
+pub fn write_message<W: Write + ?Sized, T: Serialize>(writer: &mut W, message: &T) -> Result<()> {
+    let data = bincode::serialize(message)
+        .map_err(|err| Error::bug(format!("failed to serialize proxy message - {}", err)))?;
+    if data.len() > PROXY_MAX_MESSAGE_SIZE {
+        return Err(Error::backend(format!(
+            "proxy message of {} bytes exceeds the {} byte limit",
+            data.len(),
+            PROXY_MAX_MESSAGE_SIZE
+        )));
+    }
+
+    let size = data.len() as u32;
+    writer
+        .write_all(&size.to_le_bytes())
+        .and_then(|_| writer.write_all(&data))
+        .map_err(|err| Error::backend(format!("failed to write proxy message - {}", err)))
+}
+
+/// Flushes the messages written so far, i.e. before waiting for the peer to respond.
+pub fn flush_messages<W: Write + ?Sized>(writer: &mut W) -> Result<()> {
+    writer
+        .flush()
+        .map_err(|err| Error::backend(format!("failed to write proxy message - {}", err)))
+}
+
+/// Reads a length prefixed message, returning `None` if the stream was closed cleanly.
+pub fn read_message<R: Read + ?Sized, T: DeserializeOwned>(reader: &mut R) -> Result<Option<T>> {
+    let mut size = [0u8; 4];
+    if let Err(err) = reader.read_exact(&mut size) {
+        return if err.kind() == IoErrorKind::UnexpectedEof {
+            Ok(None)
+        } else {
+            Err(Error::backend(format!(
+                "failed to read proxy message - {}",
+                err
+            )))
+        };
+    }
+
+    let size = u32::from_le_bytes(size) as usize;
+    if size > PROXY_MAX_MESSAGE_SIZE {
+        return Err(Error::backend(format!(
+            "proxy message of {} bytes exceeds the {} byte limit",
+            size, PROXY_MAX_MESSAGE_SIZE
+        )));
+    }
+
+    let mut data = Vec::with_capacity(cmp::min(size, MESSAGE_CHUNK_SIZE));
+    let read = (&mut *reader)
+        .take(size as u64)
+        .read_to_end(&mut data)
+        .map_err(|err| Error::backend(format!("failed to read proxy message - {}", err)))?;
+    if read < size {
+        return Err(Error::backend(format!(
+            "failed to read proxy message - truncated after {} of {} bytes",
+            read, size
+        )));
+    }
+    let message = bincode::deserialize(&data)
+        .map_err(|err| Error::parse(format!("invalid proxy message - {}", err)))?;
+    Ok(Some(message))
+}
@@ -0,0 +1,335 @@
+use crate::protocol::*;
+use crate::transport::{ProxyAddress, ProxyListener, ProxyStream};
+use render_core::backend::{RenderBackend, RenderBackendModule};
+use render_core::device::{RenderDevice, RenderDeviceId};
+use render_core::error::{Error, Result};
+use render_core::handles::RenderConcurrentHandleAllocator;
+use render_core::types::{
+    RenderSwapChainDesc, RenderSwapChainWindow, RenderTextureSubResourceData,
+};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+
+/// Hosts a backend module and executes the calls forwarded by `RenderBackendProxy` clients.
+///
+/// Connections are served one at a time, each with a fresh backend created from the module.
+pub struct RenderProxyServer {
+    module: Box<dyn RenderBackendModule>,
+    listener: ProxyListener,
+}
+
+impl RenderProxyServer {
+    pub fn bind(module: Box<dyn RenderBackendModule>, address: &str) -> Result<Self> {
+        let listener = ProxyListener::bind(&ProxyAddress::parse(address)?)?;
+        Ok(RenderProxyServer { module, listener })
+    }
+
+    /// The bound address, i.e. to find the port when binding "127.0.0.1:0".
+    pub fn local_address(&self) -> Result<ProxyAddress> {
+        self.listener.local_address()
+    }
+
+    /// Accepts a single client and serves it until it disconnects.
+    pub fn serve_connection(&self) -> Result<()> {
+        let mut stream = self.listener.accept()?;
+        let mut session = ProxySession::new(self.module.api(), self.module.create());
+        session.serve(&mut *stream)
+    }
+
+    /// Serves clients until the listener fails; a failed session does not stop the server.
+    pub fn run(&self) -> Result<()> {
+        loop {
+            if let Err(err) = self.serve_connection() {
+                error!("Proxy session for {} failed - {}", self.module.api(), err);
+            }
+        }
+    }
+}
+
+/// Executes requests against a backend, for a client connection or a capture replay.
+pub(crate) struct ProxySession {
+    api: &'static str,
+    backend: Box<dyn RenderBackend>,
+    handles: Arc<RenderConcurrentHandleAllocator>,
+}
+
+impl ProxySession {
+    pub(crate) fn new(api: &'static str, backend: Box<dyn RenderBackend>) -> Self {
+        ProxySession {
+            api,
+            backend,
+            handles: Arc::new(RenderConcurrentHandleAllocator::new()),
+        }
+    }
+
+    pub(crate) fn backend(&self) -> &dyn RenderBackend {
+        &*self.backend
+    }
+
+    /// Handles that command lists are recorded against on this side of the connection.
+    pub(crate) fn handles(&self) -> Arc<RenderConcurrentHandleAllocator> {
+        Arc::clone(&self.handles)
+    }
+
+    fn serve(&mut self, stream: &mut dyn ProxyStream) -> Result<()> {
+        while let Some(request) = read_message::<_, ProxyRequest>(stream)? {
+            let response = self.handle(request);
+            write_message(stream, &response)?;
+            flush_messages(stream)?;
+        }
+        Ok(())
+    }
+
+    /// Executes a request, turning backend panics into errors so the server survives them.
+    pub(crate) fn handle(&mut self, request: ProxyRequest) -> ProxyResponse {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| self.dispatch(request)));
+        match result {
+            Ok(result) => ProxyResponse::from_result(result, |response| response),
+            Err(cause) => {
+                let message = cause
+                    .downcast_ref::<&str>()
+                    .map(|message| message.to_string())
+                    .or_else(|| cause.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "unknown panic".to_string());
+                ProxyResponse::Error(
+                    Error::backend(format!("proxied backend panicked - {}", message))
+                        .kind()
+                        .clone(),
+                )
+            }
+        }
+    }
+
+    fn dispatch(&mut self, request: ProxyRequest) -> Result<ProxyResponse> {
+        match request {
+            ProxyRequest::Hello { .. } => Ok(ProxyResponse::Hello {
+                version: PROXY_PROTOCOL_VERSION,
+                api: self.api.to_string(),
+            }),
+            ProxyRequest::EnumerateDevices {
+                max_devices,
+                mirror_count,
+                software,
+            } => Ok(ProxyResponse::Devices(self.backend.enumerate_devices(
+                max_devices,
+                mirror_count,
+                software,
+            )?)),
+            ProxyRequest::CreateDevice { device_id } => {
+                self.backend.create_device(device_id)?;
+                Ok(ProxyResponse::Ok)
+            }
+            ProxyRequest::DestroyDevice { device_id } => {
+                self.backend.destroy_device(device_id)?;
+                Ok(ProxyResponse::Ok)
+            }
+            ProxyRequest::BeginDebugCapture { name } => {
+                self.backend.begin_debug_capture(&name)?;
+                Ok(ProxyResponse::Ok)
+            }
+            ProxyRequest::FinishDebugCapture => {
+                self.backend.finish_debug_capture()?;
+                Ok(ProxyResponse::Ok)
+            }
+            ProxyRequest::TriggerDebugCapture => {
+                self.backend.trigger_debug_capture()?;
+                Ok(ProxyResponse::Ok)
+            }
+            ProxyRequest::LaunchDebugCapture { quit } => {
+                self.backend.launch_debug_capture(quit)?;
+                Ok(ProxyResponse::Ok)
+            }
+            ProxyRequest::Device { device_id, call } => self.dispatch_device(device_id, *call),
+        }
+    }
+
+    fn dispatch_device(
+        &mut self,
+        device_id: RenderDeviceId,
+        call: ProxyDeviceCall,
+    ) -> Result<ProxyResponse> {
+        let entry = self.backend.get_device(device_id)?;
+        // A panic caught during an earlier call poisons the entry, but the device is still usable
+        let mut entry = entry
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let device: &mut Box<dyn RenderDevice> = match *entry {
+            Some(ref mut device) => device,
+            None => {
+                return Err(Error::backend(format!(
+                    "device {} has not been created",
+                    device_id
+                )))
+            }
+        };
+
+        match call {
+            ProxyDeviceCall::ValidResource(handle) => {
+                return Ok(ProxyResponse::Bool(device.valid_resource(handle)))
+            }
+            ProxyDeviceCall::DestroyResource(handle) => device.destroy_resource(handle)?,
+            ProxyDeviceCall::CreateSwapChain(handle, desc, debug_name) => {
+                let desc = RenderSwapChainDesc {
+                    width: desc.width,
+                    height: desc.height,
+                    format: desc.format,
+                    buffer_count: desc.buffer_count,
+                    window: headless_window(),
+                };
+                device.create_swap_chain(handle, &desc, debug_name.into())?
+            }
+            ProxyDeviceCall::CreateBuffer(handle, desc, initial_data, debug_name) => {
+                device.create_buffer(handle, &desc, initial_data.as_deref(), debug_name.into())?
+            }
+            ProxyDeviceCall::CreateTexture(handle, desc, initial_data, debug_name) => device
+                .create_texture(
+                    handle,
+                    &desc,
+                    initial_data
+                        .as_ref()
+                        .map(|data| RenderTextureSubResourceData {
+                            data: &data.data,
+                            row_pitch: data.row_pitch,
+                            slice_pitch: data.slice_pitch,
+                        }),
+                    debug_name.into(),
+                )?,
+            ProxyDeviceCall::CreateSamplerState(handle, state, debug_name) => {
+                device.create_sampler_state(handle, &state, debug_name.into())?
+            }
+            ProxyDeviceCall::CreateShader(handle, desc, debug_name) => {
+                device.create_shader(handle, &desc, debug_name.into())?
+            }
+            ProxyDeviceCall::CreateShaderViews(handle, desc, debug_name) => {
+                device.create_shader_views(handle, &desc, debug_name.into())?
+            }
+            ProxyDeviceCall::CreateRayTracingProgram(handle, desc, debug_name) => {
+                device.create_ray_tracing_program(handle, &desc, debug_name.into())?
+            }
+            ProxyDeviceCall::CreateRayTracingGeometry(handle, desc, debug_name) => {
+                device.create_ray_tracing_geometry(handle, &desc, debug_name.into())?
+            }
+            ProxyDeviceCall::CreateRayTracingTopAcceleration(handle, desc, debug_name) => {
+                device.create_ray_tracing_top_acceleration(handle, &desc, debug_name.into())?
+            }
+            ProxyDeviceCall::CreateRayTracingBottomAcceleration(handle, desc, debug_name) => {
+                device.create_ray_tracing_bottom_acceleration(handle, &desc, debug_name.into())?
+            }
+            ProxyDeviceCall::CreateRayTracingPipelineState(handle, desc, debug_name) => {
+                device.create_ray_tracing_pipeline_state(handle, &desc, debug_name.into())?
+            }
+            ProxyDeviceCall::CreateRayTracingShaderTable(handle, desc, debug_name) => {
+                device.create_ray_tracing_shader_table(handle, &desc, debug_name.into())?
+            }
+            ProxyDeviceCall::CreateGraphicsPipelineState(handle, desc, debug_name) => {
+                device.create_graphics_pipeline_state(handle, &desc, debug_name.into())?
+            }
+            ProxyDeviceCall::CreateComputePipelineState(handle, desc, debug_name) => {
+                device.create_compute_pipeline_state(handle, &desc, debug_name.into())?
+            }
+            ProxyDeviceCall::CreateDrawBindingSet(handle, desc, debug_name) => {
+                device.create_draw_binding_set(handle, &desc, debug_name.into())?
+            }
+            ProxyDeviceCall::CreateFrameBindingSet(handle, desc, debug_name) => {
+                device.create_frame_binding_set(handle, &desc, debug_name.into())?
+            }
+            ProxyDeviceCall::CreateRenderPass(handle, desc, debug_name) => {
+                device.create_render_pass(handle, &desc, debug_name.into())?
+            }
+            ProxyDeviceCall::CreateCommandList(handle, debug_name) => {
+                device.create_command_list(handle, debug_name.into())?
+            }
+            ProxyDeviceCall::CreateFence(handle, desc, debug_name) => {
+                device.create_fence(handle, &desc, debug_name.into())?
+            }
+            ProxyDeviceCall::CreateTimingHeap(handle, desc, debug_name) => {
+                device.create_timing_heap(handle, &desc, debug_name.into())?
+            }
+            ProxyDeviceCall::GetTimingFrequency => {
+                return Ok(ProxyResponse::Float(device.get_timing_frequency()?))
+            }
+            ProxyDeviceCall::SubmitCommandList {
+                handle,
+                flush,
+                wait_before,
+                signal_after,
+            } => device.submit_command_list(handle, flush, wait_before.as_deref(), signal_after)?,
+            ProxyDeviceCall::CompileCommandList(handle, command_list) => {
+                let command_list = command_list.to_command_list(Arc::clone(&self.handles))?;
+                device.compile_command_list(handle, &command_list)?
+            }
+            ProxyDeviceCall::CompileCommandLists(handle, command_lists) => {
+                let command_lists = command_lists
+                    .iter()
+                    .map(|command_list| command_list.to_command_list(Arc::clone(&self.handles)))
+                    .collect::<Result<Vec<_>>>()?;
+                device.compile_command_lists(handle, &command_lists)?
+            }
+            ProxyDeviceCall::PresentSwapChain {
+                swap_chain,
+                source_texture,
+            } => device.present_swap_chain(swap_chain, source_texture)?,
+            ProxyDeviceCall::ResizeSwapChain {
+                swap_chain,
+                width,
+                height,
+            } => device.resize_swap_chain(swap_chain, width, height)?,
+            ProxyDeviceCall::AdvanceFrame => device.advance_frame()?,
+            ProxyDeviceCall::DeviceTransfer {
+                wait_value,
+                signal_value,
+                fence,
+                command_list,
+            } => {
+                let command_list = command_list.to_command_list(Arc::clone(&self.handles))?;
+                device.device_transfer(wait_value, signal_value, fence, &command_list)?
+            }
+            ProxyDeviceCall::DeviceGraphicsSignal(signal_value, fence) => {
+                device.device_graphics_signal(signal_value, fence)?
+            }
+            ProxyDeviceCall::DeviceGraphicsWait(wait_value, fence) => {
+                device.device_graphics_wait(wait_value, fence)?
+            }
+            ProxyDeviceCall::DeviceCopySignal(signal_value, fence) => {
+                device.device_copy_signal(signal_value, fence)?
+            }
+            ProxyDeviceCall::DeviceCopyWait(wait_value, fence) => {
+                device.device_copy_wait(wait_value, fence)?
+            }
+            ProxyDeviceCall::DeviceAcquire(resource) => device.device_acquire(resource)?,
+            ProxyDeviceCall::DeviceUnacquire(resource) => device.device_unacquire(resource)?,
+            ProxyDeviceCall::DeviceFlush => device.device_flush()?,
+            ProxyDeviceCall::GetDeviceInfo => {
+                return Ok(ProxyResponse::DeviceInfo(device.get_device_info()?))
+            }
+            ProxyDeviceCall::ShaderFormat => {
+                return Ok(ProxyResponse::Text(device.shader_format()?))
+            }
+            ProxyDeviceCall::RayTracingSupported => {
+                return Ok(ProxyResponse::Bool(device.ray_tracing_supported()))
+            }
+        }
+        Ok(ProxyResponse::Ok)
+    }
+}
+
+/// Client windows can't cross the process boundary, so swap chains are created headless.
+#[cfg(windows)]
+fn headless_window() -> RenderSwapChainWindow {
+    RenderSwapChainWindow {
+        hinstance: std::ptr::null(),
+        hwnd: std::ptr::null(),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn headless_window() -> RenderSwapChainWindow {
+    RenderSwapChainWindow {
+        ns_view: std::ptr::null(),
+    }
+}
+
+#[cfg(all(unix, not(target_os = "android"), not(target_os = "macos")))]
+fn headless_window() -> RenderSwapChainWindow {
+    RenderSwapChainWindow {}
+}
@@ -0,0 +1,153 @@
+use render_core::error::{Error, Result};
+use std::fmt;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+
+pub const DEFAULT_PROXY_ADDRESS: &str = "127.0.0.1:50080";
+
+pub trait ProxyStream: Read + Write + Send {}
+
+impl<T: Read + Write + Send> ProxyStream for T {}
+
+/// Where a proxy server listens, parsed from `RenderBackendSettings::address`.
+///
+/// Accepts "host:port" or "tcp://host:port" for TCP, and "unix:/path/to/socket" for
+/// Unix domain sockets.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProxyAddress {
+    Tcp(String),
+    Unix(PathBuf),
+}
+
+impl ProxyAddress {
+    pub fn parse(address: &str) -> Result<Self> {
+        let address = address.trim();
+        if let Some(path) = address.strip_prefix("unix:") {
+            let path = path.trim_start_matches("//");
+            if path.is_empty() {
+                return Err(Error::backend(format!(
+                    "invalid proxy address {:?} - missing socket path",
+                    address
+                )));
+            }
+            Ok(ProxyAddress::Unix(PathBuf::from(path)))
+        } else {
+            let host = address.trim_start_matches("tcp://");
+            if host.is_empty() {
+                return Err(Error::backend("invalid proxy address - missing host"));
+            }
+            Ok(ProxyAddress::Tcp(host.to_string()))
+        }
+    }
+
+    pub fn connect(&self) -> Result<Box<dyn ProxyStream>> {
+        match self {
+            ProxyAddress::Tcp(host) => {
+                let stream = TcpStream::connect(host).map_err(|err| {
+                    Error::backend(format!("failed to connect to proxy {} - {}", self, err))
+                })?;
+                stream.set_nodelay(true).ok();
+                Ok(Box::new(stream))
+            }
+            #[cfg(unix)]
+            ProxyAddress::Unix(path) => {
+                let stream = UnixStream::connect(path).map_err(|err| {
+                    Error::backend(format!("failed to connect to proxy {} - {}", self, err))
+                })?;
+                Ok(Box::new(stream))
+            }
+            #[cfg(not(unix))]
+            ProxyAddress::Unix(_) => Err(Error::backend(
+                "unix socket proxy addresses are not supported on this platform",
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ProxyAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProxyAddress::Tcp(host) => write!(f, "tcp://{}", host),
+            ProxyAddress::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+pub(crate) enum ProxyListener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener, PathBuf),
+}
+
+impl ProxyListener {
+    pub fn bind(address: &ProxyAddress) -> Result<Self> {
+        match address {
+            ProxyAddress::Tcp(host) => {
+                let listener = TcpListener::bind(host).map_err(|err| {
+                    Error::backend(format!("failed to bind proxy {} - {}", address, err))
+                })?;
+                Ok(ProxyListener::Tcp(listener))
+            }
+            #[cfg(unix)]
+            ProxyAddress::Unix(path) => {
+                // A stale socket file from a previous server blocks binding
+                if path.exists() {
+                    std::fs::remove_file(path).ok();
+                }
+                let listener = UnixListener::bind(path).map_err(|err| {
+                    Error::backend(format!("failed to bind proxy {} - {}", address, err))
+                })?;
+                Ok(ProxyListener::Unix(listener, path.clone()))
+            }
+            #[cfg(not(unix))]
+            ProxyAddress::Unix(_) => Err(Error::backend(
+                "unix socket proxy addresses are not supported on this platform",
+            )),
+        }
+    }
+
+    pub fn local_address(&self) -> Result<ProxyAddress> {
+        match self {
+            ProxyListener::Tcp(listener) => {
+                let address = listener
+                    .local_addr()
+                    .map_err(|err| Error::backend(format!("invalid proxy listener - {}", err)))?;
+                Ok(ProxyAddress::Tcp(address.to_string()))
+            }
+            #[cfg(unix)]
+            ProxyListener::Unix(_, path) => Ok(ProxyAddress::Unix(path.clone())),
+        }
+    }
+
+    pub fn accept(&self) -> Result<Box<dyn ProxyStream>> {
+        match self {
+            ProxyListener::Tcp(listener) => {
+                let (stream, _) = listener.accept().map_err(|err| {
+                    Error::backend(format!("failed to accept proxy connection - {}", err))
+                })?;
+                stream.set_nodelay(true).ok();
+                Ok(Box::new(stream))
+            }
+            #[cfg(unix)]
+            ProxyListener::Unix(listener, _) => {
+                let (stream, _) = listener.accept().map_err(|err| {
+                    Error::backend(format!("failed to accept proxy connection - {}", err))
+                })?;
+                Ok(Box::new(stream))
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for ProxyListener {
+    fn drop(&mut self) {
+        if let ProxyListener::Unix(_, path) = self {
+            std::fs::remove_file(path).ok();
+        }
+    }
+}
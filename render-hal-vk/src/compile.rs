@@ -51,6 +51,9 @@ pub struct RenderCompileContext {
     cached_viewport: RenderViewportRect,
     cached_scissor: RenderScissorRect,
     cached_stencil_ref: u8,
+    bound_graphics_pipeline: Option<RenderResourceHandle>,
+    bound_compute_pipeline: Option<RenderResourceHandle>,
+    bound_draw_binding: Option<RenderResourceHandle>,
     command_buffer: Option<Arc<ash::vk::CommandBuffer>>,
     active_render_pass: Option<Arc<RwLock<Box<RenderResourceBase>>>>,
     resource_tracker: RefCell<HashMap<RenderResourceHandle, RenderResourceStates>>,
@@ -75,6 +78,9 @@ impl RenderCompileContext {
             cached_viewport: Default::default(),
             cached_scissor: Default::default(),
             cached_stencil_ref: 0,
+            bound_graphics_pipeline: None,
+            bound_compute_pipeline: None,
+            bound_draw_binding: None,
             command_buffer: None,
             active_render_pass: None,
             resource_tracker: RefCell::new(HashMap::new()),
@@ -87,6 +93,9 @@ impl RenderCompileContext {
         assert!(!native.is_open());
         assert!(self.command_buffer.is_none());
         self.command_buffer = Some(native.open()?);
+        self.bound_graphics_pipeline = None;
+        self.bound_compute_pipeline = None;
+        self.bound_draw_binding = None;
         Ok(())
     }
 
@@ -112,69 +121,75 @@ impl RenderCompileContext {
         let command_buffer = native.get()?;
 
         for command in encoder.get_commands() {
+            // Bindings read resource states, so they are applied again once those may change
+            if command.get_type().invalidates_bindings() {
+                self.bound_graphics_pipeline = None;
+                self.bound_compute_pipeline = None;
+                self.bound_draw_binding = None;
+            }
             match command.get_type() {
                 RenderCommandType::Draw => {
-                    self.draw(*command_buffer, *command)?;
+                    self.draw(*command_buffer, command.as_command())?;
                 }
                 RenderCommandType::DrawIndirect => {
-                    self.draw_indirect(*command_buffer, *command)?;
+                    self.draw_indirect(*command_buffer, command.as_command())?;
                 }
                 RenderCommandType::Dispatch => {
-                    self.dispatch(*command_buffer, *command)?;
+                    self.dispatch(*command_buffer, command.as_command())?;
                 }
                 RenderCommandType::DispatchIndirect => {
-                    self.dispatch_indirect(*command_buffer, *command)?;
+                    self.dispatch_indirect(*command_buffer, command.as_command())?;
                 }
                 RenderCommandType::UpdateBuffer => {
-                    self.update_buffer(*command_buffer, *command, &encoder)?;
+                    self.update_buffer(*command_buffer, command.as_command(), &encoder)?;
                 }
                 RenderCommandType::UpdateTexture => {
-                    self.update_texture(*command_buffer, *command, &encoder)?;
+                    self.update_texture(*command_buffer, command.as_command(), &encoder)?;
                 }
                 RenderCommandType::CopyBuffer => {
-                    self.copy_buffer(*command_buffer, *command)?;
+                    self.copy_buffer(*command_buffer, command.as_command())?;
                 }
                 RenderCommandType::CopyTexture => {
-                    self.copy_texture(*command_buffer, *command)?;
+                    self.copy_texture(*command_buffer, command.as_command())?;
                 }
                 RenderCommandType::Barriers => {
-                    self.barriers(*command_buffer, *command)?;
+                    self.barriers(*command_buffer, command.as_command())?;
                 }
                 RenderCommandType::Transitions => {
-                    self.transitions(*command_buffer, *command)?;
+                    self.transitions(*command_buffer, command.as_command())?;
                 }
                 RenderCommandType::BeginTiming => {
-                    self.begin_timing(*command_buffer, *command)?;
+                    self.begin_timing(*command_buffer, command.as_command())?;
                 }
                 RenderCommandType::EndTiming => {
-                    self.end_timing(*command_buffer, *command)?;
+                    self.end_timing(*command_buffer, command.as_command())?;
                 }
                 RenderCommandType::ResolveTimings => {
-                    self.resolve_timings(*command_buffer, *command)?;
+                    self.resolve_timings(*command_buffer, command.as_command())?;
                 }
                 RenderCommandType::BeginEvent => {
-                    self.begin_event(*command_buffer, *command)?;
+                    self.begin_event(*command_buffer, command.as_command())?;
                 }
                 RenderCommandType::EndEvent => {
-                    self.end_event(*command_buffer, *command)?;
+                    self.end_event(*command_buffer, command.as_command())?;
                 }
                 RenderCommandType::BeginRenderPass => {
-                    self.begin_render_pass(*command_buffer, *command)?;
+                    self.begin_render_pass(*command_buffer, command.as_command())?;
                 }
                 RenderCommandType::EndRenderPass => {
-                    self.end_render_pass(*command_buffer, *command)?;
+                    self.end_render_pass(*command_buffer, command.as_command())?;
                 }
                 RenderCommandType::RayTrace => {
-                    self.ray_trace(*command_buffer, *command)?;
+                    self.ray_trace(*command_buffer, command.as_command())?;
                 }
                 RenderCommandType::UpdateTopLevelAcceleration => {
-                    self.update_top_level_acceleration(*command_buffer, *command)?;
+                    self.update_top_level_acceleration(*command_buffer, command.as_command())?;
                 }
                 RenderCommandType::UpdateBottomLevelAcceleration => {
-                    self.update_bottom_level_acceleration(*command_buffer, *command)?;
+                    self.update_bottom_level_acceleration(*command_buffer, command.as_command())?;
                 }
                 RenderCommandType::UpdateShaderTable => {
-                    self.update_shader_table(*command_buffer, *command)?;
+                    self.update_shader_table(*command_buffer, command.as_command())?;
                 }
             }
         }
@@ -199,25 +214,34 @@ impl RenderCompileContext {
         let pipeline_state = pipeline_state
             .downcast_ref::<RenderGraphicsPipelineStateVk>()
             .unwrap();
-        self.apply_graphics_pipeline_state(native, &pipeline_state)?;
-        self.apply_shader_arguments(
-            native,
-            typed_command.pipeline_state,
-            &pipeline_state.data,
-            ash::vk::PipelineBindPoint::GRAPHICS,
-            &typed_command.shader_arguments,
-        )?;
+        if self.bound_graphics_pipeline != Some(typed_command.pipeline_state) {
+            self.apply_graphics_pipeline_state(native, &pipeline_state)?;
+            self.bound_graphics_pipeline = Some(typed_command.pipeline_state);
+        }
+        // Arguments left out are already bound to this pipeline state
+        if let Some(ref shader_arguments) = typed_command.shader_arguments {
+            self.apply_shader_arguments(
+                native,
+                typed_command.pipeline_state,
+                &pipeline_state.data,
+                ash::vk::PipelineBindPoint::GRAPHICS,
+                shader_arguments,
+            )?;
+        }
         self.apply_transitions(native); // TODO: Move this into the draw sections
         if let Some(ref draw_state) = typed_command.draw_state {
             self.apply_draw_state(native, draw_state)?; //, pipeline_state.primitive_topology
         }
-        if let Some(draw_binding) = typed_command.draw_binding {
-            let draw_binding = self.storage.get(draw_binding)?;
+        if let Some(draw_binding_handle) = typed_command.draw_binding {
+            let draw_binding = self.storage.get(draw_binding_handle)?;
             let draw_binding = draw_binding.read().unwrap();
             let draw_binding = draw_binding
                 .downcast_ref::<RenderDrawBindingSetVk>()
                 .unwrap();
-            self.apply_draw_binding(native, draw_binding)?;
+            if self.bound_draw_binding != Some(draw_binding_handle) {
+                self.apply_draw_binding(native, draw_binding)?;
+                self.bound_draw_binding = Some(draw_binding_handle);
+            }
             self.apply_transitions(native);
             if let Some(ref index_buffer) = draw_binding.index_buffer {
                 unsafe {
@@ -285,14 +309,19 @@ impl RenderCompileContext {
         let pipeline_state = pipeline_state
             .downcast_ref::<RenderComputePipelineStateVk>()
             .unwrap();
-        self.apply_compute_pipeline_state(native, &pipeline_state)?;
-        self.apply_shader_arguments(
-            native,
-            typed_command.pipeline_state,
-            &pipeline_state.data,
-            ash::vk::PipelineBindPoint::COMPUTE,
-            &typed_command.shader_arguments,
-        )?;
+        if self.bound_compute_pipeline != Some(typed_command.pipeline_state) {
+            self.apply_compute_pipeline_state(native, &pipeline_state)?;
+            self.bound_compute_pipeline = Some(typed_command.pipeline_state);
+        }
+        if let Some(ref shader_arguments) = typed_command.shader_arguments {
+            self.apply_shader_arguments(
+                native,
+                typed_command.pipeline_state,
+                &pipeline_state.data,
+                ash::vk::PipelineBindPoint::COMPUTE,
+                shader_arguments,
+            )?;
+        }
         self.apply_transitions(native); // TODO: Move this into the dispatch sections
         unsafe {
             self.device.raw.cmd_dispatch(
@@ -1327,7 +1356,6 @@ impl RenderCompileContext {
     ) -> Result<()> {
         assert_ne!(pipeline_state.pipeline, ash::vk::Pipeline::null());
         unsafe {
-            // TODO: Check for redundancy
             self.device.raw.cmd_bind_pipeline(
                 native,
                 ash::vk::PipelineBindPoint::COMPUTE,
@@ -1345,7 +1373,6 @@ impl RenderCompileContext {
     ) -> Result<()> {
         assert_ne!(pipeline_state.pipeline, ash::vk::Pipeline::null());
         unsafe {
-            // TODO: Check for redundancy
             self.device.raw.cmd_bind_pipeline(
                 native,
                 ash::vk::PipelineBindPoint::GRAPHICS,
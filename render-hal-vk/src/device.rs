@@ -34,13 +34,15 @@ use digest::Digest;
 use meowhash;
 use num_traits::FromPrimitive;
 use render_core::constants::*;
+use render_core::deferred::*;
 use render_core::device::*;
 use render_core::encoder::*;
 use render_core::error::{Error, Result};
 use render_core::format::*;
-use render_core::handles::RenderResourceHandle;
+use render_core::handles::*;
 use render_core::resources::{RenderResourceBase, RenderResourceStorage};
 use render_core::state::*;
+use render_core::statistics::*;
 use render_core::types::*;
 use render_core::utilities::any_as_u8_slice;
 use spirv_reflect;
@@ -54,6 +56,7 @@ use std::{
     mem,
     mem::align_of,
     ptr,
+    sync::atomic::{AtomicU64, Ordering},
     sync::{Arc, RwLock},
     u32,
 };
@@ -125,6 +128,9 @@ pub struct RenderDeviceVk {
     pipeline_cache: ash::vk::PipelineCache,
     descriptor_cache: Arc<DescriptorSetCache>,
     global_allocator: Arc<RwLock<vk_mem::Allocator>>,
+    frame_count: AtomicU64,
+    destroy_queue: RenderDestroyQueue,
+    frame_stats: RenderFrameStatsCollector,
 }
 
 impl fmt::Debug for RenderDeviceVk {
@@ -428,6 +434,9 @@ impl RenderDeviceVk {
                 })
                 .unwrap(),
             )),
+            frame_count: AtomicU64::new(0),
+            destroy_queue: RenderDestroyQueue::new(MAX_GPU_FRAMES as u64),
+            frame_stats: RenderFrameStatsCollector::new(),
         })
     }
 
@@ -481,6 +490,10 @@ impl Drop for RenderDeviceVk {
             }
         }
 
+        if let Err(err) = self.destroy_queue.flush_all(self) {
+            error!("Error destroying deferred resources during render device drop: {:?}", err);
+        }
+
         unsafe {
             raw_device.device_wait_idle().unwrap();
             if self.pipeline_cache != ash::vk::PipelineCache::null() {
@@ -613,6 +626,7 @@ impl RenderDevice for RenderDeviceVk {
                 let resource = resource
                     .downcast_mut::<RenderGraphicsPipelineStateVk>()
                     .unwrap();
+                self.frame_stats.unregister_pipeline(handle);
 
                 unsafe {
                     raw_device.destroy_pipeline(resource.pipeline, None);
@@ -718,10 +732,29 @@ impl RenderDevice for RenderDeviceVk {
         Ok(())
     }
 
+    fn destroy_resource_deferred(
+        &self,
+        handle: RenderResourceHandle,
+        on_destroyed: Option<RenderDestroyedFn>,
+    ) -> Result<()> {
+        if !self.storage.valid(handle) {
+            if let Some(on_destroyed) = on_destroyed {
+                on_destroyed(handle);
+            }
+            return Err(Error::backend(format!(
+                "resource not found: {:?}",
+                handle
+            )));
+        }
+        self.destroy_queue
+            .push(self.get_frame_index(), handle, on_destroyed);
+        Ok(())
+    }
+
     // Resource Management
     fn create_swap_chain(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderSwapChainHandle,
         desc: &RenderSwapChainDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
@@ -912,7 +945,7 @@ impl RenderDevice for RenderDeviceVk {
 
     fn create_buffer(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderBufferHandle,
         desc: &RenderBufferDesc,
         initial_data: Option<&[u8]>,
         debug_name: Cow<'static, str>,
@@ -1040,7 +1073,7 @@ impl RenderDevice for RenderDeviceVk {
 
     fn create_texture(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderTextureHandle,
         desc: &RenderTextureDesc,
         initial_data: Option<RenderTextureSubResourceData>,
         debug_name: Cow<'static, str>,
@@ -1305,7 +1338,7 @@ impl RenderDevice for RenderDeviceVk {
 
     fn create_sampler_state(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderSamplerStateHandle,
         desc: &RenderSamplerState,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
@@ -1328,7 +1361,7 @@ impl RenderDevice for RenderDeviceVk {
 
     fn create_shader(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderShaderHandle,
         desc: &RenderShaderDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
@@ -1548,7 +1581,7 @@ impl RenderDevice for RenderDeviceVk {
 
     fn create_shader_views(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderShaderViewsHandle,
         desc: &RenderShaderViewsDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
@@ -1759,7 +1792,7 @@ impl RenderDevice for RenderDeviceVk {
     // Ray tracing features are only supported on some devices
     fn create_ray_tracing_program(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderRayTracingProgramHandle,
         desc: &RayTracingProgramDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
@@ -1769,7 +1802,7 @@ impl RenderDevice for RenderDeviceVk {
 
     fn create_ray_tracing_geometry(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderRayTracingGeometryHandle,
         desc: &RayTracingGeometryDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
@@ -1779,7 +1812,7 @@ impl RenderDevice for RenderDeviceVk {
 
     fn create_ray_tracing_top_acceleration(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderRayTracingAccelerationHandle,
         desc: &RayTracingTopAccelerationDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
@@ -1793,7 +1826,7 @@ impl RenderDevice for RenderDeviceVk {
 
     fn create_ray_tracing_bottom_acceleration(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderRayTracingAccelerationHandle,
         desc: &RayTracingBottomAccelerationDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
@@ -1807,7 +1840,7 @@ impl RenderDevice for RenderDeviceVk {
 
     fn create_ray_tracing_pipeline_state(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderRayTracingPipelineStateHandle,
         desc: &RayTracingPipelineStateDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
@@ -1821,7 +1854,7 @@ impl RenderDevice for RenderDeviceVk {
 
     fn create_ray_tracing_shader_table(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderRayTracingShaderTableHandle,
         desc: &RayTracingShaderTableDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
@@ -1835,7 +1868,7 @@ impl RenderDevice for RenderDeviceVk {
 
     fn create_graphics_pipeline_state(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderGraphicsPipelineStateHandle,
         desc: &RenderGraphicsPipelineStateDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
@@ -2297,12 +2330,13 @@ impl RenderDevice for RenderDeviceVk {
             })));
 
         self.storage.put(handle, resource)?;
+        self.frame_stats.register_pipeline(handle.into(), desc);
         Ok(())
     }
 
     fn create_compute_pipeline_state(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderComputePipelineStateHandle,
         desc: &RenderComputePipelineStateDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
@@ -2316,7 +2350,7 @@ impl RenderDevice for RenderDeviceVk {
 
     fn create_draw_binding_set(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderDrawBindingSetHandle,
         desc: &RenderDrawBindingSetDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
@@ -2382,7 +2416,7 @@ impl RenderDevice for RenderDeviceVk {
 
     fn create_frame_binding_set(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderFrameBindingSetHandle,
         desc: &RenderFrameBindingSetDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
@@ -2586,7 +2620,7 @@ impl RenderDevice for RenderDeviceVk {
 
     fn create_render_pass(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderPassHandle,
         desc: &RenderPassDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
@@ -2818,7 +2852,7 @@ impl RenderDevice for RenderDeviceVk {
 
     fn create_command_list(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderCommandListHandle,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
         info!("Creating command list: {}", debug_name);
@@ -2838,7 +2872,7 @@ impl RenderDevice for RenderDeviceVk {
 
     fn create_fence(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderFenceHandle,
         desc: &RenderFenceDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
@@ -2870,7 +2904,7 @@ impl RenderDevice for RenderDeviceVk {
 
     fn create_timing_heap(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderTimingHeapHandle,
         desc: &RenderTimingHeapDesc,
         debug_name: Cow<'static, str>,
     ) -> Result<()> {
@@ -2890,10 +2924,10 @@ impl RenderDevice for RenderDeviceVk {
     // CommandList Management
     fn submit_command_list(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderCommandListHandle,
         flush: bool,
-        wait_before: Option<&[RenderResourceHandle]>,
-        signal_after: Option<RenderResourceHandle>,
+        wait_before: Option<&[RenderFenceHandle]>,
+        signal_after: Option<RenderFenceHandle>,
     ) -> Result<()> {
         assert_eq!(handle.get_type(), RenderResourceType::CommandList);
         self.flush_transfers();
@@ -2911,7 +2945,7 @@ impl RenderDevice for RenderDeviceVk {
 
     fn compile_command_list(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderCommandListHandle,
         command_list: &RenderCommandList,
     ) -> Result<()> {
         assert_eq!(handle.get_type(), RenderResourceType::CommandList);
@@ -2925,7 +2959,9 @@ impl RenderDevice for RenderDeviceVk {
                 self.storage.clone(),
                 queue.clone(),
             );
-            Ok(compile_context.compile_list(&mut native_command_list, &command_list)?)
+            compile_context.compile_list(&mut native_command_list, &command_list)?;
+            self.frame_stats.record(command_list);
+            Ok(())
         } else {
             Err(Error::backend("no queue available for command list"))
         }
@@ -2933,7 +2969,7 @@ impl RenderDevice for RenderDeviceVk {
 
     fn compile_command_lists(
         &self,
-        handle: RenderResourceHandle,
+        handle: RenderCommandListHandle,
         command_lists: &[RenderCommandList],
     ) -> Result<()> {
         assert_eq!(handle.get_type(), RenderResourceType::CommandList);
@@ -2950,6 +2986,7 @@ impl RenderDevice for RenderDeviceVk {
             compile_context.begin_compile(&mut native_command_list)?;
             for command_list in command_lists {
                 compile_context.compile_list(&mut native_command_list, &command_list)?;
+                self.frame_stats.record(command_list);
             }
             compile_context.finish_compile(&mut native_command_list)?;
             Ok(())
@@ -2961,8 +2998,8 @@ impl RenderDevice for RenderDeviceVk {
     // Present Management
     fn present_swap_chain(
         &mut self,
-        swap_chain: RenderResourceHandle,
-        source_texture: RenderResourceHandle,
+        swap_chain: RenderSwapChainHandle,
+        source_texture: RenderTextureHandle,
     ) -> Result<()> {
         trace!("Presenting swap chain - {:?}", swap_chain);
 
@@ -3260,7 +3297,7 @@ impl RenderDevice for RenderDeviceVk {
 
     fn resize_swap_chain(
         &self,
-        swap_chain: RenderResourceHandle,
+        swap_chain: RenderSwapChainHandle,
         width: u32,
         height: u32,
     ) -> Result<()> {
@@ -3368,8 +3405,19 @@ impl RenderDevice for RenderDeviceVk {
             .write()
             .unwrap()
             .reset();
+        drop(frames);
 
-        Ok(())
+        let frame_count = self.frame_count.fetch_add(1, Ordering::AcqRel) + 1;
+        self.frame_stats.advance(frame_count);
+        self.destroy_queue.flush(self, frame_count)
+    }
+
+    fn get_frame_index(&self) -> u64 {
+        self.frame_count.load(Ordering::Acquire)
+    }
+
+    fn get_frame_stats(&self) -> RenderFrameStats {
+        self.frame_stats.completed()
     }
 
     // Cross-Node Transfer [Prototype]
@@ -3377,25 +3425,25 @@ impl RenderDevice for RenderDeviceVk {
         &self,
         wait_value: u64,
         signal_value: u64,
-        fence: RenderResourceHandle,
+        fence: RenderFenceHandle,
         command_list: &RenderCommandList,
     ) -> Result<()> {
         unimplemented!()
     }
 
-    fn device_graphics_signal(&self, signal_value: u64, fence: RenderResourceHandle) -> Result<()> {
+    fn device_graphics_signal(&self, signal_value: u64, fence: RenderFenceHandle) -> Result<()> {
         unimplemented!()
     }
 
-    fn device_graphics_wait(&self, wait_value: u64, fence: RenderResourceHandle) -> Result<()> {
+    fn device_graphics_wait(&self, wait_value: u64, fence: RenderFenceHandle) -> Result<()> {
         unimplemented!()
     }
 
-    fn device_copy_signal(&self, signal_value: u64, fence: RenderResourceHandle) -> Result<()> {
+    fn device_copy_signal(&self, signal_value: u64, fence: RenderFenceHandle) -> Result<()> {
         unimplemented!()
     }
 
-    fn device_copy_wait(&self, wait_value: u64, fence: RenderResourceHandle) -> Result<()> {
+    fn device_copy_wait(&self, wait_value: u64, fence: RenderFenceHandle) -> Result<()> {
         unimplemented!()
     }
 
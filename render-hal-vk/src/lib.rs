@@ -71,7 +71,4 @@ mod raw;
 mod shader_views;
 mod types;
 
-#[no_mangle]
-pub extern "C" fn render_backend_factory() -> Box<RenderBackendModule> {
-    Box::new(RenderBackendModuleVk::new())
-}
+render_core::render_backend_module!("vk", RenderBackendModuleVk::new());